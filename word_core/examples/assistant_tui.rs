@@ -0,0 +1,11 @@
+use word_core::{load_words::load_guesses_and_answers_from_args, session::SolverSession, tui::run};
+
+const WORD_SIZE: usize = 5;
+
+fn main() {
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args::<WORD_SIZE, 26>(false)
+            .expect("failed to load word lists");
+    let session = SolverSession::new(allowed_guesses, possible_answers).with_guess_list_fallback();
+    run(session).expect("terminal error");
+}
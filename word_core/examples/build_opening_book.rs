@@ -0,0 +1,19 @@
+use std::env::args;
+
+use word_core::{opening_book::OpeningBook, readable_tree::ReadableTreeNode};
+
+const WORD_SIZE: usize = 5;
+
+fn main() {
+    let tree = ReadableTreeNode::<WORD_SIZE>::load(
+        &args()
+            .nth(1)
+            .expect("must supply a tree file path and optionally a number of plies"),
+    )
+    .expect("failed to load tree");
+    let num_plies: u8 = args().nth(2).map_or(2, |raw| {
+        raw.parse().expect("number of plies must be an integer")
+    });
+
+    println!("{}", OpeningBook::from_tree(&tree, num_plies).to_json());
+}
@@ -0,0 +1,37 @@
+use std::{env::args, path::PathBuf};
+
+use word_core::{
+    load_words::load_guesses_and_answers_from_args,
+    readable_tree::ReadableTreeNode,
+    starter_pack::{StarterPackConfig, build_starter_pack},
+};
+
+const WORD_SIZE: usize = 5;
+
+fn main() {
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args::<WORD_SIZE, 26>(true)
+            .expect("failed to load word lists");
+    let allowed_name = args().nth(1).unwrap();
+    let answers_name = args().nth(2).unwrap();
+    let out_dir = PathBuf::from(
+        args()
+            .nth(3)
+            .expect("output directory required as third arg"),
+    );
+    let tree = args()
+        .nth(4)
+        .map(|path| ReadableTreeNode::<WORD_SIZE>::load(&path).expect("failed to load tree"));
+
+    let manifest = build_starter_pack(
+        (&allowed_name, &allowed_guesses),
+        (&answers_name, &possible_answers),
+        tree.as_ref(),
+        &StarterPackConfig::default(),
+        &out_dir,
+    )
+    .expect("failed to build starter pack");
+    for file in &manifest.files {
+        println!("{}\t{}", file.hash, out_dir.join(&file.name).display());
+    }
+}
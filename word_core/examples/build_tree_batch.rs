@@ -0,0 +1,40 @@
+use std::{env::args, fs};
+
+use word_core::{
+    batch_build::{BatchConfig, BuildJob, build_trees},
+    worker_pool::PoolConfig,
+};
+
+fn main() {
+    let jobs_path = args()
+        .nth(1)
+        .expect("must supply a JSON file of build jobs and optionally a number of threads");
+    let jobs: Vec<BuildJob> =
+        serde_json::from_str(&fs::read_to_string(&jobs_path).expect("failed to read build jobs"))
+            .expect("failed to parse build jobs");
+    let num_threads: Option<usize> = args()
+        .nth(2)
+        .map(|raw| raw.parse().expect("number of threads must be an integer"));
+
+    // Set globally so the hint matrices built inside each job share the thread count
+    PoolConfig::set_threads(num_threads);
+    println!("building {} trees...", jobs.len());
+    let results = build_trees(&jobs, BatchConfig::default()).expect("batch failed");
+    for (job, result) in jobs.iter().zip(results) {
+        match result {
+            Ok(header) => println!(
+                "{}: est cost {:.4}{} in {:.3}s -> {}",
+                job.name,
+                header.est_cost,
+                if header.approximate {
+                    " (approximate)"
+                } else {
+                    ""
+                },
+                header.build_millis as f64 / 1000.0,
+                job.output_path.display()
+            ),
+            Err(err) => println!("{}: failed - {}", job.name, err),
+        }
+    }
+}
@@ -7,7 +7,8 @@ use word_core::{
 const WORD_SIZE: usize = 3;
 
 fn main() {
-    let words = load_words(&args().nth(1).expect("Must supply word list as first arg"));
+    let words = load_words(&args().nth(1).expect("Must supply word list as first arg"))
+        .expect("failed to load word list");
     println!("loaded {} words", words.len());
 
     let possible_answers: SearchableWords<WORD_SIZE, 26> = SearchableWords::build(words.clone());
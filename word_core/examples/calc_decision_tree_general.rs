@@ -1,61 +1,26 @@
-use std::{collections::HashMap, time::Instant};
+use std::{env::args, path::PathBuf, sync::Arc, time::Instant};
 
-use serde::{Deserialize, Serialize};
 use word_core::{
-    decision_tree_general::{DebugPrinter, GuessFrom, TreeNode, compute_decision_tree_aggressive},
+    decision_tree_general::{
+        CheckpointConfig, DebugPrinter, DepthSettings, PrintingObserver, SearchOptions,
+        compute_decision_tree_bounded, compute_decision_tree_checkpointed,
+    },
     hint::WordHint,
+    hint_matrix::compute_hints,
     load_words::load_guesses_and_answers_from_args,
-    query_generation::{clue_possible, clue_to_query},
+    readable_tree::ReadableTreeNode,
     word::Word,
-    word_search::SearchableWords,
+    word_search::{LetterScreen, SearchableWords},
+    worker_pool::CancelToken,
 };
 
 const WORD_SIZE: usize = 5;
 const ALPHABET_SIZE: u8 = 26;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReadableTreeNode {
-    should_guess: Word<WORD_SIZE, ALPHABET_SIZE>,
-    est_cost: f64,
-    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
-    next: HashMap<WordHint<WORD_SIZE>, ReadableTreeNode>,
-}
-
-impl ReadableTreeNode {
-    fn from_generalized_tree_node(
-        tree_node: &TreeNode,
-        allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
-        possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
-    ) -> Self {
-        Self {
-            should_guess: match tree_node.should_guess {
-                GuessFrom::Guess(guess_ind) => allowed_guesses[guess_ind as usize],
-                GuessFrom::Answer(answer_ind) => possible_answers[answer_ind as usize],
-            },
-            est_cost: tree_node.est_cost,
-            next: tree_node
-                .next
-                .iter()
-                .map(|(hint_id, next_node)| {
-                    (
-                        WordHint::from_id(*hint_id),
-                        ReadableTreeNode::from_generalized_tree_node(
-                            &next_node,
-                            allowed_guesses,
-                            possible_answers,
-                        ),
-                    )
-                })
-                .collect(),
-        }
-    }
-}
-
 struct MyDebugPrinter<'a> {
     allowed_guesses: &'a [Word<WORD_SIZE, ALPHABET_SIZE>],
     possible_answers: &'a [Word<WORD_SIZE, ALPHABET_SIZE>],
     max_print_depth: Option<u8>,
-    prefix: String,
 }
 
 impl<'a> DebugPrinter for MyDebugPrinter<'a> {
@@ -82,65 +47,93 @@ impl<'a> DebugPrinter for MyDebugPrinter<'a> {
             None => true,
         }
     }
-
-    fn with_prefix(&self, prefix: String) -> Self {
-        Self {
-            allowed_guesses: self.allowed_guesses,
-            possible_answers: self.possible_answers,
-            max_print_depth: self.max_print_depth,
-            prefix: format!("{}{}", self.prefix, prefix),
-        }
-    }
-
-    fn get_prefix(&self) -> &str {
-        &self.prefix
-    }
 }
 
 fn main() {
-    let (allowed_guesses, possible_answers) = load_guesses_and_answers_from_args(true);
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args(true).expect("failed to load word lists");
+    // How far above optimal the tree may be, traded for a faster search
+    let tolerance: f64 = args()
+        .nth(3)
+        .map(|raw| raw.parse().expect("tolerance must be a number"))
+        .unwrap_or(0.0);
+    // Pass "parallel" to spread the opening guesses across threads
+    let parallel_root = args().nth(4).is_some_and(|raw| raw == "parallel");
+    // Pass a file to save progress to every minute, resuming from it if it's already there, or
+    // "-" to not save progress
+    let checkpoint = args()
+        .nth(5)
+        .filter(|path| path != "-")
+        .map(|path| CheckpointConfig {
+            path: PathBuf::from(path),
+            interval_millis: 60_000,
+        });
+    // Evaluate only this many of the most promising guesses at each node, for a faster tree
+    // that may not be optimal
+    let beam_width: Option<usize> = args()
+        .nth(6)
+        .map(|raw| raw.parse().expect("beam width must be a whole number"));
 
     println!("precomputing all hints...");
     let start = Instant::now();
-    let mut all_hints: Vec<Vec<u8>> = Vec::with_capacity(allowed_guesses.len());
-    let searchable_answers = SearchableWords::build(possible_answers.clone());
-    for guess in &allowed_guesses {
-        let mut hints_for_guess = vec![0; possible_answers.len()];
-        for hint in WordHint::all_possible() {
-            if !clue_possible(*guess, hint) {
-                continue;
-            }
-            let answers_giving_this_hint_mask =
-                searchable_answers.eval_query(clue_to_query(*guess, hint));
-            let hint_id = hint.hint_id();
-            for answer_ind in answers_giving_this_hint_mask.true_inds() {
-                hints_for_guess[answer_ind] = hint_id;
-            }
-        }
-        all_hints.push(hints_for_guess);
-    }
+    let all_hints = compute_hints(&allowed_guesses, &possible_answers);
     let total_elapsed = start.elapsed().as_secs_f64();
     println!("done in {:.3}s", total_elapsed);
 
     println!("generating decision tree...");
     let start = Instant::now();
-    let decision_tree = compute_decision_tree_aggressive(
-        &all_hints,
-        (0..possible_answers.len() as u16).into_iter().collect(),
-        0,
-        6,
-        3.0402,
-        // None::<&MyDebugPrinter>,
-        Some(&MyDebugPrinter {
-            allowed_guesses: &allowed_guesses,
-            possible_answers: &possible_answers,
-            max_print_depth: Some(0),
-            prefix: "".to_string(),
-        }),
-    )
+    // Ctrl-C stops the search and prints the best tree found so far
+    let cancel = CancelToken::new();
+    let handler_cancel = cancel.clone();
+    ctrlc::set_handler(move || handler_cancel.cancel()).expect("failed to set Ctrl-C handler");
+    let options = SearchOptions {
+        tolerance,
+        parallel_root,
+        cancel: Some(cancel),
+        depth_settings: vec![DepthSettings {
+            beam_width,
+            ..DepthSettings::default()
+        }],
+        letter_screen: Some(Arc::new(LetterScreen::new(
+            &allowed_guesses,
+            &SearchableWords::build(possible_answers.clone()),
+        ))),
+        ..SearchOptions::default()
+    };
+    let printer = MyDebugPrinter {
+        allowed_guesses: &allowed_guesses,
+        possible_answers: &possible_answers,
+        max_print_depth: Some(0),
+    };
+    let observer = PrintingObserver::new(&printer);
+    let answers = (0..possible_answers.len() as u16).collect();
+    let bounded = match &checkpoint {
+        Some(checkpoint) => compute_decision_tree_checkpointed(
+            &all_hints,
+            answers,
+            0,
+            6,
+            f64::INFINITY,
+            options,
+            checkpoint,
+            Some(&observer),
+        )
+        .expect("failed to checkpoint search"),
+        None => compute_decision_tree_bounded(
+            &all_hints,
+            answers,
+            0,
+            6,
+            f64::INFINITY,
+            options,
+            Some(&observer),
+        )
+        .expect("failed to search"),
+    }
     .expect("failed to compute top-level result");
+    let decision_tree = &bounded.tree;
     let readable_decision_tree = ReadableTreeNode::from_generalized_tree_node(
-        &decision_tree,
+        decision_tree,
         &allowed_guesses,
         &possible_answers,
     );
@@ -149,6 +142,9 @@ fn main() {
         "{}",
         serde_json::to_string_pretty(&readable_decision_tree).unwrap()
     );
-    println!("est cost: {}", decision_tree.est_cost);
+    println!("est cost: {}", decision_tree.est_cost());
+    if bounded.gap() > 0.0 {
+        println!("stopped early, at most {} above optimal", bounded.gap());
+    }
     println!("done in {:.3}s", total_elapsed);
 }
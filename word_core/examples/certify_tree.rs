@@ -0,0 +1,28 @@
+use std::env::args;
+
+use word_core::{
+    certificate::Certificate, load_words::load_words, mask_encoding::WordListId,
+    readable_tree::ReadableTreeNode,
+};
+
+const WORD_SIZE: usize = 5;
+
+fn main() {
+    let tree = ReadableTreeNode::<WORD_SIZE>::load(&args().nth(1).expect(
+        "must supply a tree file path, an answers file path and optionally a max number of guesses",
+    ))
+    .expect("failed to load tree");
+    let answers_path = args()
+        .nth(2)
+        .expect("answers word list file required as second arg");
+    let possible_answers = load_words(&answers_path).expect("failed to load answers");
+    let max_guesses: usize = args().nth(3).map_or(6, |raw| {
+        raw.parse()
+            .expect("max number of guesses must be an integer")
+    });
+
+    let list = WordListId::new(&answers_path, &possible_answers);
+    let certificate = Certificate::from_tree(&tree, &list, &possible_answers, max_guesses)
+        .expect("tree doesn't solve every answer in time");
+    println!("{}", certificate.to_json());
+}
@@ -0,0 +1,48 @@
+use std::env::args;
+
+use word_core::{
+    load_words::load_guesses_and_answers_from_args,
+    priors::AnswerPriors,
+    ruleset::Standard,
+    simulator::{SimulationConfig, compare_strategies},
+    strategy::{GreedyObjective, GreedyStrategy, Strategy},
+};
+
+const WORD_SIZE: usize = 5;
+const ALPHABET_SIZE: u8 = 26;
+
+fn main() {
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args::<WORD_SIZE, ALPHABET_SIZE>(true)
+            .expect("failed to load word lists");
+    // Optional third arg: a file of `word weight` lines to compare weighted variants against
+    let priors = args()
+        .nth(3)
+        .map(|path| AnswerPriors::load(&path, 0.0).expect("failed to load priors"));
+
+    let mut strategies: Vec<GreedyStrategy<WORD_SIZE, ALPHABET_SIZE>> = vec![];
+    for objective in [
+        GreedyObjective::Entropy,
+        GreedyObjective::ExpectedRemaining,
+        GreedyObjective::Minimax,
+    ] {
+        strategies.push(GreedyStrategy::unweighted(objective));
+        if let Some(priors) = &priors {
+            strategies.push(GreedyStrategy::weighted(objective, priors.clone()));
+        }
+    }
+    let strategy_refs: Vec<&(dyn Strategy<WORD_SIZE, ALPHABET_SIZE> + Sync)> = strategies
+        .iter()
+        .map(|strategy| strategy as &(dyn Strategy<WORD_SIZE, ALPHABET_SIZE> + Sync))
+        .collect();
+
+    let report = compare_strategies(
+        &Standard,
+        &strategy_refs,
+        &allowed_guesses,
+        &possible_answers,
+        &SimulationConfig::default(),
+    )
+    .expect("failed to build thread pool");
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
@@ -0,0 +1,43 @@
+use std::{env::args, path::Path};
+
+use word_core::batch_build::ArtifactHeader;
+
+fn main() {
+    let paths: Vec<String> = args().skip(1).collect();
+    let [left_path, right_path] = paths.as_slice() else {
+        panic!("must supply exactly two tree artifact files");
+    };
+    let read_header =
+        |path: &str| ArtifactHeader::read(Path::new(path)).expect("failed to read artifact header");
+    let left_header = read_header(left_path);
+    let right_header = read_header(right_path);
+
+    // The headers record each run's result, and the manifests what went into it
+    println!(
+        "trees: {} vs {}{}",
+        left_header.tree_hash,
+        right_header.tree_hash,
+        if left_header.tree_hash == right_header.tree_hash {
+            " (same)"
+        } else {
+            ""
+        }
+    );
+    println!(
+        "est cost: {:.4} vs {:.4}",
+        left_header.est_cost, right_header.est_cost
+    );
+    let left = left_header
+        .manifest
+        .unwrap_or_else(|| panic!("{} has no run manifest", left_path));
+    let right = right_header
+        .manifest
+        .unwrap_or_else(|| panic!("{} has no run manifest", right_path));
+    let differences = left.input_differences(&right);
+    if differences.is_empty() {
+        println!("inputs are identical");
+    }
+    for (field, left_value, right_value) in differences {
+        println!("{}: {} vs {}", field, left_value, right_value);
+    }
+}
@@ -0,0 +1,60 @@
+use std::{env::args, fs};
+
+use word_core::{
+    load_words::load_guesses_and_answers_from_args, loss_analysis::explain_loss,
+    readable_tree::ReadableTreeNode, transcript::Transcript, word::Word,
+};
+
+const WORD_SIZE: usize = 5;
+const MAX_GUESSES: u8 = 6;
+
+fn main() {
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args::<WORD_SIZE, 26>(true)
+            .expect("failed to load word lists");
+    let transcript_path = args()
+        .nth(3)
+        .expect("transcript file required as third arg");
+    let raw = fs::read_to_string(&transcript_path).expect("failed to read transcript");
+    let transcript: Transcript<WORD_SIZE> =
+        Transcript::from_json(&raw).expect("failed to parse transcript");
+    let answer: Word<WORD_SIZE, 26> = args()
+        .nth(4)
+        .expect("answer required as fourth arg")
+        .parse()
+        .expect("invalid answer");
+    let tree = args()
+        .nth(5)
+        .map(|path| ReadableTreeNode::<WORD_SIZE>::load(&path).expect("failed to load tree"));
+
+    let analysis = explain_loss(
+        &transcript,
+        answer,
+        &allowed_guesses,
+        &possible_answers,
+        MAX_GUESSES,
+        tree.as_ref(),
+    )
+    .expect("failed to analyze game");
+
+    let format_cost =
+        |cost: Option<f64>| cost.map_or("-".to_string(), |cost| format!("{:.3}", cost));
+    for (turn_ind, turn) in analysis.turns.iter().enumerate() {
+        println!(
+            "{}. {} {}  {} left, best {} ({}), actual {}, delta {}",
+            turn_ind + 1,
+            turn.guess,
+            turn.hint,
+            turn.num_candidates,
+            turn.optimal_guess
+                .map_or("-".to_string(), |guess| guess.to_string()),
+            format_cost(turn.optimal_cost),
+            format_cost(turn.actual_cost),
+            format_cost(turn.cost_delta()),
+        );
+    }
+    match analysis.first_mistake {
+        Some(turn_ind) => println!("first mistake: guess {}", turn_ind + 1),
+        None => println!("no mistakes"),
+    }
+}
@@ -7,16 +7,19 @@ use word_core::{
     hint::WordHint,
     load_words::load_guesses_and_answers_from_args,
     query_generation::{clue_possible, clue_to_query},
+    render::render_mode,
+    text_plot::histogram,
     word::Word,
     word_search::SearchableWords,
 };
 
 const WORD_SIZE: usize = 5;
 const ALPHABET_SIZE: u8 = 26;
-const MAX_BAR_SIZE: f64 = 64.0;
+const MAX_BAR_SIZE: usize = 64;
 
 fn main() {
-    let (allowed_guesses, possible_answers) = load_guesses_and_answers_from_args(true);
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args(true).expect("failed to load word lists");
 
     println!("<- running query engine scan ->");
     let start = Instant::now();
@@ -44,26 +47,27 @@ fn main() {
 
     // Get the distribution of num possible hints across guesses
     let mut num_guesses_by_num_possible_hints: HashMap<usize, usize> = HashMap::new();
-    for (_guess, possible_hints) in &possible_hints_per_guess {
+    for possible_hints in possible_hints_per_guess.values() {
         *num_guesses_by_num_possible_hints
             .entry(possible_hints.len())
             .or_insert(0) += 1
     }
     let max_num_guesses = *num_guesses_by_num_possible_hints.keys().max().unwrap();
-    let max_possible_hints = *num_guesses_by_num_possible_hints.values().max().unwrap();
     println!("# hints\t# guesses");
-    for i in 0..max_num_guesses {
-        let num_guesses = num_guesses_by_num_possible_hints
-            .get(&i)
-            .cloned()
-            .unwrap_or(0);
-        let bar_size = MAX_BAR_SIZE * num_guesses as f64 / max_possible_hints as f64;
-        let bar = (0..bar_size.round() as u64)
-            .map(|_| "=")
-            .collect::<Vec<&str>>()
-            .join("");
-        println!("{i}\t{num_guesses}\t| {bar}");
-    }
+    print!(
+        "{}",
+        histogram(
+            (0..max_num_guesses).map(|i| (
+                i,
+                num_guesses_by_num_possible_hints
+                    .get(&i)
+                    .cloned()
+                    .unwrap_or(0)
+            )),
+            MAX_BAR_SIZE,
+            render_mode(),
+        )
+    );
     let possible_hints_as_list: Vec<usize> = possible_hints_per_guess
         .values()
         .map(|possible_hints| possible_hints.len())
@@ -0,0 +1,66 @@
+use std::{env::args, time::Instant};
+
+use word_core::{
+    decision_tree_general::{NoPrinter, SearchOptions, rank_openers},
+    hint_matrix::compute_hints,
+    load_words::load_guesses_and_answers_from_args,
+    word::Word,
+};
+
+const WORD_SIZE: usize = 5;
+const ALPHABET_SIZE: u8 = 26;
+
+fn main() {
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args::<WORD_SIZE, ALPHABET_SIZE>(true)
+            .expect("failed to load word lists");
+    // Every arg after the word lists is an opener to evaluate
+    let candidates: Vec<u16> = args()
+        .skip(3)
+        .map(|raw| {
+            let word = Word::try_from(raw.as_str()).expect("opener must be a valid word");
+            allowed_guesses
+                .iter()
+                .position(|guess| *guess == word)
+                .expect("opener must be an allowed guess") as u16
+        })
+        .collect();
+    if candidates.is_empty() {
+        panic!("must supply at least one opener after the word lists");
+    }
+
+    println!("precomputing all hints...");
+    let all_hints = compute_hints(&allowed_guesses, &possible_answers);
+
+    println!("evaluating {} openers...", candidates.len());
+    let start = Instant::now();
+    let ranked = rank_openers(
+        &all_hints,
+        (0..possible_answers.len() as u16).collect(),
+        6,
+        f64::INFINITY,
+        &candidates,
+        SearchOptions::default(),
+        None::<&NoPrinter>,
+    );
+    println!("rank\topener\ttotal\test cost");
+    for (rank, cost) in ranked.iter().enumerate() {
+        let opener = allowed_guesses[cost.guess_ind as usize];
+        match (cost.total_guesses, cost.est_cost()) {
+            (Some(total_guesses), Some(est_cost)) => println!(
+                "{}\t{}\t{}\t{:.4}{}",
+                rank + 1,
+                opener,
+                total_guesses,
+                est_cost,
+                if cost.approximate {
+                    " (approximate)"
+                } else {
+                    ""
+                }
+            ),
+            _ => println!("{}\t{}\t-\tno tree within 6 guesses", rank + 1, opener),
+        }
+    }
+    println!("done in {:.3}s", start.elapsed().as_secs_f64());
+}
@@ -0,0 +1,36 @@
+use std::env::args;
+
+use word_core::self_test::{SelfTestConfig, run_self_test};
+
+const WORD_SIZE: usize = 5;
+
+fn main() {
+    let allowed_guesses_path = args()
+        .nth(1)
+        .expect("allowed guesses file required as first arg");
+    let possible_answers_path = args()
+        .nth(2)
+        .expect("possible answers file required as second arg");
+    let report = run_self_test::<WORD_SIZE>(
+        &allowed_guesses_path,
+        &possible_answers_path,
+        SelfTestConfig::default(),
+    );
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report.environment).unwrap()
+    );
+    for check in &report.checks {
+        println!(
+            "[{}] {} ({}ms): {}",
+            if check.passed { "ok" } else { "FAIL" },
+            check.name,
+            check.millis,
+            check.detail
+        );
+    }
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Instant;
+
+use word_core::{
+    decision_tree_general::{DebugPrinter, NodeBudget, PrintingObserver, SearchOptions},
+    hint::WordHint,
+    mastermind::MastermindSetting,
+};
+
+const CODE_LENGTH: usize = 4;
+const NUM_COLORS: u8 = 6;
+
+struct CodePrinter<'a> {
+    setting: &'a MastermindSetting<CODE_LENGTH, NUM_COLORS>,
+}
+
+impl<'a> CodePrinter<'a> {
+    fn fmt_code(&self, ind: u16) -> String {
+        self.setting.codes[ind as usize]
+            .0
+            .iter()
+            .map(|color| (b'1' + color) as char)
+            .collect()
+    }
+}
+
+impl<'a> DebugPrinter for CodePrinter<'a> {
+    fn fmt_guess(&self, guess_ind: u16) -> String {
+        self.fmt_code(guess_ind)
+    }
+
+    fn fmt_answer(&self, answer_ind: u16) -> String {
+        self.fmt_code(answer_ind)
+    }
+
+    fn fmt_hint(&self, hint_id: u8) -> String {
+        format!("{}", WordHint::<CODE_LENGTH>::from_id(hint_id))
+    }
+
+    fn fmt_clue(&self, hint_id: u8, guess_ind: u16) -> String {
+        format!("{} {}", self.fmt_code(guess_ind), self.fmt_hint(hint_id))
+    }
+
+    fn should_print_at_depth(&self, depth: u8) -> bool {
+        depth == 0
+    }
+}
+
+fn main() {
+    let setting = MastermindSetting::<CODE_LENGTH, NUM_COLORS>::new(true);
+    println!("generated {} codes", setting.codes.len());
+
+    // An exhaustive search of every subtree takes hours, but any tree that finishes within 5
+    // guesses is proof enough, so cap each subtree and let slow ones fall back to greedy
+    let options = SearchOptions {
+        budget: NodeBudget {
+            max_nodes: None,
+            max_millis: Some(100),
+        },
+        ..SearchOptions::default()
+    };
+    let start = Instant::now();
+    let tree = setting
+        .solve(
+            5,
+            4.5,
+            options,
+            Some(&PrintingObserver::new(&CodePrinter { setting: &setting })),
+        )
+        .expect("no strategy solves every code within 5 guesses");
+    let worst_case = setting
+        .codes
+        .iter()
+        .map(|code| {
+            setting
+                .guesses_needed(&tree, code)
+                .expect("tree is missing a branch")
+        })
+        .max()
+        .unwrap();
+    println!("est cost: {}", tree.est_cost());
+    println!("worst case: {} guesses", worst_case);
+    println!("done in {:.3}s", start.elapsed().as_secs_f64());
+}
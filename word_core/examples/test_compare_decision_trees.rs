@@ -1,25 +1,33 @@
-use std::{collections::HashMap, env::args, fs};
+use std::env::args;
 
-use serde::{Deserialize, Serialize};
-use word_core::{hint::WordHint, word::Word};
+use word_core::{
+    load_words::load_words, readable_tree::ReadableTreeNode, tree_report::compare_trees,
+};
 
 const WORD_SIZE: usize = 5;
-const ALPHABET_SIZE: u8 = 26;
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct TreeNode {
-    should_guess: Word<WORD_SIZE, ALPHABET_SIZE>,
-    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
-    next: HashMap<WordHint<WORD_SIZE>, TreeNode>,
-}
-
-fn load_tree(file_path: &str) -> TreeNode {
-    serde_json::from_str(&fs::read_to_string(file_path).unwrap()).unwrap()
-}
 
 fn main() {
-    let tree_a = load_tree(&args().nth(1).expect("must supply two file paths"));
-    let tree_b = load_tree(&args().nth(2).expect("must supply two file paths"));
+    let answers = load_words::<WORD_SIZE, 26>(
+        &args()
+            .nth(1)
+            .expect("must supply an answer list and at least two tree file paths"),
+    )
+    .expect("failed to load answer list");
+    let tree_paths: Vec<String> = args().skip(2).collect();
+    assert!(
+        tree_paths.len() >= 2,
+        "must supply an answer list and at least two tree file paths"
+    );
+    let trees: Vec<ReadableTreeNode<WORD_SIZE>> = tree_paths
+        .iter()
+        .map(|tree_path| ReadableTreeNode::load(tree_path).expect("failed to load tree"))
+        .collect();
 
-    assert_eq!(tree_a, tree_b);
+    let named_trees: Vec<(&str, &ReadableTreeNode<WORD_SIZE>)> = tree_paths
+        .iter()
+        .map(String::as_str)
+        .zip(trees.iter())
+        .collect();
+    let report = compare_trees(&named_trees, &answers);
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
 }
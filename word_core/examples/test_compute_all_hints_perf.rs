@@ -10,7 +10,8 @@ use word_core::{
 const WORD_SIZE: usize = 5;
 
 fn main() {
-    let (allowed_guesses, possible_answers) = load_guesses_and_answers_from_args(true);
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args(true).expect("failed to load word lists");
 
     println!("<- testing simple scan ->");
     let start = Instant::now();
@@ -4,18 +4,16 @@ use std::{
 };
 
 use word_core::{
-    hint::WordHint,
-    load_words::load_guesses_and_answers_from_args,
-    query_generation::{clue_possible, clue_to_query},
+    hint::WordHint, load_words::load_guesses_and_answers_from_args, possible_hints::PossibleHints,
     word::Word,
-    word_search::SearchableWords,
 };
 
 const WORD_SIZE: usize = 5;
 const ALPHABET_SIZE: u8 = 26;
 
 fn main() {
-    let (allowed_guesses, possible_answers) = load_guesses_and_answers_from_args(true);
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args(true).expect("failed to load word lists");
 
     println!("<- testing simple scan ->");
     let start = Instant::now();
@@ -26,7 +24,7 @@ fn main() {
     for guess in &allowed_guesses {
         let mut possible_hints: HashSet<WordHint<WORD_SIZE>> = HashSet::new();
         for answer in &possible_answers {
-            let hint = WordHint::from_guess_and_answer(&guess, answer);
+            let hint = WordHint::from_guess_and_answer(guess, answer);
             possible_hints.insert(hint);
         }
         possible_hints_per_guess_simple.insert(*guess, possible_hints);
@@ -40,20 +38,10 @@ fn main() {
         Word<WORD_SIZE, ALPHABET_SIZE>,
         HashSet<WordHint<WORD_SIZE>>,
     > = HashMap::new();
-    let searchable_answers = SearchableWords::build(possible_answers);
-    for guess in &allowed_guesses {
-        let mut possible_hints: HashSet<WordHint<WORD_SIZE>> = HashSet::new();
-        for hint in WordHint::all_possible() {
-            if !clue_possible(*guess, hint) {
-                continue;
-            }
-            let answers_giving_this_hint_mask =
-                searchable_answers.eval_query(clue_to_query(*guess, hint));
-            if answers_giving_this_hint_mask.count_true() > 0 {
-                possible_hints.insert(hint);
-            }
-        }
-        possible_hints_per_guess_query_engine.insert(*guess, possible_hints);
+    let possible_hints = PossibleHints::build(allowed_guesses.clone(), possible_answers);
+    for (guess_ind, guess) in allowed_guesses.iter().enumerate() {
+        possible_hints_per_guess_query_engine
+            .insert(*guess, possible_hints.iter_feasible(guess_ind).collect());
     }
     let total_elapsed = start.elapsed().as_secs_f64();
     println!("finished in {:.3}s", total_elapsed);
@@ -61,7 +49,6 @@ fn main() {
     assert_eq!(
         possible_hints_per_guess_simple
             .keys()
-            .into_iter()
             .cloned()
             .collect::<HashSet<Word<WORD_SIZE, ALPHABET_SIZE>>>(),
         allowed_guesses
@@ -72,7 +59,6 @@ fn main() {
     assert_eq!(
         possible_hints_per_guess_query_engine
             .keys()
-            .into_iter()
             .cloned()
             .collect::<HashSet<Word<WORD_SIZE, ALPHABET_SIZE>>>(),
         allowed_guesses
@@ -91,7 +77,7 @@ fn main() {
             .difference(possible_hints_simple)
             .collect();
 
-        if a_not_b.len() > 0 || b_not_a.len() > 0 {
+        if !a_not_b.is_empty() || !b_not_a.is_empty() {
             println!("Two scans got different results for {}", guess);
             println!("Hints discovered by simple scan but not query engine:");
             for hint in a_not_b {
@@ -101,7 +87,7 @@ fn main() {
             for hint in b_not_a {
                 println!("{}", hint);
             }
-            println!("");
+            println!();
         }
     }
     println!("both scans gave equivalent results")
@@ -9,7 +9,8 @@ const WORD_SIZE: usize = 5;
 const ALPHABET_SIZE: u8 = 26;
 
 fn main() {
-    let words = load_words(&args().nth(1).expect("Must supply word list as first arg"));
+    let words = load_words(&args().nth(1).expect("Must supply word list as first arg"))
+        .expect("failed to load word list");
     let num_trials = words.len() * words.len();
 
     println!("loaded {} words -> {} test cases", words.len(), num_trials);
@@ -1,16 +1,20 @@
 use std::{cmp::min, env::args, time::Instant};
 
 use word_core::{
-    dumb_word_search::dumb_search_words, hint::WordHint, load_words::load_words,
-    query_generation::clue_to_query, word_search::SearchableWords,
+    dumb_word_search::dumb_search_words,
+    hint::WordHint,
+    load_words::load_words,
+    query_generation::{CountForm, clue_to_query_with_count_form},
+    word_search::SearchableWords,
 };
 
 const WORD_SIZE: usize = 5;
 
 fn main() {
-    let words = load_words(&args().nth(1).expect("Must supply word list as first arg"));
+    let words = load_words(&args().nth(1).expect("Must supply word list as first arg"))
+        .expect("failed to load word list");
 
-    let limit_trials: Option<usize> = args().nth(2).map(|limit| limit.parse().ok()).flatten();
+    let limit_trials: Option<usize> = args().nth(2).and_then(|limit| limit.parse().ok());
 
     let num_trials = match limit_trials {
         Some(limit) => min(limit, words.len() * words.len()),
@@ -57,40 +61,42 @@ fn main() {
         num_trials as f64 / total_elapsed
     );
 
-    println!("<- testing smart search ->");
-    let start = Instant::now();
-    let mut i = 0;
-    for answer in &words {
-        for guess in &words {
-            if i >= num_trials {
-                break;
-            }
-            if i % 10000 == 0 {
-                let elapsed_s = start.elapsed().as_secs_f64();
-                let total_est = (elapsed_s * num_trials as f64) / i as f64;
-                println!(
-                    "finished {} in {:.3}s - {:.2} iter/s - {:.3}s remaining - {:.3}s total",
-                    i,
-                    elapsed_s,
-                    i as f64 / elapsed_s,
-                    total_est - elapsed_s,
-                    total_est,
-                );
-            }
+    for count_form in [CountForm::Exact, CountForm::Bounds] {
+        println!("<- testing smart search with {:?} counts ->", count_form);
+        let start = Instant::now();
+        let mut i = 0;
+        for answer in &words {
+            for guess in &words {
+                if i >= num_trials {
+                    break;
+                }
+                if i % 10000 == 0 {
+                    let elapsed_s = start.elapsed().as_secs_f64();
+                    let total_est = (elapsed_s * num_trials as f64) / i as f64;
+                    println!(
+                        "finished {} in {:.3}s - {:.2} iter/s - {:.3}s remaining - {:.3}s total",
+                        i,
+                        elapsed_s,
+                        i as f64 / elapsed_s,
+                        total_est - elapsed_s,
+                        total_est,
+                    );
+                }
 
-            let word_hint = WordHint::from_guess_and_answer(guess, answer);
+                let word_hint = WordHint::from_guess_and_answer(guess, answer);
 
-            // Get possible answers via smart search
-            let query = clue_to_query(*guess, word_hint);
-            smart_search.filter_words(&smart_search.eval_query(query.clone()));
-            i += 1;
+                // Get possible answers via smart search
+                let query = clue_to_query_with_count_form(*guess, word_hint, count_form);
+                smart_search.filter_words(&smart_search.eval_query(query.clone()));
+                i += 1;
+            }
         }
+        let total_elapsed = start.elapsed().as_secs_f64();
+        println!(
+            "finished {} in {:.3}s - {:.2} iter/s",
+            num_trials,
+            total_elapsed,
+            num_trials as f64 / total_elapsed
+        );
     }
-    let total_elapsed = start.elapsed().as_secs_f64();
-    println!(
-        "finished {} in {:.3}s - {:.2} iter/s",
-        num_trials,
-        total_elapsed,
-        num_trials as f64 / total_elapsed
-    );
 }
@@ -0,0 +1,30 @@
+use std::{env::args, fs};
+
+use word_core::{certificate::Certificate, load_words::load_guesses_and_answers_from_args};
+
+const WORD_SIZE: usize = 5;
+
+fn main() {
+    let (allowed_guesses, possible_answers) =
+        load_guesses_and_answers_from_args::<WORD_SIZE, 26>(true)
+            .expect("failed to load word lists");
+    let certificate_path = args()
+        .nth(3)
+        .expect("certificate file required as third arg");
+    let raw = fs::read_to_string(&certificate_path).expect("failed to read certificate");
+    let certificate: Certificate<WORD_SIZE> =
+        Certificate::from_json(&raw).expect("failed to parse certificate");
+
+    match certificate.verify(&possible_answers, &allowed_guesses) {
+        Ok(worst_case) => println!(
+            "verified: all {} answers solved within {} guesses (claimed {})",
+            possible_answers.len(),
+            worst_case,
+            certificate.max_guesses
+        ),
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
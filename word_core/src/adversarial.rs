@@ -0,0 +1,226 @@
+use crate::{
+    bounded_cache::{BoundedCache, MemoryFootprint},
+    column::Column,
+    decision_tree_general::count_by_hint,
+    hint::WordHint,
+    hint_matrix::{answers_first, compute_hints},
+    mask_encoding::{FNV_OFFSET_BASIS, fnv1a},
+    ruleset::absurdle_response,
+    word::Word,
+};
+
+/// Find the fewest guesses that win Absurdle against every possible answer, within
+/// `max_guesses`, returning each guess with the hint the host gives it.
+///
+/// The host keeps the largest bucket of candidates after every guess, settling ties as the
+/// `Absurdle` ruleset does, so each guess leads to exactly one position and the worst case is
+/// the only case. The search deepens one guess at a time, so the first line it finds is the
+/// shortest.
+///
+/// Sets of candidates already shown not to be won are remembered within `memo_bytes` of memory,
+/// dropping the least recently used first.
+pub fn solve_absurdle<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    max_guesses: u8,
+    memo_bytes: usize,
+) -> Option<Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)>> {
+    if possible_answers.is_empty() {
+        return None;
+    }
+    let guesses = answers_first(possible_answers, allowed_guesses);
+    let mut search = AbsurdleSearch::<WORD_SIZE> {
+        hints: &compute_hints(&guesses, possible_answers),
+        max_hints: 3u32
+            .saturating_pow(WORD_SIZE as u32)
+            .min(u8::MAX as u32 + 1),
+        lost_within: BoundedCache::with_memory_budget(memo_bytes),
+    };
+    let candidates = Column::from_true(possible_answers.len());
+    let line = (1..=max_guesses).find_map(|limit| search.solve(&candidates, limit))?;
+    Some(
+        line.into_iter()
+            .map(|(guess_ind, hint)| (guesses[guess_ind as usize], WordHint::from_id(hint)))
+            .collect(),
+    )
+}
+
+/// The search over sets of candidates the host could have left.
+struct AbsurdleSearch<'a, const WORD_SIZE: usize> {
+    hints: &'a [Vec<u8>],
+    /// The most distinct hints a guess can get
+    max_hints: u32,
+    /// The most guesses each set of candidates is known not to be won within, keyed on a hash
+    /// of the set
+    lost_within: BoundedCache<u64, LostEntry>,
+}
+
+/// A memo entry, keeping the exact set of candidates so a lookup whose hash collides with a
+/// different set's is treated as a miss.
+struct LostEntry {
+    candidates: Vec<u64>,
+    lost_within: u8,
+}
+
+impl MemoryFootprint for LostEntry {
+    fn memory_footprint(&self) -> usize {
+        size_of::<Self>() + self.candidates.len() * size_of::<u64>()
+    }
+}
+
+fn candidates_hash(candidates: &Column) -> u64 {
+    fnv1a(
+        FNV_OFFSET_BASIS,
+        candidates
+            .to_chunks()
+            .iter()
+            .flat_map(|chunk| chunk.to_le_bytes()),
+    )
+}
+
+impl<const WORD_SIZE: usize> AbsurdleSearch<'_, WORD_SIZE> {
+    /// Find a line that wins from a set of candidates within `guesses_left` guesses.
+    fn solve(&mut self, candidates: &Column, guesses_left: u8) -> Option<Vec<(u16, u8)>> {
+        let num_candidates = candidates.count_true() as u32;
+        if num_candidates == 1 {
+            // The host has nothing left to dodge with, and the answer's guess index is its own
+            let answer_ind = candidates.iter_true().next().unwrap() as u16;
+            return Some(vec![(answer_ind, 0)]);
+        }
+        // With more than one candidate, the host always keeps a bucket other than the guess's
+        if guesses_left < 2 {
+            return None;
+        }
+        let key = candidates_hash(candidates);
+        let chunks = candidates.to_chunks();
+        if self
+            .lost_within
+            .get(&key)
+            .is_some_and(|entry| entry.candidates == chunks && guesses_left <= entry.lost_within)
+        {
+            return None;
+        }
+
+        // Try the guesses that leave the host the fewest candidates first
+        let mut responses: Vec<(u32, u16, u8)> = (0..self.hints.len())
+            .filter_map(|guess_ind| {
+                let counts = count_by_hint(&self.hints[guess_ind], candidates);
+                let response = absurdle_response(
+                    counts
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, count)| **count > 0)
+                        .map(|(hint, count)| (WordHint::<WORD_SIZE>::from_id(hint as u8), *count)),
+                )?;
+                let hint = response.hint_id();
+                let num_kept = counts[hint as usize] as u32;
+                // A guess the host can answer with every candidate makes no progress
+                (num_kept < num_candidates).then_some((num_kept, guess_ind as u16, hint))
+            })
+            .collect();
+        responses.sort_unstable();
+        for (num_kept, guess_ind, hint) in responses {
+            // Each guess splits what's left at most `max_hints` ways, and the last guess must
+            // face a single candidate
+            if num_kept > self.max_hints.saturating_pow(guesses_left as u32 - 2) {
+                break;
+            }
+            let mut kept = Column::from_false(candidates.len());
+            for answer_ind in candidates.iter_true() {
+                if self.hints[guess_ind as usize][answer_ind] == hint {
+                    kept.set(answer_ind, true);
+                }
+            }
+            if let Some(mut line) = self.solve(&kept, guesses_left - 1) {
+                line.insert(0, (guess_ind, hint));
+                return Some(line);
+            }
+        }
+        self.lost_within.insert(
+            key,
+            LostEntry {
+                candidates: chunks,
+                lost_within: guesses_left,
+            },
+        );
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::{Game, GameStatus},
+        ruleset::{Absurdle, Ruleset},
+        test_words,
+    };
+
+    #[test]
+    fn test_solve_absurdle() {
+        let words = test_words::small_list();
+        let line = solve_absurdle(&words, &words, 8, 1 << 20).unwrap();
+
+        // The game gives the same hints, and the line wins it
+        let mut game = Game::new(Absurdle, words[0], words.clone());
+        for (guess, hint) in &line {
+            assert_eq!(game.guess(*guess).unwrap(), *hint);
+        }
+        assert_eq!(game.status(), GameStatus::Won);
+
+        // No shorter line wins
+        assert!(solve_absurdle(&words, &words, line.len() as u8 - 1, 1 << 20).is_none());
+        // A single candidate is won by guessing it
+        assert_eq!(
+            solve_absurdle(&words, &words[3..4], 1, 1 << 20),
+            Some(vec![(words[3], WordHint::from_id(0))])
+        );
+        // Forgetting every lost set finds the same line
+        assert_eq!(solve_absurdle(&words, &words, 8, 0), Some(line));
+    }
+
+    #[test]
+    fn test_solve_absurdle_tie_break() {
+        let words = test_words::small_list();
+        // FIGHT splits FIGHT and LIGHT one each, and the host keeps the bucket that reveals less
+        let line = solve_absurdle(&words[..1], &words[..2], 2, 1 << 20).unwrap();
+        let ruleset = Absurdle;
+        assert_eq!(
+            line,
+            vec![
+                (words[0], ruleset.hint(&words[0], &words[1])),
+                (words[1], WordHint::from_id(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_hints_prunes() {
+        // Each guess only tells itself apart from the rest, so gets at most 2 distinct hints
+        let hints: Vec<Vec<u8>> = (0..5)
+            .map(|guess_ind| {
+                (0..5)
+                    .map(|answer_ind| u8::from(guess_ind != answer_ind))
+                    .collect()
+            })
+            .collect();
+        let mut search = AbsurdleSearch::<5> {
+            hints: &hints,
+            max_hints: 2,
+            lost_within: BoundedCache::with_memory_budget(1 << 20),
+        };
+        let candidates = Column::from_true(5);
+
+        // Every guess keeps 4 candidates, more than 2 hints can split within the last 2 guesses,
+        // so the root gives up without searching any guess
+        assert!(search.solve(&candidates, 3).is_none());
+        assert_eq!(search.lost_within.len(), 1);
+        // With one more guess, the guesses are searched, and each is cut off a level down
+        assert!(search.solve(&candidates, 4).is_none());
+        assert!(search.lost_within.len() > 1);
+        // Each guess removes one candidate, so it takes one guess per candidate
+        let line = search.solve(&candidates, 5).unwrap();
+        assert_eq!(line.len(), 5);
+        assert_eq!(line.last().unwrap().1, 0);
+    }
+}
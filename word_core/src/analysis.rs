@@ -0,0 +1,612 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+};
+
+use serde::Serialize;
+
+use crate::{
+    answer_set::{canonical_partition_labels, partition_fingerprint},
+    word::Word,
+    word_search::{Query, SearchableWords},
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+/// Expected tile colors for a guess, averaged uniformly over the remaining candidates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct TileStats {
+    pub expected_greens: f64,
+    pub expected_yellows: f64,
+    pub expected_grays: f64,
+    pub prob_any_green: f64,
+}
+
+/// Compute the expected number of green / yellow / gray tiles a guess would reveal.
+///
+/// Everything is derived from the precomputed Match and CountExact columns of the candidate
+/// table, so no hints are generated. Greens come straight from the table's cached counts.
+pub fn tile_stats<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    candidates: &SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+    guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+) -> TileStats {
+    if candidates.is_empty() {
+        return TileStats::default();
+    }
+    let num_candidates = candidates.len() as f64;
+
+    // Greens are just the Match columns at each position
+    let match_queries: Vec<Query> = (0..WORD_SIZE)
+        .map(|ind| Query::Match {
+            ind,
+            chr: guess.0[ind],
+        })
+        .collect();
+    let total_greens: u64 = match_queries
+        .iter()
+        .map(|query| candidates.leaf_count(query).unwrap())
+        .sum();
+    let num_any_green = candidates.eval_query(Query::Or(match_queries)).count_true();
+
+    // Yellows for a char depend on how many of its positions were green and how many of that
+    // char the answer contains in total
+    let mut positions_by_chr: BTreeMap<u8, Vec<usize>> = BTreeMap::new();
+    for (ind, chr) in guess.0.iter().enumerate() {
+        positions_by_chr.entry(*chr).or_default().push(ind);
+    }
+    let mut total_yellows = 0;
+    for (chr, positions) in positions_by_chr {
+        let num_positions = positions.len();
+        for green_subset in 0..(1u32 << num_positions) {
+            let num_greens = green_subset.count_ones() as usize;
+            let position_queries: Vec<Query> = positions
+                .iter()
+                .enumerate()
+                .map(|(bit, ind)| {
+                    let query = Query::Match { ind: *ind, chr };
+                    if green_subset & (1 << bit) != 0 {
+                        query
+                    } else {
+                        Query::Not(Box::new(query))
+                    }
+                })
+                .collect();
+            for count in num_greens..=WORD_SIZE {
+                let num_yellows = (count - num_greens).min(num_positions - num_greens);
+                if num_yellows == 0 {
+                    continue;
+                }
+                let mut sub_queries = position_queries.clone();
+                sub_queries.push(Query::CountExact { count, chr });
+                let num_answers = candidates.eval_query(Query::And(sub_queries)).count_true();
+                total_yellows += num_answers * num_yellows as u64;
+            }
+        }
+    }
+
+    let expected_greens = total_greens as f64 / num_candidates;
+    let expected_yellows = total_yellows as f64 / num_candidates;
+    TileStats {
+        expected_greens,
+        expected_yellows,
+        expected_grays: WORD_SIZE as f64 - expected_greens - expected_yellows,
+        prob_any_green: num_any_green as f64 / num_candidates,
+    }
+}
+
+/// Compute [`TileStats`] for each of the given guesses against the remaining candidates.
+pub fn tile_stats_per_guess<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    candidates: &SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+    guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, TileStats)> {
+    guesses
+        .iter()
+        .map(|guess| (*guess, tile_stats(candidates, *guess)))
+        .collect()
+}
+
+/// A group of answers that reference guesses can't (or can barely) tell apart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnswerCluster {
+    /// Indices of the answers in this cluster, ascending
+    pub answer_inds: Vec<u16>,
+
+    /// Hint ids against each reference guess, for the lowest-indexed answer in the cluster
+    pub signature: Vec<u8>,
+}
+
+impl AnswerCluster {
+    /// Get the number of answers in this cluster.
+    pub fn len(&self) -> usize {
+        self.answer_inds.len()
+    }
+
+    /// Whether this cluster contains no answers.
+    pub fn is_empty(&self) -> bool {
+        self.answer_inds.is_empty()
+    }
+}
+
+/// Cluster answers whose hint signatures against a set of reference guesses are identical or
+/// nearly identical.
+///
+/// An answer's signature is its hint id against each reference guess. Two answers are linked
+/// if their signatures differ on at most `max_differing` reference guesses, and clusters are
+/// the connected groups of linked answers. Clusters are returned largest first.
+pub fn cluster_answers_by_signature(
+    hints: &[Vec<u8>],
+    reference_guesses: &[u16],
+    answer_inds: &[u16],
+    max_differing: usize,
+) -> Vec<AnswerCluster> {
+    // Group identical signatures first, so near-identical linking only compares distinct ones
+    let mut answers_by_signature: BTreeMap<Vec<u8>, Vec<u16>> = BTreeMap::new();
+    for answer_ind in answer_inds {
+        let signature: Vec<u8> = reference_guesses
+            .iter()
+            .map(|guess_ind| hints[*guess_ind as usize][*answer_ind as usize])
+            .collect();
+        answers_by_signature
+            .entry(signature)
+            .or_default()
+            .push(*answer_ind);
+    }
+    let groups: Vec<(Vec<u8>, Vec<u16>)> = answers_by_signature.into_iter().collect();
+
+    // Union-find over signature groups
+    let mut parents: Vec<usize> = (0..groups.len()).collect();
+    fn find_root(parents: &mut [usize], mut ind: usize) -> usize {
+        while parents[ind] != ind {
+            parents[ind] = parents[parents[ind]];
+            ind = parents[ind];
+        }
+        ind
+    }
+    if max_differing > 0 {
+        for a in 0..groups.len() {
+            for b in (a + 1)..groups.len() {
+                let num_differing = groups[a]
+                    .0
+                    .iter()
+                    .zip(groups[b].0.iter())
+                    .filter(|(hint_a, hint_b)| hint_a != hint_b)
+                    .count();
+                if num_differing <= max_differing {
+                    let root_a = find_root(&mut parents, a);
+                    let root_b = find_root(&mut parents, b);
+                    parents[root_b.max(root_a)] = root_a.min(root_b);
+                }
+            }
+        }
+    }
+
+    let mut clusters_by_root: HashMap<usize, AnswerCluster> = HashMap::new();
+    for (group_ind, (signature, group_answer_inds)) in groups.into_iter().enumerate() {
+        let root = find_root(&mut parents, group_ind);
+        let cluster = clusters_by_root
+            .entry(root)
+            .or_insert_with(|| AnswerCluster {
+                answer_inds: vec![],
+                signature: signature.clone(),
+            });
+        if group_answer_inds[0] < cluster.answer_inds.first().copied().unwrap_or(u16::MAX) {
+            cluster.signature = signature;
+        }
+        cluster.answer_inds.extend(group_answer_inds);
+    }
+    let mut clusters: Vec<AnswerCluster> = clusters_by_root
+        .into_values()
+        .map(|mut cluster| {
+            cluster.answer_inds.sort_unstable();
+            cluster
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.len()
+            .cmp(&a.len())
+            .then_with(|| a.answer_inds[0].cmp(&b.answer_inds[0]))
+    });
+    clusters
+}
+
+/// Split each answer's class by the hint the guess gives it, returning the refined class ids
+/// and the number of distinct classes.
+fn refine_classes(
+    hints: &[Vec<u8>],
+    classes: &[u32],
+    guess_ind: u16,
+    answer_inds: &[u16],
+) -> (Vec<u32>, usize) {
+    let guess_hints = &hints[guess_ind as usize];
+    let mut class_ids: HashMap<(u32, u8), u32> = HashMap::new();
+    let refined: Vec<u32> = classes
+        .iter()
+        .zip(answer_inds.iter())
+        .map(|(class, answer_ind)| {
+            let next_id = class_ids.len() as u32;
+            *class_ids
+                .entry((*class, guess_hints[*answer_ind as usize]))
+                .or_insert(next_id)
+        })
+        .collect();
+    (refined, class_ids.len())
+}
+
+/// Greedily build a fixed set of guesses that, played together, uniquely identifies every
+/// answer.
+///
+/// Each step adds the guess that splits the answers into the most distinguishable classes.
+/// Returns `None` if the candidate guesses can't distinguish every answer.
+pub fn greedy_distinguishing_guess_set(
+    hints: &[Vec<u8>],
+    candidate_guesses: &[u16],
+    answer_inds: &[u16],
+) -> Option<Vec<u16>> {
+    let mut classes = vec![0; answer_inds.len()];
+    let mut num_classes = 1.min(answer_inds.len());
+    let mut chosen = vec![];
+    while num_classes < answer_inds.len() {
+        let (best_guess, best_classes, best_num_classes) = candidate_guesses
+            .iter()
+            .map(|guess_ind| {
+                let (refined, num_refined) =
+                    refine_classes(hints, &classes, *guess_ind, answer_inds);
+                (*guess_ind, refined, num_refined)
+            })
+            .max_by(|(guess_a, _, num_a), (guess_b, _, num_b)| {
+                num_a.cmp(num_b).then_with(|| guess_b.cmp(guess_a))
+            })?;
+        if best_num_classes == num_classes {
+            return None;
+        }
+        chosen.push(best_guess);
+        classes = best_classes;
+        num_classes = best_num_classes;
+    }
+    Some(chosen)
+}
+
+/// Find a smallest fixed set of guesses that, played together, uniquely identifies every
+/// answer, searching set sizes up to `max_size`.
+///
+/// This is an exact combinatorial search, so it's only practical on a restricted candidate
+/// guess list. Returns `None` if no set of at most `max_size` guesses works.
+pub fn minimal_distinguishing_guess_set(
+    hints: &[Vec<u8>],
+    candidate_guesses: &[u16],
+    answer_inds: &[u16],
+    max_size: usize,
+) -> Option<Vec<u16>> {
+    // Order candidates by how well they split on their own, so good sets are found sooner
+    let initial_classes = vec![0; answer_inds.len()];
+    let mut ordered_guesses: Vec<(u16, usize)> = candidate_guesses
+        .iter()
+        .map(|guess_ind| {
+            let (_, num_classes) = refine_classes(hints, &initial_classes, *guess_ind, answer_inds);
+            (*guess_ind, num_classes)
+        })
+        .collect();
+    ordered_guesses.sort_by(|(guess_a, num_a), (guess_b, num_b)| {
+        num_b.cmp(num_a).then_with(|| guess_a.cmp(guess_b))
+    });
+    let ordered_guesses: Vec<u16> = ordered_guesses
+        .into_iter()
+        .map(|(guess_ind, _)| guess_ind)
+        .collect();
+
+    fn search(
+        hints: &[Vec<u8>],
+        ordered_guesses: &[u16],
+        answer_inds: &[u16],
+        classes: &[u32],
+        num_classes: usize,
+        remaining: usize,
+        chosen: &mut Vec<u16>,
+    ) -> bool {
+        if num_classes == answer_inds.len() {
+            return true;
+        }
+        if remaining == 0 {
+            return false;
+        }
+        for (ind, guess_ind) in ordered_guesses.iter().enumerate() {
+            let (refined, num_refined) = refine_classes(hints, classes, *guess_ind, answer_inds);
+            if num_refined == num_classes {
+                continue;
+            }
+            chosen.push(*guess_ind);
+            if search(
+                hints,
+                &ordered_guesses[ind + 1..],
+                answer_inds,
+                &refined,
+                num_refined,
+                remaining - 1,
+                chosen,
+            ) {
+                return true;
+            }
+            chosen.pop();
+        }
+        false
+    }
+
+    let num_classes = 1.min(answer_inds.len());
+    for size in 0..=max_size {
+        let mut chosen = vec![];
+        if search(
+            hints,
+            &ordered_guesses,
+            answer_inds,
+            &initial_classes,
+            num_classes,
+            size,
+            &mut chosen,
+        ) {
+            return Some(chosen);
+        }
+    }
+    None
+}
+
+/// A fixed two-guess opening and how well it narrows down the answers.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OpeningPair {
+    pub first: u16,
+    pub second: u16,
+
+    /// Expected number of candidates left after both guesses, for a uniformly random answer
+    pub expected_remaining: f64,
+
+    /// Number of candidates left in the largest bucket after both guesses
+    pub worst_bucket: usize,
+}
+
+/// Rank fixed two-guess openings by expected remaining candidates, then by worst bucket.
+///
+/// The joint partition of two guesses doesn't depend on the order they're played, so each
+/// unordered pair is evaluated once. Guesses that partition the answers identically are
+/// collapsed to their first occurrence before pairing, and pairs are evaluated in parallel.
+pub fn best_opening_pairs(
+    hints: &[Vec<u8>],
+    candidate_guesses: &[u16],
+    answer_inds: &[u16],
+    top_n: usize,
+) -> Vec<OpeningPair> {
+    // Keep one representative per distinct partition
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut representatives: Vec<(u16, Vec<u8>)> = vec![];
+    for guess_ind in candidate_guesses {
+        let guess_hints = &hints[*guess_ind as usize];
+        let labels = canonical_partition_labels(guess_hints, answer_inds);
+        let same_fingerprint = seen
+            .entry(partition_fingerprint(guess_hints, answer_inds))
+            .or_default();
+        if same_fingerprint
+            .iter()
+            .any(|rep_ind| representatives[*rep_ind].1 == labels)
+        {
+            continue;
+        }
+        same_fingerprint.push(representatives.len());
+        representatives.push((*guess_ind, labels));
+    }
+
+    // Sort key is (sum of squared bucket sizes, worst bucket, first, second)
+    type PairKey = (u64, usize, u16, u16);
+    fn keep_best(best: &mut Vec<PairKey>, key: PairKey, top_n: usize) {
+        if best.len() == top_n && best.last().is_none_or(|worst| key >= *worst) {
+            return;
+        }
+        let ind = best.partition_point(|existing| *existing < key);
+        best.insert(ind, key);
+        best.truncate(top_n);
+    }
+
+    thread_local! {
+        // Cleared after each pair, so one buffer serves every pair scored on a thread
+        static BUCKET_SIZES: RefCell<Vec<u32>> = RefCell::new(vec![0; 256 * 256]);
+    }
+    let best = WorkerPool::new(PoolConfig::default())
+        .map_or_unwind(&representatives, |first_ind, (first, first_labels)| {
+            BUCKET_SIZES.with_borrow_mut(|bucket_sizes| {
+                let mut best: Vec<PairKey> = Vec::with_capacity(top_n + 1);
+                for (second, second_labels) in &representatives[first_ind + 1..] {
+                    let mut sum_squares = 0;
+                    let mut worst_bucket = 0;
+                    for (label_a, label_b) in first_labels.iter().zip(second_labels.iter()) {
+                        let bucket = &mut bucket_sizes[*label_a as usize * 256 + *label_b as usize];
+                        // (n + 1)^2 - n^2 = 2n + 1
+                        sum_squares += 2 * *bucket as u64 + 1;
+                        *bucket += 1;
+                        worst_bucket = worst_bucket.max(*bucket as usize);
+                    }
+                    for (label_a, label_b) in first_labels.iter().zip(second_labels.iter()) {
+                        bucket_sizes[*label_a as usize * 256 + *label_b as usize] = 0;
+                    }
+                    let (a, b) = (*first.min(second), *first.max(second));
+                    keep_best(&mut best, (sum_squares, worst_bucket, a, b), top_n);
+                }
+                best
+            })
+        })
+        .into_iter()
+        .fold(Vec::new(), |mut best_a, best_b| {
+            for key in best_b {
+                keep_best(&mut best_a, key, top_n);
+            }
+            best_a
+        });
+
+    best.into_iter()
+        .map(|(sum_squares, worst_bucket, first, second)| OpeningPair {
+            first,
+            second,
+            expected_remaining: sum_squares as f64 / answer_inds.len() as f64,
+            worst_bucket,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        hint::{CharHint, WordHint},
+        hint_matrix::compute_hints,
+        test_words::words,
+    };
+
+    #[test]
+    fn test_tile_stats_match_hints() {
+        let answers: Vec<Word<5, 26>> = words(&[
+            "badly", "basic", "beach", "bible", "blade", "board", "bread", "eerie", "geese",
+            "sassy", "abbey",
+        ]);
+        let candidates = SearchableWords::build(answers.clone());
+        for guess in words(&["board", "eerie", "sasse", "abbey", "xxxxx"]) {
+            let stats = tile_stats(&candidates, guess);
+            let (mut greens, mut yellows, mut grays, mut any_green) = (0, 0, 0, 0);
+            for answer in &answers {
+                let hint = WordHint::from_guess_and_answer(&guess, answer);
+                let count = |target| {
+                    hint.0
+                        .iter()
+                        .filter(|char_hint| **char_hint == target)
+                        .count()
+                };
+                greens += count(CharHint::Correct);
+                yellows += count(CharHint::Elsewhere);
+                grays += count(CharHint::Nowhere);
+                any_green += (count(CharHint::Correct) > 0) as usize;
+            }
+            let num_answers = answers.len() as f64;
+            assert!((stats.expected_greens - greens as f64 / num_answers).abs() < 1e-9);
+            assert!((stats.expected_yellows - yellows as f64 / num_answers).abs() < 1e-9);
+            assert!((stats.expected_grays - grays as f64 / num_answers).abs() < 1e-9);
+            assert!((stats.prob_any_green - any_green as f64 / num_answers).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_tile_stats_empty() {
+        let candidates: SearchableWords<5, 26> = SearchableWords::build(vec![]);
+        assert_eq!(
+            tile_stats(&candidates, Word::try_from("board").unwrap()),
+            TileStats::default()
+        );
+    }
+
+    #[test]
+    fn test_cluster_identical_signatures() {
+        let answers: Vec<Word<5, 26>> = words(&[
+            "fight", "light", "might", "night", "sight", "tight", "board", "bread",
+        ]);
+        let guesses: Vec<Word<5, 26>> = words(&["board", "stale"]);
+        let hints = compute_hints(&guesses, &answers);
+        let clusters = cluster_answers_by_signature(&hints, &[0], &[0, 1, 2, 3, 4, 5, 6, 7], 0);
+        // Against BOARD alone the whole _IGHT family looks the same
+        assert_eq!(clusters[0].answer_inds, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(clusters[0].signature, vec![hints[0][0]]);
+        assert_eq!(clusters.len(), 3);
+
+        // STALE splits off the S and L words
+        let clusters = cluster_answers_by_signature(&hints, &[0, 1], &[0, 1, 2, 3, 4, 5], 0);
+        assert_eq!(
+            clusters
+                .iter()
+                .map(|cluster| cluster.answer_inds.clone())
+                .collect::<Vec<Vec<u16>>>(),
+            vec![vec![0, 2, 3, 5], vec![1], vec![4]]
+        );
+    }
+
+    #[test]
+    fn test_cluster_near_identical_signatures() {
+        let answers: Vec<Word<5, 26>> =
+            words(&["fight", "light", "might", "night", "sight", "tight"]);
+        let guesses: Vec<Word<5, 26>> = words(&["board", "stale"]);
+        let hints = compute_hints(&guesses, &answers);
+        let clusters = cluster_answers_by_signature(&hints, &[0, 1], &[0, 1, 2, 3, 4, 5], 1);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].len(), 6);
+    }
+
+    #[test]
+    fn test_distinguishing_guess_sets() {
+        let answers: Vec<Word<5, 26>> =
+            words(&["fight", "light", "might", "night", "sight", "tight"]);
+        let guesses: Vec<Word<5, 26>> = words(&["board", "fight", "lemon", "mints", "sight"]);
+        let hints = compute_hints(&guesses, &answers);
+        let answer_inds: Vec<u16> = (0..answers.len() as u16).collect();
+        let all_guesses: Vec<u16> = (0..guesses.len() as u16).collect();
+
+        // LEMON + MINTS covers L, M, N and S, FIGHT then separates F from T
+        let minimal =
+            minimal_distinguishing_guess_set(&hints, &all_guesses, &answer_inds, 3).unwrap();
+        assert_eq!(minimal.len(), 3);
+        let greedy = greedy_distinguishing_guess_set(&hints, &all_guesses, &answer_inds).unwrap();
+        assert!(greedy.len() >= minimal.len());
+        for chosen in [minimal, greedy] {
+            let signatures: HashSet<Vec<u8>> = answer_inds
+                .iter()
+                .map(|answer_ind| {
+                    chosen
+                        .iter()
+                        .map(|guess_ind| hints[*guess_ind as usize][*answer_ind as usize])
+                        .collect()
+                })
+                .collect();
+            assert_eq!(signatures.len(), answers.len());
+        }
+
+        assert_eq!(
+            minimal_distinguishing_guess_set(&hints, &all_guesses, &answer_inds, 2),
+            None
+        );
+        assert_eq!(
+            greedy_distinguishing_guess_set(&hints, &[0], &answer_inds),
+            None
+        );
+    }
+
+    #[test]
+    fn test_best_opening_pairs() {
+        let answers: Vec<Word<5, 26>> = words(&[
+            "fight", "light", "might", "night", "sight", "tight", "board", "bread",
+        ]);
+        // BOARD and BROAD partition these answers identically, so only one pair uses them
+        let guesses: Vec<Word<5, 26>> = words(&["board", "broad", "lemon", "mints", "fight"]);
+        let hints = compute_hints(&guesses, &answers);
+        let answer_inds: Vec<u16> = (0..answers.len() as u16).collect();
+        let pairs = best_opening_pairs(&hints, &[0, 1, 2, 3, 4], &answer_inds, 100);
+        assert_eq!(pairs.len(), 6);
+        assert!(pairs.iter().all(|pair| pair.first != 1 && pair.second != 1));
+
+        // Brute force the metrics for each returned pair
+        for pair in &pairs {
+            let mut bucket_sizes: HashMap<(u8, u8), usize> = HashMap::new();
+            for answer_ind in &answer_inds {
+                *bucket_sizes
+                    .entry((
+                        hints[pair.first as usize][*answer_ind as usize],
+                        hints[pair.second as usize][*answer_ind as usize],
+                    ))
+                    .or_default() += 1;
+            }
+            let sum_squares: usize = bucket_sizes.values().map(|size| size * size).sum();
+            assert_eq!(
+                pair.expected_remaining,
+                sum_squares as f64 / answers.len() as f64
+            );
+            assert_eq!(pair.worst_bucket, *bucket_sizes.values().max().unwrap());
+        }
+        for window in pairs.windows(2) {
+            assert!(window[0].expected_remaining <= window[1].expected_remaining);
+        }
+
+        let top = best_opening_pairs(&hints, &[0, 1, 2, 3, 4], &answer_inds, 2);
+        assert_eq!(top, pairs[..2]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::hash::{Hash, Hasher};
+
+use crate::column::Column;
+
+/// Mix a single answer index into a well-distributed 64-bit value (splitmix64 finalizer).
+fn mix(answer_ind: u64) -> u64 {
+    let mut z = answer_ind.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Fold the set size into the combined member hashes.
+fn finish(combined: u64, len: usize) -> u64 {
+    mix(combined ^ (len as u64).wrapping_mul(0xff51_afd7_ed55_8ccd))
+}
+
+/// Compute the canonical hash of a set of answer indices.
+///
+/// Member hashes are combined with XOR, so the result doesn't depend on order and matches
+/// [`hash_answer_column`] for the same set.
+pub fn hash_answer_inds(answer_inds: &[u16]) -> u64 {
+    let combined = answer_inds
+        .iter()
+        .fold(0, |acc, answer_ind| acc ^ mix(*answer_ind as u64));
+    finish(combined, answer_inds.len())
+}
+
+/// Compute the canonical hash of the set of answers marked true in a column.
+pub fn hash_answer_column(answers: &Column) -> u64 {
+    let answer_inds = answers.true_inds();
+    let combined = answer_inds
+        .iter()
+        .fold(0, |acc, answer_ind| acc ^ mix(*answer_ind as u64));
+    finish(combined, answer_inds.len())
+}
+
+/// A set of answer indices keyed by its canonical hash.
+///
+/// Hashing only uses the precomputed canonical hash, while equality also compares the full
+/// membership, so hash collisions can never alias two different sets.
+#[derive(Debug, Clone, Eq)]
+pub struct AnswerSetKey {
+    hash: u64,
+    answer_inds: Vec<u16>,
+}
+
+impl AnswerSetKey {
+    /// Build a key from answer indices in any order.
+    pub fn from_inds(mut answer_inds: Vec<u16>) -> Self {
+        answer_inds.sort_unstable();
+        answer_inds.dedup();
+        Self {
+            hash: hash_answer_inds(&answer_inds),
+            answer_inds,
+        }
+    }
+
+    /// Build a key from the answers marked true in a column.
+    pub fn from_column(answers: &Column) -> Self {
+        let answer_inds: Vec<u16> = answers
+            .true_inds()
+            .into_iter()
+            .map(|answer_ind| answer_ind as u16)
+            .collect();
+        Self {
+            hash: hash_answer_inds(&answer_inds),
+            answer_inds,
+        }
+    }
+
+    /// Get the canonical hash of this set.
+    pub fn canonical_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Get the sorted answer indices in this set.
+    pub fn answer_inds(&self) -> &[u16] {
+        &self.answer_inds
+    }
+}
+
+impl PartialEq for AnswerSetKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.answer_inds == other.answer_inds
+    }
+}
+
+impl Hash for AnswerSetKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+/// Relabel a guess's hints over a set of answers so labels count up in order of first
+/// appearance.
+///
+/// Two guesses that split the answers into the same buckets get identical labels, even if the
+/// hints themselves differ.
+pub fn canonical_partition_labels(guess_hints: &[u8], answer_inds: &[u16]) -> Vec<u8> {
+    let mut labels_by_hint = [u8::MAX; 256];
+    let mut num_labels = 0;
+    answer_inds
+        .iter()
+        .map(|answer_ind| {
+            let hint = guess_hints[*answer_ind as usize] as usize;
+            if labels_by_hint[hint] == u8::MAX {
+                labels_by_hint[hint] = num_labels;
+                num_labels += 1;
+            }
+            labels_by_hint[hint]
+        })
+        .collect()
+}
+
+/// Compute a fingerprint of how a guess partitions a set of answers.
+///
+/// Guesses inducing the same partition share a fingerprint regardless of their hint ids.
+pub fn partition_fingerprint(guess_hints: &[u8], answer_inds: &[u16]) -> u64 {
+    canonical_partition_labels(guess_hints, answer_inds)
+        .into_iter()
+        .fold(0xcbf2_9ce4_8422_2325, |acc, label| mix(acc ^ label as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_order_independent() {
+        assert_eq!(hash_answer_inds(&[1, 5, 9]), hash_answer_inds(&[9, 1, 5]));
+    }
+
+    #[test]
+    fn test_column_matches_inds() {
+        let mut col = Column::from_false(200);
+        for ind in [3, 64, 65, 199] {
+            col.set(ind, true);
+        }
+        assert_eq!(
+            hash_answer_column(&col),
+            hash_answer_inds(&[3, 64, 65, 199])
+        );
+        assert_eq!(
+            AnswerSetKey::from_column(&col),
+            AnswerSetKey::from_inds(vec![199, 65, 64, 3])
+        );
+    }
+
+    #[test]
+    fn test_distinguishes_sets() {
+        assert_ne!(hash_answer_inds(&[]), hash_answer_inds(&[0]));
+        assert_ne!(hash_answer_inds(&[0, 1]), hash_answer_inds(&[0, 2]));
+        assert_ne!(hash_answer_inds(&[1, 2]), hash_answer_inds(&[1, 2, 3, 3]));
+    }
+
+    #[test]
+    fn test_usable_as_map_key() {
+        let mut map = HashMap::new();
+        map.insert(AnswerSetKey::from_inds(vec![4, 2]), "a");
+        map.insert(AnswerSetKey::from_inds(vec![2, 4, 6]), "b");
+        assert_eq!(map.get(&AnswerSetKey::from_inds(vec![2, 4])), Some(&"a"));
+        assert_eq!(map.get(&AnswerSetKey::from_inds(vec![6, 4, 2])), Some(&"b"));
+        assert_eq!(map.get(&AnswerSetKey::from_inds(vec![6])), None);
+    }
+
+    #[test]
+    fn test_partition_fingerprint_ignores_hint_ids() {
+        let guess_a_hints = [5, 5, 7, 9, 7];
+        let guess_b_hints = [1, 1, 0, 2, 0];
+        let guess_c_hints = [1, 1, 0, 0, 2];
+        let answer_inds = [0, 1, 2, 3, 4];
+        assert_eq!(
+            canonical_partition_labels(&guess_a_hints, &answer_inds),
+            vec![0, 0, 1, 2, 1]
+        );
+        assert_eq!(
+            partition_fingerprint(&guess_a_hints, &answer_inds),
+            partition_fingerprint(&guess_b_hints, &answer_inds)
+        );
+        assert_ne!(
+            partition_fingerprint(&guess_a_hints, &answer_inds),
+            partition_fingerprint(&guess_c_hints, &answer_inds)
+        );
+        assert_eq!(
+            partition_fingerprint(&guess_a_hints, &[0, 2]),
+            partition_fingerprint(&guess_c_hints, &[0, 4])
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{batch_build::ArtifactHeader, error::WordGameError};
+
+/// How a saved tree in one artifact directory relates to the file of the same name in another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ArtifactMatch {
+    /// The files are exactly the same
+    ByteIdentical,
+
+    /// The files differ, but their trees play the same guesses after every hint
+    SemanticallyIdentical,
+
+    Different,
+    OnlyLeft,
+    OnlyRight,
+}
+
+/// The comparison of one artifact file name across two directories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArtifactComparison {
+    pub file_name: String,
+    pub status: ArtifactMatch,
+}
+
+/// Just enough of a saved artifact to read its header without knowing its word size.
+#[derive(Deserialize)]
+struct HeaderOnly {
+    header: ArtifactHeader,
+}
+
+/// Compare every JSON artifact in two directories by file name.
+///
+/// Files are first compared byte for byte, then by the tree hash in their headers. Files
+/// without a readable header or tree hash can only be byte-identical or different.
+pub fn compare_artifact_dirs(
+    left: &Path,
+    right: &Path,
+) -> Result<Vec<ArtifactComparison>, WordGameError> {
+    let left_files = json_files(left)?;
+    let right_files = json_files(right)?;
+    let file_names: BTreeSet<&String> = left_files.keys().chain(right_files.keys()).collect();
+    file_names
+        .into_iter()
+        .map(|file_name| {
+            let status = match (left_files.get(file_name), right_files.get(file_name)) {
+                (Some(left_path), Some(right_path)) => {
+                    let left_raw = read(left_path)?;
+                    let right_raw = read(right_path)?;
+                    if left_raw == right_raw {
+                        ArtifactMatch::ByteIdentical
+                    } else if tree_hash(&left_raw)
+                        .is_some_and(|hash| Some(hash) == tree_hash(&right_raw))
+                    {
+                        ArtifactMatch::SemanticallyIdentical
+                    } else {
+                        ArtifactMatch::Different
+                    }
+                }
+                (Some(_), None) => ArtifactMatch::OnlyLeft,
+                (None, _) => ArtifactMatch::OnlyRight,
+            };
+            Ok(ArtifactComparison {
+                file_name: file_name.clone(),
+                status,
+            })
+        })
+        .collect()
+}
+
+/// Group the artifacts in the given directories by tree hash, keeping only groups of more than
+/// one file. Each group lists its paths in order, and groups are ordered by their first path.
+pub fn find_duplicate_trees(dirs: &[&Path]) -> Result<Vec<Vec<PathBuf>>, WordGameError> {
+    let mut paths_by_hash: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for dir in dirs {
+        for path in json_files(dir)?.into_values() {
+            if let Some(hash) = tree_hash(&read(&path)?) {
+                paths_by_hash.entry(hash).or_default().push(path);
+            }
+        }
+    }
+    let mut groups: Vec<Vec<PathBuf>> = paths_by_hash
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|mut paths| {
+            paths.sort();
+            paths
+        })
+        .collect();
+    groups.sort();
+    Ok(groups)
+}
+
+/// The JSON files directly inside a directory, keyed by file name.
+fn json_files(dir: &Path) -> Result<BTreeMap<String, PathBuf>, WordGameError> {
+    let io_err = |source| WordGameError::Io {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut files = BTreeMap::new();
+    for entry in fs::read_dir(dir).map_err(io_err)? {
+        let path = entry.map_err(io_err)?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            files.insert(
+                path.file_name().unwrap().to_string_lossy().into_owned(),
+                path,
+            );
+        }
+    }
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, WordGameError> {
+    fs::read(path).map_err(|source| WordGameError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// The tree hash recorded in an artifact's header, if it has one.
+fn tree_hash(raw: &[u8]) -> Option<String> {
+    serde_json::from_slice::<HeaderOnly>(raw)
+        .ok()
+        .map(|artifact| artifact.header.tree_hash)
+        .filter(|hash| !hash.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::{batch_build::TreeArtifact, mask_encoding::WordListId, test_words, word::Word};
+
+    fn artifact(name: &str, est_cost: f64) -> TreeArtifact<5> {
+        let answers: Vec<Word<5, 26>> = ["fight", "board"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let tree = test_words::guess_then_answers(answers[0], &answers, est_cost);
+        TreeArtifact {
+            header: ArtifactHeader {
+                name: name.to_string(),
+                word_size: 5,
+                allowed_guesses: WordListId::new("answers", &answers),
+                possible_answers: WordListId::new("answers", &answers),
+                max_depth: 6,
+                est_cost,
+                approximate: false,
+                tree_hash: tree.content_hash(),
+                build_millis: 0,
+                manifest: None,
+            },
+            tree,
+        }
+    }
+
+    #[test]
+    fn test_compare_artifact_dirs() {
+        let root = env::temp_dir().join(format!("word_core_artifact_diff_{}", std::process::id()));
+        let (left, right) = (root.join("left"), root.join("right"));
+        fs::create_dir_all(&left).unwrap();
+        fs::create_dir_all(&right).unwrap();
+        let write = |dir: &Path, file_name: &str, raw: String| {
+            fs::write(dir.join(file_name), raw).unwrap();
+        };
+        let same = serde_json::to_string(&artifact("a", 1.5)).unwrap();
+        write(&left, "same.json", same.clone());
+        write(&right, "same.json", same);
+        // Only the recorded cost differs, so the trees still play identically
+        write(
+            &left,
+            "costs.json",
+            serde_json::to_string(&artifact("b", 1.5)).unwrap(),
+        );
+        write(
+            &right,
+            "costs.json",
+            serde_json::to_string(&artifact("b", 1.6)).unwrap(),
+        );
+        write(&left, "bare.json", "{}".to_string());
+        write(&right, "bare.json", "[]".to_string());
+        write(&right, "new.json", "{}".to_string());
+        write(&right, "notes.txt", String::new());
+
+        let statuses: Vec<(String, ArtifactMatch)> = compare_artifact_dirs(&left, &right)
+            .unwrap()
+            .into_iter()
+            .map(|comparison| (comparison.file_name, comparison.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("bare.json".to_string(), ArtifactMatch::Different),
+                (
+                    "costs.json".to_string(),
+                    ArtifactMatch::SemanticallyIdentical
+                ),
+                ("new.json".to_string(), ArtifactMatch::OnlyRight),
+                ("same.json".to_string(), ArtifactMatch::ByteIdentical),
+            ]
+        );
+
+        let groups = find_duplicate_trees(&[&left, &right]).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 4);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
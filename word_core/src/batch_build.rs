@@ -0,0 +1,268 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decision_tree_general::{NoPrinter, SearchOptions},
+    error::WordGameError,
+    load_words::load_guesses_and_answers,
+    mask_encoding::WordListId,
+    readable_tree::ReadableTreeNode,
+    run_manifest::{RunManifest, solve_recorded},
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+/// One tree to build as part of a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildJob {
+    pub name: String,
+    pub word_size: usize,
+    pub allowed_guesses_path: PathBuf,
+    pub possible_answers_path: PathBuf,
+    pub max_depth: u8,
+
+    /// Prune any subtree whose expected cost exceeds this
+    pub max_cost: f64,
+
+    /// How far above optimal the tree's expected cost may be, to build it faster
+    #[serde(default)]
+    pub tolerance: f64,
+
+    /// Where to write the finished artifact
+    pub output_path: PathBuf,
+}
+
+/// What was built for a job, written alongside its tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactHeader {
+    pub name: String,
+    pub word_size: usize,
+    pub allowed_guesses: WordListId,
+    pub possible_answers: WordListId,
+    pub max_depth: u8,
+    pub est_cost: f64,
+
+    /// Whether any part of the tree came from a fallback rather than the exhaustive search
+    pub approximate: bool,
+
+    /// The tree's `content_hash`, empty for artifacts written before it was recorded
+    #[serde(default)]
+    pub tree_hash: String,
+
+    /// Time spent computing hints and searching, in milliseconds
+    pub build_millis: u64,
+
+    /// Everything the build depended on, for telling apart runs that disagree. Missing from
+    /// artifacts written before it was recorded.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub manifest: Option<RunManifest>,
+}
+
+impl ArtifactHeader {
+    /// Read just the header of an artifact file, whatever its word size.
+    pub fn read(path: &Path) -> Result<Self, WordGameError> {
+        /// Just enough of an artifact to get at its header
+        #[derive(Deserialize)]
+        struct HeaderOnly {
+            header: ArtifactHeader,
+        }
+
+        let raw = fs::read_to_string(path).map_err(|source| WordGameError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(serde_json::from_str::<HeaderOnly>(&raw)?.header)
+    }
+}
+
+/// A built tree with its header, as written to disk by a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeArtifact<const WORD_SIZE: usize> {
+    pub header: ArtifactHeader,
+    pub tree: ReadableTreeNode<WORD_SIZE>,
+}
+
+/// How a batch spreads its jobs across threads.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Threads to build trees on
+    pub pool: PoolConfig,
+}
+
+/// Build every job's tree in parallel, writing each artifact as it finishes.
+///
+/// A job that fails doesn't stop the others, though one that panics stops the batch. Results
+/// are returned in job order.
+pub fn build_trees(
+    jobs: &[BuildJob],
+    config: BatchConfig,
+) -> Result<Vec<Result<ArtifactHeader, WordGameError>>, WordGameError> {
+    WorkerPool::new(config.pool).map(jobs, |_, job| build_job(job))
+}
+
+/// Build a single job's tree, dispatching on its word size.
+///
+/// Hints are labelled per guess, so words too long for every hint to have a byte-sized id still
+/// build, as long as no guess can get more than 256 distinct hints from the answers.
+pub fn build_job(job: &BuildJob) -> Result<ArtifactHeader, WordGameError> {
+    match job.word_size {
+        3 => build_job_sized::<3>(job),
+        4 => build_job_sized::<4>(job),
+        5 => build_job_sized::<5>(job),
+        6 => build_job_sized::<6>(job),
+        7 => build_job_sized::<7>(job),
+        8 => build_job_sized::<8>(job),
+        word_size => Err(WordGameError::UnsupportedWordSize(word_size)),
+    }
+}
+
+fn build_job_sized<const WORD_SIZE: usize>(
+    job: &BuildJob,
+) -> Result<ArtifactHeader, WordGameError> {
+    let (allowed_guesses, possible_answers) = load_guesses_and_answers::<WORD_SIZE, 26>(
+        &job.allowed_guesses_path.to_string_lossy(),
+        &job.possible_answers_path.to_string_lossy(),
+        false,
+    )?;
+    let (tree, mut manifest) = solve_recorded(
+        (
+            &job.allowed_guesses_path.to_string_lossy(),
+            &allowed_guesses,
+        ),
+        (
+            &job.possible_answers_path.to_string_lossy(),
+            &possible_answers,
+        ),
+        job.max_depth,
+        job.max_cost,
+        SearchOptions {
+            tolerance: job.tolerance,
+            ..SearchOptions::default()
+        },
+        None,
+        None::<&NoPrinter>,
+    )?;
+    let tree = tree.ok_or_else(|| {
+        WordGameError::Infeasible(format!(
+            "{}: no tree within depth {} and cost {}",
+            job.name, job.max_depth, job.max_cost
+        ))
+    })?;
+    // The header records the result itself
+    let result = manifest.result.take().unwrap();
+    let header = ArtifactHeader {
+        name: job.name.clone(),
+        word_size: WORD_SIZE,
+        allowed_guesses: manifest.allowed_guesses.clone(),
+        possible_answers: manifest.possible_answers.clone(),
+        max_depth: job.max_depth,
+        est_cost: result.est_cost,
+        approximate: result.approximate,
+        tree_hash: result.tree_hash,
+        build_millis: result.wall_millis,
+        manifest: Some(manifest),
+    };
+    let artifact = TreeArtifact {
+        header: header.clone(),
+        tree,
+    };
+    fs::write(
+        &job.output_path,
+        serde_json::to_string_pretty(&artifact).unwrap(),
+    )
+    .map_err(|source| WordGameError::Io {
+        path: job.output_path.clone(),
+        source,
+    })?;
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn write_list(dir: &std::path::Path, name: &str, words: &[&str]) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, words.join("\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_build_trees() {
+        let dir = env::temp_dir().join(format!("word_core_batch_build_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let four = write_list(&dir, "four.txt", &["fish", "dish", "wish", "cart"]);
+        let five = write_list(&dir, "five.txt", &["fight", "light", "board", "bread"]);
+        let seven_words = ["fashion", "passion", "mission", "mansion", "pension"];
+        let seven = write_list(&dir, "seven.txt", &seven_words);
+        let job = |name: &str, word_size, list: &PathBuf| BuildJob {
+            name: name.to_string(),
+            word_size,
+            allowed_guesses_path: list.clone(),
+            possible_answers_path: list.clone(),
+            max_depth: 6,
+            max_cost: f64::INFINITY,
+            tolerance: 0.0,
+            output_path: dir.join(format!("{}.json", name)),
+        };
+        let jobs = vec![
+            job("four", 4, &four),
+            job("five", 5, &five),
+            job("mismatched", 4, &five),
+            job("huge", 12, &five),
+            job("seven", 7, &seven),
+        ];
+
+        let results = build_trees(
+            &jobs,
+            BatchConfig {
+                pool: PoolConfig {
+                    num_threads: Some(2),
+                },
+            },
+        )
+        .unwrap();
+        let header = results[0].as_ref().unwrap();
+        assert_eq!((header.name.as_str(), header.word_size), ("four", 4));
+        assert_eq!(header.possible_answers.len, 4);
+        assert!(matches!(results[2], Err(WordGameError::InvalidWord { .. })));
+        assert!(matches!(
+            results[3],
+            Err(WordGameError::UnsupportedWordSize(12))
+        ));
+
+        let raw = fs::read_to_string(&jobs[1].output_path).unwrap();
+        let artifact: TreeArtifact<5> = serde_json::from_str(&raw).unwrap();
+        assert_eq!(&artifact.header, results[1].as_ref().unwrap());
+        assert_eq!(artifact.header.tree_hash, artifact.tree.content_hash());
+        assert!(
+            ["fight", "light", "board", "bread"]
+                .iter()
+                .all(|answer| artifact.tree.play_out(&answer.parse().unwrap()).is_ok())
+        );
+
+        // Seven-letter hints don't have byte-sized ids, but are labelled per guess instead
+        let raw = fs::read_to_string(&jobs[4].output_path).unwrap();
+        let artifact: TreeArtifact<7> = serde_json::from_str(&raw).unwrap();
+        assert_eq!(&artifact.header, results[4].as_ref().unwrap());
+        assert!(
+            seven_words
+                .iter()
+                .all(|answer| artifact.tree.play_out(&answer.parse().unwrap()).is_ok())
+        );
+
+        // The header and the run's manifest can be read back without the tree
+        let header = ArtifactHeader::read(&jobs[1].output_path).unwrap();
+        assert_eq!(&header, results[1].as_ref().unwrap());
+        let manifest = header.manifest.unwrap();
+        assert_eq!(manifest.result, None);
+        assert_eq!(manifest.possible_answers, header.possible_answers);
+        assert_eq!(manifest.search.max_cost, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
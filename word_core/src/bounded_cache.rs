@@ -0,0 +1,237 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    mem::size_of,
+};
+
+use crate::decision_tree_general::TreeNode;
+
+/// A value whose approximate heap + inline memory usage can be estimated.
+pub trait MemoryFootprint {
+    /// Get the approximate number of bytes this value occupies.
+    fn memory_footprint(&self) -> usize;
+}
+
+impl MemoryFootprint for TreeNode {
+    fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self
+                .next
+                .values()
+                .map(|child| size_of::<u8>() + child.memory_footprint())
+                .sum::<usize>()
+            + self
+                .alternatives
+                .iter()
+                .map(|alternative| size_of::<bool>() + alternative.tree_node.memory_footprint())
+                .sum::<usize>()
+    }
+}
+
+/// Statistics on how well a cache is serving lookups.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Get the fraction of lookups that were served from the cache.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A memoization cache that stays within a memory budget by evicting least-recently-used entries.
+pub struct BoundedCache<K, V> {
+    max_bytes: usize,
+    used_bytes: usize,
+    next_stamp: u64,
+    entries: HashMap<K, CacheEntry<V>>,
+    recency: BTreeMap<u64, K>,
+    stats: CacheStats,
+}
+
+struct CacheEntry<V> {
+    value: V,
+    stamp: u64,
+    bytes: usize,
+}
+
+impl<K: Hash + Eq + Clone, V: MemoryFootprint> BoundedCache<K, V> {
+    /// Create a cache that will hold at most `max_bytes` of (estimated) keys and values.
+    pub fn with_memory_budget(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            used_bytes: 0,
+            next_stamp: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Look up a value, marking it as most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let stamp = self.next_stamp;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                self.stats.hits += 1;
+                self.next_stamp += 1;
+                let key = self.recency.remove(&entry.stamp).unwrap();
+                self.recency.insert(stamp, key);
+                entry.stamp = stamp;
+                Some(&entry.value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Insert a value, evicting least-recently-used entries until the budget is satisfied.
+    ///
+    /// Values larger than the entire budget are not stored.
+    pub fn insert(&mut self, key: K, value: V) {
+        let bytes = size_of::<K>() + size_of::<CacheEntry<V>>() + value.memory_footprint();
+        self.remove(&key);
+        if bytes > self.max_bytes {
+            return;
+        }
+        while self.used_bytes + bytes > self.max_bytes {
+            let (_, evict_key) = self.recency.pop_first().unwrap();
+            let evicted = self.entries.remove(&evict_key).unwrap();
+            self.used_bytes -= evicted.bytes;
+            self.stats.evictions += 1;
+        }
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.recency.insert(stamp, key.clone());
+        self.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                stamp,
+                bytes,
+            },
+        );
+        self.used_bytes += bytes;
+        self.stats.insertions += 1;
+    }
+
+    /// Remove a value from the cache, if present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.stamp);
+        self.used_bytes -= entry.bytes;
+        Some(entry.value)
+    }
+
+    /// Iterate over the cached entries, least recently used first, so inserting them in order
+    /// into another cache reproduces this one.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.recency
+            .values()
+            .map(|key| (key, &self.entries[key].value))
+    }
+
+    /// Get the number of entries currently cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache currently holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the estimated number of bytes currently used by cached entries.
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    /// Get the configured memory budget in bytes.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Get the hit / miss / eviction statistics so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl MemoryFootprint for u64 {
+        fn memory_footprint(&self) -> usize {
+            size_of::<u64>()
+        }
+    }
+
+    fn entry_bytes() -> usize {
+        size_of::<u64>() + size_of::<CacheEntry<u64>>() + size_of::<u64>()
+    }
+
+    #[test]
+    fn test_hit_and_miss() {
+        let mut cache: BoundedCache<u64, u64> = BoundedCache::with_memory_budget(1024);
+        cache.insert(1, 10);
+        assert_eq!(cache.get(&1), Some(&10));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                insertions: 1,
+                evictions: 0
+            }
+        );
+        assert_eq!(cache.stats().hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache: BoundedCache<u64, u64> = BoundedCache::with_memory_budget(entry_bytes() * 2);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        // Touch 1 so that 2 becomes the least recently used
+        cache.get(&1);
+        cache.insert(3, 30);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some(&10));
+        assert_eq!(cache.get(&3), Some(&30));
+        assert_eq!(cache.stats().evictions, 1);
+        assert!(cache.used_bytes() <= cache.max_bytes());
+    }
+
+    #[test]
+    fn test_reinsert_replaces() {
+        let mut cache: BoundedCache<u64, u64> = BoundedCache::with_memory_budget(entry_bytes() * 2);
+        cache.insert(1, 10);
+        cache.insert(1, 11);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.used_bytes(), entry_bytes());
+        assert_eq!(cache.get(&1), Some(&11));
+    }
+
+    #[test]
+    fn test_oversized_value_not_stored() {
+        let mut cache: BoundedCache<u64, u64> = BoundedCache::with_memory_budget(4);
+        cache.insert(1, 10);
+        assert!(cache.is_empty());
+        assert_eq!(cache.used_bytes(), 0);
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::WordGameError,
+    hint::WordHint,
+    mask_encoding::{WordListId, word_list_hash},
+    readable_tree::ReadableTreeNode,
+    word::Word,
+};
+
+/// The certificate format version written by this crate
+pub const CERTIFICATE_VERSION: u32 = 1;
+
+/// The guesses a strategy plays against one answer, ending with the answer itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificatePath<const WORD_SIZE: usize> {
+    pub answer: Word<WORD_SIZE, 26>,
+    pub guesses: Vec<Word<WORD_SIZE, 26>>,
+}
+
+/// A claim that some strategy solves every answer in a list within `max_guesses`, spelled out
+/// answer by answer so it can be checked without the tree or the search that produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Certificate<const WORD_SIZE: usize> {
+    pub version: u32,
+    pub answers: WordListId,
+    pub max_guesses: usize,
+
+    /// One path per answer, in answer list order
+    pub paths: Vec<CertificatePath<WORD_SIZE>>,
+}
+
+impl<const WORD_SIZE: usize> Certificate<WORD_SIZE> {
+    /// Play every answer through a tree, failing if any isn't solved within `max_guesses`.
+    pub fn from_tree(
+        tree: &ReadableTreeNode<WORD_SIZE>,
+        list: &WordListId,
+        answers: &[Word<WORD_SIZE, 26>],
+        max_guesses: usize,
+    ) -> Result<Self, WordGameError> {
+        let paths = answers
+            .iter()
+            .map(|answer| {
+                let guesses: Vec<Word<WORD_SIZE, 26>> = tree
+                    .play_out(answer)?
+                    .into_iter()
+                    .map(|(guess, _)| guess)
+                    .collect();
+                if guesses.len() > max_guesses {
+                    return Err(WordGameError::Infeasible(format!(
+                        "tree needs {} guesses for {}, more than {}",
+                        guesses.len(),
+                        answer,
+                        max_guesses
+                    )));
+                }
+                Ok(CertificatePath {
+                    answer: *answer,
+                    guesses,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            version: CERTIFICATE_VERSION,
+            answers: list.clone(),
+            max_guesses,
+            paths,
+        })
+    }
+
+    /// Check the certificate against the answer list it claims to cover, returning the most
+    /// guesses any answer actually needs.
+    ///
+    /// This only scores hints, so it's independent of the solver. Every answer must have a path
+    /// ending in it within `max_guesses`, no earlier guess may already win, every guess must be
+    /// in `allowed_guesses` or the answer list, and answers that have received the same hints so
+    /// far must be given the same next guess, so the paths really come from a single strategy.
+    pub fn verify(
+        &self,
+        answers: &[Word<WORD_SIZE, 26>],
+        allowed_guesses: &[Word<WORD_SIZE, 26>],
+    ) -> Result<usize, WordGameError> {
+        let invalid = |reason: String| Err(WordGameError::InvalidCertificate(reason));
+        if self.version != CERTIFICATE_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("certificate v{}", CERTIFICATE_VERSION),
+                found: format!("certificate v{}", self.version),
+            });
+        }
+        let hash = format!("{:016x}", word_list_hash(answers));
+        if self.answers.hash != hash || self.answers.len != answers.len() {
+            return invalid(format!(
+                "made for answer list {} ({} words, hash {}), not this one ({} words, hash {})",
+                self.answers.name,
+                self.answers.len,
+                self.answers.hash,
+                answers.len(),
+                hash
+            ));
+        }
+        if self.paths.len() != answers.len() {
+            return invalid(format!(
+                "{} paths for {} answers",
+                self.paths.len(),
+                answers.len()
+            ));
+        }
+
+        let legal: HashSet<&Word<WORD_SIZE, 26>> =
+            allowed_guesses.iter().chain(answers.iter()).collect();
+        let mut strategy: HashMap<Vec<WordHint<WORD_SIZE>>, Word<WORD_SIZE, 26>> = HashMap::new();
+        let mut worst_case = 0;
+        for (path, answer) in self.paths.iter().zip(answers) {
+            if path.answer != *answer {
+                return invalid(format!(
+                    "expected a path for {}, found {}",
+                    answer, path.answer
+                ));
+            }
+            if path.guesses.len() > self.max_guesses {
+                return invalid(format!(
+                    "{} takes {} guesses, more than {}",
+                    answer,
+                    path.guesses.len(),
+                    self.max_guesses
+                ));
+            }
+            let mut hints = vec![];
+            for guess in &path.guesses {
+                if !legal.contains(guess) {
+                    return invalid(format!("{} is not an allowed guess", guess));
+                }
+                if hints.last().is_some_and(WordHint::all_correct) {
+                    return invalid(format!("{} keeps guessing after it's solved", answer));
+                }
+                let expected = *strategy.entry(hints.clone()).or_insert(*guess);
+                if expected != *guess {
+                    return invalid(format!(
+                        "{} guesses {} where another answer with the same hints guesses {}",
+                        answer, guess, expected
+                    ));
+                }
+                hints.push(WordHint::from_guess_and_answer(guess, answer));
+            }
+            if !hints.last().is_some_and(WordHint::all_correct) {
+                return invalid(format!("{} is never guessed", answer));
+            }
+            worst_case = worst_case.max(path.guesses.len());
+        }
+        Ok(worst_case)
+    }
+
+    /// Serialize to compact JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Parse from JSON.
+    pub fn from_json(raw: &str) -> Result<Self, WordGameError> {
+        Ok(serde_json::from_str(raw)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words::{self, words};
+
+    fn tree(guess: &str, answers: &[Word<5, 26>]) -> ReadableTreeNode<5> {
+        test_words::guess_then_answers(Word::try_from(guess).unwrap(), answers, 0.0)
+    }
+
+    #[test]
+    fn test_round_trip_and_verify() {
+        let answers = words(&["fight", "bread", "board"]);
+        let allowed_guesses = words(&["lemon"]);
+        let list = WordListId::new("test", &answers);
+        let tree = tree("board", &answers);
+
+        let certificate = Certificate::from_tree(&tree, &list, &answers, 2).unwrap();
+        let certificate = Certificate::from_json(&certificate.to_json()).unwrap();
+        assert_eq!(certificate.verify(&answers, &allowed_guesses).unwrap(), 2);
+
+        assert!(matches!(
+            Certificate::from_tree(&tree, &list, &answers, 1),
+            Err(WordGameError::Infeasible(_))
+        ));
+        assert!(matches!(
+            certificate.verify(&answers[..2], &allowed_guesses),
+            Err(WordGameError::InvalidCertificate(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_bad_paths() {
+        let answers = words(&["fight", "bread", "board"]);
+        let list = WordListId::new("test", &answers);
+        let mut certificate =
+            Certificate::from_tree(&tree("board", &answers), &list, &answers, 3).unwrap();
+        assert_eq!(certificate.verify(&answers, &[]).unwrap(), 2);
+
+        // Nothing has been revealed yet, so every answer must open with the same guess
+        certificate.paths[0].guesses = words(&["fight"]);
+        assert!(matches!(
+            certificate.verify(&answers, &[]),
+            Err(WordGameError::InvalidCertificate(_))
+        ));
+
+        // A guess outside both lists
+        certificate.paths[0].guesses = words(&["board", "tight", "fight"]);
+        assert!(matches!(
+            certificate.verify(&answers, &[]),
+            Err(WordGameError::InvalidCertificate(_))
+        ));
+        assert_eq!(certificate.verify(&answers, &words(&["tight"])).unwrap(), 3);
+    }
+}
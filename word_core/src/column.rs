@@ -1,7 +1,9 @@
-use std::ops;
+use std::{collections::BTreeMap, ops};
+
+use crate::error::WordGameError;
 
 /// A simple column of booleans packed into a u64 for performant binary ops.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Column {
     len: usize,
     col: Vec<u64>,
@@ -10,7 +12,7 @@ pub struct Column {
 impl Column {
     /// Generate a column with `len` true values
     pub fn from_true(len: usize) -> Self {
-        let num_chunks = (len + 63) / 64;
+        let num_chunks = len.div_ceil(64);
         Self {
             len,
             col: vec![u64::MAX; num_chunks],
@@ -19,7 +21,7 @@ impl Column {
 
     /// Generate a column with `len` false values
     pub fn from_false(len: usize) -> Self {
-        let num_chunks = (len + 63) / 64;
+        let num_chunks = len.div_ceil(64);
         Self {
             len,
             col: vec![0; num_chunks],
@@ -37,7 +39,7 @@ impl Column {
 
     /// Generate a column from a list of bools
     pub fn from_bools(bools: &[bool]) -> Self {
-        let num_chunks = (bools.len() + 63) / 64; // Divide & round-up
+        let num_chunks = bools.len().div_ceil(64); // Divide & round-up
         let mut col = Vec::with_capacity(num_chunks);
 
         for chunk in bools.chunks(64) {
@@ -54,6 +56,27 @@ impl Column {
         }
     }
 
+    /// Rebuild a column from its packed 64-bit chunks, as produced by `to_chunks`
+    pub fn from_chunks(len: usize, chunks: Vec<u64>) -> Self {
+        if chunks.len() != len.div_ceil(64) {
+            panic!(
+                "Cannot build col of len {} from {} chunks",
+                len,
+                chunks.len()
+            );
+        }
+        let mut out = Self { len, col: chunks };
+        out.clear_past_end();
+        out
+    }
+
+    /// Get the packed 64-bit chunks of this col, with any bits past the end cleared
+    pub fn to_chunks(&self) -> Vec<u64> {
+        let mut out = self.clone();
+        out.clear_past_end();
+        out.col
+    }
+
     /// Reconstruct a vec of bools from a column
     pub fn to_bools(&self) -> Vec<bool> {
         let mut out = Vec::with_capacity(self.len);
@@ -75,6 +98,11 @@ impl Column {
         self.len
     }
 
+    /// Whether this col holds no items
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     /// Count how many true values exist in this col
     pub fn count_true(&self) -> u64 {
         let (full_chunks, partial_chunk) = self.by_chunk_fill();
@@ -93,32 +121,87 @@ impl Column {
         self.len as u64 - self.count_true()
     }
 
-    /// Get the indices in the column that have true.
-    pub fn true_inds(&self) -> Vec<usize> {
-        let mut out = Vec::with_capacity(self.count_true() as usize);
-        for (chunk_ind, value) in self.col.iter().enumerate() {
-            if *value == 0 {
+    /// Count the true values in each of many columns with one pass over their chunks, rather
+    /// than a full pass per column. The columns may differ in length.
+    pub fn count_true_each(cols: &[&Self]) -> Vec<u64> {
+        let num_chunks = cols.iter().map(|col| col.col.len()).max().unwrap_or(0);
+        let mut counts = vec![0; cols.len()];
+        for chunk_ind in 0..num_chunks {
+            for (count, col) in counts.iter_mut().zip(cols) {
+                *count += col.masked_chunk(chunk_ind).count_ones() as u64;
+            }
+        }
+        counts
+    }
+
+    /// Count the true values each column shares with the mask, without building the anded
+    /// columns. Every column must be the mask's length.
+    pub fn count_true_each_within(cols: &[&Self], mask: &Self) -> Vec<u64> {
+        if let Some(col) = cols.iter().find(|col| col.len != mask.len) {
+            panic!(
+                "Cannot count col of len {} within mask of len {}",
+                col.len, mask.len
+            );
+        }
+        let mut counts = vec![0; cols.len()];
+        for chunk_ind in 0..mask.col.len() {
+            let mask_chunk = mask.masked_chunk(chunk_ind);
+            if mask_chunk == 0 {
                 continue;
             }
-            for bit_ind in 0..64 {
-                let global_ind = chunk_ind * 64 + bit_ind;
-                if global_ind >= self.len {
-                    break;
-                }
-                if (*value & (1 << bit_ind)) != 0 {
-                    out.push(global_ind);
-                }
+            for (count, col) in counts.iter_mut().zip(cols) {
+                *count += (col.col[chunk_ind] & mask_chunk).count_ones() as u64;
             }
         }
+        counts
+    }
+
+    /// Tally how many columns have each number of true values, given counts like those from
+    /// `count_true_each`.
+    pub fn count_histogram(counts: &[u64]) -> BTreeMap<u64, usize> {
+        let mut histogram = BTreeMap::new();
+        for count in counts {
+            *histogram.entry(*count).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Get the indices in the column that have true.
+    pub fn true_inds(&self) -> Vec<usize> {
+        let mut out = Vec::with_capacity(self.count_true() as usize);
+        out.extend(self.iter_true());
         out
     }
 
+    /// Iterate the indices in the column that have true, in ascending order.
+    ///
+    /// Jumps between set bits with `trailing_zeros`, so sparse columns cost about one step per
+    /// true value plus one per chunk.
+    pub fn iter_true(&self) -> TrueInds<'_> {
+        TrueInds {
+            col: self,
+            chunk_ind: 0,
+            chunk: self.masked_chunk(0),
+        }
+    }
+
+    /// Get a chunk with any bits past the end of the column cleared, or 0 past the last chunk.
+    fn masked_chunk(&self, chunk_ind: usize) -> u64 {
+        match self.col.get(chunk_ind) {
+            Some(chunk) if chunk_ind == self.col.len() - 1 && !self.len.is_multiple_of(64) => {
+                chunk & first_n_bits(self.len as u64 % 64)
+            }
+            Some(chunk) => *chunk,
+            None => 0,
+        }
+    }
+
     /// Get the value at a particular ind
     pub fn get(&self, ind: usize) -> bool {
         if ind >= self.len {
             panic!("Cannot access col ind {} with len {}", ind, self.len)
         }
-        return (self.col[ind / 64] & (1 << (ind % 64))) != 0;
+        (self.col[ind / 64] & (1 << (ind % 64))) != 0
     }
 
     /// Set the value at a particular ind
@@ -133,6 +216,47 @@ impl Column {
         }
     }
 
+    /// Get the value at a particular ind, or an error if it's out of range
+    pub fn try_get(&self, ind: usize) -> Result<bool, WordGameError> {
+        if ind >= self.len {
+            return Err(WordGameError::ColumnIndex { ind, len: self.len });
+        }
+        Ok(self.get(ind))
+    }
+
+    /// Set the value at a particular ind, or return an error if it's out of range
+    pub fn try_set(&mut self, ind: usize, val: bool) -> Result<(), WordGameError> {
+        if ind >= self.len {
+            return Err(WordGameError::ColumnIndex { ind, len: self.len });
+        }
+        self.set(ind, val);
+        Ok(())
+    }
+
+    /// Bitwise and the rhs into this value, or return an error if different length
+    pub fn try_and_assign(&mut self, rhs: Self) -> Result<(), WordGameError> {
+        if self.len != rhs.len {
+            return Err(WordGameError::ColumnLengthMismatch {
+                left: self.len,
+                right: rhs.len,
+            });
+        }
+        *self &= rhs;
+        Ok(())
+    }
+
+    /// Bitwise or the rhs into this value, or return an error if different length
+    pub fn try_or_assign(&mut self, rhs: Self) -> Result<(), WordGameError> {
+        if self.len != rhs.len {
+            return Err(WordGameError::ColumnLengthMismatch {
+                left: self.len,
+                right: rhs.len,
+            });
+        }
+        *self |= rhs;
+        Ok(())
+    }
+
     /// Get a new column with only the entries with indices in the given list.
     ///
     /// ```rs
@@ -142,16 +266,70 @@ impl Column {
     /// ```
     pub fn filter(&self, inds: &[usize]) -> Self {
         let mut out = Self::from_false(inds.len());
-        inds.iter()
-            .enumerate()
-            .filter(|(_new_ind, old_ind)| self.get(**old_ind))
-            .for_each(|(new_ind, _old_ind)| out.set(new_ind, true));
+        if inds.windows(2).all(|pair| pair[0] < pair[1]) {
+            // Walk only our true values, looking each one up among the remaining inds
+            let mut new_ind = 0;
+            for old_ind in self.iter_true() {
+                new_ind += inds[new_ind..].partition_point(|ind| *ind < old_ind);
+                if new_ind == inds.len() {
+                    break;
+                }
+                if inds[new_ind] == old_ind {
+                    out.col[new_ind / 64] |= 1 << (new_ind % 64);
+                }
+            }
+        } else {
+            inds.iter()
+                .enumerate()
+                .filter(|(_new_ind, old_ind)| self.get(**old_ind))
+                .for_each(|(new_ind, _old_ind)| out.set(new_ind, true));
+        }
         out
     }
 
+    /// Split into a column of the first `mid` values and a column of the rest.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        if mid > self.len {
+            panic!("Cannot split column of length {} at {}", self.len, mid);
+        }
+        let mut left = Self {
+            len: mid,
+            col: self.col[..mid.div_ceil(64)].to_vec(),
+        };
+        let right_len = self.len - mid;
+        let start_chunk = mid / 64;
+        let shift = mid % 64;
+        let mut right = Self {
+            len: right_len,
+            col: (0..right_len.div_ceil(64))
+                .map(|chunk_ind| {
+                    let low = self.col[start_chunk + chunk_ind] >> shift;
+                    let high = match (shift, self.col.get(start_chunk + chunk_ind + 1)) {
+                        (0, _) | (_, None) => 0,
+                        (_, Some(next_chunk)) => next_chunk << (64 - shift),
+                    };
+                    low | high
+                })
+                .collect(),
+        };
+        left.clear_past_end();
+        right.clear_past_end();
+        (left, right)
+    }
+
+    /// Zero any bits in the last chunk that lie past the end of the column
+    fn clear_past_end(&mut self) {
+        let fill = self.len % 64;
+        if let Some(last_chunk) = self.col.last_mut()
+            && fill != 0
+        {
+            *last_chunk &= first_n_bits(fill as u64);
+        }
+    }
+
     /// Return all of the full chunks and optionally a non-full end chunk
     fn by_chunk_fill(&self) -> (&[u64], Option<u64>) {
-        if self.len % 64 == 0 {
+        if self.len.is_multiple_of(64) {
             (&self.col, None)
         } else {
             (
@@ -200,6 +378,32 @@ impl ops::Not for Column {
     }
 }
 
+/// Iterator over the true indices of a column, see [`Column::iter_true`].
+pub struct TrueInds<'a> {
+    col: &'a Column,
+    chunk_ind: usize,
+
+    /// Bits of the current chunk not yet yielded
+    chunk: u64,
+}
+
+impl Iterator for TrueInds<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.chunk == 0 {
+            self.chunk_ind += 1;
+            if self.chunk_ind >= self.col.col.len() {
+                return None;
+            }
+            self.chunk = self.col.masked_chunk(self.chunk_ind);
+        }
+        let bit_ind = self.chunk.trailing_zeros() as usize;
+        self.chunk &= self.chunk - 1;
+        Some(self.chunk_ind * 64 + bit_ind)
+    }
+}
+
 /// Generate a u64 with the first n bits set to 1
 fn first_n_bits(n: u64) -> u64 {
     if n >= 64 { u64::MAX } else { (1 << n) - 1 }
@@ -213,6 +417,20 @@ mod tests {
         raw.bytes().map(|byte| byte == b'1').collect()
     }
 
+    #[test]
+    fn test_split_at() {
+        let bools: Vec<bool> = (0..223).map(|i| i % 5 == 0 || i % 7 == 0).collect();
+        let col = Column::from_bools(&bools);
+        for mid in [0, 1, 63, 64, 100, 128, 222, 223] {
+            let (left, right) = col.split_at(mid);
+            assert_eq!(left.to_bools(), bools[..mid]);
+            assert_eq!(right.to_bools(), bools[mid..]);
+            assert_eq!(left.count_true() + right.count_true(), col.count_true());
+        }
+        let (left, right) = Column::from_true(70).split_at(3);
+        assert_eq!((left.count_true(), right.count_true()), (3, 67));
+    }
+
     #[test]
     fn test_pack_unpack_empty() {
         let bools = vec![];
@@ -288,6 +506,38 @@ mod tests {
         assert_eq!(col.count_false(), 223 - 45);
     }
 
+    #[test]
+    fn test_count_true_each() {
+        let cols: Vec<Column> = [3, 5, 7]
+            .iter()
+            .map(|step| Column::from_bools(&(0..223).map(|i| i % step == 0).collect::<Vec<_>>()))
+            .chain([Column::from_true(223), Column::from_true(10)])
+            .collect();
+        let col_refs: Vec<&Column> = cols.iter().collect();
+        let counts = Column::count_true_each(&col_refs);
+        assert_eq!(
+            counts,
+            cols.iter().map(Column::count_true).collect::<Vec<u64>>()
+        );
+
+        let mask = Column::from_bools(&(0..223).map(|i| i % 2 == 0).collect::<Vec<_>>());
+        let within = Column::count_true_each_within(&col_refs[..4], &mask);
+        let expected: Vec<u64> = cols[..4]
+            .iter()
+            .map(|col| {
+                let mut anded = col.clone();
+                anded &= mask.clone();
+                anded.count_true()
+            })
+            .collect();
+        assert_eq!(within, expected);
+
+        assert_eq!(
+            Column::count_histogram(&[3, 0, 3, 10]),
+            BTreeMap::from([(0, 1), (3, 2), (10, 1)])
+        );
+    }
+
     #[test]
     fn test_count_true_false_when_ones_in_junk() {
         let col = Column::from_true(223);
@@ -353,4 +603,47 @@ mod tests {
 
         assert_eq!(col.filter(&mask.true_inds()), expected)
     }
+
+    #[test]
+    fn test_iter_true_and_filter_sparse() {
+        // Negating sets the junk bits past the end, which must never be yielded
+        let mut col = !Column::from_false(200);
+        for ind in 0..200 {
+            col.set(ind, [0, 63, 64, 130, 199].contains(&ind));
+        }
+        assert_eq!(
+            col.iter_true().collect::<Vec<usize>>(),
+            [0, 63, 64, 130, 199]
+        );
+        assert_eq!((!Column::from_false(70)).iter_true().count(), 70);
+        assert_eq!(Column::from_false(0).iter_true().next(), None);
+
+        let sorted_inds = [1, 63, 64, 65, 129, 130, 199];
+        let filtered = col.filter(&sorted_inds);
+        assert_eq!(filtered.true_inds(), [1, 2, 5, 6]);
+
+        // Unsorted and repeated inds take the slower path but give the same answer
+        let shuffled_inds = [199, 1, 64, 64, 130];
+        assert_eq!(col.filter(&shuffled_inds).true_inds(), [0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_checked_access() {
+        let mut col = Column::from_false(70);
+        assert!(col.try_set(69, true).is_ok());
+        assert!(col.try_get(69).unwrap());
+        assert!(matches!(
+            col.try_get(70),
+            Err(WordGameError::ColumnIndex { ind: 70, len: 70 })
+        ));
+        assert!(matches!(
+            col.try_and_assign(Column::from_true(71)),
+            Err(WordGameError::ColumnLengthMismatch {
+                left: 70,
+                right: 71
+            })
+        ));
+        assert!(col.try_or_assign(Column::from_true(70)).is_ok());
+        assert_eq!(col.count_true(), 70);
+    }
 }
@@ -1,12 +1,9 @@
-use std::{
-    collections::{HashMap, HashSet},
-    f64::INFINITY,
-};
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    column::Column, hint::WordHint, query_generation::clue_to_query, word::Word,
+    column::Column, hint::WordHint, query_generation::clue_to_query, render::Bold, word::Word,
     word_search::SearchableWords,
 };
 
@@ -40,8 +37,10 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
         let answer = possible_answers.filter_words(&Column::from_true(1))[0];
         if do_print {
             println!(
-                "{}best guess is \x1b[1m{}\x1b[0m with est cost of {}",
-                prefix, answer, 1.0
+                "{}best guess is {} with est cost of {}",
+                prefix,
+                Bold(answer),
+                1.0
             );
         }
         return Some((
@@ -59,8 +58,10 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
         let possible_answer_b = possible_answer_words[1];
         if do_print {
             println!(
-                "{}best guess is \x1b[1m{}\x1b[0m with est cost of {}",
-                prefix, possible_answer_a, 1.5
+                "{}best guess is {} with est cost of {}",
+                prefix,
+                Bold(possible_answer_a),
+                1.5
             );
         }
         return Some((
@@ -83,16 +84,28 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
         f64,
     )> = None;
     for (guess_ind, guess) in allowed_guesses.iter().enumerate() {
-        if !do_print && depth <= 0 {
+        if !do_print && depth == 0 {
             println!(
-                "evaluating level {} guess \x1b[1m{}\x1b[0m - {:.0}%",
+                "evaluating level {} guess {} - {:.0}%",
                 depth,
-                guess,
+                Bold(guess),
                 100.0 * guess_ind as f64 / allowed_guesses.len() as f64
             );
         }
         if do_print {
-            println!("{}evaluating guess \x1b[1m{}\x1b[0m", prefix, guess)
+            println!("{}evaluating guess {}", prefix, Bold(guess))
+        }
+
+        // Cheap pre-screen - a guess sharing no letters with any possible answer can't split them
+        if !possible_answers.shares_any_letter(guess) {
+            if do_print {
+                println!(
+                    "{}guess {} shares no letters with any answer, skipping",
+                    prefix,
+                    Bold(guess)
+                );
+            }
+            continue;
         }
 
         // Evaluate if this guess is useless before scanning all possible hints
@@ -104,10 +117,7 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
         ));
         if mask.count_true() == possible_answers.len() as u64 {
             if do_print {
-                println!(
-                    "{}guess \x1b[1m{}\x1b[0m is useless, skipping",
-                    prefix, guess
-                );
+                println!("{}guess {} is useless, skipping", prefix, Bold(guess));
             }
             continue;
         }
@@ -131,7 +141,7 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
         for (word_hint_ind, word_hint) in possible_hints.into_iter().enumerate() {
             if !do_print && depth < 1 {
                 println!(
-                    "evaluating level {} clue {}\x1b[0m - {:.0}%",
+                    "evaluating level {} clue {} - {:.0}%",
                     depth,
                     word_hint.color_guess(guess),
                     100.0 * word_hint_ind as f64 / num_possible_hints as f64
@@ -168,9 +178,9 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
             if depth == max_depth - 1 {
                 // We've used all our allowed guesses, don't consider this path
                 if do_print {
-                    println!("{}guess \x1b[1m{}\x1b[0m is too expensive", prefix, guess);
+                    println!("{}guess {} is too expensive", prefix, Bold(guess));
                 }
-                guess_est_cost = INFINITY;
+                guess_est_cost = f64::INFINITY;
                 break;
             }
             if let Some((child_node, child_est_addl_cost)) = compute_node_aggressive(
@@ -186,31 +196,32 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
             } else {
                 if do_print {
                     println!(
-                        "{}guess \x1b[1m{}\x1b[0m cannot guarantee an answer within depth limit",
-                        prefix, guess
+                        "{}guess {} cannot guarantee an answer within depth limit",
+                        prefix,
+                        Bold(guess)
                     );
                 }
-                guess_est_cost = INFINITY;
+                guess_est_cost = f64::INFINITY;
                 break;
             }
         }
-        if guess_est_cost == INFINITY {
+        if guess_est_cost == f64::INFINITY {
             continue;
         }
-        let this_guess_is_new_best = match best {
-            Some((_, _, best_guess_est_cost)) if best_guess_est_cost <= guess_est_cost => false,
-            _ => true,
-        };
+        let this_guess_is_new_best = !matches!(
+            best,
+            Some((_, _, best_guess_est_cost)) if best_guess_est_cost <= guess_est_cost
+        );
         if do_print {
             println!(
-                "{}guess \x1b[1m{}\x1b[0m has est cost {} - {}",
+                "{}guess {} has est cost {} - {}",
                 prefix,
-                guess,
+                Bold(guess),
                 guess_est_cost,
                 if this_guess_is_new_best {
-                    "\x1b[1mnew best\x1b[0m"
+                    Bold("new best").to_string()
                 } else {
-                    "rejecting"
+                    "rejecting".to_string()
                 }
             );
         }
@@ -221,8 +232,10 @@ pub fn compute_node_aggressive<const WORD_SIZE: usize>(
     let (best_guess, best_guess_decision_tree, best_guess_est_cost) = best?;
     if do_print {
         println!(
-            "{}best guess is \x1b[1m{}\x1b[0m with est cost of {}",
-            prefix, best_guess, best_guess_est_cost
+            "{}best guess is {} with est cost of {}",
+            prefix,
+            Bold(best_guess),
+            best_guess_est_cost
         );
     }
     Some((
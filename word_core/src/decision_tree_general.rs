@@ -1,438 +1,4597 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering as AtomicOrdering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounded_cache::{BoundedCache, MemoryFootprint},
+    column::Column,
+    error::WordGameError,
+    hint_matrix::HintMatrixView,
+    mask_encoding::{FNV_OFFSET_BASIS, fnv1a},
+    render::render_mode,
+    suggestion::PartitionStats,
+    text_plot::{size_counts, sparkline},
+    word_search::LetterScreen,
+    worker_pool::{CancelToken, PoolConfig, WorkerPool},
+};
 
 /// A representation of a guess coming from one of either input list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GuessFrom {
     Guess(u16),
     Answer(u16),
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TreeNode {
     pub should_guess: GuessFrom,
-    pub est_cost: f64,
+    /// The guesses it takes to solve each answer under this node, this guess included, summed
+    /// over the answers. Costs are compared by this exact count, never by their average.
+    pub total_guesses: u32,
+    pub num_answers: u32,
+    #[serde(with = "hint_pairs")]
     pub next: HashMap<u8, TreeNode>,
+    /// Whether this subtree came from a fallback, a beam-limited search or a search with
+    /// tolerance rather than the exhaustive search, in which case its cost is only an upper
+    /// bound on the best achievable
+    pub approximate: bool,
+    /// The runner-up guesses at this node, best first, when requested in the search options
+    pub alternatives: Vec<Alternative<TreeNode>>,
+}
+
+impl TreeNode {
+    /// Get the expected number of guesses to solve from this node, over its answers.
+    pub fn est_cost(&self) -> f64 {
+        self.total_guesses as f64 / self.num_answers as f64
+    }
+
+    /// Get the most guesses it takes to solve any answer from this node.
+    pub fn worst_case(&self) -> u32 {
+        1 + self.next.values().map(Self::worst_case).max().unwrap_or(0)
+    }
+}
+
+/// Serializes a node's children as `[hint, child]` pairs in hint order, so a tree always writes
+/// the same JSON. Hint ids as map keys would become strings in JSON, which don't parse back as
+/// ids within a flattened `Alternative`.
+mod hint_pairs {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::TreeNode;
+
+    pub fn serialize<S: Serializer>(
+        next: &HashMap<u8, TreeNode>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<(&u8, &TreeNode)> = next.iter().collect();
+        pairs.sort_unstable_by_key(|(hint, _)| **hint);
+        serializer.collect_seq(pairs)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<u8, TreeNode>, D::Error> {
+        Ok(Vec::<(u8, TreeNode)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// A guess that lost out to the best guess at some node, with however much of it was evaluated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alternative<T> {
+    #[serde(flatten)]
+    pub tree_node: T,
+    /// Whether every hint was evaluated. If not, the search pruned this guess partway through
+    /// and its cost is only a lower bound.
+    pub complete: bool,
+}
+
+/// Optional behaviour of the exhaustive search.
+#[derive(Debug, Default, Clone)]
+pub struct SearchOptions {
+    pub budget: NodeBudget,
+    /// How many runner-up guesses to keep alongside the best guess at each node
+    pub num_alternatives: usize,
+    /// Evaluate the runner-ups at the node the search starts from in full rather than pruning
+    /// them against the best guess, so its alternatives are the next best guesses with their
+    /// complete subtrees. This prunes less, so searches take longer.
+    pub complete_alternatives: bool,
+    /// Settings for nodes at each depth, indexed by depth. Deeper nodes reuse the last entry,
+    /// and with no entries every node uses the defaults.
+    pub depth_settings: Vec<DepthSettings>,
+    /// Guess indices never played at the node the search starts from, though they may still be
+    /// played deeper in the tree. The shortcuts for one or two remaining answers guess from the
+    /// answer list and so ignore this.
+    pub root_exclusions: HashSet<u16>,
+    /// Preference rank of each guess index, lower preferred, used to choose between guesses
+    /// whose est costs tie, like those from `preference_ranks`. Empty to leave ties to the
+    /// search order.
+    pub guess_ranks: Vec<u32>,
+    /// How far above optimal the tree's est cost may be, in exchange for pruning harder. Each
+    /// level of the search gets an equal share, and skips any guess that can't beat the best
+    /// so far by more than that share. Zero searches exactly.
+    pub tolerance: f64,
+    /// Evaluate the guesses at the node the search starts from across threads, sharing the best
+    /// est cost so far between them so they still prune each other. Ties and runner-ups may
+    /// settle differently from the single-threaded search, and workers only report their
+    /// progress to observers that give a `WorkerObserver`.
+    pub parallel_root: bool,
+    /// The threads `parallel_root` runs on
+    pub pool: PoolConfig,
+    /// Memory budget in bytes for remembering solved subtrees by their set of possible answers,
+    /// so a set reached through different guesses is only searched once. Each parallel root
+    /// worker keeps a table of its own. Zero disables it.
+    pub transposition_bytes: usize,
+    /// What the search minimizes
+    pub objective: Objective,
+    /// Wall-clock time the whole search may take, in milliseconds. Once it's up the search
+    /// stops and returns the cheaper of the best complete tree found so far and the greedy
+    /// tree, marked as approximate, so long as one fits within the max cost.
+    pub time_limit_millis: Option<u64>,
+    /// Stops the search like the time limit does once cancelled, such as from a Ctrl-C handler
+    pub cancel: Option<CancelToken>,
+    /// Orders the guesses at every node in place of each depth's heuristic
+    pub guess_ordering: Option<Arc<dyn GuessOrdering>>,
+    /// Guess only words from the answer list, at every node, as the "hard candidate" strategy
+    /// does. A guess counts as an answer if it can get the all-correct hint. Nodes that run over
+    /// budget or time still fall back on the greedy tree over every guess.
+    pub answers_only: bool,
+    /// At nodes with fewer possible answers than this, guess only the answers still possible,
+    /// going for the win rather than for information once few answers remain. Like
+    /// `answers_only`, the tree is then the best under that rule rather than overall.
+    pub candidates_only_below: Option<usize>,
+    /// Limits the guesses at every node to those legal after the clues leading to it, as in
+    /// hard mode. The shortcuts for one or two remaining answers guess a possible answer, which
+    /// is legal under any policy that only asks guesses to use the clues. Nodes that run over
+    /// budget or time still fall back on the greedy tree over every guess.
+    pub guess_legality: Option<Arc<dyn ClueLegality>>,
+    /// The guesses legal at the node the search starts from, as a mask over the hint matrix's
+    /// rows, for a search started after clues that already limit play. Deeper nodes narrow it
+    /// by `guess_legality`. Every guess is legal at the root if not given.
+    pub root_legal_guesses: Option<Column>,
+    /// Skips guesses sharing no letters with any possible answer before partitioning the
+    /// answers by them, which at late nodes is most of the guesses. It must be built from the
+    /// same guesses and answers as the hint matrix.
+    pub letter_screen: Option<Arc<LetterScreen>>,
+    /// Guess indices the search may play at every node, as a mask over the hint matrix's rows,
+    /// or every guess if not given. Like `answers_only`, the shortcuts for one or two remaining
+    /// answers guess a possible answer, and nodes that run over budget or time fall back on the
+    /// greedy tree over every guess.
+    pub guess_subset: Option<Column>,
+}
+
+/// What makes one decision tree better than another.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    /// Fewest guesses on average, with the max cost capping the average
+    #[default]
+    ExpectedGuesses,
+
+    /// Fewest guesses for the hardest answer, with the max cost capping that many guesses.
+    /// Trees that tie on the worst case are settled by the fewest guesses on average.
+    WorstCase,
+}
+
+impl SearchOptions {
+    /// Get these options with the time limit reduced by the time since `started`, for a search
+    /// made of several attempts that share one limit.
+    fn with_time_since(&self, started: Instant) -> Self {
+        Self {
+            time_limit_millis: self.time_limit_millis.map(|time_limit_millis| {
+                time_limit_millis.saturating_sub(started.elapsed().as_millis() as u64)
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Get the guesses still legal after a clue, given those legal before it, if limited.
+    pub fn legal_after(
+        &self,
+        parent_legal: Option<&Column>,
+        guess_ind: u16,
+        hint: u8,
+    ) -> Option<Column> {
+        let legal = self
+            .guess_legality
+            .as_ref()
+            .and_then(|legality| legality.legal_after(guess_ind, hint));
+        match (legal, parent_legal) {
+            (Some(mut legal), Some(parent_legal)) => {
+                legal &= parent_legal.clone();
+                Some(legal)
+            }
+            (legal, parent_legal) => legal.or_else(|| parent_legal.cloned()),
+        }
+    }
+
+    /// Get the guess indices the search may play, if limited to fewer than all of them.
+    fn guess_pool(&self, hints: &[Vec<u8>]) -> Option<Column> {
+        let answers = self.answers_only.then(|| {
+            Column::from_bools(
+                &hints
+                    .iter()
+                    .map(|guess_hints| guess_hints.contains(&0))
+                    .collect::<Vec<bool>>(),
+            )
+        });
+        match (answers, &self.guess_subset) {
+            (Some(mut answers), Some(subset)) => {
+                answers &= subset.clone();
+                Some(answers)
+            }
+            (answers, subset) => answers.or_else(|| subset.clone()),
+        }
+    }
+
+    /// Get the settings for nodes at the given depth.
+    pub fn at_depth(&self, depth: u8) -> DepthSettings {
+        self.depth_settings
+            .get(depth as usize)
+            .or(self.depth_settings.last())
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Compare two guess indices by their preference rank, treating missing ranks as equal.
+fn compare_ranks(guess_ranks: &[u32], a: u16, b: u16) -> std::cmp::Ordering {
+    match (guess_ranks.get(a as usize), guess_ranks.get(b as usize)) {
+        (Some(rank_a), Some(rank_b)) => rank_a.cmp(rank_b),
+        _ => std::cmp::Ordering::Equal,
+    }
+}
+
+/// How the search treats the guesses at nodes of one depth.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthSettings {
+    pub heuristic: GuessHeuristic,
+    /// Evaluate only this many guesses, in heuristic order. A node that leaves guesses out
+    /// this way is marked as approximate.
+    pub beam_width: Option<usize>,
+    /// Skip guesses that split the possible answers no finer than some other guess does. The
+    /// check costs a pass over the answers per pair of guesses, so it pays off at shallow
+    /// depths where each node's search is long.
+    #[serde(default)]
+    pub prune_dominated: bool,
+}
+
+/// The order guesses are evaluated in at a node. Seeing the best guess sooner lets the search
+/// prune the rest harder.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuessHeuristic {
+    /// Fewest answers under the guess's most common hint first
+    #[default]
+    MaxBucket,
+
+    /// Most informative hint distribution first, by Shannon entropy. Entropy weighs every
+    /// hint rather than only the most common one, so it tends to tell apart guesses that
+    /// max-bucket ties on and see the best guess sooner.
+    Entropy,
+}
+
+/// A way of ordering the guesses at a node, for trying heuristics beyond `GuessHeuristic`.
+pub trait GuessOrdering: std::fmt::Debug + Send + Sync {
+    /// Get the key to sort a guess by, lower evaluated sooner, from how many of the possible
+    /// answers give each hint.
+    fn sort_key(&self, guess_ind: u16, num_answers_by_hint: &[usize]) -> f64;
+
+    /// Identify the ordering, so a checkpoint or run manifest can tell it from others. Defaults
+    /// to the type's name, so orderings with settings of their own should include them.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+/// Which guesses a clue leaves legal, for searching under rules like hard mode.
+pub trait ClueLegality: std::fmt::Debug + Send + Sync {
+    /// Get a mask over the guesses of those still legal after a guess gets a hint, or `None`
+    /// if the clue doesn't restrict them.
+    fn legal_after(&self, guess_ind: u16, hint: u8) -> Option<Column>;
+
+    /// Identify the policy, so a checkpoint can tell it from others. Defaults to the type's
+    /// name, as for `GuessOrdering`.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+impl GuessOrdering for GuessHeuristic {
+    fn sort_key(&self, _guess_ind: u16, num_answers_by_hint: &[usize]) -> f64 {
+        match self {
+            Self::MaxBucket => *num_answers_by_hint.iter().max().unwrap_or(&0) as f64,
+            Self::Entropy => -PartitionStats::entropy_of(num_answers_by_hint),
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Optional limits on how much work the search may spend on any single subtree below the root.
+///
+/// A subtree that trips a limit is resolved with the greedy strategy instead, keeping whichever
+/// of that and the best guess found so far is cheaper, and marked as approximate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NodeBudget {
+    pub max_nodes: Option<u64>,
+    pub max_millis: Option<u64>,
+}
+
+impl NodeBudget {
+    /// Find the outermost non-root frame that has exceeded this budget.
+    fn find_tripped<O: SearchObserver>(
+        &self,
+        stack: &[Frame<O>],
+        nodes_expanded: u64,
+    ) -> Option<usize> {
+        if self.max_nodes.is_none() && self.max_millis.is_none() {
+            return None;
+        }
+        let now = Instant::now();
+        stack.iter().enumerate().skip(1).find_map(|(ind, frame)| {
+            let over_nodes = self.max_nodes.is_some_and(|max_nodes| {
+                nodes_expanded - frame.nodes_expanded_at_entry > max_nodes
+            });
+            let over_time = self.max_millis.is_some_and(|max_millis| {
+                now.duration_since(frame.entered_at) > Duration::from_millis(max_millis)
+            });
+            (over_nodes || over_time).then_some(ind)
+        })
+    }
+}
+
+/// Something that happened during the exhaustive search, reported to a `SearchObserver` for the
+/// node it happened at.
+#[derive(Debug, Clone, Copy)]
+pub enum SearchEvent<'a> {
+    /// Work began on a node with this many possible answers, which must be solved in under
+    /// `max_total` guesses in total
+    NodeEntered { num_answers: u32, max_total: u32 },
+
+    /// The node was given up on without evaluating any guesses
+    NodeAbandoned(AbandonReason),
+
+    /// The node's guesses will be evaluated in this order
+    GuessOrder(&'a [u16]),
+
+    /// Evaluation of a guess began, out of `num_guesses` guesses in the list
+    GuessStarted { guess_ind: u16, num_guesses: usize },
+
+    /// A guess splits the answers into buckets of these sizes, so its total can't be lower than
+    /// `total_lower_bound`
+    GuessSplit {
+        guess_ind: u16,
+        bucket_sizes: &'a [usize],
+        total_lower_bound: u32,
+    },
+
+    /// The child node under one of a guess's hints is about to be solved
+    ClueStarted {
+        guess_ind: u16,
+        hint: u8,
+        num_answers: u32,
+        num_possible_answers: u32,
+    },
+
+    /// A guess was dropped before it was fully evaluated
+    GuessPruned { guess_ind: u16, reason: PruneReason },
+
+    /// A guess was fully evaluated, and either became the new best or was rejected
+    GuessEvaluated {
+        guess_ind: u16,
+        tree_node: &'a TreeNode,
+        new_best: bool,
+    },
+
+    /// The node was solved with this tree, or found to have none within its constraints, after
+    /// searching for `elapsed`. Nodes solved outright take no time.
+    NodeFinished {
+        best: Option<&'a TreeNode>,
+        elapsed: Duration,
+    },
+
+    /// The node ran over budget after searching for `elapsed`, and falls back on the cheaper of
+    /// this greedy tree and its best so far
+    BudgetExceeded {
+        greedy: Option<&'a TreeNode>,
+        elapsed: Duration,
+    },
+
+    /// The node's answer set was looked up in the transposition table, and either its result
+    /// was there or it needs a fresh search
+    TranspositionLookup { hit: bool },
+
+    /// The time limit or the cancel token stopped the search
+    Stopped,
+
+    /// Progress was saved to the checkpoint file
+    CheckpointSaved { guesses_left: usize },
+
+    /// Progress was loaded from the checkpoint file
+    CheckpointResumed { guesses_left: usize },
+}
+
+/// Why a node was given up on without evaluating any guesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbandonReason {
+    DepthLimitReached,
+    CostLimitExceeded,
+    DepthLimitUnavoidable,
+    CostLimitUnavoidable,
+}
+
+/// Why a guess was dropped before it was fully evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    /// Every answer gives the guess the same hint
+    Useless,
+
+    /// The guess's lower bound already reaches the most it may total
+    LowerBound {
+        total_lower_bound: u32,
+        max_total: u32,
+    },
+
+    /// Some hint's child node has no tree within its constraints
+    NoGuarantee,
+
+    /// The totals of the children solved so far already reach the most it may total
+    OverMax { total: u32, max_total: u32 },
+
+    /// Another guess gets the same hint for every possible answer, so leads to the same subtrees
+    Duplicate { of: u16 },
+
+    /// Another guess splits the answers at least as finely, so can do no worse
+    Dominated { by: u16 },
+}
+
+/// A step down from a node of the search to the nodes below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStep {
+    Guess(u16),
+    Clue { guess_ind: u16, hint: u8 },
+}
+
+/// Receives the events of an exhaustive search, for progress bars, logs or anything else that
+/// follows along.
+///
+/// Each node of the search gets its own copy of the observer, made with `descend`, so
+/// observers that care where events come from can keep track of the path.
+pub trait SearchObserver: Clone {
+    /// Whether to report events at nodes of this depth. Deeper nodes are far more numerous, so
+    /// leaving them out keeps the overhead down.
+    fn observes_depth(&self, _depth: u8) -> bool {
+        true
+    }
+
+    /// Get the observer for the nodes below a step.
+    fn descend(&self, _step: SearchStep) -> Self {
+        self.clone()
+    }
+
+    /// Handle an event at this observer's node.
+    fn on_event(&self, event: SearchEvent);
+
+    /// Get an observer to stand in for this one on the workers of a parallel root search, or
+    /// `None` to leave them unobserved, as by default. Only observers that can be shared
+    /// across threads can give one.
+    fn for_worker(&self) -> Option<WorkerObserver> {
+        None
+    }
+}
+
+/// Observes the search below one of the root's guesses on a worker of a parallel root search,
+/// in place of the root's own observer. Events at the worker's own node are left out, as the
+/// root reports its node itself once every worker is done.
+#[derive(Clone)]
+pub struct WorkerObserver {
+    observer: Arc<dyn ThreadSafeObserver>,
+    at_root: bool,
+}
+
+impl WorkerObserver {
+    pub fn new<O: SearchObserver + Send + Sync + 'static>(observer: O) -> Self {
+        Self {
+            observer: Arc::new(observer),
+            at_root: true,
+        }
+    }
+}
+
+/// The parts of a `SearchObserver` a `WorkerObserver` passes events on to.
+trait ThreadSafeObserver: Send + Sync {
+    fn observes_depth(&self, depth: u8) -> bool;
+    fn descend(&self, step: SearchStep) -> Arc<dyn ThreadSafeObserver>;
+    fn on_event(&self, event: SearchEvent);
+}
+
+impl<O: SearchObserver + Send + Sync + 'static> ThreadSafeObserver for O {
+    fn observes_depth(&self, depth: u8) -> bool {
+        SearchObserver::observes_depth(self, depth)
+    }
+
+    fn descend(&self, step: SearchStep) -> Arc<dyn ThreadSafeObserver> {
+        Arc::new(SearchObserver::descend(self, step))
+    }
+
+    fn on_event(&self, event: SearchEvent) {
+        SearchObserver::on_event(self, event);
+    }
+}
+
+impl SearchObserver for WorkerObserver {
+    fn observes_depth(&self, depth: u8) -> bool {
+        self.observer.observes_depth(depth)
+    }
+
+    fn descend(&self, step: SearchStep) -> Self {
+        Self {
+            observer: self.observer.descend(step),
+            at_root: false,
+        }
+    }
+
+    fn on_event(&self, event: SearchEvent) {
+        if !self.at_root {
+            self.observer.on_event(event);
+        }
+    }
 }
 
+/// Formats the guesses, answers and hints of a search as text.
 pub trait DebugPrinter {
     fn fmt_guess(&self, guess_ind: u16) -> String;
     fn fmt_answer(&self, answer_ind: u16) -> String;
     fn fmt_hint(&self, hint_id: u8) -> String;
     fn fmt_clue(&self, hint_id: u8, guess_ind: u16) -> String;
     fn should_print_at_depth(&self, depth: u8) -> bool;
-    fn with_prefix(&self, prefix: String) -> Self;
-    fn get_prefix(&self) -> &str;
 }
 
-pub fn compute_decision_tree_aggressive(
+/// An observer that prints every event to stdout, prefixed by the guesses and clues leading to
+/// its node.
+pub struct PrintingObserver<'a, P: DebugPrinter> {
+    printer: &'a P,
+    prefix: String,
+}
+
+impl<'a, P: DebugPrinter> PrintingObserver<'a, P> {
+    pub fn new(printer: &'a P) -> Self {
+        Self {
+            printer,
+            prefix: String::new(),
+        }
+    }
+
+    fn fmt_guess_from(&self, guess: GuessFrom) -> String {
+        match guess {
+            GuessFrom::Guess(guess_ind) => self.printer.fmt_guess(guess_ind),
+            GuessFrom::Answer(answer_ind) => self.printer.fmt_answer(answer_ind),
+        }
+    }
+}
+
+impl<P: DebugPrinter> Clone for PrintingObserver<'_, P> {
+    fn clone(&self) -> Self {
+        Self {
+            printer: self.printer,
+            prefix: self.prefix.clone(),
+        }
+    }
+}
+
+impl<P: DebugPrinter> SearchObserver for PrintingObserver<'_, P> {
+    fn observes_depth(&self, depth: u8) -> bool {
+        self.printer.should_print_at_depth(depth)
+    }
+
+    fn descend(&self, step: SearchStep) -> Self {
+        let step = match step {
+            SearchStep::Guess(guess_ind) => self.printer.fmt_guess(guess_ind),
+            SearchStep::Clue { guess_ind, hint } => self.printer.fmt_clue(hint, guess_ind),
+        };
+        Self {
+            printer: self.printer,
+            prefix: format!("{}{} > ", self.prefix, step),
+        }
+    }
+
+    fn on_event(&self, event: SearchEvent) {
+        let printer = self.printer;
+        let message = match event {
+            SearchEvent::NodeEntered {
+                num_answers,
+                max_total,
+            } => format!(
+                "must compute {} possible answers with max total of {}",
+                num_answers, max_total
+            ),
+            SearchEvent::NodeAbandoned(reason) => match reason {
+                AbandonReason::DepthLimitReached => "depth limit reached",
+                AbandonReason::CostLimitExceeded => "cost limit exceeded",
+                AbandonReason::DepthLimitUnavoidable => "depth limit cannot be avoided",
+                AbandonReason::CostLimitUnavoidable => "cost limit cannot be avoided",
+            }
+            .to_string(),
+            SearchEvent::GuessOrder(guess_order) => format!(
+                "first guesses will be {}",
+                guess_order
+                    .iter()
+                    .take(5)
+                    .map(|guess_ind| printer.fmt_guess(*guess_ind))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            SearchEvent::GuessStarted {
+                guess_ind,
+                num_guesses,
+            } => format!(
+                "evaluating guess {} - {:.0}% complete",
+                printer.fmt_guess(guess_ind),
+                100.0 * guess_ind as f64 / num_guesses as f64
+            ),
+            SearchEvent::GuessSplit {
+                bucket_sizes,
+                total_lower_bound,
+                ..
+            } => {
+                let counts = size_counts(bucket_sizes.iter().copied());
+                format!(
+                    "distribution: {}<{}\n{}considering {} possible hints - lower bound total of {}",
+                    sparkline(&counts, render_mode()),
+                    counts.len() - 1,
+                    self.prefix,
+                    bucket_sizes.len(),
+                    total_lower_bound
+                )
+            }
+            SearchEvent::ClueStarted {
+                guess_ind,
+                hint,
+                num_answers,
+                num_possible_answers,
+            } => format!(
+                "evaluating clue {} with {}/{} possible answers - {:.2}% chance",
+                printer.fmt_clue(hint, guess_ind),
+                num_answers,
+                num_possible_answers,
+                100.0 * num_answers as f64 / num_possible_answers as f64,
+            ),
+            SearchEvent::GuessPruned { guess_ind, reason } => match reason {
+                PruneReason::Useless => {
+                    format!(
+                        "guess {} is useless, skipping",
+                        printer.fmt_guess(guess_ind)
+                    )
+                }
+                PruneReason::LowerBound {
+                    total_lower_bound,
+                    max_total,
+                } => format!(
+                    "total lower bound of {} already exceeds max of {}",
+                    total_lower_bound, max_total
+                ),
+                PruneReason::NoGuarantee => format!(
+                    "guess {} cannot guarantee an answer within constraints",
+                    printer.fmt_guess(guess_ind)
+                ),
+                PruneReason::OverMax { total, max_total } => format!(
+                    "guess {} total of {} already exceeds max of {}",
+                    printer.fmt_guess(guess_ind),
+                    total,
+                    max_total
+                ),
+                PruneReason::Duplicate { of } => format!(
+                    "guess {} duplicates {}, skipping",
+                    printer.fmt_guess(guess_ind),
+                    printer.fmt_guess(of)
+                ),
+                PruneReason::Dominated { by } => format!(
+                    "guess {} is dominated by {}, skipping",
+                    printer.fmt_guess(guess_ind),
+                    printer.fmt_guess(by)
+                ),
+            },
+            SearchEvent::GuessEvaluated {
+                guess_ind,
+                tree_node,
+                new_best,
+            } => format!(
+                "guess {} has est cost {} - {}",
+                printer.fmt_guess(guess_ind),
+                tree_node.est_cost(),
+                if new_best { "new best" } else { "rejecting" }
+            ),
+            SearchEvent::NodeFinished {
+                best: Some(tree_node),
+                ..
+            } => format!(
+                "best guess is {} with est cost of {}{}",
+                self.fmt_guess_from(tree_node.should_guess),
+                tree_node.est_cost(),
+                if tree_node.num_answers == 1 {
+                    " (certain)"
+                } else {
+                    ""
+                }
+            ),
+            SearchEvent::NodeFinished { best: None, .. } => {
+                "no guesses are guaranteed to solve within depth limit".to_string()
+            }
+            SearchEvent::BudgetExceeded { greedy, .. } => format!(
+                "budget exceeded, falling back to greedy with est cost of {}",
+                greedy.map_or("n/a".to_string(), |tree_node| tree_node
+                    .est_cost()
+                    .to_string()),
+            ),
+            SearchEvent::TranspositionLookup { hit: true } => {
+                "found in transposition table".to_string()
+            }
+            SearchEvent::TranspositionLookup { hit: false } => return,
+            SearchEvent::Stopped => "search stopped early".to_string(),
+            SearchEvent::CheckpointSaved { guesses_left } => {
+                format!("saved checkpoint with {} guesses left", guesses_left)
+            }
+            SearchEvent::CheckpointResumed { guesses_left } => {
+                format!(
+                    "resuming from checkpoint with {} guesses left",
+                    guesses_left
+                )
+            }
+        };
+        println!("{}{}", self.prefix, message);
+    }
+}
+
+/// An observer that ignores every event, for searches whose progress isn't wanted.
+#[derive(Clone, Copy)]
+pub struct NoPrinter;
+
+impl SearchObserver for NoPrinter {
+    fn observes_depth(&self, _depth: u8) -> bool {
+        false
+    }
+
+    fn on_event(&self, _event: SearchEvent) {}
+}
+
+/// Compute the decision tree with the lowest expected number of guesses.
+///
+/// The search is driven by an explicit stack of frames rather than by recursion, so deep
+/// searches can't overflow the call stack and the full search state is plain data. Within the
+/// search, each node's possible answers are a bitset over the answer list, so splitting them by
+/// hint and counting them don't need a hash set per node.
+///
+/// Returns `None` if there's no tree within the limits, or if searching the root in parallel
+/// fails, which `compute_decision_tree_bounded` reports as an error.
+pub fn compute_decision_tree_aggressive<O: SearchObserver>(
     hints: &[Vec<u8>],
     possible_answers: HashSet<u16>,
     depth: u8,
     max_depth: u8,
-    mut max_cost: f64,
-    printer: Option<&impl DebugPrinter>,
+    max_cost: f64,
+    options: SearchOptions,
+    observer: Option<&O>,
+) -> Option<TreeNode> {
+    compute_decision_tree_bounded(
+        hints,
+        possible_answers,
+        depth,
+        max_depth,
+        max_cost,
+        options,
+        observer,
+    )
+    .ok()
+    .flatten()
+    .map(|bounded| bounded.tree)
+}
+
+/// Compute the decision tree with the lowest expected number of guesses over a view of a hint
+/// matrix, playing only the view's guesses for only its answers.
+///
+/// The view borrows the matrix rather than copying it, and the tree indexes guesses and answers
+/// by their positions in the whole matrix, so trees from different views of one matrix share
+/// their indices.
+pub fn compute_decision_tree_in_view<O: SearchObserver>(
+    view: &HintMatrixView,
+    depth: u8,
+    max_depth: u8,
+    max_cost: f64,
+    options: SearchOptions,
+    observer: Option<&O>,
 ) -> Option<TreeNode> {
-    // Set the printer to `None` if we're past the configured depth
-    let printer = match printer {
-        Some(printer) if printer.should_print_at_depth(depth) => Some(printer),
-        _ => None,
+    let matrix = view.matrix();
+    let mut guess_subset = Column::from_false(matrix.num_guesses());
+    for guess_ind in view.guess_mapping().original_inds() {
+        guess_subset.set(*guess_ind, true);
+    }
+    if let Some(subset) = options.guess_subset {
+        guess_subset &= subset;
+    }
+    compute_decision_tree_aggressive(
+        matrix,
+        view.answer_mapping()
+            .original_inds()
+            .iter()
+            .map(|answer_ind| *answer_ind as u16)
+            .collect(),
+        depth,
+        max_depth,
+        max_cost,
+        SearchOptions {
+            guess_subset: Some(guess_subset),
+            ..options
+        },
+        observer,
+    )
+}
+
+/// A tree from a search that may have stopped early, with a bound on how far from the best
+/// tree it could be.
+#[derive(Clone)]
+pub struct BoundedTree {
+    pub tree: TreeNode,
+    /// No tree has a lower total than this. It equals the tree's total unless the search was
+    /// stopped early, and only truly bounds it when the search doesn't otherwise approximate,
+    /// with tolerance, beam widths or budgets.
+    pub lower_bound_total: u32,
+}
+
+impl BoundedTree {
+    /// Get how many more guesses on average the tree could take than the best tree.
+    pub fn gap(&self) -> f64 {
+        (self.tree.total_guesses - self.lower_bound_total) as f64 / self.tree.num_answers as f64
+    }
+}
+
+/// Compute the decision tree with the lowest expected number of guesses, as
+/// `compute_decision_tree_aggressive` does, along with a lower bound on the best tree's total.
+///
+/// This is for searches that may be stopped early by the time limit or the cancel token, which
+/// still return the best tree found so far and how much better the best tree could be.
+///
+/// Fails if a root worker panics or its thread pool can't be built when searching the root in
+/// parallel.
+pub fn compute_decision_tree_bounded<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    depth: u8,
+    max_depth: u8,
+    max_cost: f64,
+    options: SearchOptions,
+    observer: Option<&O>,
+) -> Result<Option<BoundedTree>, WordGameError> {
+    if options.objective == Objective::WorstCase {
+        return search_worst_case(depth, max_depth, max_cost, options, |max_depth, options| {
+            compute_decision_tree_bounded(
+                hints,
+                possible_answers.clone(),
+                depth,
+                max_depth,
+                f64::INFINITY,
+                options,
+                observer,
+            )
+        });
+    }
+
+    let (entered, context) = enter_root(
+        hints,
+        possible_answers,
+        depth,
+        max_depth,
+        max_cost,
+        &options,
+        observer,
+    );
+    let root = match entered {
+        Entered::Solved(result) => {
+            return Ok(result.map(|tree| BoundedTree {
+                lower_bound_total: tree.total_guesses,
+                tree,
+            }));
+        }
+        Entered::Frame(frame) => frame,
+    };
+    let root = if options.parallel_root {
+        search_root_in_parallel(hints, *root, max_depth, &options, &context)?
+    } else {
+        drive(hints, *root, max_depth, &options, &context)
+    };
+    Ok(root.finish_root(hints, max_depth, &options))
+}
+
+/// Narrow the guess pool to the guesses legal at a node, or `None` if every guess is legal there
+/// and the pool applies as it is.
+fn legal_pool(legal_guesses: Option<&Column>, guess_pool: Option<&Column>) -> Option<Column> {
+    let mut pool = legal_guesses?.clone();
+    if let Some(guess_pool) = guess_pool {
+        pool &= guess_pool.clone();
+    }
+    Some(pool)
+}
+
+/// What every node below a search's root shares, worked out once on entering the root.
+struct SearchContext {
+    /// How much worse than the best guess each level may settle for
+    slack: f64,
+
+    /// The guess indices the search may play, if limited to fewer than all of them
+    guess_pool: Option<Column>,
+}
+
+/// Enter the node a search starts from, returning it along with what the nodes below share.
+fn enter_root<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    depth: u8,
+    max_depth: u8,
+    max_cost: f64,
+    options: &SearchOptions,
+    observer: Option<&O>,
+) -> (Entered<O>, SearchContext) {
+    // Errors add up across levels, so splitting the tolerance evenly keeps the total within it
+    let slack = options.tolerance.max(0.0) / max_depth.saturating_sub(depth).max(1) as f64;
+    // The cast saturates, so an infinite max cost allows any total
+    let max_total = (max_cost * possible_answers.len() as f64).ceil() as u32;
+    let mut answer_set = Column::from_false(hints.first().map_or(0, Vec::len));
+    for answer_ind in possible_answers {
+        answer_set.set(answer_ind as usize, true);
+    }
+    let guess_pool = options.guess_pool(hints);
+    let root_pool = legal_pool(options.root_legal_guesses.as_ref(), guess_pool.as_ref());
+    let mut entered = Frame::enter(
+        hints,
+        answer_set,
+        depth,
+        max_depth,
+        max_total,
+        NodeSettings {
+            depth: options.at_depth(depth),
+            ordering: options.guess_ordering.as_deref(),
+            guess_pool: root_pool.as_ref().or(guess_pool.as_ref()),
+            candidates_only_below: options.candidates_only_below,
+            max_hints: u8::MAX as u32 + 1,
+            excluded_guesses: Some(&options.root_exclusions),
+            letter_screen: options.letter_screen.as_deref(),
+            guess_ranks: &options.guess_ranks,
+            slack,
+        },
+        observer.cloned(),
+    );
+    if let Entered::Frame(root) = &mut entered {
+        root.legal_guesses = options.root_legal_guesses.clone();
+        root.num_alternatives = options.num_alternatives;
+        root.complete_alternatives = options.complete_alternatives;
+        root.deadline = options
+            .time_limit_millis
+            .map(|time_limit_millis| Instant::now() + Duration::from_millis(time_limit_millis));
+    }
+    (entered, SearchContext { slack, guess_pool })
+}
+
+/// Find the tree with the fewest guesses for its hardest answer by searching under each depth
+/// limit in turn, shallowest first, until one has a tree. Any tree within the limit found
+/// first would do, but the search under it already minimizes the average as well, and its
+/// lower bound is among trees within that limit.
+///
+/// `search` runs the expected guesses search under the given max depth and options.
+fn search_worst_case(
+    depth: u8,
+    max_depth: u8,
+    max_cost: f64,
+    options: SearchOptions,
+    mut search: impl FnMut(u8, SearchOptions) -> Result<Option<BoundedTree>, WordGameError>,
+) -> Result<Option<BoundedTree>, WordGameError> {
+    // The cast saturates, so an infinite max cost leaves only the depth limit
+    let max_guesses = (max_cost.floor() as u8).min(max_depth.saturating_sub(depth));
+    let options = SearchOptions {
+        objective: Objective::ExpectedGuesses,
+        ..options
     };
+    let started = Instant::now();
+    for guesses in 1..=max_guesses {
+        let bounded = search(depth + guesses, options.with_time_since(started))?;
+        if bounded.is_some() {
+            return Ok(bounded);
+        }
+    }
+    Ok(None)
+}
+
+/// Search below a frame until every one of its guesses has been evaluated, returning it ready
+/// to finish.
+fn drive<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    root: Frame<O>,
+    max_depth: u8,
+    options: &SearchOptions,
+    context: &SearchContext,
+) -> Frame<O> {
+    let transpositions = (options.transposition_bytes > 0)
+        .then(|| BoundedCache::with_memory_budget(options.transposition_bytes));
+    match drive_with(
+        hints,
+        root,
+        transpositions,
+        max_depth,
+        options,
+        context,
+        None,
+    ) {
+        Ok(root) => root,
+        Err(_) => unreachable!("only saving a checkpoint can fail"),
+    }
+}
+
+/// Search below a frame like `drive`, starting from the given transposition table and saving
+/// a checkpoint of the frame between its guesses if given a checkpointer.
+fn drive_with<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    root: Frame<O>,
+    mut transpositions: Option<BoundedCache<TranspositionKey, TranspositionEntry>>,
+    max_depth: u8,
+    options: &SearchOptions,
+    context: &SearchContext,
+    mut checkpointer: Option<&mut Checkpointer>,
+) -> Result<Frame<O>, WordGameError> {
+    let mut stack: Vec<Frame<O>> = vec![root];
+    let mut nodes_expanded: u64 = 0;
+    let SearchContext { slack, guess_pool } = context;
+    loop {
+        // Abandon everything below the root once time is up or the search is cancelled. The
+        // periodic checkpoints still hold, so no checkpoint is saved for the abandoned guesses.
+        if stack[0]
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+            || options
+                .cancel
+                .as_ref()
+                .is_some_and(CancelToken::is_cancelled)
+        {
+            stack.truncate(1);
+            let mut root = stack.pop().unwrap();
+            root.stop_early(hints);
+            return Ok(root);
+        }
+
+        // Advance the top frame until it either needs a child solved or has finished
+        let frame = stack.last_mut().unwrap();
+        let entered = match frame.step(hints, &options.guess_ranks) {
+            Some(child) => {
+                nodes_expanded += 1;
+                let depth = frame.depth + 1;
+                let (guess_ind, hint) = child.clue;
+                let legal_guesses =
+                    options.legal_after(frame.legal_guesses.as_ref(), guess_ind, hint);
+                let node_pool = legal_pool(legal_guesses.as_ref(), guess_pool.as_ref());
+                let cached = transpositions.as_mut().and_then(|transpositions| {
+                    lookup_transposition(
+                        transpositions,
+                        &child.possible_answers,
+                        legal_guesses.as_ref(),
+                        depth,
+                        child.max_total,
+                        child
+                            .observer
+                            .as_ref()
+                            .filter(|observer| observer.observes_depth(depth)),
+                    )
+                });
+                match cached {
+                    Some(result) => Entered::Solved(result),
+                    None => match Frame::enter(
+                        hints,
+                        child.possible_answers,
+                        depth,
+                        max_depth,
+                        child.max_total,
+                        NodeSettings {
+                            depth: options.at_depth(depth),
+                            ordering: options.guess_ordering.as_deref(),
+                            guess_pool: node_pool.as_ref().or(guess_pool.as_ref()),
+                            candidates_only_below: options.candidates_only_below,
+                            max_hints: child.max_hints,
+                            excluded_guesses: None,
+                            letter_screen: options.letter_screen.as_deref(),
+                            guess_ranks: &options.guess_ranks,
+                            slack: *slack,
+                        },
+                        child.observer,
+                    ) {
+                        Entered::Frame(mut frame) => {
+                            frame.legal_guesses = legal_guesses;
+                            Entered::Frame(frame)
+                        }
+                        solved => solved,
+                    },
+                }
+            }
+            None if stack.len() == 1 => {
+                let root = stack.pop().unwrap();
+                if let Some(checkpointer) = checkpointer {
+                    checkpointer.save(&root, transpositions.as_ref())?;
+                }
+                return Ok(root);
+            }
+            None => {
+                let frame = stack.pop().unwrap();
+                let node = transpositions.is_some().then(|| {
+                    (
+                        transposition_key(
+                            &frame.possible_answers,
+                            frame.legal_guesses.as_ref(),
+                            frame.depth,
+                        ),
+                        frame.possible_answers.to_chunks(),
+                        frame.legal_guesses.as_ref().map(Column::to_chunks),
+                    )
+                });
+                let max_total = frame.max_total;
+                let result = frame.finish();
+                if let (Some(transpositions), Some((key, possible_answers, legal_guesses))) =
+                    (transpositions.as_mut(), node)
+                {
+                    transpositions.insert(
+                        key,
+                        TranspositionEntry {
+                            possible_answers,
+                            legal_guesses,
+                            result: match &result {
+                                Some(tree_node) => Transposition::Solved(tree_node.clone()),
+                                None => Transposition::NoneBelow(max_total),
+                            },
+                        },
+                    );
+                }
+                Entered::Solved(result)
+            }
+        };
+
+        // Either hand a resolved node back to its parent, or start working on a new frame
+        match entered {
+            Entered::Solved(result) => stack
+                .last_mut()
+                .unwrap()
+                .accept_child(result, &options.guess_ranks),
+            Entered::Frame(mut frame) => {
+                frame.nodes_expanded_at_entry = nodes_expanded;
+                frame.num_alternatives = options.num_alternatives;
+                stack.push(*frame);
+            }
+        }
+
+        // Cut short any subtree that has used up its budget, discarding the work beneath it
+        if let Some(tripped_ind) = options.budget.find_tripped(&stack, nodes_expanded) {
+            stack.truncate(tripped_ind + 1);
+            let result = stack
+                .pop()
+                .unwrap()
+                .finish_over_budget(hints, max_depth, options);
+            stack
+                .last_mut()
+                .unwrap()
+                .accept_child(result, &options.guess_ranks);
+        }
+
+        // Only the root's finished guesses are saved, so save between them
+        if let Some(checkpointer) = checkpointer.as_deref_mut()
+            && stack.len() == 1
+            && stack[0].current.is_none()
+            && checkpointer.is_due()
+        {
+            checkpointer.save(&stack[0], transpositions.as_ref())?;
+        }
+    }
+}
+
+/// Identifies a node by its depth and set of possible answers, the set by its size and a hash
+/// of its bits along with those of the node's legal guesses if limited. The hash is stable
+/// across builds, so keys saved in a checkpoint still match once it's resumed.
+type TranspositionKey = (u8, u64, u64);
+
+/// A transposition table entry, keeping the node's exact sets so a lookup whose key collides
+/// with a different node's is treated as a miss.
+#[derive(Clone, Serialize, Deserialize)]
+struct TranspositionEntry {
+    possible_answers: Vec<u64>,
+    legal_guesses: Option<Vec<u64>>,
+    result: Transposition,
+}
+
+impl TranspositionEntry {
+    fn matches(&self, possible_answers: &Column, legal_guesses: Option<&Column>) -> bool {
+        self.possible_answers == possible_answers.to_chunks()
+            && self.legal_guesses == legal_guesses.map(Column::to_chunks)
+    }
+}
+
+/// What a finished search found for one set of possible answers.
+#[derive(Clone, Serialize, Deserialize)]
+enum Transposition {
+    /// The best tree for the set. A search under any max total above its total finds it again,
+    /// and one under a lower max total finds nothing.
+    Solved(TreeNode),
+
+    /// No tree for the set comes in under this total
+    NoneBelow(u32),
+}
+
+impl MemoryFootprint for TranspositionEntry {
+    fn memory_footprint(&self) -> usize {
+        let chunks = self.possible_answers.len() + self.legal_guesses.as_ref().map_or(0, Vec::len);
+        let result = match &self.result {
+            Transposition::Solved(tree_node) => tree_node.memory_footprint(),
+            Transposition::NoneBelow(_) => 0,
+        };
+        chunks * size_of::<u64>() + result
+    }
+}
+
+fn transposition_key(
+    possible_answers: &Column,
+    legal_guesses: Option<&Column>,
+    depth: u8,
+) -> TranspositionKey {
+    let mut hash = fnv1a(
+        FNV_OFFSET_BASIS,
+        possible_answers
+            .to_chunks()
+            .iter()
+            .flat_map(|chunk| chunk.to_le_bytes()),
+    );
+    if let Some(legal_guesses) = legal_guesses {
+        hash = fnv1a(
+            hash,
+            legal_guesses
+                .to_chunks()
+                .iter()
+                .flat_map(|chunk| chunk.to_le_bytes()),
+        );
+    }
+    (depth, possible_answers.count_true(), hash)
+}
+
+/// Get the result of searching a node under `max_total` from the table, or `None` if it needs a
+/// fresh search. Sets of one or two answers are solved outright and never stored.
+fn lookup_transposition<O: SearchObserver>(
+    transpositions: &mut BoundedCache<TranspositionKey, TranspositionEntry>,
+    possible_answers: &Column,
+    legal_guesses: Option<&Column>,
+    depth: u8,
+    max_total: u32,
+    observer: Option<&O>,
+) -> Option<Option<TreeNode>> {
+    if possible_answers.count_true() < 3 {
+        return None;
+    }
+    let key = transposition_key(possible_answers, legal_guesses, depth);
+    let cached = transpositions
+        .get(&key)
+        .filter(|entry| entry.matches(possible_answers, legal_guesses))
+        .map(|entry| &entry.result);
+    let result = match cached {
+        Some(Transposition::Solved(tree_node)) if tree_node.total_guesses < max_total => {
+            Some(Some(tree_node.clone()))
+        }
+        Some(Transposition::Solved(_)) => Some(None),
+        Some(Transposition::NoneBelow(bound)) if max_total <= *bound => Some(None),
+        Some(Transposition::NoneBelow(_)) | None => None,
+    };
+    if let Some(observer) = observer {
+        observer.on_event(SearchEvent::TranspositionLookup {
+            hit: result.is_some(),
+        });
+    }
+    result
+}
+
+/// Evaluate each of the root's guesses as its own search on the worker pool, then settle the
+/// best among them on the root as if it had evaluated them in order.
+///
+/// Workers share the best total found so far through an atomic lowered with `fetch_min`, so
+/// each can prune against guesses finished on other threads. Workers are only observed if the
+/// root's observer gives a `WorkerObserver`, and with a tolerance any guess pruned against
+/// another worker's result marks the tree as approximate.
+fn search_root_in_parallel<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    mut root: Frame<O>,
+    max_depth: u8,
+    options: &SearchOptions,
+    context: &SearchContext,
+) -> Result<Frame<O>, WordGameError> {
+    let guess_order: Vec<u16> = root.guess_order.by_ref().collect();
+    let best_total = AtomicU32::new(u32::MAX);
+    let worker_observer = root.observer.as_ref().and_then(SearchObserver::for_worker);
+    let workers: Vec<(u16, Frame<WorkerObserver>, bool)> =
+        WorkerPool::new(options.pool).map(&guess_order, |_, &guess_ind| {
+            let best_so_far = best_total.load(AtomicOrdering::Relaxed);
+            let bounded_by_best = !root.complete_alternatives && best_so_far < root.guess_max_total;
+            let guess_max_total = if bounded_by_best {
+                // Let ties through, so the guess ranks can settle them below
+                best_so_far.saturating_sub(root.slack) + 1
+            } else {
+                root.guess_max_total
+            };
+            let mut worker = Frame::single_guess(
+                root.possible_answers.clone(),
+                root.depth,
+                root.num_guesses,
+                guess_ind,
+                guess_max_total,
+                root.slack,
+                root.max_hints,
+            );
+            worker.legal_guesses = root.legal_guesses.clone();
+            worker.observer = worker_observer.clone();
+            worker.num_alternatives = options.num_alternatives;
+            worker.deadline = root.deadline;
+            let worker = drive(hints, worker, max_depth, options, context);
+            if let Some(best) = &worker.best {
+                best_total.fetch_min(best.total_guesses, AtomicOrdering::Relaxed);
+            }
+            (guess_ind, worker, bounded_by_best)
+        })?;
+
+    for (guess_ind, worker, bounded_by_best) in workers {
+        root.slack_pruned |=
+            worker.slack_pruned || (root.slack > 0 && bounded_by_best && worker.best.is_none());
+        root.stopped |= worker.stopped;
+        root.unexplored_lower_bound =
+            match (root.unexplored_lower_bound, worker.unexplored_lower_bound) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        for alternative in worker.alternatives {
+            root.retain_alternative(alternative.tree_node, alternative.complete);
+        }
+        if let Some(guess) = worker.best {
+            root.offer(guess, guess_ind, None, &options.guess_ranks);
+        }
+    }
+    Ok(root)
+}
+
+/// The checkpoint format version written by this crate
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// Where and how often a long search saves its progress, so a killed run can pick up where it
+/// left off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointConfig {
+    /// The file progress is saved to. If it already holds progress for the same search, the
+    /// search resumes from it.
+    pub path: PathBuf,
+    /// The least time between saves. Progress is only saved between root guesses, so saves can
+    /// be further apart than this.
+    pub interval_millis: u64,
+}
+
+/// The progress of a search through the guesses at its root, as saved to disk.
+#[derive(Serialize, Deserialize)]
+struct SearchCheckpoint {
+    version: u32,
+    /// Identifies the inputs the progress was made on, as 16 hex digits
+    search_hash: String,
+    /// The root guesses still to evaluate, in order
+    remaining_guesses: Vec<u16>,
+    best: Option<TreeNode>,
+    guess_max_total: u32,
+    slack_pruned: bool,
+    alternatives: Vec<Alternative<TreeNode>>,
+    /// The transposition table, least recently used first
+    transpositions: Vec<(TranspositionKey, TranspositionEntry)>,
+}
+
+/// Saves a root frame's progress to a checkpoint file at most once per interval.
+struct Checkpointer {
+    config: CheckpointConfig,
+    search_hash: String,
+    last_saved: Instant,
+}
+
+impl Checkpointer {
+    fn is_due(&self) -> bool {
+        self.last_saved.elapsed() >= Duration::from_millis(self.config.interval_millis)
+    }
+
+    /// Write the root's progress, replacing the file in one step so a kill mid-write leaves the
+    /// previous checkpoint intact.
+    fn save<O: SearchObserver>(
+        &mut self,
+        root: &Frame<O>,
+        transpositions: Option<&BoundedCache<TranspositionKey, TranspositionEntry>>,
+    ) -> Result<(), WordGameError> {
+        let checkpoint = SearchCheckpoint {
+            version: CHECKPOINT_VERSION,
+            search_hash: self.search_hash.clone(),
+            remaining_guesses: root.guess_order.as_slice().to_vec(),
+            best: root.best.clone(),
+            guess_max_total: root.guess_max_total,
+            slack_pruned: root.slack_pruned,
+            alternatives: root.alternatives.clone(),
+            transpositions: transpositions.map_or(Vec::new(), |transpositions| {
+                transpositions
+                    .iter()
+                    .map(|(key, entry)| (*key, entry.clone()))
+                    .collect()
+            }),
+        };
+        let raw = serde_json::to_string(&checkpoint)?;
+        let partial_path = self.config.path.with_extension("partial");
+        fs::write(&partial_path, raw).map_err(|source| WordGameError::Io {
+            path: partial_path.clone(),
+            source,
+        })?;
+        fs::rename(&partial_path, &self.config.path).map_err(|source| WordGameError::Io {
+            path: self.config.path.clone(),
+            source,
+        })?;
+        if let Some(observer) = &root.observer {
+            observer.on_event(SearchEvent::CheckpointSaved {
+                guesses_left: checkpoint.remaining_guesses.len(),
+            });
+        }
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+}
+
+/// Hash everything that decides how a search explores its root, so a checkpoint is only
+/// resumed by the search that saved it.
+fn search_hash<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    root: &Frame<O>,
+    max_depth: u8,
+    options: &SearchOptions,
+) -> String {
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, (hints.len() as u64).to_le_bytes());
+    for guess_hints in hints {
+        hash = fnv1a(hash, guess_hints.iter().copied());
+    }
+    for answer_ind in root.possible_answers.iter_true() {
+        hash = fnv1a(hash, (answer_ind as u64).to_le_bytes());
+    }
+    hash = fnv1a(hash, [root.depth, max_depth]);
+    hash = fnv1a(hash, root.max_total.to_le_bytes());
+    hash = fnv1a(hash, root.slack.to_le_bytes());
+    // The root's guess order already reflects its exclusions and settings, but deeper levels
+    // have their own
+    for guess_ind in root.guess_order.as_slice() {
+        hash = fnv1a(hash, guess_ind.to_le_bytes());
+    }
+    for rank in &options.guess_ranks {
+        hash = fnv1a(hash, rank.to_le_bytes());
+    }
+    hash = fnv1a(hash, options.tolerance.to_le_bytes());
+    hash = fnv1a(hash, [options.answers_only as u8]);
+    if let Some(guess_subset) = &options.guess_subset {
+        for guess_ind in guess_subset.iter_true() {
+            hash = fnv1a(hash, (guess_ind as u64).to_le_bytes());
+        }
+    }
+    hash = fnv1a(
+        hash,
+        (options.candidates_only_below.unwrap_or(0) as u64).to_le_bytes(),
+    );
+    hash = fnv1a(hash, (options.num_alternatives as u64).to_le_bytes());
+    hash = fnv1a(hash, [options.complete_alternatives as u8]);
+    hash = fnv1a(
+        hash,
+        options
+            .guess_legality
+            .as_ref()
+            .map_or(String::new(), |legality| legality.name())
+            .into_bytes(),
+    );
+    if let Some(root_legal_guesses) = &options.root_legal_guesses {
+        for guess_ind in root_legal_guesses.iter_true() {
+            hash = fnv1a(hash, (guess_ind as u64).to_le_bytes());
+        }
+    }
+    hash = fnv1a(
+        hash,
+        options
+            .guess_ordering
+            .as_ref()
+            .map_or(String::new(), |ordering| ordering.name())
+            .into_bytes(),
+    );
+    hash = fnv1a(
+        hash,
+        serde_json::to_string(&options.depth_settings)
+            .unwrap()
+            .into_bytes(),
+    );
+    for limit in [options.budget.max_nodes, options.budget.max_millis] {
+        hash = fnv1a(hash, limit.unwrap_or(u64::MAX).to_le_bytes());
+    }
+    format!("{:016x}", hash)
+}
+
+/// Compute the decision tree with the lowest expected number of guesses and a lower bound on
+/// its total, as `compute_decision_tree_bounded` does, saving progress to a checkpoint file as
+/// it goes.
+///
+/// If the file already holds progress for the same search, such as from a run that was
+/// killed, the search picks up after the last root guess it saved. A file saved by a different
+/// search is an error rather than being overwritten. The root guesses are always searched in
+/// order on one thread, so `parallel_root` is ignored. The worst case objective runs a search
+/// per depth limit, each saving to the path with `.depth<N>` appended for its max depth.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_decision_tree_checkpointed<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    depth: u8,
+    max_depth: u8,
+    max_cost: f64,
+    options: SearchOptions,
+    checkpoint: &CheckpointConfig,
+    observer: Option<&O>,
+) -> Result<Option<BoundedTree>, WordGameError> {
+    if options.objective == Objective::WorstCase {
+        return search_worst_case(depth, max_depth, max_cost, options, |max_depth, options| {
+            let mut path = checkpoint.path.clone().into_os_string();
+            path.push(format!(".depth{}", max_depth));
+            compute_decision_tree_checkpointed(
+                hints,
+                possible_answers.clone(),
+                depth,
+                max_depth,
+                f64::INFINITY,
+                options,
+                &CheckpointConfig {
+                    path: path.into(),
+                    ..checkpoint.clone()
+                },
+                observer,
+            )
+        });
+    }
+    let (entered, context) = enter_root(
+        hints,
+        possible_answers,
+        depth,
+        max_depth,
+        max_cost,
+        &options,
+        observer,
+    );
+    let mut root = match entered {
+        Entered::Solved(result) => {
+            return Ok(result.map(|tree| BoundedTree {
+                lower_bound_total: tree.total_guesses,
+                tree,
+            }));
+        }
+        Entered::Frame(frame) => *frame,
+    };
+    let search_hash = search_hash(hints, &root, max_depth, &options);
+    let mut transpositions = (options.transposition_bytes > 0)
+        .then(|| BoundedCache::with_memory_budget(options.transposition_bytes));
+
+    if checkpoint.path.exists() {
+        let raw = fs::read_to_string(&checkpoint.path).map_err(|source| WordGameError::Io {
+            path: checkpoint.path.clone(),
+            source,
+        })?;
+        let saved: SearchCheckpoint = serde_json::from_str(&raw)?;
+        if saved.version != CHECKPOINT_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("checkpoint version {}", CHECKPOINT_VERSION),
+                found: format!("checkpoint version {}", saved.version),
+            });
+        }
+        if saved.search_hash != search_hash {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("checkpoint for search {}", search_hash),
+                found: format!("checkpoint for search {}", saved.search_hash),
+            });
+        }
+        if let Some(observer) = &root.observer {
+            observer.on_event(SearchEvent::CheckpointResumed {
+                guesses_left: saved.remaining_guesses.len(),
+            });
+        }
+        root.guess_order = saved.remaining_guesses.into_iter();
+        root.best = saved.best;
+        root.guess_max_total = saved.guess_max_total;
+        root.slack_pruned = saved.slack_pruned;
+        root.alternatives = saved.alternatives;
+        if let Some(transpositions) = transpositions.as_mut() {
+            for (key, entry) in saved.transpositions {
+                transpositions.insert(key, entry);
+            }
+        }
+    }
+
+    let mut checkpointer = Checkpointer {
+        config: checkpoint.clone(),
+        search_hash,
+        last_saved: Instant::now(),
+    };
+    // Save right away, so an unwritable path fails now rather than an interval from now
+    checkpointer.save(&root, transpositions.as_ref())?;
+    let root = drive_with(
+        hints,
+        root,
+        transpositions,
+        max_depth,
+        &options,
+        &context,
+        Some(&mut checkpointer),
+    )?;
+    Ok(root.finish_root(hints, max_depth, &options))
+}
+
+/// How iterative deepening loosens the max cost between attempts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeepeningSchedule {
+    /// The first max cost to try, or `None` to start from the lowest cost any tree could have
+    pub initial: Option<f64>,
+    /// How much the max cost grows after each attempt that finds no tree
+    pub step: f64,
+    /// The loosest max cost to try. The last attempt is made at exactly this, or at a max cost
+    /// that allows every tree within the depth limit if that's lower.
+    pub limit: f64,
+}
+
+impl Default for DeepeningSchedule {
+    fn default() -> Self {
+        Self {
+            initial: None,
+            step: 0.05,
+            limit: f64::INFINITY,
+        }
+    }
+}
+
+/// A tree found by iterative deepening, and the max cost that found it.
+pub struct DeepeningResult {
+    pub tree: TreeNode,
+    /// The tightest max cost tried that found a tree
+    pub max_cost: f64,
+    pub attempts: usize,
+}
+
+/// Search under a max cost that starts tight and loosens each time no tree is found, so the
+/// budget doesn't have to be tuned by hand. The first attempt that finds any tree finds the
+/// best one. With the worst-case objective, a step of 1 tries each number of guesses in turn.
+///
+/// Returns `None` if even the limit finds no tree.
+pub fn compute_decision_tree_deepening<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    depth: u8,
+    max_depth: u8,
+    schedule: DeepeningSchedule,
+    options: SearchOptions,
+    observer: Option<&O>,
+) -> Option<DeepeningResult> {
+    let num_answers = possible_answers.len().max(1) as f64;
+    // At best one answer is guessed right away and the rest on the next guess, and a max cost
+    // equal to a tree's cost excludes it, so start just above that
+    let lowest_cost = (2.0 * num_answers - 1.0) / num_answers;
+    // No tree within the depth limit can cost more than a guess per remaining level
+    let limit = schedule
+        .limit
+        .min(max_depth.saturating_sub(depth) as f64 + 1.0);
+    let mut max_cost = schedule
+        .initial
+        .unwrap_or(lowest_cost + f64::EPSILON)
+        .min(limit);
+    let mut attempts = 0;
+    let started = Instant::now();
+    loop {
+        attempts += 1;
+        if let Some(tree) = compute_decision_tree_aggressive(
+            hints,
+            possible_answers.clone(),
+            depth,
+            max_depth,
+            max_cost,
+            options.with_time_since(started),
+            observer,
+        ) {
+            return Some(DeepeningResult {
+                tree,
+                max_cost,
+                attempts,
+            });
+        }
+        if max_cost >= limit || schedule.step <= 0.0 {
+            return None;
+        }
+        max_cost = (max_cost + schedule.step).min(limit);
+    }
+}
+
+/// Compute the decision tree with the lowest expected number of guesses that opens with the
+/// given guess indices in order, whatever hints they get, searching exhaustively only below
+/// them. Positions with a single answer left guess it rather than finishing the sequence. The
+/// root exclusions don't apply, as the sequence takes the place of the root's guess.
+///
+/// Returns `None` if the sequence leaves some position with no tree within the depth limit, if
+/// it names a guess index outside the hint matrix or one that isn't legal where it's played, or
+/// if the tree isn't within the max cost. Under the worst case objective, the sequence is
+/// solved under each depth limit in turn, as the plain search does, and the max cost caps the
+/// guesses for the hardest answer.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_decision_tree_with_openers<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    depth: u8,
+    max_depth: u8,
+    max_cost: f64,
+    openers: &[u16],
+    options: SearchOptions,
+    observer: Option<&O>,
+) -> Option<TreeNode> {
+    // The cast saturates, so an infinite max cost allows any total
+    let max_total = (max_cost * possible_answers.len() as f64).ceil() as u32;
+    let options = SearchOptions {
+        root_exclusions: HashSet::new(),
+        ..options
+    };
+    if options.objective == Objective::WorstCase {
+        return search_worst_case(depth, max_depth, max_cost, options, |max_depth, options| {
+            let tree = solve_with_openers(
+                hints,
+                possible_answers.clone(),
+                depth,
+                max_depth,
+                openers,
+                &options,
+                Instant::now(),
+                observer,
+            );
+            Ok(tree.map(|tree| BoundedTree {
+                lower_bound_total: tree.total_guesses,
+                tree,
+            }))
+        })
+        .ok()
+        .flatten()
+        .map(|bounded| bounded.tree);
+    }
+    solve_with_openers(
+        hints,
+        possible_answers,
+        depth,
+        max_depth,
+        openers,
+        &options,
+        Instant::now(),
+        observer,
+    )
+    .filter(|tree_node| tree_node.total_guesses < max_total)
+}
+
+/// The cost of the best tree that opens with one guess.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenerCost {
+    pub guess_ind: u16,
+    /// The guesses it takes to solve every answer, summed, or `None` if no tree opening with
+    /// this guess is within the limits
+    pub total_guesses: Option<u32>,
+    /// The most guesses it takes to solve any answer, if there's a tree
+    pub worst_case: Option<u32>,
+    pub num_answers: u32,
+    /// Whether a budget, beam, or time limit cut the search short, so the total is only an
+    /// upper bound
+    pub approximate: bool,
+}
+
+impl OpenerCost {
+    /// The expected number of guesses for a uniformly random answer, if there's a tree.
+    pub fn est_cost(&self) -> Option<f64> {
+        self.total_guesses
+            .map(|total_guesses| total_guesses as f64 / self.num_answers.max(1) as f64)
+    }
+}
+
+/// Compute the best tree opening with each of the given guess indices and rank them, best first
+/// by the objective, with guesses that have no tree within the limits last and ties in the
+/// given order. Unlike the plain search, which only proves the winner best, every guess gets
+/// its full cost.
+pub fn rank_openers<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    max_depth: u8,
+    max_cost: f64,
+    candidates: &[u16],
+    options: SearchOptions,
+    observer: Option<&O>,
+) -> Vec<OpenerCost> {
+    let started = Instant::now();
+    let objective = options.objective;
+    let mut costs: Vec<OpenerCost> = candidates
+        .iter()
+        .map(|&guess_ind| {
+            let tree = compute_decision_tree_with_openers(
+                hints,
+                possible_answers.clone(),
+                0,
+                max_depth,
+                max_cost,
+                &[guess_ind],
+                options.with_time_since(started),
+                observer,
+            );
+            OpenerCost {
+                guess_ind,
+                total_guesses: tree.as_ref().map(|tree| tree.total_guesses),
+                worst_case: tree.as_ref().map(TreeNode::worst_case),
+                num_answers: possible_answers.len() as u32,
+                approximate: tree.is_some_and(|tree| tree.approximate),
+            }
+        })
+        .collect();
+    costs.sort_by_key(|cost| {
+        let worst_case = match objective {
+            Objective::ExpectedGuesses => None,
+            Objective::WorstCase => cost.worst_case,
+        };
+        (cost.total_guesses.is_none(), worst_case, cost.total_guesses)
+    });
+    costs
+}
+
+/// Play the first of the openers against the possible answers and solve each resulting position
+/// with the rest, searching exhaustively once they run out. Every search shares the time limit
+/// from `started`.
+#[allow(clippy::too_many_arguments)]
+fn solve_with_openers<O: SearchObserver>(
+    hints: &[Vec<u8>],
+    possible_answers: HashSet<u16>,
+    depth: u8,
+    max_depth: u8,
+    openers: &[u16],
+    options: &SearchOptions,
+    started: Instant,
+    observer: Option<&O>,
+) -> Option<TreeNode> {
+    let Some((&guess_ind, later_openers)) = openers.split_first() else {
+        return compute_decision_tree_aggressive(
+            hints,
+            possible_answers,
+            depth,
+            max_depth,
+            f64::INFINITY,
+            options.with_time_since(started),
+            observer,
+        );
+    };
+    if possible_answers.len() <= 1 {
+        return solve_with_openers(
+            hints,
+            possible_answers,
+            depth,
+            max_depth,
+            &[],
+            options,
+            started,
+            observer,
+        );
+    }
+    if depth >= max_depth {
+        return None;
+    }
+
+    let guess_hints = hints.get(guess_ind as usize)?;
+    if options
+        .root_legal_guesses
+        .as_ref()
+        .is_some_and(|legal| !legal.get(guess_ind as usize))
+    {
+        return None;
+    }
+    let mut answers_by_hint: BTreeMap<u8, HashSet<u16>> = BTreeMap::new();
+    for &answer_ind in &possible_answers {
+        answers_by_hint
+            .entry(guess_hints[answer_ind as usize])
+            .or_default()
+            .insert(answer_ind);
+    }
+    let guess_observer = observer.map(|observer| observer.descend(SearchStep::Guess(guess_ind)));
+    let mut tree_node = TreeNode {
+        should_guess: GuessFrom::Guess(guess_ind),
+        total_guesses: possible_answers.len() as u32,
+        num_answers: possible_answers.len() as u32,
+        next: HashMap::new(),
+        approximate: false,
+        alternatives: Vec::new(),
+    };
+    for (hint, hint_possible_answers) in answers_by_hint {
+        // If we happened to guess correctly, there is no additional cost
+        if hint == 0 {
+            continue;
+        }
+        let hint_observer = guess_observer
+            .as_ref()
+            .map(|observer| observer.descend(SearchStep::Clue { guess_ind, hint }));
+        let child_options = SearchOptions {
+            root_legal_guesses: options.legal_after(
+                options.root_legal_guesses.as_ref(),
+                guess_ind,
+                hint,
+            ),
+            ..options.clone()
+        };
+        let child = solve_with_openers(
+            hints,
+            hint_possible_answers,
+            depth + 1,
+            max_depth,
+            later_openers,
+            &child_options,
+            started,
+            hint_observer.as_ref(),
+        )?;
+        tree_node.total_guesses += child.total_guesses;
+        tree_node.approximate |= child.approximate;
+        tree_node.next.insert(hint, child);
+    }
+    Some(tree_node)
+}
+
+/// Compute a decision tree by always guessing whatever leaves the fewest answers under its most
+/// common hint, preferring guesses that could themselves be the answer, then guesses ranked
+/// lower in `guess_ranks` if given.
+///
+/// This is much cheaper than the exhaustive search but gives no guarantee of optimality. Returns
+/// `None` if the answers can't all be distinguished within `max_depth` guesses.
+pub fn compute_decision_tree_greedy(
+    hints: &[Vec<u8>],
+    possible_answers: &HashSet<u16>,
+    max_depth: u8,
+    guess_ranks: &[u32],
+) -> Option<TreeNode> {
+    greedy_within(hints, possible_answers, max_depth, guess_ranks, None, None)
+}
+
+/// Compute the greedy tree, only playing guesses the clues before them leave legal. The node's
+/// own legal guesses are `legal_guesses`, or every guess if `None`.
+fn greedy_within(
+    hints: &[Vec<u8>],
+    possible_answers: &HashSet<u16>,
+    max_depth: u8,
+    guess_ranks: &[u32],
+    guess_legality: Option<&dyn ClueLegality>,
+    legal_guesses: Option<&Column>,
+) -> Option<TreeNode> {
+    if max_depth == 0 || possible_answers.is_empty() {
+        return None;
+    }
+    if possible_answers.len() == 1 {
+        return Some(TreeNode {
+            should_guess: GuessFrom::Answer(*possible_answers.iter().next().unwrap()),
+            total_guesses: 1,
+            num_answers: 1,
+            next: HashMap::new(),
+            approximate: false,
+            alternatives: Vec::new(),
+        });
+    }
+
+    // Rank by largest remaining bucket, then whether the guess is a candidate, then preference,
+    // then index
+    let mut best: Option<(usize, bool, u32, u16)> = None;
+    for (guess_ind, guess_hints) in hints.iter().enumerate() {
+        if legal_guesses.is_some_and(|legal| !legal.get(guess_ind)) {
+            continue;
+        }
+        let mut num_answers_by_hint: HashMap<u8, usize> = HashMap::new();
+        for &answer_ind in possible_answers {
+            *num_answers_by_hint
+                .entry(guess_hints[answer_ind as usize])
+                .or_insert(0) += 1;
+        }
+        let most_answers_for_any_hint = *num_answers_by_hint.values().max().unwrap();
+        if most_answers_for_any_hint == possible_answers.len() {
+            continue;
+        }
+        let key = (
+            most_answers_for_any_hint,
+            !num_answers_by_hint.contains_key(&0),
+            guess_ranks.get(guess_ind).copied().unwrap_or(0),
+            guess_ind as u16,
+        );
+        if best.is_none_or(|best| key < best) {
+            best = Some(key);
+        }
+    }
+    let (_, _, _, guess_ind) = best?;
+
+    let guess_hints = &hints[guess_ind as usize];
+    let answers_by_hint: HashMap<u8, HashSet<u16>> =
+        possible_answers
+            .iter()
+            .fold(HashMap::new(), |mut map, &answer_ind| {
+                map.entry(guess_hints[answer_ind as usize])
+                    .or_default()
+                    .insert(answer_ind);
+                map
+            });
+    let mut tree_node = TreeNode {
+        should_guess: GuessFrom::Guess(guess_ind),
+        total_guesses: possible_answers.len() as u32,
+        num_answers: possible_answers.len() as u32,
+        next: HashMap::new(),
+        approximate: false,
+        alternatives: Vec::new(),
+    };
+    for (hint, hint_possible_answers) in answers_by_hint {
+        if hint == 0 {
+            continue;
+        }
+        let child_legal_guesses = guess_legality
+            .and_then(|legality| legality.legal_after(guess_ind, hint))
+            .map(|mut legal| {
+                if let Some(parent_legal) = legal_guesses {
+                    legal &= parent_legal.clone();
+                }
+                legal
+            });
+        let child = greedy_within(
+            hints,
+            &hint_possible_answers,
+            max_depth - 1,
+            guess_ranks,
+            guess_legality,
+            child_legal_guesses.as_ref().or(legal_guesses),
+        )?;
+        tree_node.total_guesses += child.total_guesses;
+        tree_node.next.insert(hint, child);
+    }
+    Some(tree_node)
+}
+
+/// Get a lower bound on the total guesses to solve some number of answers, when no guess gets
+/// more than `max_hints` distinct hints for them.
+///
+/// Each guess solves at most the one answer it is, and each node has at most `max_hints` nodes
+/// below it, so at most `max_hints^(k - 1)` answers can be solved on guess k. The bound fills
+/// those in from the first guess on, which is `2p - 1` for p answers once `max_hints` is at
+/// least `p - 1`.
+fn total_lower_bound(num_answers: u32, max_hints: u32) -> u32 {
+    let mut total: u32 = 0;
+    let mut num_left = num_answers;
+    let mut num_solvable: u32 = 1;
+    let mut guesses: u32 = 1;
+    while num_left > 0 {
+        let num_solved = num_left.min(num_solvable);
+        total = total.saturating_add(num_solved.saturating_mul(guesses));
+        num_left -= num_solved;
+        num_solvable = num_solvable.saturating_mul(max_hints.max(1));
+        guesses += 1;
+    }
+    total
+}
+
+/// Get the most answers that could be solved within some number of guesses, when no guess gets
+/// more than `max_hints` distinct hints. Like `total_lower_bound`, each guess solves at most the
+/// one answer it is, and each node has at most `max_hints` nodes below it.
+fn max_solvable(num_guesses: u8, max_hints: u32) -> u32 {
+    let mut num_solvable: u32 = 0;
+    let mut num_nodes: u32 = 1;
+    for _ in 0..num_guesses {
+        num_solvable = num_solvable.saturating_add(num_nodes);
+        num_nodes = num_nodes.saturating_mul(max_hints);
+    }
+    num_solvable
+}
+
+/// Count the answers under each hint a guess could give. This runs for every guess at every
+/// node, so it counts into a fixed array rather than a map.
+pub(crate) fn count_by_hint(guess_hints: &[u8], possible_answers: &Column) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for answer_ind in possible_answers.iter_true() {
+        counts[guess_hints[answer_ind] as usize] += 1;
+    }
+    counts
+}
+
+/// Split the possible answers by the hint a guess gets for each, in hint order.
+pub(crate) fn split_by_hint(guess_hints: &[u8], possible_answers: &Column) -> Vec<(u8, Column)> {
+    let mut answers_by_hint: BTreeMap<u8, Column> = BTreeMap::new();
+    for answer_ind in possible_answers.iter_true() {
+        answers_by_hint
+            .entry(guess_hints[answer_ind])
+            .or_insert_with(|| Column::from_false(possible_answers.len()))
+            .set(answer_ind, true);
+    }
+    answers_by_hint.into_iter().collect()
+}
+
+/// Find the guesses that get the same hint as an earlier guess for every possible answer, mapped
+/// to that earlier guess. Guesses count as earlier by preference rank, then by index.
+///
+/// Only guesses with the same sort key are compared, which the built-in heuristics always give
+/// guesses with the same hints, so most guesses need no more than that.
+fn find_duplicates(
+    hints: &[Vec<u8>],
+    possible_answers: &Column,
+    guess_order: &[(u16, f64)],
+    guess_ranks: &[u32],
+) -> HashMap<u16, u16> {
+    let mut by_sort_key: HashMap<u64, Vec<u16>> = HashMap::new();
+    for &(guess_ind, sort_key) in guess_order {
+        by_sort_key
+            .entry(sort_key.to_bits())
+            .or_default()
+            .push(guess_ind);
+    }
+    let answer_inds = possible_answers.true_inds();
+    let mut duplicates = HashMap::new();
+    for mut guess_inds in by_sort_key.into_values() {
+        if guess_inds.len() < 2 {
+            continue;
+        }
+        guess_inds.sort_unstable_by_key(|&guess_ind| {
+            (
+                guess_ranks.get(guess_ind as usize).copied().unwrap_or(0),
+                guess_ind,
+            )
+        });
+        // Hash each guess's hints to find likely duplicates, then compare them in full
+        let mut kept_by_hash: HashMap<u64, Vec<u16>> = HashMap::new();
+        for guess_ind in guess_inds {
+            let guess_hints = &hints[guess_ind as usize];
+            let hash = fnv1a(
+                FNV_OFFSET_BASIS,
+                answer_inds
+                    .iter()
+                    .map(|&answer_ind| guess_hints[answer_ind]),
+            );
+            let kept = kept_by_hash.entry(hash).or_default();
+            let same_as = kept.iter().copied().find(|&kept_ind| {
+                let kept_hints = &hints[kept_ind as usize];
+                answer_inds
+                    .iter()
+                    .all(|&answer_ind| kept_hints[answer_ind] == guess_hints[answer_ind])
+            });
+            match same_as {
+                Some(kept_ind) => {
+                    duplicates.insert(guess_ind, kept_ind);
+                }
+                None => kept.push(guess_ind),
+            }
+        }
+    }
+    duplicates
+}
+
+/// How many of the guesses that split the answers finest `find_dominated` checks every other
+/// guess against. Checking every pair would cost far more than it could save.
+const MAX_DOMINATORS: usize = 32;
+
+/// Find the guesses another guess dominates, mapped to the guess dominating them.
+///
+/// Guess A dominates guess B when every hint A can get narrows the possible answers to within
+/// some hint B can get, and B isn't a possible answer unless A is too. Following A with the
+/// tree that followed B, restricted to each of A's hints, then takes no more guesses for any
+/// answer except B itself, which costs a guess more only when A makes up for it by being an
+/// answer. A guess only dominates guesses ranked no better than it, and of guesses that split
+/// the answers the same way, the first by index stays.
+fn find_dominated(
+    hints: &[Vec<u8>],
+    possible_answers: &Column,
+    guess_order: &[(u16, f64)],
+    guess_ranks: &[u32],
+) -> HashMap<u16, u16> {
+    let answer_inds = possible_answers.true_inds();
+    // The number of distinct hints of each guess, and whether it's a possible answer
+    let splits: HashMap<u16, (usize, bool)> = guess_order
+        .iter()
+        .map(|&(guess_ind, _)| {
+            let num_answers_by_hint = count_by_hint(&hints[guess_ind as usize], possible_answers);
+            let num_hints = num_answers_by_hint.iter().filter(|num| **num != 0).count();
+            (guess_ind, (num_hints, num_answers_by_hint[0] != 0))
+        })
+        .collect();
+    let mut dominators: Vec<u16> = guess_order
+        .iter()
+        .map(|(guess_ind, _)| *guess_ind)
+        .collect();
+    dominators.sort_by_key(|guess_ind| std::cmp::Reverse(splits[guess_ind].0));
+    dominators.truncate(MAX_DOMINATORS);
+
+    let dominates = |a: u16, b: u16| {
+        let (a_hints, a_is_answer) = splits[&a];
+        let (b_hints, b_is_answer) = splits[&b];
+        // A guess ranked after B could tie with it and lose to it on rank, so it can't stand in
+        if a == b
+            || a_hints < b_hints
+            || (b_is_answer && !a_is_answer)
+            || compare_ranks(guess_ranks, a, b).is_gt()
+        {
+            return false;
+        }
+        // An equivalent guess only dominates one after it, so one of them stays
+        if a_hints == b_hints && a_is_answer == b_is_answer && a > b {
+            return false;
+        }
+        // A refines B if each of A's hints always comes with the same hint from B
+        let mut b_hint_for = [None; 256];
+        answer_inds.iter().all(|&answer_ind| {
+            let a_hint = hints[a as usize][answer_ind] as usize;
+            let b_hint = hints[b as usize][answer_ind];
+            *b_hint_for[a_hint].get_or_insert(b_hint) == b_hint
+        })
+    };
+    guess_order
+        .iter()
+        .filter_map(|&(b, _)| {
+            dominators
+                .iter()
+                .find(|&&a| dominates(a, b))
+                .map(|&a| (b, a))
+        })
+        .collect()
+}
+
+/// The outcome of entering a node of the search.
+enum Entered<O: SearchObserver> {
+    /// The node was resolved without evaluating any guesses
+    Solved(Option<TreeNode>),
+
+    /// The node requires evaluating guesses, which the frame will step through
+    Frame(Box<Frame<O>>),
+}
+
+/// How the search treats the guesses at one node.
+#[derive(Clone, Copy)]
+struct NodeSettings<'a> {
+    depth: DepthSettings,
+    /// Overrides the depth's heuristic if given
+    ordering: Option<&'a dyn GuessOrdering>,
+    /// Guess indices that may be played, or every guess if not given
+    guess_pool: Option<&'a Column>,
+    /// Guess only the possible answers at nodes with fewer of them than this
+    candidates_only_below: Option<usize>,
+    /// The most distinct hints any guess can get for the node's answers, as known from the node
+    /// above
+    max_hints: u32,
+    /// Guess indices to leave out at this node only
+    excluded_guesses: Option<&'a HashSet<u16>>,
+    letter_screen: Option<&'a LetterScreen>,
+    guess_ranks: &'a [u32],
+    /// How far within the best so far a guess may come and still be skipped, as an est cost
+    slack: f64,
+}
+
+/// A child node that a frame needs solved before it can continue.
+struct ChildTask<O: SearchObserver> {
+    possible_answers: Column,
+    /// The guess and hint leading to the child
+    clue: (u16, u8),
+    max_total: u32,
+    /// The most distinct hints any guess can get for the child's answers
+    max_hints: u32,
+    observer: Option<O>,
+}
+
+/// The search state of a single node in the decision tree.
+struct Frame<O: SearchObserver> {
+    possible_answers: Column,
+    depth: u8,
+    entered_at: Instant,
+    nodes_expanded_at_entry: u64,
+    num_alternatives: usize,
+    /// Whether guesses must beat only the worst alternative kept rather than the best guess
+    complete_alternatives: bool,
+    alternatives: Vec<Alternative<TreeNode>>,
+    observer: Option<O>,
+    num_guesses: usize,
+    guess_order: std::vec::IntoIter<u16>,
+    /// Whether the beam width left some guesses unevaluated
+    beam_truncated: bool,
+    guess_max_total: u32,
+    /// The max total the frame was entered with, before any best guess lowered it
+    max_total: u32,
+    /// The slack as a total over this node's answers, rounded down so the tree stays within
+    /// the tolerance
+    slack: u32,
+    /// Whether the slack skipped a guess that might have beaten the best
+    slack_pruned: bool,
+    /// When the search must stop, set only on the root and the frames splitting its guesses
+    deadline: Option<Instant>,
+    /// Whether the search stopped before every guess was evaluated
+    stopped: bool,
+    /// The lowest total any guess left unevaluated by stopping could have
+    unexplored_lower_bound: Option<u32>,
+    /// The most distinct hints any guess gets over this node's answers. No guess can split the
+    /// answers of a node below any finer, which bounds how cheaply they can be solved.
+    max_hints: u32,
+    /// The guesses the clues leading here leave legal, or every guess if not limited
+    legal_guesses: Option<Column>,
+    best: Option<TreeNode>,
+    current: Option<GuessEval<O>>,
+}
+
+/// The search state of the guess currently being evaluated within a frame.
+struct GuessEval<O: SearchObserver> {
+    guess: TreeNode,
+    guess_ind: u16,
+    hints_answers: std::vec::IntoIter<(u8, Column)>,
+    observer: Option<O>,
+    pending: Option<PendingHint>,
+}
+
+/// Bookkeeping for the hint whose child node is currently being solved.
+struct PendingHint {
+    hint: u8,
+    child_total_lower_bound: u32,
+}
+
+impl<O: SearchObserver> Frame<O> {
+    /// Start work on a node, resolving it immediately if no guesses need evaluating.
+    fn enter(
+        hints: &[Vec<u8>],
+        possible_answers: Column,
+        depth: u8,
+        max_depth: u8,
+        mut max_total: u32,
+        settings: NodeSettings,
+        observer: Option<O>,
+    ) -> Entered<O> {
+        // Set the observer to `None` if we're past the depths it observes
+        let observer = observer.filter(|observer| observer.observes_depth(depth));
+        let num_possible_answers = possible_answers.count_true() as usize;
+        let abandon = |reason| {
+            if let Some(observer) = &observer {
+                observer.on_event(SearchEvent::NodeAbandoned(reason));
+            }
+            Entered::Solved(None)
+        };
+
+        if let Some(observer) = &observer {
+            observer.on_event(SearchEvent::NodeEntered {
+                num_answers: num_possible_answers as u32,
+                max_total,
+            });
+        }
+
+        // Don't continue if we've already hit depth limit
+        if depth == max_depth {
+            return abandon(AbandonReason::DepthLimitReached);
+        }
+
+        // Cap max total at every answer taking the remaining depth
+        let num_answers = num_possible_answers as u32;
+        max_total = max_total.min((max_depth - depth) as u32 * num_answers + 1);
+
+        // Don't continue if we've already hit cost limit, as every answer takes a guess
+        if max_total <= num_answers {
+            return abandon(AbandonReason::CostLimitExceeded);
+        }
+
+        // Shortcut - if only one option left, just guess it
+        if num_possible_answers == 1 {
+            let answer = possible_answers.iter_true().next().unwrap() as u16;
+            let tree_node = TreeNode {
+                should_guess: GuessFrom::Answer(answer),
+                total_guesses: 1,
+                num_answers: 1,
+                next: HashMap::new(),
+                approximate: false,
+                alternatives: Vec::new(),
+            };
+            if let Some(observer) = &observer {
+                observer.on_event(SearchEvent::NodeFinished {
+                    best: Some(&tree_node),
+                    elapsed: Duration::ZERO,
+                });
+            }
+            return Entered::Solved(Some(tree_node));
+        }
+
+        // Don't continue if we aren't guaranteed to avoid depth limit, either with a single
+        // guess left or with more answers than the guesses left could ever tell apart
+        if depth == max_depth - 1
+            || num_answers > max_solvable(max_depth - depth, settings.max_hints)
+        {
+            return abandon(AbandonReason::DepthLimitUnavoidable);
+        }
+
+        // Don't continue if we aren't guaranteed to avoid cost limit. At best one answer is
+        // guessed right away and the rest on the next guess.
+        if max_total < 2 * num_answers {
+            return abandon(AbandonReason::CostLimitUnavoidable);
+        }
+
+        // Shortcut - if only two options left, just guess one of them
+        if num_possible_answers == 2 {
+            let mut possible_answers_iter = possible_answers.iter_true();
+            let mut possible_answer_a = possible_answers_iter.next().unwrap() as u16;
+            let mut possible_answer_b = possible_answers_iter.next().unwrap() as u16;
+            // Answer indices double as guess indices, so they share the guess ranks
+            if compare_ranks(settings.guess_ranks, possible_answer_b, possible_answer_a).is_lt() {
+                std::mem::swap(&mut possible_answer_a, &mut possible_answer_b);
+            }
+            let tree_node = TreeNode {
+                should_guess: GuessFrom::Answer(possible_answer_a),
+                total_guesses: 3,
+                num_answers: 2,
+                next: HashMap::from([(
+                    hints[possible_answer_a as usize][possible_answer_b as usize],
+                    TreeNode {
+                        should_guess: GuessFrom::Answer(possible_answer_b),
+                        total_guesses: 1,
+                        num_answers: 1,
+                        next: HashMap::new(),
+                        approximate: false,
+                        alternatives: Vec::new(),
+                    },
+                )]),
+                approximate: false,
+                alternatives: Vec::new(),
+            };
+            if let Some(observer) = &observer {
+                observer.on_event(SearchEvent::NodeFinished {
+                    best: Some(&tree_node),
+                    elapsed: Duration::ZERO,
+                });
+            }
+            return Entered::Solved(Some(tree_node));
+        }
+
+        // We can filter more aggressively if we happen to see the best possible guess sooner
+        // The best possible guess _tends_ to have an "even" distribution of hints. i.e. no
+        // single hint downstream of that guess gives a huge of the answers.
+        // To improve how early we see the best possible guess, we can thus order guesses by
+        // the frequency of their most common subsequent hint, or by the entropy of their hints.
+        // We can also take this as an opportunity to filter out "useless" guesses, as they
+        // will have all answers under a single hint.
+        let candidates_only = settings
+            .candidates_only_below
+            .is_some_and(|threshold| num_possible_answers < threshold);
+        // A guess sharing no letters with any possible answer has a single hint for all of
+        // them, so screen those out before counting hints
+        let letters = settings
+            .letter_screen
+            .map(|screen| (screen, screen.letters_within(&possible_answers)));
+        let mut max_hints = 1;
+        let mut guess_order: Vec<(u16, f64)> = (0..hints.len())
+            .filter(|guess_ind| settings.guess_pool.is_none_or(|pool| pool.get(*guess_ind)))
+            .filter(|guess_ind| {
+                letters
+                    .as_ref()
+                    .is_none_or(|(screen, letters)| screen.shares_any_letter(*guess_ind, letters))
+            })
+            .filter_map(|guess_ind| {
+                let num_answers_by_hint = count_by_hint(&hints[guess_ind], &possible_answers);
+                let most_answers_for_any_hint = *num_answers_by_hint.iter().max().unwrap();
+                // Guesses excluded here may still be played below, so they count towards how
+                // finely the nodes below can be split
+                let num_hints = num_answers_by_hint.iter().filter(|num| **num != 0).count();
+                max_hints = max_hints.max(num_hints as u32);
+                if settings
+                    .excluded_guesses
+                    .is_some_and(|excluded| excluded.contains(&(guess_ind as u16)))
+                {
+                    return None;
+                }
+                if most_answers_for_any_hint == num_possible_answers {
+                    return None;
+                }
+                // Only a guess that's still a possible answer can get the all-correct hint
+                if candidates_only && num_answers_by_hint[0] == 0 {
+                    return None;
+                }
+                let sort_key = settings
+                    .ordering
+                    .unwrap_or(&settings.depth.heuristic)
+                    .sort_key(guess_ind as u16, &num_answers_by_hint);
+                Some((guess_ind as u16, sort_key))
+            })
+            .collect();
+        // Guesses with the same hints for every possible answer lead to the same subtrees, so
+        // only one of them needs searching
+        let duplicates =
+            find_duplicates(hints, &possible_answers, &guess_order, settings.guess_ranks);
+        guess_order.retain(|(guess_ind, _)| !duplicates.contains_key(guess_ind));
+        guess_order.sort_unstable_by(|(a_ind, a_sort_key), (b_ind, b_sort_key)| {
+            a_sort_key.total_cmp(b_sort_key).then(a_ind.cmp(b_ind))
+        });
+        let dominated = if settings.depth.prune_dominated {
+            let dominated =
+                find_dominated(hints, &possible_answers, &guess_order, settings.guess_ranks);
+            guess_order.retain(|(guess_ind, _)| !dominated.contains_key(guess_ind));
+            dominated
+        } else {
+            HashMap::new()
+        };
+        if let Some(observer) = &observer {
+            let mut pruned: Vec<(u16, PruneReason)> = duplicates
+                .into_iter()
+                .map(|(guess_ind, of)| (guess_ind, PruneReason::Duplicate { of }))
+                .chain(
+                    dominated
+                        .into_iter()
+                        .map(|(guess_ind, by)| (guess_ind, PruneReason::Dominated { by })),
+                )
+                .collect();
+            pruned.sort_unstable_by_key(|&(guess_ind, _)| guess_ind);
+            for (guess_ind, reason) in pruned {
+                observer
+                    .descend(SearchStep::Guess(guess_ind))
+                    .on_event(SearchEvent::GuessPruned { guess_ind, reason });
+            }
+        }
+        let beam_truncated = settings
+            .depth
+            .beam_width
+            .is_some_and(|beam_width| guess_order.len() > beam_width);
+        if let Some(beam_width) = settings.depth.beam_width {
+            guess_order.truncate(beam_width);
+        }
+        let guess_order: Vec<u16> = guess_order
+            .into_iter()
+            .map(|(guess_ind, _)| guess_ind)
+            .collect();
+
+        if let Some(observer) = &observer {
+            observer.on_event(SearchEvent::GuessOrder(&guess_order));
+        }
+
+        Entered::Frame(Box::new(Self {
+            possible_answers,
+            depth,
+            entered_at: Instant::now(),
+            nodes_expanded_at_entry: 0,
+            num_alternatives: 0,
+            complete_alternatives: false,
+            legal_guesses: None,
+            alternatives: Vec::new(),
+            observer,
+            num_guesses: hints.len(),
+            guess_order: guess_order.into_iter(),
+            beam_truncated,
+            guess_max_total: max_total,
+            max_total,
+            slack: (settings.slack * num_possible_answers as f64).floor() as u32,
+            slack_pruned: false,
+            deadline: None,
+            stopped: false,
+            unexplored_lower_bound: None,
+            max_hints,
+            best: None,
+            current: None,
+        }))
+    }
+
+    /// A frame that evaluates only the one guess, for splitting a node's guesses across
+    /// threads. It must beat `guess_max_total` to be kept as the best.
+    fn single_guess(
+        possible_answers: Column,
+        depth: u8,
+        num_guesses: usize,
+        guess_ind: u16,
+        guess_max_total: u32,
+        slack: u32,
+        max_hints: u32,
+    ) -> Self {
+        Self {
+            possible_answers,
+            depth,
+            entered_at: Instant::now(),
+            nodes_expanded_at_entry: 0,
+            num_alternatives: 0,
+            complete_alternatives: false,
+            legal_guesses: None,
+            alternatives: Vec::new(),
+            observer: None,
+            num_guesses,
+            guess_order: vec![guess_ind].into_iter(),
+            beam_truncated: false,
+            guess_max_total,
+            max_total: guess_max_total,
+            slack,
+            slack_pruned: false,
+            deadline: None,
+            stopped: false,
+            unexplored_lower_bound: None,
+            max_hints,
+            best: None,
+            current: None,
+        }
+    }
+
+    /// Advance through guesses and hints until a child node must be solved.
+    ///
+    /// Returns `None` once every guess has been evaluated.
+    fn step(&mut self, hints: &[Vec<u8>], guess_ranks: &[u32]) -> Option<ChildTask<O>> {
+        loop {
+            if let Some(guess_ind) = self.current.as_ref().map(|current| current.guess_ind) {
+                let max_total = self.max_total_for(guess_ind, guess_ranks);
+                let num_possible_answers = self.possible_answers.count_true() as usize;
+                let max_hints = self.max_hints;
+                let current = self.current.as_mut().unwrap();
+                match current.next_child(num_possible_answers, max_total, max_hints) {
+                    Some(child) => return Some(child),
+                    None => {
+                        let current = self.current.take().unwrap();
+                        self.consider(current, guess_ranks);
+                    }
+                }
+            }
+            let guess_ind = self.guess_order.next()?;
+            self.current = self.start_guess(hints, guess_ind, guess_ranks);
+        }
+    }
+
+    /// The total a guess must come in under to beat the best guess so far, less the slack. A
+    /// guess ranked ahead of the best may also tie it.
+    fn max_total_for(&self, guess_ind: u16, guess_ranks: &[u32]) -> u32 {
+        if self.complete_alternatives && self.num_alternatives > 0 {
+            // A guess only needs to beat the worst alternative, once there are enough of them
+            return match self.alternatives.get(self.num_alternatives - 1) {
+                Some(worst) => worst.tree_node.total_guesses.saturating_sub(self.slack),
+                None => self.max_total,
+            };
+        }
+        match &self.best {
+            Some(TreeNode {
+                should_guess: GuessFrom::Guess(best_ind),
+                ..
+            }) if compare_ranks(guess_ranks, guess_ind, *best_ind).is_lt() => {
+                self.guess_max_total.saturating_sub(self.slack) + 1
+            }
+            Some(_) => self.guess_max_total.saturating_sub(self.slack),
+            None => self.guess_max_total,
+        }
+    }
+
+    /// Set up evaluation of a guess, or return `None` if it can be skipped outright.
+    fn start_guess(
+        &mut self,
+        hints: &[Vec<u8>],
+        guess_ind: u16,
+        guess_ranks: &[u32],
+    ) -> Option<GuessEval<O>> {
+        let guess_hints = &hints[guess_ind as usize];
+
+        let observer = self
+            .observer
+            .as_ref()
+            .map(|observer| observer.descend(SearchStep::Guess(guess_ind)));
+        if let Some(observer) = &observer {
+            observer.on_event(SearchEvent::GuessStarted {
+                guess_ind,
+                num_guesses: self.num_guesses,
+            });
+        }
+
+        // Check first if this guess is useless
+        // If only 1 hint is possible for this guess, then it doesn't narrow down the
+        // possible answer pool at all.
+        let mut useless = true;
+        let mut possible_answers_iter = self.possible_answers.iter_true();
+        let some_possible_answer = possible_answers_iter.next().unwrap();
+        let some_possible_guess = guess_hints[some_possible_answer];
+        for possible_answer in possible_answers_iter {
+            if guess_hints[possible_answer] != some_possible_guess {
+                useless = false;
+                break;
+            }
+        }
+        if useless {
+            if let Some(observer) = &observer {
+                observer.on_event(SearchEvent::GuessPruned {
+                    guess_ind,
+                    reason: PruneReason::Useless,
+                });
+            }
+            return None;
+        }
+
+        // Split the possible answers by the hint they'd give, ordered by number of answers
+        // ascending, then by hint
+        let mut hints_answers = split_by_hint(guess_hints, &self.possible_answers);
+        hints_answers.sort_by_cached_key(|(hint, answers)| (answers.count_true(), *hint));
+
+        // Set lower bound on total guesses given what we know so far, so we can prune earlier
+        // Every answer takes this guess, and past it each hint's answers need at least
+        // `total_lower_bound` more, or none if the hint is all-correct. With few answers that's
+        // `2p - 1` for p answers, from guessing one of them next and telling the rest apart
+        // with it, but a hint with more answers than any guess can tell apart needs more.
+        let num_possible_answers = self.possible_answers.count_true() as u32;
+        let total_lower_bound = num_possible_answers
+            + hints_answers
+                .iter()
+                .filter(|(hint, _)| *hint != 0)
+                .map(|(_, answers)| total_lower_bound(answers.count_true() as u32, self.max_hints))
+                .sum::<u32>();
+        if let Some(observer) = &observer {
+            let bucket_sizes: Vec<usize> = hints_answers
+                .iter()
+                .map(|(_, answers)| answers.count_true() as usize)
+                .collect();
+            observer.on_event(SearchEvent::GuessSplit {
+                guess_ind,
+                bucket_sizes: &bucket_sizes,
+                total_lower_bound,
+            });
+        }
+
+        let max_total = self.max_total_for(guess_ind, guess_ranks);
+        if total_lower_bound >= max_total {
+            self.slack_pruned |= total_lower_bound < self.guess_max_total;
+            if let Some(observer) = &observer {
+                observer.on_event(SearchEvent::GuessPruned {
+                    guess_ind,
+                    reason: PruneReason::LowerBound {
+                        total_lower_bound,
+                        max_total,
+                    },
+                });
+            }
+            self.retain_alternative(
+                TreeNode {
+                    should_guess: GuessFrom::Guess(guess_ind),
+                    total_guesses: total_lower_bound,
+                    num_answers: num_possible_answers,
+                    next: HashMap::new(),
+                    approximate: false,
+                    alternatives: Vec::new(),
+                },
+                false,
+            );
+            return None;
+        }
+
+        // Reorder hints to be ascending on number of possible answers, with 1s & 2s in the back
+        let first_ind_at_least_3 = hints_answers
+            .iter()
+            .enumerate()
+            .find(|(_, (_, answers))| answers.count_true() >= 3)
+            .map(|(ind, _)| ind);
+        if let Some(split_ind) = first_ind_at_least_3 {
+            hints_answers.rotate_left(split_ind);
+        }
+
+        // Initialize guess with lower bound total
+        Some(GuessEval {
+            guess: TreeNode {
+                should_guess: GuessFrom::Guess(guess_ind),
+                total_guesses: total_lower_bound,
+                num_answers: num_possible_answers,
+                next: HashMap::new(),
+                approximate: false,
+                alternatives: Vec::new(),
+            },
+            guess_ind,
+            hints_answers: hints_answers.into_iter(),
+            observer,
+            pending: None,
+        })
+    }
+
+    /// Fold a solved child node into the guess currently being evaluated.
+    fn accept_child(&mut self, child_tree_node: Option<TreeNode>, guess_ranks: &[u32]) {
+        let max_total = self.max_total_for(self.current.as_ref().unwrap().guess_ind, guess_ranks);
+        let current = self.current.as_mut().unwrap();
+        let pending = current.pending.take().unwrap();
+        let observer = current.observer.as_ref();
+
+        let Some(child_tree_node) = child_tree_node else {
+            if let Some(observer) = observer {
+                observer.on_event(SearchEvent::GuessPruned {
+                    guess_ind: current.guess_ind,
+                    reason: PruneReason::NoGuarantee,
+                });
+            }
+            // The child may only have failed because the slack tightened its max total
+            self.slack_pruned |= max_total < self.guess_max_total;
+            let current = self.current.take().unwrap();
+            self.retain_alternative(current.guess, false);
+            return;
+        };
+
+        // Swap the child's lower bound for its actual total, which can't be any lower
+        current.guess.total_guesses += child_tree_node.total_guesses;
+        current.guess.total_guesses -= pending.child_total_lower_bound;
+        // A guess is only as exact as the subtrees under it
+        current.guess.approximate |= child_tree_node.approximate;
+        current.guess.next.insert(pending.hint, child_tree_node);
+
+        if current.guess.total_guesses >= max_total {
+            self.slack_pruned |= current.guess.total_guesses < self.guess_max_total;
+            if let Some(observer) = observer {
+                observer.on_event(SearchEvent::GuessPruned {
+                    guess_ind: current.guess_ind,
+                    reason: PruneReason::OverMax {
+                        total: current.guess.total_guesses,
+                        max_total,
+                    },
+                });
+            }
+            let current = self.current.take().unwrap();
+            self.retain_alternative(current.guess, false);
+        }
+    }
+
+    /// Evaluate if a fully-evaluated guess beats the current best guess, using the guess ranks
+    /// to settle ties.
+    fn consider(&mut self, current: GuessEval<O>, guess_ranks: &[u32]) {
+        self.offer(
+            current.guess,
+            current.guess_ind,
+            current.observer.as_ref(),
+            guess_ranks,
+        );
+    }
+
+    /// Take a fully-evaluated guess as the new best if it beats the current one, or keep it as
+    /// a runner-up otherwise.
+    fn offer(
+        &mut self,
+        guess: TreeNode,
+        guess_ind: u16,
+        observer: Option<&O>,
+        guess_ranks: &[u32],
+    ) {
+        let this_guess_is_new_best = match &self.best {
+            None => true,
+            Some(best_guess) => {
+                let rank_order = match best_guess.should_guess {
+                    GuessFrom::Guess(best_ind) => compare_ranks(guess_ranks, guess_ind, best_ind),
+                    GuessFrom::Answer(_) => std::cmp::Ordering::Equal,
+                };
+                if rank_order.is_ne() && guess.total_guesses == best_guess.total_guesses {
+                    rank_order.is_lt()
+                } else {
+                    guess.total_guesses < best_guess.total_guesses
+                }
+            }
+        };
+        if let Some(observer) = observer {
+            observer.on_event(SearchEvent::GuessEvaluated {
+                guess_ind,
+                tree_node: &guess,
+                new_best: this_guess_is_new_best,
+            });
+        }
+        if this_guess_is_new_best {
+            self.guess_max_total = guess.total_guesses;
+            if let Some(prev_best) = self.best.replace(guess) {
+                self.retain_alternative(prev_best, true);
+            }
+        } else {
+            self.retain_alternative(guess, true);
+        }
+    }
+
+    /// Keep a losing guess if it's among the best runners-up seen so far.
+    fn retain_alternative(&mut self, tree_node: TreeNode, complete: bool) {
+        if self.num_alternatives == 0 || (self.complete_alternatives && !complete) {
+            return;
+        }
+        let ind = self.alternatives.partition_point(|alternative| {
+            alternative.tree_node.total_guesses <= tree_node.total_guesses
+        });
+        if ind < self.num_alternatives {
+            self.alternatives.insert(
+                ind,
+                Alternative {
+                    tree_node,
+                    complete,
+                },
+            );
+            self.alternatives.truncate(self.num_alternatives);
+        }
+    }
+
+    /// Report the best guess and return it.
+    fn finish(self) -> Option<TreeNode> {
+        if let Some(observer) = &self.observer {
+            observer.on_event(SearchEvent::NodeFinished {
+                best: self.best.as_ref(),
+                elapsed: self.entered_at.elapsed(),
+            });
+        }
+        self.best.map(|mut tree_node| {
+            tree_node.approximate |= self.beam_truncated || self.slack_pruned;
+            tree_node.alternatives = self.alternatives;
+            tree_node
+        })
+    }
+
+    /// Stop evaluating guesses once time is up or the search is cancelled, keeping the guess
+    /// underway as an incomplete runner-up and noting how low the guesses left could go.
+    fn stop_early(&mut self, hints: &[Vec<u8>]) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(SearchEvent::Stopped);
+        }
+        // A guess underway has its unsolved children at their lower bounds already
+        let current = self.current.take();
+        let num_possible_answers = self.possible_answers.count_true() as u32;
+        let unexplored_lower_bound = current
+            .as_ref()
+            .map(|current| current.guess.total_guesses)
+            .into_iter()
+            .chain(self.guess_order.by_ref().map(|guess_ind| {
+                let num_answers_by_hint =
+                    count_by_hint(&hints[guess_ind as usize], &self.possible_answers);
+                let num_hints = num_answers_by_hint
+                    .iter()
+                    .filter(|count| **count > 0)
+                    .count();
+                3 * num_possible_answers - num_hints as u32 - (num_answers_by_hint[0] > 0) as u32
+            }))
+            .min();
+        if let Some(current) = current {
+            self.retain_alternative(current.guess, false);
+        }
+        self.unexplored_lower_bound = unexplored_lower_bound;
+        self.stopped = true;
+    }
+
+    /// Finish the node a search started from. If it stopped early it falls back on the greedy
+    /// tree, so long as that fits within the max total, and bounds the best tree by the guesses
+    /// it didn't get to.
+    fn finish_root(
+        self,
+        hints: &[Vec<u8>],
+        max_depth: u8,
+        options: &SearchOptions,
+    ) -> Option<BoundedTree> {
+        if !self.stopped {
+            return self.finish().map(|tree| BoundedTree {
+                lower_bound_total: tree.total_guesses,
+                tree,
+            });
+        }
+        let max_total = self.max_total;
+        let unexplored_lower_bound = self.unexplored_lower_bound;
+        let tree = self
+            .finish_over_budget(hints, max_depth, options)
+            .filter(|tree_node| tree_node.total_guesses < max_total)?;
+        Some(BoundedTree {
+            lower_bound_total: unexplored_lower_bound
+                .map_or(tree.total_guesses, |bound| bound.min(tree.total_guesses)),
+            tree,
+        })
+    }
+
+    /// Resolve this node after it ran over budget, taking the cheaper of the greedy tree and the
+    /// best guess found so far.
+    fn finish_over_budget(
+        mut self,
+        hints: &[Vec<u8>],
+        max_depth: u8,
+        options: &SearchOptions,
+    ) -> Option<TreeNode> {
+        let possible_answers: HashSet<u16> = self
+            .possible_answers
+            .iter_true()
+            .map(|answer_ind| answer_ind as u16)
+            .collect();
+        let greedy = greedy_within(
+            hints,
+            &possible_answers,
+            max_depth - self.depth,
+            &options.guess_ranks,
+            options.guess_legality.as_deref(),
+            self.legal_guesses.as_ref(),
+        );
+        if let Some(observer) = &self.observer {
+            observer.on_event(SearchEvent::BudgetExceeded {
+                greedy: greedy.as_ref(),
+                elapsed: self.entered_at.elapsed(),
+            });
+        }
+        let mut tree_node = match (self.best.take(), greedy) {
+            (Some(best), Some(greedy)) if greedy.total_guesses < best.total_guesses => {
+                self.retain_alternative(best, true);
+                greedy
+            }
+            (Some(best), _) => best,
+            (None, greedy) => greedy?,
+        };
+        tree_node.approximate = true;
+        tree_node.alternatives = self.alternatives;
+        Some(tree_node)
+    }
+}
+
+impl<O: SearchObserver> GuessEval<O> {
+    /// Move on to the next hint that needs its child node solved.
+    ///
+    /// Returns `None` once every hint for this guess has been accounted for.
+    fn next_child(
+        &mut self,
+        num_possible_answers: usize,
+        guess_max_total: u32,
+        max_hints: u32,
+    ) -> Option<ChildTask<O>> {
+        // Add up total guesses across all possibilities
+        for (hint, hint_possible_answers) in self.hints_answers.by_ref() {
+            // If we happened to guess correctly, there is no additional cost
+            if hint == 0 {
+                continue;
+            }
+
+            let hint_num_possible_answers = hint_possible_answers.count_true() as usize;
+            if let Some(observer) = &self.observer {
+                observer.on_event(SearchEvent::ClueStarted {
+                    guess_ind: self.guess_ind,
+                    hint,
+                    num_answers: hint_num_possible_answers as u32,
+                    num_possible_answers: num_possible_answers as u32,
+                });
+            }
+
+            // Reconstruct the lower bound we made earlier, for this specific hint
+            let child_total_lower_bound =
+                total_lower_bound(hint_num_possible_answers as u32, max_hints);
+
+            // The child gets whatever "budget" is left once the other hints are at their
+            // lower bounds or actual totals
+            let child_max_total = guess_max_total
+                .saturating_sub(self.guess.total_guesses)
+                .saturating_add(child_total_lower_bound);
+
+            self.pending = Some(PendingHint {
+                hint,
+                child_total_lower_bound,
+            });
+            return Some(ChildTask {
+                possible_answers: hint_possible_answers,
+                clue: (self.guess_ind, hint),
+                max_total: child_max_total,
+                max_hints,
+                observer: self.observer.as_ref().map(|observer| {
+                    observer.descend(SearchStep::Clue {
+                        guess_ind: self.guess_ind,
+                        hint,
+                    })
+                }),
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::{hint_matrix::compute_hints, test_words, word::Word, word_search::SearchableWords};
+
+    #[test]
+    fn test_root_exclusions() {
+        let words: Vec<Word<5, 26>> = ["fight", "light", "might", "sight", "tight", "lemon"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let hints = compute_hints(&words, &words[..5]);
+        let solve = |options| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..5).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                options,
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        let tree = solve(SearchOptions::default());
+        let GuessFrom::Guess(best_opener) = tree.should_guess else {
+            panic!("expected an opener from the guess list");
+        };
+        let excluded_tree = solve(SearchOptions {
+            root_exclusions: HashSet::from([best_opener]),
+            ..SearchOptions::default()
+        });
+        assert_ne!(excluded_tree.should_guess, GuessFrom::Guess(best_opener));
+        assert!(excluded_tree.total_guesses >= tree.total_guesses);
+    }
+
+    #[test]
+    fn test_letter_screen() {
+        /// Records the order the root's guesses are evaluated in.
+        #[derive(Clone, Default)]
+        struct RootOrderObserver {
+            below_root: bool,
+            order: Arc<std::sync::Mutex<Vec<u16>>>,
+        }
+
+        impl SearchObserver for RootOrderObserver {
+            fn observes_depth(&self, depth: u8) -> bool {
+                depth == 0
+            }
+
+            fn descend(&self, _step: SearchStep) -> Self {
+                Self {
+                    below_root: true,
+                    order: self.order.clone(),
+                }
+            }
 
-    if let Some(printer) = printer {
-        println!(
-            "{}must compute {} possible answers with max cost of {}",
-            printer.get_prefix(),
-            possible_answers.len(),
-            max_cost
+            fn on_event(&self, event: SearchEvent) {
+                if let SearchEvent::GuessOrder(order) = event
+                    && !self.below_root
+                {
+                    self.order.lock().unwrap().extend_from_slice(order);
+                }
+            }
+        }
+
+        let answers: Vec<Word<5, 26>> = ["fight", "light", "might", "sight", "tight", "night"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let mut guesses = answers.clone();
+        // Only the last of these shares no letters with the answers
+        guesses.extend(
+            ["flame", "numbs", "crowd"]
+                .iter()
+                .map(|word| Word::try_from(*word).unwrap()),
         );
+        let hints = compute_hints(&guesses, &answers);
+        let screen = LetterScreen::new(&guesses, &SearchableWords::build(answers.clone()));
+        let solve = |letter_screen| {
+            let observer = RootOrderObserver::default();
+            let tree = compute_decision_tree_aggressive(
+                &hints,
+                (0..answers.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    letter_screen,
+                    ..SearchOptions::default()
+                },
+                Some(&observer),
+            )
+            .unwrap();
+            let order = observer.order.lock().unwrap().clone();
+            (tree, order)
+        };
+
+        let (tree, order) = solve(None);
+        let (screened_tree, screened_order) = solve(Some(Arc::new(screen)));
+        assert_eq!(screened_tree.total_guesses, tree.total_guesses);
+        assert_eq!(screened_tree.should_guess, tree.should_guess);
+        assert_eq!(screened_order, order);
+        assert!(!order.contains(&8));
     }
 
-    // Don't continue if we've already hit depth limit
-    if depth == max_depth {
-        if let Some(printer) = printer {
-            println!("{}depth limit reached", printer.get_prefix());
-        }
-        return None;
+    #[test]
+    fn test_in_view() {
+        let words = test_words::medium_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |hints: &[Vec<u8>], num_answers: u16| {
+            compute_decision_tree_aggressive(
+                hints,
+                (0..num_answers).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+        let solve_in_view = |view: &HintMatrixView| {
+            compute_decision_tree_in_view(
+                view,
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        let answers: Vec<usize> = (0..12).collect();
+        let tree = solve_in_view(&hints.view().with_answers(&answers));
+        let sliced_tree = solve(&compute_hints(&words, &words[..12]), 12);
+        assert_eq!(tree.total_guesses, sliced_tree.total_guesses);
+        assert_eq!(tree.should_guess, sliced_tree.should_guess);
+
+        // Leaving out the best opener makes the view's tree open with something else
+        let opener = match tree.should_guess {
+            GuessFrom::Guess(guess_ind) | GuessFrom::Answer(guess_ind) => guess_ind as usize,
+        };
+        let guesses: Vec<usize> = (0..words.len()).filter(|ind| *ind != opener).collect();
+        let narrowed = solve_in_view(&hints.view().with_answers(&answers).with_guesses(&guesses));
+        assert_ne!(narrowed.should_guess, tree.should_guess);
+        assert!(narrowed.total_guesses >= tree.total_guesses);
     }
 
-    // Cap max cost at remaining depth
-    let remaining_depth = (max_depth - depth) as f64;
-    if max_cost > remaining_depth {
-        max_cost = remaining_depth + 0.01;
+    #[test]
+    fn test_deep_search_fits_small_stack() {
+        let words: Vec<Word<5, 26>> = [
+            "fight", "light", "might", "sight", "tight", "night", "right", "wight",
+        ]
+        .iter()
+        .map(|word| Word::try_from(*word).unwrap())
+        .collect();
+        let hints = compute_hints(&words, &words);
+        // Guessing only answers, every _IGHT word may take its own guess, so the search goes as
+        // deep as there are words. The frames live on the heap, leaving the thread's stack flat.
+        let tree = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || {
+                compute_decision_tree_aggressive(
+                    &hints,
+                    (0..8).collect(),
+                    0,
+                    8,
+                    f64::INFINITY,
+                    SearchOptions {
+                        answers_only: true,
+                        ..SearchOptions::default()
+                    },
+                    None::<&NoPrinter>,
+                )
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+        assert_eq!(tree.num_answers, 8);
+        assert_eq!(tree.worst_case(), 8);
+        assert!(!tree.approximate);
     }
 
-    // Don't continue if we've already hit cost limit
-    if max_cost < 1.0 {
-        if let Some(printer) = printer {
-            println!("{}cost limit exceeded", printer.get_prefix());
+    #[test]
+    fn test_guess_ranks_break_ties() {
+        let words: Vec<Word<5, 26>> = ["fight", "light", "might", "sight", "tight"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let hints = compute_hints(&words, &words);
+        let possible_answers: HashSet<u16> = (0..5).collect();
+        let unranked = compute_decision_tree_aggressive(
+            &hints,
+            possible_answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+
+        // Every _IGHT opener costs the same, so the ranks alone pick between them
+        for preferred in 0..5 {
+            let mut guess_ranks = vec![1; words.len()];
+            guess_ranks[preferred as usize] = 0;
+            let tree = compute_decision_tree_aggressive(
+                &hints,
+                possible_answers.clone(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    guess_ranks: guess_ranks.clone(),
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap();
+            assert_eq!(tree.should_guess, GuessFrom::Guess(preferred));
+            assert_eq!(tree.total_guesses, unranked.total_guesses);
+
+            let greedy =
+                compute_decision_tree_greedy(&hints, &possible_answers, 6, &guess_ranks).unwrap();
+            assert_eq!(greedy.should_guess, GuessFrom::Guess(preferred));
         }
-        return None;
     }
 
-    // Shortcut - if only one option left, just guess it
-    if possible_answers.len() == 1 {
-        let answer = possible_answers.into_iter().next().unwrap();
-        if let Some(printer) = printer {
-            println!(
-                "{}best guess is {} with est cost of {} (certain)",
-                printer.get_prefix(),
-                printer.fmt_answer(answer),
-                1.0
-            );
+    #[test]
+    fn test_tolerance_bounds_est_cost() {
+        let words = test_words::medium_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |tolerance| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    tolerance,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        let exact = solve(0.0);
+        assert!(!exact.approximate);
+        for tolerance in [0.05, 0.2, 1.0, 2.0] {
+            let loose = solve(tolerance);
+            assert!(loose.total_guesses >= exact.total_guesses);
+            assert!(loose.est_cost() <= exact.est_cost() + tolerance);
+            // A tree worse than the exact one must say so, wherever below the root the slack
+            // skipped the better guess
+            assert!(loose.approximate || loose.total_guesses == exact.total_guesses);
+            assert_approximate_propagates(&loose);
         }
-        return Some(TreeNode {
-            should_guess: GuessFrom::Answer(answer),
-            est_cost: 1.0,
-            next: HashMap::new(),
-        });
+        assert!(solve(1.0).approximate);
+
+        // With the opener fixed, the root prunes nothing itself, so only its subtrees can make
+        // it approximate
+        let opened = compute_decision_tree_with_openers(
+            &hints,
+            (0..words.len() as u16).collect(),
+            0,
+            6,
+            f64::INFINITY,
+            &[0],
+            SearchOptions {
+                tolerance: 2.0,
+                ..SearchOptions::default()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        assert!(opened.approximate);
+        assert_approximate_propagates(&opened);
     }
 
-    // Don't continue if we aren't guaranteed to avoid depth limit
-    if depth == max_depth - 1 {
-        if let Some(printer) = printer {
-            println!("{}depth limit cannot be avoided", printer.get_prefix());
+    /// Check every node with an approximate subtree under it is marked approximate too.
+    fn assert_approximate_propagates(tree: &TreeNode) {
+        let mut stack = vec![tree];
+        while let Some(node) = stack.pop() {
+            for child in node.next.values() {
+                assert!(node.approximate || !child.approximate);
+                stack.push(child);
+            }
         }
-        return None;
     }
 
-    // Don't continue if we aren't guaranteed to avoid cost limit
-    if max_cost < 1.5 {
-        if let Some(printer) = printer {
-            println!("{}cost limit cannot be avoided", printer.get_prefix());
+    #[test]
+    fn test_budget_marks_root_approximate() {
+        let words = test_words::medium_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |budget| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    budget,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        let exact = solve(NodeBudget::default());
+        assert!(!exact.approximate);
+        // Subtrees below the root trip the budget and fall back on the greedy tree, which only
+        // the root's children know about unless it's passed up
+        for budget in [
+            NodeBudget {
+                max_nodes: Some(2),
+                max_millis: None,
+            },
+            NodeBudget {
+                max_nodes: None,
+                max_millis: Some(0),
+            },
+        ] {
+            let limited = solve(budget);
+            assert!(limited.approximate);
+            assert!(limited.total_guesses >= exact.total_guesses);
         }
-        return None;
     }
 
-    // Shortcut - if only two options left, just guess one of them
-    if possible_answers.len() == 2 {
-        let mut possible_answers_iter = possible_answers.into_iter();
-        let possible_answer_a = possible_answers_iter.next().unwrap();
-        let possible_answer_b = possible_answers_iter.next().unwrap();
-        if let Some(printer) = printer {
-            println!(
-                "{}best guess is {} with est cost of {}",
-                printer.get_prefix(),
-                printer.fmt_answer(possible_answer_a),
-                1.5
-            );
-        }
-        return Some(TreeNode {
-            should_guess: GuessFrom::Answer(possible_answer_a),
-            est_cost: 1.5,
-            next: HashMap::from([(
-                hints[possible_answer_a as usize][possible_answer_b as usize],
-                TreeNode {
-                    should_guess: GuessFrom::Answer(possible_answer_b),
-                    est_cost: 1.0,
-                    next: HashMap::new(),
+    #[test]
+    fn test_parallel_root_matches_sequential() {
+        let words = test_words::medium_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |parallel_root, max_depth| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                max_depth,
+                f64::INFINITY,
+                SearchOptions {
+                    num_alternatives: 3,
+                    guess_ranks: (0..words.len() as u32).collect(),
+                    parallel_root,
+                    ..SearchOptions::default()
                 },
-            )]),
-        });
+                None::<&NoPrinter>,
+            )
+        };
+
+        let sequential = solve(false, 6).unwrap();
+        let parallel = solve(true, 6).unwrap();
+        assert_eq!(parallel.total_guesses, sequential.total_guesses);
+        // Distinct ranks leave no ties to settle differently
+        assert_eq!(parallel.should_guess, sequential.should_guess);
+        assert!(!parallel.approximate);
+        assert_eq!(parallel.alternatives.len(), 3);
+        // Incomplete alternatives only have a lower bound, which may fall below the best
+        assert!(
+            parallel
+                .alternatives
+                .iter()
+                .filter(|alternative| alternative.complete)
+                .all(|alternative| alternative.tree_node.total_guesses >= parallel.total_guesses)
+        );
+        assert!(solve(true, 2).is_none());
     }
 
-    // Go through every possible guess and determine which is the best
-    let mut best: Option<TreeNode> = None;
-    let mut guess_max_est_cost = max_cost;
-
-    // We can filter more aggressively if we happen to see the best possible guess sooner
-    // The best possible guess _tends_ to have an "even" distribution of hints. i.e. no
-    // single hint downstream of that guess gives a huge of the answers.
-    // To improve how early we see the best possible guess, we can thus order guesses by
-    // the frequency of their most common subsequent hint.
-    // We can also take this as an opportunity to filter out "useless" guesses, as they
-    // will have all answers under a single hint.
-    let mut guess_order: Vec<(u16, usize)> = (0..hints.len())
-        .map(|guess_ind| {
-            let guess_hints = &hints[guess_ind];
-            let num_answers_by_hint: HashMap<u8, usize> =
-                possible_answers
-                    .iter()
-                    .fold(HashMap::new(), |mut map, &answer_ind| {
-                        let hint = guess_hints[answer_ind as usize];
-                        *map.entry(hint).or_insert(0) += 1;
-                        map
-                    });
-            let most_answers_for_any_hint = *num_answers_by_hint.values().max().unwrap();
-            (guess_ind as u16, most_answers_for_any_hint)
-        })
-        .filter(|(_, most_answers_for_any_hint)| {
-            *most_answers_for_any_hint != possible_answers.len()
-        })
-        .collect();
-    guess_order.sort_unstable_by(
-        |(_, a_most_answers_possible), (_, b_most_answers_possible)| {
-            a_most_answers_possible.cmp(b_most_answers_possible)
-        },
-    );
-    let guess_order: Vec<u16> = guess_order
-        .into_iter()
-        .map(|(guess_ind, _)| guess_ind)
-        .collect();
+    #[test]
+    fn test_transpositions_match_fresh_search() {
+        let words = test_words::medium_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |transposition_bytes, max_depth| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                max_depth,
+                f64::INFINITY,
+                SearchOptions {
+                    guess_ranks: (0..words.len() as u32).collect(),
+                    transposition_bytes,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+        };
 
-    if let Some(printer) = printer {
-        println!(
-            "{}first guesses will be {}",
-            printer.get_prefix(),
-            guess_order[..5]
-                .iter()
-                .map(|guess_ind| printer.fmt_guess(*guess_ind))
-                .collect::<Vec<String>>()
-                .join(", ")
+        for max_depth in [4, 6] {
+            let fresh = solve(0, max_depth).unwrap();
+            // A tiny table keeps evicting, which must not change the result either
+            for transposition_bytes in [1 << 10, 1 << 24] {
+                let cached = solve(transposition_bytes, max_depth).unwrap();
+                assert_eq!(cached.total_guesses, fresh.total_guesses);
+                assert_eq!(cached.should_guess, fresh.should_guess);
+                assert!(!cached.approximate);
+            }
+        }
+        assert!(solve(0, 3).is_none());
+        assert!(solve(1 << 24, 3).is_none());
+    }
+
+    #[test]
+    fn test_transposition_key_collision_misses() {
+        let possible_answers = Column::from_bools(&[true, true, true, false]);
+        let other_answers = Column::from_bools(&[true, true, false, true]);
+        let key = transposition_key(&possible_answers, None, 1);
+        let mut transpositions = BoundedCache::with_memory_budget(1 << 16);
+        // File another set's result under this set's key, as a hash collision would
+        transpositions.insert(
+            key,
+            TranspositionEntry {
+                possible_answers: other_answers.to_chunks(),
+                legal_guesses: None,
+                result: Transposition::NoneBelow(u32::MAX),
+            },
+        );
+        let lookup = |transpositions: &mut BoundedCache<_, _>, legal_guesses| {
+            lookup_transposition(
+                transpositions,
+                &possible_answers,
+                legal_guesses,
+                1,
+                10,
+                None::<&NoPrinter>,
+            )
+        };
+        assert!(lookup(&mut transpositions, None).is_none());
+
+        transpositions.insert(
+            key,
+            TranspositionEntry {
+                possible_answers: possible_answers.to_chunks(),
+                legal_guesses: None,
+                result: Transposition::NoneBelow(u32::MAX),
+            },
         );
+        assert!(matches!(lookup(&mut transpositions, None), Some(None)));
+        // The same answers with limited legal guesses is a different node
+        let legal_guesses = Column::from_true(4);
+        assert!(lookup(&mut transpositions, Some(&legal_guesses)).is_none());
     }
 
-    'guess_loop: for guess_ind in guess_order {
-        let guess_hints = &hints[guess_ind as usize];
+    #[test]
+    fn test_custom_guess_ordering() {
+        /// Evaluates the guesses backwards, counting how often it's asked
+        #[derive(Debug, Default)]
+        struct Reversed(AtomicU32);
 
-        let printer_owned = printer
-            .map(|printer| printer.with_prefix(format!("{} > ", printer.fmt_guess(guess_ind))));
-        let printer = printer_owned.as_ref();
-        if let Some(printer) = printer {
-            println!(
-                "{}evaluating guess {} - {:.0}% complete",
-                printer.get_prefix(),
-                printer.fmt_guess(guess_ind),
-                100.0 * guess_ind as f64 / hints.len() as f64
-            );
+        impl GuessOrdering for Reversed {
+            fn sort_key(&self, guess_ind: u16, _num_answers_by_hint: &[usize]) -> f64 {
+                self.0.fetch_add(1, AtomicOrdering::Relaxed);
+                -(guess_ind as f64)
+            }
         }
 
-        // Check first if this guess is useless
-        // If only 1 hint is possible for this guess, then it doesn't narrow down the
-        // possible answer pool at all.
-        let mut useless = true;
-        let mut possible_answers_iter = possible_answers.iter();
-        let some_possible_answer = *possible_answers_iter.next().unwrap() as usize;
-        let some_possible_guess = guess_hints[some_possible_answer];
-        for &possible_answer in possible_answers_iter {
-            if guess_hints[possible_answer as usize] != some_possible_guess {
-                useless = false;
-                break;
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |guess_ordering| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    guess_ordering,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        let reversed = Arc::new(Reversed::default());
+        let tree = solve(None);
+        let reversed_tree = solve(Some(reversed.clone() as Arc<dyn GuessOrdering>));
+        assert!(reversed.0.load(AtomicOrdering::Relaxed) > 0);
+        // The order only changes how soon the search prunes, not what it finds
+        assert_eq!(reversed_tree.total_guesses, tree.total_guesses);
+        assert!(!reversed_tree.approximate);
+    }
+
+    #[test]
+    fn test_entropy_ordering() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let possible_answers = Column::from_true(words.len());
+        let solve = |depth_settings| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    depth_settings,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+        let entropy = DepthSettings {
+            heuristic: GuessHeuristic::Entropy,
+            beam_width: None,
+            ..DepthSettings::default()
+        };
+
+        let tree = solve(vec![]);
+        let entropy_tree = solve(vec![entropy]);
+        assert_eq!(entropy_tree.total_guesses, tree.total_guesses);
+        assert!(!entropy_tree.approximate);
+
+        // A beam of one at the root keeps only the guess entropy ranks first
+        let beam_tree = solve(vec![
+            DepthSettings {
+                beam_width: Some(1),
+                ..entropy
+            },
+            entropy,
+        ]);
+        let GuessFrom::Guess(opener) = beam_tree.should_guess else {
+            panic!("expected an opener from the guess list");
+        };
+        let entropy_of = |guess_ind: usize| {
+            PartitionStats::entropy_of(&count_by_hint(&hints[guess_ind], &possible_answers))
+        };
+        let best_entropy = (0..words.len()).map(entropy_of).fold(0.0, f64::max);
+        assert_eq!(entropy_of(opener as usize), best_entropy);
+        assert!(beam_tree.approximate);
+    }
+
+    #[test]
+    fn test_answers_only() {
+        let words = test_words::small_list();
+        // Only the first dozen words are answers, so the rest are guesses it must pass up. The
+        // -ight words take many answer guesses to tell apart, hence the deep limit.
+        let hints = compute_hints(&words, &words[..12]);
+        let solve = |answers_only| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..12).collect(),
+                0,
+                10,
+                f64::INFINITY,
+                SearchOptions {
+                    answers_only,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        fn assert_answers_only(tree_node: &TreeNode) {
+            if let GuessFrom::Guess(guess_ind) = tree_node.should_guess {
+                assert!(guess_ind < 12);
             }
+            tree_node.next.values().for_each(assert_answers_only);
         }
-        if useless {
-            if let Some(printer) = printer {
-                println!(
-                    "{}guess {} is useless, skipping",
-                    printer.get_prefix(),
-                    printer.fmt_guess(guess_ind),
+        let tree = solve(false);
+        let answers_tree = solve(true);
+        assert_answers_only(&answers_tree);
+        assert!(answers_tree.total_guesses >= tree.total_guesses);
+        assert!(!answers_tree.approximate);
+    }
+
+    #[test]
+    fn test_candidates_only_below() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |candidates_only_below| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                10,
+                f64::INFINITY,
+                SearchOptions {
+                    candidates_only_below,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+
+        fn assert_candidates(hints: &[Vec<u8>], tree_node: &TreeNode, answers: &[usize]) {
+            let GuessFrom::Guess(guess_ind) = tree_node.should_guess else {
+                return;
+            };
+            let guess_hints = &hints[guess_ind as usize];
+            if answers.len() < 5 {
+                assert!(
+                    answers
+                        .iter()
+                        .any(|&answer_ind| guess_hints[answer_ind] == 0)
                 );
             }
-            continue;
+            for (hint, child) in &tree_node.next {
+                let child_answers: Vec<usize> = answers
+                    .iter()
+                    .copied()
+                    .filter(|&answer_ind| guess_hints[answer_ind] == *hint)
+                    .collect();
+                assert_candidates(hints, child, &child_answers);
+            }
         }
+        let tree = solve(None);
+        let candidates_tree = solve(Some(5));
+        let all_answers: Vec<usize> = (0..words.len()).collect();
+        assert_candidates(&hints, &candidates_tree, &all_answers);
+        assert!(candidates_tree.total_guesses >= tree.total_guesses);
+        assert!(!candidates_tree.approximate);
+    }
 
-        // Build map from possible hint to possible answers if we were to receive that hint
-        let answers_by_hint: HashMap<u8, HashSet<u16>> =
-            possible_answers
-                .iter()
-                .fold(HashMap::new(), |mut map, &answer_ind| {
-                    let answers_for_hint = map.entry(guess_hints[answer_ind as usize]).or_default();
-                    answers_for_hint.insert(answer_ind as u16);
-                    map
-                });
+    #[test]
+    fn test_openers() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let solve = |openers: &[u16]| {
+            compute_decision_tree_with_openers(
+                &hints,
+                answers.clone(),
+                0,
+                6,
+                f64::INFINITY,
+                openers,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+        };
 
-        if let Some(printer) = printer {
-            let distribution: HashMap<usize, usize> =
-                answers_by_hint
-                    .iter()
-                    .fold(HashMap::new(), |mut map, (_, answers)| {
-                        *map.entry(answers.len()).or_insert(0) += 1;
-                        map
-                    });
-            let mode_val = *distribution.values().max().unwrap();
-            let max_key = *distribution.keys().max().unwrap();
-            let distribution_flat: Vec<usize> = (0..=max_key)
-                .map(|ind| distribution.get(&ind).cloned().unwrap_or(0))
-                .collect();
-            let heights = [" ", "⡀", "⣀", "⣄", "⣤", "⣦", "⣶", "⣷", "⣿"];
-            let distribution_fmt: Vec<&str> = distribution_flat
-                .into_iter()
-                .map(|n_hints| heights[(8 * n_hints + mode_val - 1) / mode_val])
-                .collect();
-            println!(
-                "{}distribution: {}<{}",
-                printer.get_prefix(),
-                distribution_fmt.join(""),
-                max_key
-            );
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let GuessFrom::Guess(best_opener) = tree.should_guess else {
+            panic!("expected an opener from the guess list");
+        };
+        assert_eq!(solve(&[]).unwrap().total_guesses, tree.total_guesses);
+        assert_eq!(
+            solve(&[best_opener]).unwrap().total_guesses,
+            tree.total_guesses
+        );
+
+        // "fight" then "board" whatever the hint, except where one answer is left
+        let sequence_tree = solve(&[0, 7]).unwrap();
+        assert_eq!(sequence_tree.should_guess, GuessFrom::Guess(0));
+        assert!(sequence_tree.total_guesses >= tree.total_guesses);
+        for child in sequence_tree.next.values() {
+            if child.num_answers > 1 {
+                assert_eq!(child.should_guess, GuessFrom::Guess(7));
+            }
         }
+        assert!(solve(&[0, 7, 1, 2, 3, 4]).is_none());
+        assert!(solve(&[hints.len() as u16]).is_none());
+        assert!(solve(&[0, u16::MAX]).is_none());
+    }
 
-        let correct_hint_present = answers_by_hint.contains_key(&0);
+    #[test]
+    fn test_rank_openers() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            5,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
 
-        // Convert into list of tuples, ordered by number of answers descending
-        let mut hints_answers: Vec<(u8, HashSet<u16>)> = answers_by_hint.into_iter().collect();
-        hints_answers.sort_unstable_by(|(_, answers_a), (_, answers_b)| {
-            answers_a.len().cmp(&answers_b.len())
-        });
+        let candidates: Vec<u16> = (0..words.len() as u16).collect();
+        let ranked = rank_openers(
+            &hints,
+            answers,
+            5,
+            f64::INFINITY,
+            &candidates,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        );
+        assert_eq!(ranked.len(), candidates.len());
+        assert!(ranked.windows(2).all(|pair| pair[1].total_guesses.is_none()
+            || pair[0].total_guesses <= pair[1].total_guesses));
+        // Not every opener solves everything within five guesses
+        assert!(ranked.last().unwrap().total_guesses.is_none());
+        assert_eq!(ranked[0].total_guesses, Some(tree.total_guesses));
+        assert_eq!(
+            ranked[0].est_cost(),
+            Some(tree.total_guesses as f64 / words.len() as f64)
+        );
 
-        // Set lower bound on estimated cost given what we know so far, so we can prune earlier
-        // Lower bound cost for a single hint is `(2p - 1)` / p (p is # of possible answers for that hint)
-        // or 0 if the hint is all-correct.
-        // This is based on the best-case scenario of guessing the correct answer next with 1/p odds, or
-        // knowing exactly which of the remaining is the answer with (p-1)/p odds.
-        // The lower bound for the whole set of hints then simplifies to:
-        // > `2 - h / p` if correct hint not present
-        // > `2 - (h + 1) / p` if correct hint present
-        // h = total # of hints, p = total # of possible answers
-        // h is the total number of hints and p is the total number of possible answers.
-        // We then must add 1 more to accommodate the hint we just made above=
-        let est_cost_lower_bound = if correct_hint_present {
-            3.0 - ((hints_answers.len() as f64 + 1.0) / possible_answers.len() as f64)
-        } else {
-            3.0 - (hints_answers.len() as f64 / possible_answers.len() as f64)
+        // Under the worst case objective, the shallowest openers come first
+        let worst_case_options = SearchOptions {
+            objective: Objective::WorstCase,
+            ..SearchOptions::default()
         };
+        let shallowest = compute_decision_tree_aggressive(
+            &hints,
+            (0..words.len() as u16).collect(),
+            0,
+            5,
+            f64::INFINITY,
+            worst_case_options.clone(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let ranked = rank_openers(
+            &hints,
+            (0..words.len() as u16).collect(),
+            5,
+            f64::INFINITY,
+            &candidates,
+            worst_case_options.clone(),
+            None::<&NoPrinter>,
+        );
+        assert!(ranked.windows(2).all(|pair| pair[1].worst_case.is_none()
+            || (pair[0].worst_case, pair[0].total_guesses)
+                <= (pair[1].worst_case, pair[1].total_guesses)));
+        assert_eq!(ranked[0].worst_case, Some(shallowest.worst_case()));
+        assert_eq!(ranked[0].total_guesses, Some(shallowest.total_guesses));
 
-        if est_cost_lower_bound >= guess_max_est_cost {
-            if let Some(printer) = printer {
-                println!(
-                    "{}est cost lower bound of {:.3} already exceeds max of {:.3}",
-                    printer.get_prefix(),
-                    est_cost_lower_bound,
-                    guess_max_est_cost,
-                );
+        // And the max cost caps the worst case for the sequence as a whole
+        let opener = ranked[0].guess_ind;
+        let with_opener = |max_cost| {
+            compute_decision_tree_with_openers(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                5,
+                max_cost,
+                &[opener],
+                worst_case_options.clone(),
+                None::<&NoPrinter>,
+            )
+        };
+        let worst_case = shallowest.worst_case() as f64;
+        assert!(with_opener(worst_case).is_some());
+        assert!(with_opener(worst_case - 0.5).is_none());
+    }
+
+    #[test]
+    fn test_complete_alternatives() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            5,
+            f64::INFINITY,
+            SearchOptions {
+                num_alternatives: 2,
+                complete_alternatives: true,
+                ..SearchOptions::default()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        assert_eq!(tree.alternatives.len(), 2);
+        assert!(
+            tree.alternatives
+                .iter()
+                .all(|alternative| alternative.complete)
+        );
+
+        // The best guess and its alternatives are the three cheapest openers
+        let candidates: Vec<u16> = (0..words.len() as u16).collect();
+        let ranked = rank_openers(
+            &hints,
+            answers,
+            5,
+            f64::INFINITY,
+            &candidates,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        );
+        let totals: Vec<Option<u32>> = [&tree]
+            .into_iter()
+            .chain(
+                tree.alternatives
+                    .iter()
+                    .map(|alternative| &alternative.tree_node),
+            )
+            .map(|tree_node| Some(tree_node.total_guesses))
+            .collect();
+        let ranked_totals: Vec<Option<u32>> =
+            ranked[..3].iter().map(|cost| cost.total_guesses).collect();
+        assert_eq!(totals, ranked_totals);
+    }
+
+    #[test]
+    fn test_failed_guesses_kept_as_alternatives() {
+        /// Records the root guesses whose subtrees couldn't be solved in time.
+        #[derive(Clone, Default)]
+        struct NoGuaranteeObserver {
+            depth: usize,
+            guesses: Arc<std::sync::Mutex<Vec<u16>>>,
+        }
+
+        impl SearchObserver for NoGuaranteeObserver {
+            fn observes_depth(&self, depth: u8) -> bool {
+                depth == 0
+            }
+
+            fn descend(&self, _step: SearchStep) -> Self {
+                Self {
+                    depth: self.depth + 1,
+                    guesses: self.guesses.clone(),
+                }
+            }
+
+            fn on_event(&self, event: SearchEvent) {
+                if let SearchEvent::GuessPruned {
+                    guess_ind,
+                    reason: PruneReason::NoGuarantee,
+                } = event
+                    && self.depth == 1
+                {
+                    self.guesses.lock().unwrap().push(guess_ind);
+                }
             }
-            continue;
         }
 
-        if let Some(printer) = printer {
-            println!(
-                "{}considering {} possible hints - lower bound est_cost of {:.3}",
-                printer.get_prefix(),
-                hints_answers.len(),
-                est_cost_lower_bound,
-            );
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let observer = NoGuaranteeObserver::default();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            (0..words.len() as u16).collect(),
+            0,
+            5,
+            f64::INFINITY,
+            SearchOptions {
+                num_alternatives: words.len(),
+                ..SearchOptions::default()
+            },
+            Some(&observer),
+        )
+        .unwrap();
+
+        // Not every opener solves everything within five guesses, and those still show up as
+        // alternatives, only without a complete tree
+        let failed = observer.guesses.lock().unwrap();
+        assert!(!failed.is_empty());
+        for guess_ind in failed.iter() {
+            assert!(tree.alternatives.iter().any(|alternative| {
+                alternative.tree_node.should_guess == GuessFrom::Guess(*guess_ind)
+                    && !alternative.complete
+            }));
         }
+    }
 
-        // Initialize guess with lower bound est cost
-        let mut guess = TreeNode {
-            should_guess: GuessFrom::Guess(guess_ind),
-            est_cost: est_cost_lower_bound,
-            next: HashMap::new(),
+    #[test]
+    fn test_guess_legality() {
+        use crate::{
+            hint::WordHint,
+            ruleset::{ClueLegalityMasks, GuessLegality},
         };
 
-        // Reorder hints to be ascending on number of possible answers, with 1s & 2s in the back
-        let first_ind_at_least_3 = hints_answers
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let solve = |guess_legality, budget| {
+            compute_decision_tree_aggressive(
+                &hints,
+                answers.clone(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    guess_legality,
+                    budget,
+                    transposition_bytes: 1 << 20,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+        let unrestricted = solve(None, NodeBudget::default());
+        let legality: Arc<dyn ClueLegality> = Arc::new(ClueLegalityMasks::new(
+            GuessLegality::UltraHard,
+            words.clone(),
+        ));
+        let tree = solve(Some(legality.clone()), NodeBudget::default());
+        assert!(tree.total_guesses >= unrestricted.total_guesses);
+        // Subtrees over budget fall back on the greedy tree, which must keep to the rules too
+        let greedy_tree = solve(
+            Some(legality.clone()),
+            NodeBudget {
+                max_nodes: None,
+                max_millis: Some(0),
+            },
+        );
+        assert!(greedy_tree.approximate);
+
+        // Every guess the trees play is legal after the clues before it
+        for (tree, answer_ind) in [&tree, &greedy_tree]
+            .into_iter()
+            .flat_map(|tree| (0..words.len()).map(move |answer_ind| (tree, answer_ind)))
+        {
+            let mut history = vec![];
+            let mut node = tree;
+            loop {
+                let (GuessFrom::Guess(guess_ind) | GuessFrom::Answer(guess_ind)) =
+                    node.should_guess;
+                let guess = words[guess_ind as usize];
+                assert!(GuessLegality::UltraHard.is_legal_guess(&guess, &history));
+                let hint = hints[guess_ind as usize][answer_ind];
+                if hint == 0 {
+                    break;
+                }
+                history.push((guess, WordHint::from_id(hint)));
+                node = &node.next[&hint];
+            }
+        }
+
+        // A search started after FIGHT shows IGHT keeps to that clue from its root, finding the
+        // same subtree as opening with FIGHT does
+        let hint = hints[0][1];
+        let after_fight: HashSet<u16> = answers
             .iter()
-            .enumerate()
-            .find(|(_, (_, answers))| answers.len() >= 3)
-            .map(|(ind, _)| ind);
-        if let Some(split_ind) = first_ind_at_least_3 {
-            hints_answers.rotate_left(split_ind);
+            .copied()
+            .filter(|&answer_ind| hints[0][answer_ind as usize] == hint)
+            .collect();
+        let options = SearchOptions {
+            guess_legality: Some(legality.clone()),
+            ..SearchOptions::default()
+        };
+        let mid_game = compute_decision_tree_aggressive(
+            &hints,
+            after_fight,
+            1,
+            8,
+            f64::INFINITY,
+            SearchOptions {
+                root_legal_guesses: legality.legal_after(0, hint),
+                ..options.clone()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let opened = compute_decision_tree_with_openers(
+            &hints,
+            answers,
+            0,
+            8,
+            f64::INFINITY,
+            &[0],
+            options,
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        assert_eq!(mid_game.total_guesses, opened.next[&hint].total_guesses);
+        let (GuessFrom::Guess(guess_ind) | GuessFrom::Answer(guess_ind)) = mid_game.should_guess;
+        assert!(GuessLegality::UltraHard.is_legal_guess(
+            &words[guess_ind as usize],
+            &[(words[0], WordHint::from_id(hint))]
+        ));
+    }
+
+    #[test]
+    fn test_search_hash_names_guess_legality() {
+        use crate::ruleset::{ClueLegalityMasks, GuessLegality};
+
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let hash = |guess_legality: Option<GuessLegality>, root_legal_guesses: Option<Column>| {
+            let options = SearchOptions {
+                guess_legality: guess_legality.map(|guess_legality| {
+                    Arc::new(ClueLegalityMasks::new(guess_legality, words.clone()))
+                        as Arc<dyn ClueLegality>
+                }),
+                root_legal_guesses,
+                ..SearchOptions::default()
+            };
+            let (Entered::Frame(root), _) = enter_root(
+                &hints,
+                answers.clone(),
+                0,
+                6,
+                f64::INFINITY,
+                &options,
+                None::<&NoPrinter>,
+            ) else {
+                panic!("root should need a search");
+            };
+            search_hash(&hints, &root, 6, &options)
+        };
+
+        // A checkpoint saved under one policy isn't resumed under another
+        let hard = hash(Some(GuessLegality::Hard), None);
+        assert_ne!(hash(None, None), hard);
+        assert_ne!(hash(Some(GuessLegality::UltraHard), None), hard);
+        let mut root_legal_guesses = Column::from_true(words.len());
+        root_legal_guesses.set(0, false);
+        assert_ne!(
+            hash(Some(GuessLegality::Hard), Some(root_legal_guesses)),
+            hard
+        );
+    }
+
+    #[test]
+    fn test_prune_dominated() {
+        let mut words = test_words::medium_list();
+        words.push(words[0]);
+        // The repeated "fight" splits the answers exactly as the first does
+        let hints = compute_hints(&words, &words[..30]);
+        let possible_answers = Column::from_true(30);
+        let guess_order: Vec<(u16, f64)> = (0..words.len() as u16).map(|ind| (ind, 0.0)).collect();
+        let dominated = find_dominated(&hints, &possible_answers, &guess_order, &[]);
+        assert_eq!(dominated.get(&30), Some(&0));
+        assert!(!dominated.contains_key(&0));
+        assert_eq!(
+            find_duplicates(&hints, &possible_answers, &guess_order, &[]),
+            HashMap::from([(30, 0)])
+        );
+        // Preferring the repeat keeps it instead
+        let mut guess_ranks = vec![1; words.len()];
+        guess_ranks[30] = 0;
+        assert_eq!(
+            find_duplicates(&hints, &possible_answers, &guess_order, &guess_ranks),
+            HashMap::from([(0, 30)])
+        );
+
+        let solve = |prune_dominated| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..30).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    depth_settings: vec![DepthSettings {
+                        prune_dominated,
+                        ..DepthSettings::default()
+                    }],
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+        };
+        let tree = solve(false);
+        let pruned_tree = solve(true);
+        assert_eq!(pruned_tree.total_guesses, tree.total_guesses);
+        assert!(!pruned_tree.approximate);
+    }
+
+    #[test]
+    fn test_total_lower_bound() {
+        assert_eq!(total_lower_bound(0, 10), 0);
+        assert_eq!(total_lower_bound(1, 10), 1);
+        assert_eq!(total_lower_bound(2, 10), 3);
+        assert_eq!(total_lower_bound(5, 4), 9);
+        // Only two answers can be told apart on the second guess, leaving two for the third
+        assert_eq!(total_lower_bound(5, 2), 11);
+        assert_eq!(total_lower_bound(4, 1), 10);
+    }
+
+    #[test]
+    fn test_max_solvable() {
+        assert_eq!(max_solvable(0, 10), 0);
+        assert_eq!(max_solvable(1, 10), 1);
+        assert_eq!(max_solvable(3, 2), 7);
+        assert_eq!(max_solvable(2, 256), 257);
+        assert_eq!(max_solvable(6, u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_search_is_deterministic() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        // Every search builds its maps with fresh hash seeds, so any dependence on their order
+        // would show up as differing trees
+        let solve = || {
+            let tree = compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions {
+                    num_alternatives: 3,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+            .unwrap();
+            serde_json::to_string(&tree).unwrap()
+        };
+
+        let first = solve();
+        for _ in 0..4 {
+            assert_eq!(solve(), first);
         }
+    }
 
-        // Add up estimated cost across all possibilities, weighted by likelihood
-        for (hint, hint_possible_answers) in hints_answers.into_iter() {
-            // If we happened to guess correctly, there is no additional cost
-            if hint == 0 {
-                continue;
-            }
+    #[test]
+    fn test_worst_case_objective() {
+        let words = test_words::medium_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |objective, max_depth, max_cost| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                max_depth,
+                max_cost,
+                SearchOptions {
+                    objective,
+                    ..SearchOptions::default()
+                },
+                None::<&NoPrinter>,
+            )
+        };
 
-            let hint_num_possible_answers = hint_possible_answers.len();
-            let hint_likelihood = hint_num_possible_answers as f64 / possible_answers.len() as f64;
+        let shallowest = solve(Objective::WorstCase, 6, f64::INFINITY).unwrap();
+        let worst_case = shallowest.worst_case();
+        assert!(
+            solve(
+                Objective::ExpectedGuesses,
+                worst_case as u8 - 1,
+                f64::INFINITY
+            )
+            .is_none()
+        );
+        let average = solve(Objective::ExpectedGuesses, 6, f64::INFINITY).unwrap();
+        assert!(average.worst_case() >= worst_case);
+        assert!(average.total_guesses <= shallowest.total_guesses);
 
-            let printer_owned = printer.map(|printer| {
-                printer.with_prefix(format!("{} > ", printer.fmt_clue(hint, guess_ind)))
-            });
-            let printer = printer_owned.as_ref();
-
-            if let Some(printer) = printer {
-                println!(
-                    "{}evaluating clue {} with {}/{} possible answers - {:.2}% chance",
-                    printer.get_prefix(),
-                    printer.fmt_clue(hint, guess_ind),
-                    hint_num_possible_answers,
-                    possible_answers.len(),
-                    100.0 * hint_likelihood,
-                )
-            }
+        // The max cost is a hard cap on guesses
+        assert!(solve(Objective::WorstCase, 6, worst_case as f64 - 0.5).is_none());
+        let capped = solve(Objective::WorstCase, 6, worst_case as f64).unwrap();
+        assert_eq!(capped.total_guesses, shallowest.total_guesses);
+    }
 
-            // Reconstruct the lower bound we made earlier, for this specific hint
-            let child_est_cost_lower_bound =
-                (2.0 * hint_num_possible_answers as f64 - 1.0) / possible_answers.len() as f64;
+    #[test]
+    fn test_deepening_finds_best_tree() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let deepen = |max_depth, schedule| {
+            compute_decision_tree_deepening(
+                &hints,
+                answers.clone(),
+                0,
+                max_depth,
+                schedule,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+        };
+
+        let unlimited = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let schedule = DeepeningSchedule {
+            step: 0.1,
+            ..DeepeningSchedule::default()
+        };
+        let result = deepen(6, schedule).unwrap();
+        assert_eq!(result.tree.total_guesses, unlimited.total_guesses);
+        assert!(result.attempts > 1);
+        // The attempt before found nothing, so the tree can't have fit under it
+        assert!(result.max_cost > result.tree.est_cost());
+        assert!(result.max_cost - schedule.step <= result.tree.est_cost());
 
-            // Compute how much "budget" we have at our level for total est cost
-            let remaining_est_cost_budget =
-                guess_max_est_cost - guess.est_cost + child_est_cost_lower_bound;
+        // The limit is tried last, and a limit no tree fits under finds nothing
+        let limited = DeepeningSchedule {
+            limit: unlimited.est_cost() - 0.01,
+            ..schedule
+        };
+        assert!(deepen(6, limited).is_none());
+        assert!(deepen(2, DeepeningSchedule::default()).is_none());
 
-            // Compute the child's est cost based on hint probability
-            let child_max_est_cost = remaining_est_cost_budget / hint_likelihood;
+        // Each attempt allows one more guess for the hardest answer, so the first tree found
+        // has the shallowest worst case
+        let shallowest = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions {
+                objective: Objective::WorstCase,
+                ..SearchOptions::default()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let result = compute_decision_tree_deepening(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            DeepeningSchedule {
+                step: 1.0,
+                ..DeepeningSchedule::default()
+            },
+            SearchOptions {
+                objective: Objective::WorstCase,
+                ..SearchOptions::default()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        assert_eq!(result.tree.worst_case(), shallowest.worst_case());
+        assert_eq!(result.tree.total_guesses, shallowest.total_guesses);
+        assert_eq!(result.attempts, shallowest.worst_case() as usize);
+    }
 
-            // Find the child node for this clue
-            if let Some(child_tree_node) = compute_decision_tree_aggressive(
-                hints,
-                hint_possible_answers,
-                depth + 1,
+    #[test]
+    fn test_checkpoint_resumes_killed_search() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let options = SearchOptions {
+            num_alternatives: 2,
+            transposition_bytes: 1 << 20,
+            ..SearchOptions::default()
+        };
+        let dir = env::temp_dir().join(format!("word_core_checkpoint_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = CheckpointConfig {
+            path: dir.join("search.json"),
+            interval_millis: 0,
+        };
+        let fresh = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            options.clone(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+
+        // Stand in for a run killed halfway through the root guesses
+        let (entered, context) = enter_root(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            &options,
+            None::<&NoPrinter>,
+        );
+        let Entered::Frame(mut root) = entered else {
+            panic!("root should need a search");
+        };
+        let mut checkpointer = Checkpointer {
+            config: config.clone(),
+            search_hash: search_hash(&hints, &root, 6, &options),
+            last_saved: Instant::now(),
+        };
+        let guess_order: Vec<u16> = root.guess_order.by_ref().collect();
+        let (first_half, second_half) = guess_order.split_at(guess_order.len() / 2);
+        root.guess_order = Vec::from(first_half).into_iter();
+        let transpositions = Some(BoundedCache::with_memory_budget(
+            options.transposition_bytes,
+        ));
+        drive_with(
+            &hints,
+            *root,
+            transpositions,
+            6,
+            &options,
+            &context,
+            Some(&mut checkpointer),
+        )
+        .unwrap();
+        let mut saved: SearchCheckpoint =
+            serde_json::from_str(&fs::read_to_string(&config.path).unwrap()).unwrap();
+        assert!(!saved.transpositions.is_empty());
+        saved.remaining_guesses = second_half.to_vec();
+        fs::write(&config.path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let resume = |max_depth, options: SearchOptions| {
+            compute_decision_tree_checkpointed(
+                &hints,
+                answers.clone(),
+                0,
                 max_depth,
-                child_max_est_cost,
-                printer,
-            ) {
-                let child_est_cost_scaled = child_tree_node.est_cost * hint_likelihood;
-                if (child_est_cost_scaled - child_est_cost_lower_bound).abs() > 1e-6 {
-                    guess.est_cost += child_est_cost_scaled - child_est_cost_lower_bound;
-                }
-                guess.next.insert(hint, child_tree_node);
-            } else {
-                if let Some(printer) = printer {
-                    println!(
-                        "{}guess {} cannot guarantee an answer within constraints",
-                        printer.get_prefix(),
-                        printer.fmt_guess(guess_ind),
-                    );
-                }
-                continue 'guess_loop;
-            }
-            if guess.est_cost >= guess_max_est_cost {
-                if let Some(printer) = printer {
-                    println!(
-                        "{}guess {} est cost of {:.3} already exceeds max of {:.3}",
-                        printer.get_prefix(),
-                        printer.fmt_guess(guess_ind),
-                        guess.est_cost,
-                        guess_max_est_cost,
-                    );
+                f64::INFINITY,
+                options,
+                &config,
+                None::<&NoPrinter>,
+            )
+        };
+        let resumed = resume(6, options.clone()).unwrap().unwrap().tree;
+        assert_eq!(resumed.should_guess, fresh.should_guess);
+        assert_eq!(resumed.total_guesses, fresh.total_guesses);
+        assert_eq!(resumed.alternatives.len(), 2);
+        let saved: SearchCheckpoint =
+            serde_json::from_str(&fs::read_to_string(&config.path).unwrap()).unwrap();
+        assert!(saved.remaining_guesses.is_empty());
+
+        // Another search's checkpoint is left alone
+        assert!(matches!(
+            resume(5, options.clone()),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+        assert!(matches!(
+            resume(
+                6,
+                SearchOptions {
+                    guess_ordering: Some(Arc::new(GuessHeuristic::Entropy)),
+                    ..options.clone()
                 }
-                continue 'guess_loop;
-            }
+            ),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+
+        // The worst case objective saves a checkpoint per depth limit beside the path
+        let worst_case_options = SearchOptions {
+            objective: Objective::WorstCase,
+            ..options
+        };
+        let shallowest = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            worst_case_options.clone(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        for _ in 0..2 {
+            let worst_case = resume(6, worst_case_options.clone()).unwrap().unwrap().tree;
+            assert_eq!(worst_case.worst_case(), shallowest.worst_case());
+            assert_eq!(worst_case.total_guesses, shallowest.total_guesses);
         }
+        assert!(
+            dir.join(format!("search.json.depth{}", shallowest.worst_case()))
+                .exists()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
-        // Evaluate if this guess beats the current best guess
-        let this_guess_is_new_best = match &best {
-            Some(best_guess) if best_guess.est_cost <= guess.est_cost => false,
-            _ => true,
+    #[test]
+    fn test_time_limit_returns_best_so_far() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |max_cost, options| {
+            compute_decision_tree_aggressive(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                max_cost,
+                options,
+                None::<&NoPrinter>,
+            )
         };
-        if let Some(printer) = printer {
-            println!(
-                "{}guess {} has est cost {} - {}",
-                printer.get_prefix(),
-                printer.fmt_guess(guess_ind),
-                guess.est_cost,
-                if this_guess_is_new_best {
-                    "new best"
-                } else {
-                    "rejecting"
-                }
-            );
+
+        let exact = solve(
+            f64::INFINITY,
+            SearchOptions {
+                time_limit_millis: Some(60_000),
+                ..SearchOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(!exact.approximate);
+
+        // With no time at all, the greedy tree is the best there is
+        for parallel_root in [false, true] {
+            let options = SearchOptions {
+                time_limit_millis: Some(0),
+                parallel_root,
+                ..SearchOptions::default()
+            };
+            let rushed = solve(f64::INFINITY, options.clone()).unwrap();
+            assert!(rushed.approximate);
+            assert_eq!(rushed.num_answers, words.len() as u32);
+            assert!(rushed.total_guesses >= exact.total_guesses);
+            // But not if it breaks the max cost
+            assert!(solve(rushed.est_cost(), options).is_none());
         }
-        if this_guess_is_new_best {
-            guess_max_est_cost = guess.est_cost;
-            best = Some(guess);
+    }
+
+    #[test]
+    fn test_cancel_bounds_best_so_far() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let solve = |options| {
+            compute_decision_tree_bounded(
+                &hints,
+                (0..words.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                options,
+                None::<&NoPrinter>,
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        let exact = solve(SearchOptions {
+            cancel: Some(CancelToken::new()),
+            ..SearchOptions::default()
+        });
+        assert_eq!(exact.lower_bound_total, exact.tree.total_guesses);
+        assert_eq!(exact.gap(), 0.0);
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        for parallel_root in [false, true] {
+            let stopped = solve(SearchOptions {
+                cancel: Some(cancel.clone()),
+                parallel_root,
+                ..SearchOptions::default()
+            });
+            assert!(stopped.tree.approximate);
+            // The best tree lies somewhere between the bound and what was found
+            assert!(stopped.lower_bound_total <= exact.tree.total_guesses);
+            assert!(stopped.tree.total_guesses >= exact.tree.total_guesses);
+            assert!(stopped.gap() > 0.0);
         }
     }
 
-    // Print the best guess and return
-    if let Some(printer) = printer {
-        match &best {
-            Some(tree_node) => println!(
-                "{}best guess is {} with est cost of {}",
-                printer.get_prefix(),
-                match tree_node.should_guess {
-                    GuessFrom::Guess(guess_ind) => printer.fmt_guess(guess_ind),
-                    GuessFrom::Answer(answer_ind) => printer.fmt_answer(answer_ind),
-                },
-                tree_node.est_cost
-            ),
-            None => println!(
-                "{}no guesses are guaranteed to solve within depth limit",
-                printer.get_prefix(),
-            ),
+    /// Records events as `(path length, event name)`, for checking what the search reports.
+    #[derive(Clone, Default)]
+    struct RecordingObserver {
+        path_len: usize,
+        events: std::sync::Arc<std::sync::Mutex<Vec<(usize, String)>>>,
+    }
+
+    impl SearchObserver for RecordingObserver {
+        fn observes_depth(&self, depth: u8) -> bool {
+            depth <= 1
+        }
+
+        fn descend(&self, _step: SearchStep) -> Self {
+            Self {
+                path_len: self.path_len + 1,
+                events: self.events.clone(),
+            }
+        }
+
+        fn on_event(&self, event: SearchEvent) {
+            let name = match event {
+                SearchEvent::NodeFinished {
+                    best: Some(tree_node),
+                    ..
+                } => {
+                    format!("finished {}", tree_node.total_guesses)
+                }
+                SearchEvent::GuessEvaluated { new_best: true, .. } => "new best".to_string(),
+                event => format!("{:?}", event)
+                    .split([' ', '(', '{'])
+                    .next()
+                    .unwrap()
+                    .to_string(),
+            };
+            self.events.lock().unwrap().push((self.path_len, name));
         }
     }
-    best
+
+    #[test]
+    fn test_observer_receives_events() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let observer = RecordingObserver::default();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            (0..words.len() as u16).collect(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            Some(&observer),
+        )
+        .unwrap();
+
+        let events = observer.events.lock().unwrap();
+        assert_eq!(events[0], (0, "NodeEntered".to_string()));
+        assert_eq!(events[1], (0, "GuessOrder".to_string()));
+        assert_eq!(
+            events.last().unwrap(),
+            &(0, format!("finished {}", tree.total_guesses))
+        );
+        // Guesses are reported a step down from their node, and their children two steps down
+        assert!(events.contains(&(1, "new best".to_string())));
+        assert!(events.contains(&(1, "ClueStarted".to_string())));
+        assert!(events.contains(&(2, "NodeEntered".to_string())));
+        // Nodes past the observed depth are searched silently
+        assert!(events.iter().all(|(path_len, _)| *path_len <= 3));
+    }
 }
@@ -32,11 +32,11 @@ mod tests {
         let results = dumb_search_words(
             &words
                 .iter()
-                .map(|word| Word::from_str(word))
+                .map(|word| Word::try_from(*word).unwrap())
                 .collect::<Vec<Word<5, 26>>>(),
-            Word::from_str("board"),
-            WordHint::from("√X~~√"),
+            Word::try_from("board").unwrap(),
+            WordHint::try_from("√X~~√").unwrap(),
         );
-        assert_eq!(results, vec![Word::from_str("bread")])
+        assert_eq!(results, vec![Word::try_from("bread").unwrap()])
     }
 }
@@ -0,0 +1,99 @@
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+/// Errors surfaced at the public API boundary of this crate.
+#[derive(Debug, Error)]
+pub enum WordGameError {
+    /// A word couldn't be parsed for the expected size and alphabet
+    #[error("invalid word {word:?}: {reason}")]
+    InvalidWord { word: String, reason: String },
+
+    /// A character isn't one of the recognized hint symbols
+    #[error("invalid char for hint: {0:?}")]
+    InvalidCharHint(char),
+
+    /// A hint string couldn't be parsed for the expected word size
+    #[error("invalid hint {hint:?}: {reason}")]
+    InvalidHint { hint: String, reason: String },
+
+    /// A word list or other file couldn't be read
+    #[error("failed to read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// A word size there's no compiled-in support for
+    #[error("unsupported word size {0}")]
+    UnsupportedWordSize(usize),
+
+    /// A guess that can get more distinct hints than the solvers can tell apart
+    #[error("{guess} can get {num_hints} distinct hints, more than fit in a byte")]
+    TooManyHints { guess: String, num_hints: usize },
+
+    /// A required command line argument wasn't supplied
+    #[error("missing argument: {0}")]
+    MissingArgument(&'static str),
+
+    /// Serialized data couldn't be parsed
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Serialized data was written in an unexpected format or version
+    #[error("unsupported format: expected {expected}, found {found}")]
+    FormatMismatch { expected: String, found: String },
+
+    /// A sequence of guesses and hints that no single answer could have produced
+    #[error("inconsistent history: {0}")]
+    InconsistentHistory(String),
+
+    /// The solver couldn't find a solution within its constraints
+    #[error("no solution found: {0}")]
+    Infeasible(String),
+
+    /// A decision tree has no branch for a hint that was actually received
+    #[error("tree has no branch for hint {hint} after guess {num_guesses}")]
+    IncompleteTree { num_guesses: usize, hint: String },
+
+    /// A solution certificate that doesn't hold up against the word lists
+    #[error("invalid certificate: {0}")]
+    InvalidCertificate(String),
+
+    /// A column was accessed past its end
+    #[error("column index {ind} out of range for length {len}")]
+    ColumnIndex { ind: usize, len: usize },
+
+    /// Two columns of different lengths were combined
+    #[error("column lengths differ: {left} != {right}")]
+    ColumnLengthMismatch { left: usize, right: usize },
+
+    /// A guess that the active ruleset doesn't allow
+    #[error("illegal guess {guess}: {reason}")]
+    IllegalGuess { guess: String, reason: String },
+
+    /// A guess that isn't in the allowed guess list
+    #[error("{guess} is not in the word list")]
+    NotInWordList { guess: String },
+
+    /// A guess was made after the game had already ended
+    #[error("game is already over")]
+    GameOver,
+
+    /// A thread pool with the configured number of threads couldn't be built
+    #[error("failed to build thread pool: {0}")]
+    ThreadPool(String),
+
+    /// A parallel worker panicked partway through its work
+    #[error("worker panicked: {0}")]
+    WorkerPanicked(String),
+
+    /// A combination of options that isn't supported
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+
+    /// Parallel work was cancelled before every item ran
+    #[error("cancelled")]
+    Cancelled,
+}
@@ -0,0 +1,367 @@
+use std::{
+    cmp::Ordering,
+    sync::atomic::{AtomicBool, Ordering as AtomicOrdering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bounded_cache::{BoundedCache, CacheStats, MemoryFootprint},
+    hint::WordHint,
+    hint_matrix::answers_first,
+    mask_encoding::{FNV_OFFSET_BASIS, fnv1a},
+    opening_book::OpeningBook,
+    suggestion::{PartitionStats, Suggestion, compare_suggestions, partition_stats},
+    word::Word,
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+/// How many guesses are scored between checks of the clock and the early-exit threshold
+const CHUNK_SIZE: usize = 256;
+
+/// Limits on how long the fast path may spend on a suggestion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastPathOptions {
+    /// Stop scoring guesses once this many milliseconds have passed and return the best so far
+    pub max_millis: u64,
+
+    /// Stop scoring guesses once one leaves at most this many candidates expected. The default
+    /// of 1 only stops at a guess that tells every candidate apart.
+    pub good_enough: f64,
+}
+
+impl Default for FastPathOptions {
+    fn default() -> Self {
+        Self {
+            max_millis: 5,
+            good_enough: 1.0,
+        }
+    }
+}
+
+/// Where a fast suggestion came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FastSource {
+    /// The position is in the opening book
+    Book,
+
+    /// The same candidates were scored by an earlier call
+    Cache,
+
+    /// Greedy scoring of the allowed guesses. `complete` is false if the latency budget ran out
+    /// before every guess was scored.
+    Greedy { complete: bool },
+}
+
+/// A next guess from the fast path, and how it was found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastSuggestion<const WORD_SIZE: usize> {
+    pub guess: Word<WORD_SIZE, 26>,
+    pub source: FastSource,
+
+    /// How the guess splits the candidates, absent for book guesses
+    pub stats: Option<PartitionStats>,
+}
+
+/// How far a greedy scan got through the guesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanEnd {
+    /// Every guess was scored
+    Finished,
+
+    /// A guess was good enough to stop early
+    GoodEnough,
+
+    /// The latency budget ran out
+    OutOfTime,
+}
+
+/// Answers "what should I guess next" within a latency budget, for interactive clients that
+/// can't wait on the exhaustive solver.
+///
+/// Positions in the opening book are answered from it. Anything else is scored greedily by
+/// expected remaining candidates, with the results of scans over every guess kept in a bounded
+/// cache keyed by a hash of the candidate set and the good enough threshold.
+pub struct FastSuggester<const WORD_SIZE: usize> {
+    allowed_guesses: Vec<Word<WORD_SIZE, 26>>,
+    book: Option<OpeningBook<WORD_SIZE>>,
+    cache: BoundedCache<u64, ScanEntry<WORD_SIZE>>,
+}
+
+/// A finished scan's best guess, keeping the exact candidates and threshold it was for so a
+/// lookup whose hash collides with a different scan's is treated as a miss.
+struct ScanEntry<const WORD_SIZE: usize> {
+    candidates: Vec<Word<WORD_SIZE, 26>>,
+    good_enough: f64,
+    best: Suggestion<WORD_SIZE, 26>,
+}
+
+impl<const WORD_SIZE: usize> ScanEntry<WORD_SIZE> {
+    fn matches(&self, candidates: &[Word<WORD_SIZE, 26>], good_enough: f64) -> bool {
+        self.candidates == candidates && self.good_enough.to_bits() == good_enough.to_bits()
+    }
+}
+
+impl<const WORD_SIZE: usize> MemoryFootprint for ScanEntry<WORD_SIZE> {
+    fn memory_footprint(&self) -> usize {
+        size_of::<Self>() + self.candidates.len() * size_of::<Word<WORD_SIZE, 26>>()
+    }
+}
+
+impl<const WORD_SIZE: usize> FastSuggester<WORD_SIZE> {
+    /// Create a suggester over the allowed guesses, caching at most `cache_bytes` of scores.
+    pub fn new(allowed_guesses: Vec<Word<WORD_SIZE, 26>>, cache_bytes: usize) -> Self {
+        Self {
+            allowed_guesses,
+            book: None,
+            cache: BoundedCache::with_memory_budget(cache_bytes),
+        }
+    }
+
+    /// Answer positions in the book from it rather than by scoring.
+    pub fn with_opening_book(mut self, book: OpeningBook<WORD_SIZE>) -> Self {
+        self.book = Some(book);
+        self
+    }
+
+    /// Get the hit rate and other counters of the cache of greedy results.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Suggest the next guess after the given history, where `candidates` are the answers still
+    /// consistent with it. Returns `None` if there are no candidates.
+    pub fn suggest(
+        &mut self,
+        history: &[(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)],
+        candidates: &[Word<WORD_SIZE, 26>],
+        options: FastPathOptions,
+    ) -> Option<FastSuggestion<WORD_SIZE>> {
+        if candidates.is_empty() {
+            return None;
+        }
+        if let Some(guess) = self.book_guess(history) {
+            return Some(FastSuggestion {
+                guess,
+                source: FastSource::Book,
+                stats: None,
+            });
+        }
+        let key = candidates.iter().fold(
+            fnv1a(FNV_OFFSET_BASIS, options.good_enough.to_le_bytes()),
+            |hash, word| fnv1a(hash, word.0),
+        );
+        if let Some(cached) = self
+            .cache
+            .get(&key)
+            .filter(|entry| entry.matches(candidates, options.good_enough))
+        {
+            return Some(FastSuggestion {
+                guess: cached.best.guess,
+                source: FastSource::Cache,
+                stats: Some(cached.best.stats),
+            });
+        }
+        let (best, end) = self.score_greedy(candidates, options);
+        // A scan that stopped early only found a guess good enough, not the best one
+        if end == ScanEnd::Finished {
+            self.cache.insert(
+                key,
+                ScanEntry {
+                    candidates: candidates.to_vec(),
+                    good_enough: options.good_enough,
+                    best,
+                },
+            );
+        }
+        Some(FastSuggestion {
+            guess: best.guess,
+            source: FastSource::Greedy {
+                complete: end != ScanEnd::OutOfTime,
+            },
+            stats: Some(best.stats),
+        })
+    }
+
+    /// The book's guess, if the history followed the book's guesses and is still inside it.
+    fn book_guess(
+        &self,
+        history: &[(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)],
+    ) -> Option<Word<WORD_SIZE, 26>> {
+        let book = self.book.as_ref()?;
+        let mut node = &book.root;
+        for (guess, hint) in history {
+            if *guess != node.guess || hint.all_correct() {
+                return None;
+            }
+            node = node.next.get(&hint.hint_id())?;
+        }
+        Some(node.guess)
+    }
+
+    /// Score guesses chunk by chunk, candidates first, until one is good enough or the budget
+    /// runs out. The budget is checked before scoring each guess, though the first candidate is
+    /// always scored so there's a guess to return. Returns the best guess seen and how the scan
+    /// ended.
+    fn score_greedy(
+        &self,
+        candidates: &[Word<WORD_SIZE, 26>],
+        options: FastPathOptions,
+    ) -> (Suggestion<WORD_SIZE, 26>, ScanEnd) {
+        let start = Instant::now();
+        let budget = Duration::from_millis(options.max_millis);
+        let out_of_time = AtomicBool::new(false);
+        let within_budget = || {
+            if start.elapsed() > budget {
+                out_of_time.store(true, AtomicOrdering::Relaxed);
+            }
+            !out_of_time.load(AtomicOrdering::Relaxed)
+        };
+        let score = |guess: &Word<WORD_SIZE, 26>, is_candidate| Suggestion {
+            guess: *guess,
+            is_candidate,
+            stats: partition_stats(guess, candidates),
+        };
+        let better = |a: Suggestion<WORD_SIZE, 26>, b: Suggestion<WORD_SIZE, 26>| {
+            if compare_suggestions(&b, &a, &[]) == Ordering::Less {
+                b
+            } else {
+                a
+            }
+        };
+
+        let pool = WorkerPool::new(PoolConfig::default());
+
+        // A candidate can win outright, so the candidates go first
+        let mut best = pool
+            .map_or_unwind(candidates, |ind, guess| {
+                (ind == 0 || within_budget()).then(|| score(guess, true))
+            })
+            .into_iter()
+            .flatten()
+            .reduce(better)
+            .unwrap();
+        let guesses = answers_first(candidates, &self.allowed_guesses);
+        let others = &guesses[candidates.len()..];
+        for chunk in others.chunks(CHUNK_SIZE) {
+            if best.stats.expected_remaining <= options.good_enough {
+                return (best, ScanEnd::GoodEnough);
+            }
+            if out_of_time.load(AtomicOrdering::Relaxed) {
+                return (best, ScanEnd::OutOfTime);
+            }
+            if let Some(chunk_best) = pool
+                .map_or_unwind(chunk, |_, guess| {
+                    within_budget().then(|| score(guess, false))
+                })
+                .into_iter()
+                .flatten()
+                .reduce(better)
+            {
+                best = better(best, chunk_best);
+            }
+        }
+        match out_of_time.load(AtomicOrdering::Relaxed) {
+            true => (best, ScanEnd::OutOfTime),
+            false => (best, ScanEnd::Finished),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decision_tree_general::{NoPrinter, SearchOptions, compute_decision_tree_aggressive},
+        hint_matrix::compute_hints,
+        readable_tree::ReadableTreeNode,
+        suggestion::rank_suggestions,
+        test_words::words,
+    };
+
+    #[test]
+    fn test_fast_path_sources() {
+        let answers = words(&["fight", "light", "might", "night", "sight", "tight"]);
+        let mut guesses = answers.clone();
+        guesses.extend(words(&["flint", "moist", "stomp"]));
+        let options = FastPathOptions {
+            max_millis: 1000,
+            good_enough: 0.0,
+        };
+
+        // Greedy scoring agrees with the full ranking, then serves repeats from the cache
+        let mut suggester = FastSuggester::new(guesses.clone(), 1 << 16);
+        let first = suggester.suggest(&[], &answers, options).unwrap();
+        assert_eq!(first.source, FastSource::Greedy { complete: true });
+        assert_eq!(
+            first.guess,
+            rank_suggestions(&guesses, &answers, 1)[0].guess
+        );
+        let again = suggester.suggest(&[], &answers, options).unwrap();
+        assert_eq!(again.source, FastSource::Cache);
+        assert_eq!(again.guess, first.guess);
+        assert_eq!(suggester.cache_stats().hits, 1);
+
+        // A scan that stops at a good enough guess isn't cached, nor served for another threshold
+        let loose = FastPathOptions {
+            good_enough: 10.0,
+            ..options
+        };
+        let mut suggester = FastSuggester::new(guesses.clone(), 1 << 16);
+        for _ in 0..2 {
+            let early = suggester.suggest(&[], &answers, loose).unwrap();
+            assert_eq!(early.source, FastSource::Greedy { complete: true });
+        }
+        suggester.suggest(&[], &answers, options).unwrap();
+        assert_eq!(
+            suggester.suggest(&[], &answers, options).unwrap().source,
+            FastSource::Cache
+        );
+        assert_eq!(
+            suggester.suggest(&[], &answers, loose).unwrap().source,
+            FastSource::Greedy { complete: true }
+        );
+
+        // No budget still gives a candidate, the first one, as it's always scored
+        let rushed = FastSuggester::new(guesses.clone(), 1 << 16)
+            .suggest(
+                &[],
+                &answers,
+                FastPathOptions {
+                    max_millis: 0,
+                    ..options
+                },
+            )
+            .unwrap();
+        assert_eq!(rushed.guess, answers[0]);
+        assert_eq!(rushed.source, FastSource::Greedy { complete: false });
+
+        // Book positions skip scoring entirely, until the player leaves the book
+        let hints = compute_hints(&guesses, &answers);
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            (0..answers.len() as u16).collect(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let tree = ReadableTreeNode::from_generalized_tree_node(&tree, &guesses, &answers);
+        let book = OpeningBook::from_tree(&tree, 1);
+        let mut suggester = FastSuggester::new(guesses.clone(), 1 << 16).with_opening_book(book);
+        let opener = suggester.suggest(&[], &answers, options).unwrap();
+        assert_eq!(opener.source, FastSource::Book);
+        assert_eq!(opener.guess, tree.should_guess);
+        let off_book = guesses
+            .iter()
+            .find(|guess| **guess != tree.should_guess)
+            .unwrap();
+        let hint = WordHint::from_guess_and_answer(off_book, &answers[0]);
+        let later = suggester
+            .suggest(&[(*off_book, hint)], &answers[..1], options)
+            .unwrap();
+        assert_eq!(later.source, FastSource::Greedy { complete: true });
+        assert_eq!(later.guess, answers[0]);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decision_tree_general::{Alternative, GuessFrom, TreeNode},
+    error::WordGameError,
+};
+
+/// A node of a `FlatTree`, pointing at its children and alternatives by index rather than
+/// owning them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatNode {
+    pub should_guess: GuessFrom,
+    pub total_guesses: u32,
+    pub num_answers: u32,
+    /// The hint leading to this node from its parent, or 0 at the root and at alternatives
+    pub hint: u8,
+    pub approximate: bool,
+    /// Where this node's children start in the tree's nodes. They're contiguous and in hint
+    /// order.
+    pub first_child: u32,
+    pub num_children: u16,
+    /// Where this node's alternatives start in the tree's alternatives, best first
+    pub first_alternative: u32,
+    pub num_alternatives: u16,
+}
+
+impl FlatNode {
+    /// Get the expected number of guesses to solve from this node, over its answers.
+    pub fn est_cost(&self) -> f64 {
+        self.total_guesses as f64 / self.num_answers as f64
+    }
+
+    fn without_links(tree_node: &TreeNode, hint: u8) -> Self {
+        Self {
+            should_guess: tree_node.should_guess,
+            total_guesses: tree_node.total_guesses,
+            num_answers: tree_node.num_answers,
+            hint,
+            approximate: tree_node.approximate,
+            first_child: 0,
+            num_children: 0,
+            first_alternative: 0,
+            num_alternatives: 0,
+        }
+    }
+}
+
+/// A runner-up guess of a `FlatTree`, by the index of its node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatAlternative {
+    pub node: u32,
+    /// Whether every hint was evaluated, as in `Alternative`
+    pub complete: bool,
+}
+
+/// A decision tree stored as two flat vectors, without the per-node maps and allocations of a
+/// `TreeNode`.
+///
+/// Nodes are laid out breadth first, so every node comes after its parent and its children sit
+/// side by side. The root is the first node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatTree {
+    pub nodes: Vec<FlatNode>,
+    pub alternatives: Vec<FlatAlternative>,
+}
+
+impl FlatTree {
+    /// Flatten a tree, alternatives included.
+    pub fn from_tree_node(tree_node: &TreeNode) -> Self {
+        let mut tree = Self {
+            nodes: vec![FlatNode::without_links(tree_node, 0)],
+            alternatives: Vec::new(),
+        };
+        let mut queue: VecDeque<(&TreeNode, usize)> = VecDeque::from([(tree_node, 0)]);
+        while let Some((tree_node, ind)) = queue.pop_front() {
+            let mut next: Vec<(&u8, &TreeNode)> = tree_node.next.iter().collect();
+            next.sort_unstable_by_key(|(hint, _)| **hint);
+            tree.nodes[ind].first_child = tree.nodes.len() as u32;
+            tree.nodes[ind].num_children = next.len() as u16;
+            for (hint, child) in next {
+                queue.push_back((child, tree.nodes.len()));
+                tree.nodes.push(FlatNode::without_links(child, *hint));
+            }
+
+            tree.nodes[ind].first_alternative = tree.alternatives.len() as u32;
+            tree.nodes[ind].num_alternatives = tree_node.alternatives.len() as u16;
+            for alternative in &tree_node.alternatives {
+                tree.alternatives.push(FlatAlternative {
+                    node: tree.nodes.len() as u32,
+                    complete: alternative.complete,
+                });
+                queue.push_back((&alternative.tree_node, tree.nodes.len()));
+                tree.nodes
+                    .push(FlatNode::without_links(&alternative.tree_node, 0));
+            }
+        }
+        tree
+    }
+
+    /// Rebuild the nested tree, failing if any index points outside the tree or back toward the
+    /// root.
+    pub fn to_tree_node(&self) -> Result<TreeNode, WordGameError> {
+        if self.nodes.is_empty() {
+            return Err(Self::malformed("an empty tree"));
+        }
+        self.build(0)
+    }
+
+    /// Get the root node, or `None` if the tree is empty.
+    pub fn root(&self) -> Option<&FlatNode> {
+        self.nodes.first()
+    }
+
+    /// Get a node's children, in hint order, or `None` if they run past the end of the tree.
+    pub fn children(&self, node: &FlatNode) -> Option<&[FlatNode]> {
+        let first = node.first_child as usize;
+        self.nodes.get(first..first + node.num_children as usize)
+    }
+
+    /// Get the child of a node under a hint, if it has one and its children are in the tree.
+    pub fn child(&self, node: &FlatNode, hint: u8) -> Option<&FlatNode> {
+        let children = self.children(node)?;
+        children
+            .binary_search_by_key(&hint, |child| child.hint)
+            .ok()
+            .map(|ind| &children[ind])
+    }
+
+    /// Get a node's alternatives with whether each was evaluated in full, best first, or `None`
+    /// if any of them point outside the tree.
+    pub fn alternatives(&self, node: &FlatNode) -> Option<Vec<(&FlatNode, bool)>> {
+        let first = node.first_alternative as usize;
+        self.alternatives
+            .get(first..first + node.num_alternatives as usize)?
+            .iter()
+            .map(|alternative| {
+                let alternative_node = self.nodes.get(alternative.node as usize)?;
+                Some((alternative_node, alternative.complete))
+            })
+            .collect()
+    }
+
+    fn build(&self, ind: usize) -> Result<TreeNode, WordGameError> {
+        let node = &self.nodes[ind];
+        let children = self.linked_nodes(
+            ind,
+            node.first_child as usize,
+            node.num_children as usize,
+            Some,
+        )?;
+        let alternatives = self.linked_nodes(
+            ind,
+            node.first_alternative as usize,
+            node.num_alternatives as usize,
+            |alternative_ind| {
+                self.alternatives
+                    .get(alternative_ind)
+                    .map(|alternative| alternative.node as usize)
+            },
+        )?;
+        Ok(TreeNode {
+            should_guess: node.should_guess,
+            total_guesses: node.total_guesses,
+            num_answers: node.num_answers,
+            next: children
+                .into_iter()
+                .map(|child_ind| Ok((self.nodes[child_ind].hint, self.build(child_ind)?)))
+                .collect::<Result<_, WordGameError>>()?,
+            approximate: node.approximate,
+            alternatives: alternatives
+                .into_iter()
+                .zip(node.first_alternative as usize..)
+                .map(|(alternative_ind, link)| {
+                    Ok(Alternative {
+                        tree_node: self.build(alternative_ind)?,
+                        complete: self.alternatives[link].complete,
+                    })
+                })
+                .collect::<Result<_, WordGameError>>()?,
+        })
+    }
+
+    /// Resolve a range of links from a node to node indices, checking each lands after it.
+    fn linked_nodes(
+        &self,
+        ind: usize,
+        first: usize,
+        len: usize,
+        resolve: impl Fn(usize) -> Option<usize>,
+    ) -> Result<Vec<usize>, WordGameError> {
+        (first..first + len)
+            .map(|link| match resolve(link) {
+                Some(linked) if linked > ind && linked < self.nodes.len() => Ok(linked),
+                _ => Err(Self::malformed(&format!("a bad link from node {}", ind))),
+            })
+            .collect()
+    }
+
+    fn malformed(found: &str) -> WordGameError {
+        WordGameError::FormatMismatch {
+            expected: "a flat tree with every link pointing further down it".to_string(),
+            found: found.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        decision_tree_general::{NoPrinter, SearchOptions, compute_decision_tree_aggressive},
+        hint_matrix::compute_hints,
+        test_words,
+    };
+
+    #[test]
+    fn test_flat_tree_round_trip() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            answers,
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions {
+                num_alternatives: 2,
+                ..SearchOptions::default()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+
+        let flat = FlatTree::from_tree_node(&tree);
+        let root = flat.root().unwrap();
+        assert_eq!(root.total_guesses, tree.total_guesses);
+        assert_eq!(
+            flat.alternatives(root).unwrap().len(),
+            tree.alternatives.len()
+        );
+        // Following any answer's hints through the flat tree lands on a node that guesses it
+        for answer_ind in 0..words.len() {
+            let mut node = root;
+            loop {
+                let guess_ind = match node.should_guess {
+                    GuessFrom::Guess(ind) | GuessFrom::Answer(ind) => ind as usize,
+                };
+                let hint = hints[guess_ind][answer_ind];
+                if hint == 0 {
+                    break;
+                }
+                node = flat.child(node, hint).unwrap();
+            }
+        }
+
+        // Nothing is lost either way
+        let rebuilt = flat.to_tree_node().unwrap();
+        assert_eq!(
+            serde_json::to_string(&rebuilt).unwrap(),
+            serde_json::to_string(&tree).unwrap()
+        );
+        let raw = serde_json::to_string(&flat).unwrap();
+        assert_eq!(serde_json::from_str::<FlatTree>(&raw).unwrap(), flat);
+
+        // A link back toward the root would loop forever, so it's rejected
+        let mut looped = flat.clone();
+        let last = looped.nodes.len() - 1;
+        looped.nodes[last].first_child = 0;
+        looped.nodes[last].num_children = 1;
+        assert!(matches!(
+            looped.to_tree_node(),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+
+        // Links past the end of a deserialized tree are reported rather than followed
+        let mut truncated = flat.clone();
+        truncated.nodes.truncate(1);
+        truncated.alternatives.clear();
+        let truncated: FlatTree =
+            serde_json::from_str(&serde_json::to_string(&truncated).unwrap()).unwrap();
+        let root = truncated.root().unwrap();
+        assert_eq!(truncated.children(root), None);
+        assert_eq!(truncated.child(root, 1), None);
+        assert_eq!(truncated.alternatives(root), None);
+        assert!(truncated.to_tree_node().is_err());
+        let empty = FlatTree {
+            nodes: Vec::new(),
+            alternatives: Vec::new(),
+        };
+        assert_eq!(empty.root(), None);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+
+use crate::{error::WordGameError, hint::WordHint, ruleset::Ruleset, word::Word};
+
+/// Where a game stands after its most recent guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    InProgress,
+    Won,
+    Lost,
+}
+
+/// What to do with a guess that isn't in the allowed guess list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutOfListPolicy {
+    /// Refuse the guess, as the official game does
+    #[default]
+    Reject,
+
+    /// Score the guess as normal, but flag it in the history
+    Accept,
+}
+
+/// A single game played under some ruleset, tracking every answer still consistent with it.
+#[derive(Debug, Clone)]
+pub struct Game<const WORD_SIZE: usize, const ALPHABET_SIZE: u8, R> {
+    ruleset: R,
+    answer: Word<WORD_SIZE, ALPHABET_SIZE>,
+    candidates: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    history: Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)>,
+    allowed_guesses: Option<HashSet<Word<WORD_SIZE, ALPHABET_SIZE>>>,
+    out_of_list_policy: OutOfListPolicy,
+    out_of_list_rows: Vec<usize>,
+    status: GameStatus,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8, R: Ruleset<WORD_SIZE, ALPHABET_SIZE>>
+    Game<WORD_SIZE, ALPHABET_SIZE, R>
+{
+    /// Start a game with the given answer, out of the given possible answers.
+    ///
+    /// Adversarial rulesets treat the answer as a placeholder and may move it to any other
+    /// candidate as the game goes on.
+    pub fn new(
+        ruleset: R,
+        answer: Word<WORD_SIZE, ALPHABET_SIZE>,
+        possible_answers: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ) -> Self {
+        Self {
+            ruleset,
+            answer,
+            candidates: possible_answers,
+            history: vec![],
+            allowed_guesses: None,
+            out_of_list_policy: OutOfListPolicy::default(),
+            out_of_list_rows: vec![],
+            status: GameStatus::InProgress,
+        }
+    }
+
+    /// Check guesses against an allowed list, handling any others according to the policy.
+    ///
+    /// Without a list every guess is considered allowed.
+    pub fn with_allowed_guesses(
+        mut self,
+        allowed_guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        policy: OutOfListPolicy,
+    ) -> Self {
+        self.allowed_guesses = Some(allowed_guesses.into_iter().collect());
+        self.out_of_list_policy = policy;
+        self
+    }
+
+    /// Whether a guess is in the allowed list, or there's no list to check against.
+    pub fn is_allowed_guess(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> bool {
+        self.allowed_guesses
+            .as_ref()
+            .is_none_or(|allowed_guesses| allowed_guesses.contains(guess))
+    }
+
+    /// Play a guess, returning the hint it receives.
+    pub fn guess(
+        &mut self,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> Result<WordHint<WORD_SIZE>, WordGameError> {
+        if self.status != GameStatus::InProgress {
+            return Err(WordGameError::GameOver);
+        }
+        let out_of_list = !self.is_allowed_guess(&guess);
+        if out_of_list && self.out_of_list_policy == OutOfListPolicy::Reject {
+            return Err(WordGameError::NotInWordList {
+                guess: format!("{:?}", guess.0),
+            });
+        }
+        if !self.ruleset.is_legal_guess(&guess, &self.history) {
+            return Err(WordGameError::IllegalGuess {
+                guess: format!("{:?}", guess.0),
+                reason: format!("not allowed by {} rules", self.ruleset.name()),
+            });
+        }
+
+        let hint = self.ruleset.respond(&guess, &self.answer, &self.candidates);
+        self.candidates
+            .retain(|candidate| self.ruleset.hint(&guess, candidate) == hint);
+        if self.ruleset.hint(&guess, &self.answer) != hint
+            && let Some(candidate) = self.candidates.first()
+        {
+            self.answer = *candidate;
+        }
+        if out_of_list {
+            self.out_of_list_rows.push(self.history.len());
+        }
+        self.history.push((guess, hint));
+
+        self.status = if self.ruleset.is_win(&hint) {
+            GameStatus::Won
+        } else if self
+            .ruleset
+            .max_guesses()
+            .is_some_and(|max_guesses| self.history.len() >= max_guesses)
+        {
+            GameStatus::Lost
+        } else {
+            GameStatus::InProgress
+        };
+        Ok(hint)
+    }
+
+    /// The ruleset this game is played under.
+    pub fn ruleset(&self) -> &R {
+        &self.ruleset
+    }
+
+    /// The current answer. Only fixed for non-adversarial rulesets.
+    pub fn answer(&self) -> &Word<WORD_SIZE, ALPHABET_SIZE> {
+        &self.answer
+    }
+
+    /// Every possible answer still consistent with the hints so far.
+    pub fn candidates(&self) -> &[Word<WORD_SIZE, ALPHABET_SIZE>] {
+        &self.candidates
+    }
+
+    /// Every guess made so far, with its hint.
+    pub fn history(&self) -> &[(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)] {
+        &self.history
+    }
+
+    /// The rows of the history whose guess was accepted despite not being in the allowed list.
+    pub fn out_of_list_rows(&self) -> &[usize] {
+        &self.out_of_list_rows
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ruleset::{Absurdle, HardMode, Standard};
+    use crate::test_words::words;
+
+    #[test]
+    fn test_standard_game() {
+        let possible_answers = words(&["fight", "light", "board"]);
+        let mut game = Game::new(Standard, possible_answers[1], possible_answers.clone());
+        let guesses = words(&["fight", "light"]);
+        assert_eq!(
+            game.guess(guesses[0]).unwrap(),
+            WordHint::try_from("X√√√√").unwrap()
+        );
+        assert_eq!(game.candidates(), &possible_answers[1..2]);
+        assert_eq!(game.status(), GameStatus::InProgress);
+        assert!(game.guess(guesses[1]).unwrap().all_correct());
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(matches!(
+            game.guess(guesses[1]),
+            Err(WordGameError::GameOver)
+        ));
+    }
+
+    #[test]
+    fn test_hard_mode_rejects_illegal_guess() {
+        let possible_answers = words(&["fight", "light", "board"]);
+        let mut game = Game::new(HardMode, possible_answers[1], possible_answers.clone());
+        game.guess(possible_answers[0]).unwrap();
+        assert!(matches!(
+            game.guess(possible_answers[2]),
+            Err(WordGameError::IllegalGuess { .. })
+        ));
+        assert_eq!(game.history().len(), 1);
+    }
+
+    #[test]
+    fn test_out_of_list_guesses() {
+        let possible_answers = words(&["fight", "light", "board"]);
+        let mut game = Game::new(Standard, possible_answers[1], possible_answers.clone())
+            .with_allowed_guesses(possible_answers.clone(), OutOfListPolicy::Reject);
+        assert!(matches!(
+            game.guess(words(&["xyzzy"])[0]),
+            Err(WordGameError::NotInWordList { .. })
+        ));
+        assert!(game.history().is_empty());
+
+        let mut game = Game::new(Standard, possible_answers[1], possible_answers.clone())
+            .with_allowed_guesses(possible_answers.clone(), OutOfListPolicy::Accept);
+        assert_eq!(
+            game.guess(words(&["tight"])[0]).unwrap(),
+            WordHint::try_from("X√√√√").unwrap()
+        );
+        game.guess(possible_answers[0]).unwrap();
+        assert_eq!(game.out_of_list_rows(), &[0]);
+        assert_eq!(game.candidates(), &possible_answers[1..2]);
+    }
+
+    #[test]
+    fn test_absurdle_moves_answer() {
+        let possible_answers = words(&["fight", "light", "might", "board"]);
+        let mut game = Game::new(Absurdle, possible_answers[3], possible_answers.clone());
+        game.guess(words(&["crown"])[0]).unwrap();
+        assert_eq!(game.candidates(), &possible_answers[..3]);
+        assert!(game.candidates().contains(game.answer()));
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::WordGameError,
+    loss_analysis::{TurnAnalysis, explain_loss},
+    readable_tree::ReadableTreeNode,
+    transcript::Transcript,
+    word::Word,
+};
+
+/// How good a move was, judged by how many more guesses it's expected to cost than the best
+/// move from the same position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveGrade {
+    Best,
+    Excellent,
+    Good,
+    Inaccuracy,
+    Blunder,
+}
+
+impl MoveGrade {
+    /// The chess-style annotation for the grade, empty for unremarkable moves.
+    pub fn annotation(&self) -> &'static str {
+        match self {
+            Self::Best => "!!",
+            Self::Excellent => "!",
+            Self::Good => "",
+            Self::Inaccuracy => "?!",
+            Self::Blunder => "??",
+        }
+    }
+}
+
+impl fmt::Display for MoveGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Best => "best",
+            Self::Excellent => "excellent",
+            Self::Good => "good",
+            Self::Inaccuracy => "inaccuracy",
+            Self::Blunder => "blunder",
+        })
+    }
+}
+
+/// The largest expected cost delta, in guesses, that still earns each grade. Anything above
+/// `inaccuracy` is a blunder, as is giving up a guaranteed win.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GradeScale {
+    pub best: f64,
+    pub excellent: f64,
+    pub good: f64,
+    pub inaccuracy: f64,
+}
+
+impl Default for GradeScale {
+    /// A blunder is a move expected to cost more than a quarter of a guess over the best one.
+    fn default() -> Self {
+        Self {
+            best: 1e-9,
+            excellent: 0.02,
+            good: 0.08,
+            inaccuracy: 0.25,
+        }
+    }
+}
+
+impl GradeScale {
+    /// Grade a move by how much more it's expected to cost than the best move.
+    pub fn grade(&self, cost_delta: f64) -> MoveGrade {
+        if cost_delta <= self.best {
+            MoveGrade::Best
+        } else if cost_delta <= self.excellent {
+            MoveGrade::Excellent
+        } else if cost_delta <= self.good {
+            MoveGrade::Good
+        } else if cost_delta <= self.inaccuracy {
+            MoveGrade::Inaccuracy
+        } else {
+            MoveGrade::Blunder
+        }
+    }
+
+    /// Grade an analyzed turn, or `None` if no win could be guaranteed from its position, so
+    /// there's no best move to compare it to.
+    pub fn grade_turn<const WORD_SIZE: usize>(
+        &self,
+        turn: &TurnAnalysis<WORD_SIZE>,
+    ) -> Option<MoveGrade> {
+        turn.cost_delta().map(|cost_delta| self.grade(cost_delta))
+    }
+}
+
+/// One move of a transcript with its grade.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GradedMove<const WORD_SIZE: usize> {
+    pub analysis: TurnAnalysis<WORD_SIZE>,
+
+    /// The grade, or `None` if no win could be guaranteed from this position
+    pub grade: Option<MoveGrade>,
+}
+
+/// Grade every move of a finished game against the best play from each position.
+///
+/// The raw cost deltas come from [`explain_loss`], which takes the same arguments.
+pub fn grade_transcript<const WORD_SIZE: usize>(
+    transcript: &Transcript<WORD_SIZE>,
+    answer: Word<WORD_SIZE, 26>,
+    allowed_guesses: &[Word<WORD_SIZE, 26>],
+    possible_answers: &[Word<WORD_SIZE, 26>],
+    max_guesses: u8,
+    tree: Option<&ReadableTreeNode<WORD_SIZE>>,
+    scale: &GradeScale,
+) -> Result<Vec<GradedMove<WORD_SIZE>>, WordGameError> {
+    let analysis = explain_loss(
+        transcript,
+        answer,
+        allowed_guesses,
+        possible_answers,
+        max_guesses,
+        tree,
+    )?;
+    Ok(analysis
+        .turns
+        .into_iter()
+        .map(|turn| GradedMove {
+            grade: scale.grade_turn(&turn),
+            analysis: turn,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hint::WordHint,
+        test_words::{self, words},
+    };
+
+    #[test]
+    fn test_grade_scale() {
+        let scale = GradeScale::default();
+        // Each bound still earns its own grade, and anything past it earns the next
+        assert_eq!(scale.grade(0.0), MoveGrade::Best);
+        assert_eq!(scale.grade(scale.best), MoveGrade::Best);
+        assert_eq!(scale.grade(scale.best * 2.0), MoveGrade::Excellent);
+        assert_eq!(scale.grade(scale.excellent), MoveGrade::Excellent);
+        assert_eq!(scale.grade(scale.excellent + 1e-6), MoveGrade::Good);
+        assert_eq!(scale.grade(scale.good), MoveGrade::Good);
+        assert_eq!(scale.grade(scale.good + 1e-6), MoveGrade::Inaccuracy);
+        assert_eq!(scale.grade(scale.inaccuracy), MoveGrade::Inaccuracy);
+        assert_eq!(scale.grade(scale.inaccuracy + 1e-6), MoveGrade::Blunder);
+        assert_eq!(scale.grade(f64::INFINITY), MoveGrade::Blunder);
+        assert!(MoveGrade::Best < MoveGrade::Blunder);
+        assert_eq!(MoveGrade::Inaccuracy.annotation(), "?!");
+    }
+
+    #[test]
+    fn test_grade_transcript() {
+        let allowed_guesses = test_words::small_list();
+        // board, bread, broad, beard, bored
+        let possible_answers = allowed_guesses[7..12].to_vec();
+        let answer = possible_answers[4];
+        // Broad is as good an opener as any, but its hint already rules out board
+        let pairs: Vec<(Word<5, 26>, WordHint<5>)> = words(&["broad", "board", "bored"])
+            .iter()
+            .map(|guess| (*guess, WordHint::from_guess_and_answer(guess, &answer)))
+            .collect();
+        let transcript = Transcript::from_pairs("standard", None, &pairs);
+        let grade = |scale: &GradeScale| {
+            grade_transcript(
+                &transcript,
+                answer,
+                &allowed_guesses,
+                &possible_answers,
+                6,
+                None,
+                scale,
+            )
+            .unwrap()
+        };
+
+        let graded = grade(&GradeScale::default());
+        assert_eq!(graded[1].analysis.cost_delta(), Some(1.0));
+        let grades: Vec<Option<MoveGrade>> = graded.iter().map(|turn| turn.grade).collect();
+        assert_eq!(
+            grades,
+            vec![
+                Some(MoveGrade::Best),
+                Some(MoveGrade::Blunder),
+                Some(MoveGrade::Best)
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(graded[1].grade).unwrap(),
+            serde_json::json!("blunder")
+        );
+
+        // A whole wasted guess is only an inaccuracy on a scale that allows for it
+        let lenient = GradeScale {
+            inaccuracy: 1.0,
+            ..GradeScale::default()
+        };
+        assert_eq!(grade(&lenient)[1].grade, Some(MoveGrade::Inaccuracy));
+    }
+}
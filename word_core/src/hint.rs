@@ -1,8 +1,17 @@
-use std::{cmp::min, collections::HashMap, fmt::Display};
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
 
 use serde::{Deserialize, Serialize, Serializer, de::Visitor};
 
-use crate::word::Word;
+use crate::{
+    error::WordGameError,
+    render::{RenderMode, render_mode},
+    word::Word,
+};
 
 /// A hint for a single character.
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -18,23 +27,60 @@ pub enum CharHint {
     Nowhere,
 }
 
+impl CharHint {
+    /// The symbol for this hint in the given render mode, which always parses back.
+    pub fn symbol(&self, mode: RenderMode) -> char {
+        match (self, mode) {
+            (CharHint::Correct, RenderMode::Unicode) => '√',
+            (CharHint::Correct, RenderMode::Ascii) => '+',
+            (CharHint::Elsewhere, _) => '~',
+            (CharHint::Nowhere, _) => 'X',
+        }
+    }
+}
+
 impl Display for CharHint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CharHint::Correct => write!(f, "√"),
-            CharHint::Elsewhere => write!(f, "~"),
-            CharHint::Nowhere => write!(f, "X"),
-        }
+        write!(f, "{}", self.symbol(render_mode()))
     }
 }
 
-impl From<char> for CharHint {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for CharHint {
+    type Error = WordGameError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            '√' => Self::Correct,
-            '~' => Self::Elsewhere,
-            'X' | 'x' => Self::Nowhere,
-            _ => panic!("Invalid char for CharHint: {}", value),
+            '√' | '+' => Ok(Self::Correct),
+            '~' => Ok(Self::Elsewhere),
+            'X' | 'x' => Ok(Self::Nowhere),
+            _ => Err(WordGameError::InvalidCharHint(value)),
+        }
+    }
+}
+
+/// How a guess containing repeated letters is scored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DuplicateScheme {
+    /// Each copy of a letter in the answer marks at most one copy in the guess, correct
+    /// positions first, as in Wordle
+    #[default]
+    Standard,
+
+    /// Every misplaced copy of a letter in the guess is marked Elsewhere if the answer contains
+    /// that letter at all, regardless of how many copies it has
+    Lenient,
+
+    /// Like standard, but only the first misplaced copy of each letter can be marked Elsewhere,
+    /// hiding whether the answer has any more copies
+    Strict,
+}
+
+impl DuplicateScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Lenient => "lenient-duplicates",
+            Self::Strict => "strict-duplicates",
         }
     }
 }
@@ -58,12 +104,12 @@ impl<const WORD_SIZE: usize> WordHint<WORD_SIZE> {
         // For every character in the guess that was missed, which inds contain it
         let mut incorrect_guess_char_inds: HashMap<u8, Vec<usize>> = HashMap::new();
 
-        for ind in 0..WORD_SIZE {
+        for (ind, char_hint) in char_hints.iter_mut().enumerate() {
             let answer_char = answer.0[ind];
             let guess_char = guess.0[ind];
 
             if answer_char == guess_char {
-                char_hints[ind] = CharHint::Correct
+                *char_hint = CharHint::Correct
             } else {
                 *missed_answer_char_counts.entry(answer_char).or_insert(0) += 1;
                 incorrect_guess_char_inds
@@ -86,6 +132,51 @@ impl<const WORD_SIZE: usize> WordHint<WORD_SIZE> {
         Self(char_hints)
     }
 
+    /// Determine what hints should be shown for a given guess and answer, scoring repeated
+    /// letters by the given scheme
+    pub fn from_guess_and_answer_with_scheme<const ALPHABET_SIZE: u8>(
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        scheme: DuplicateScheme,
+    ) -> Self {
+        let mut char_hints = [CharHint::Nowhere; WORD_SIZE];
+        match scheme {
+            DuplicateScheme::Standard => return Self::from_guess_and_answer(guess, answer),
+            DuplicateScheme::Lenient => {
+                for (ind, char_hint) in char_hints.iter_mut().enumerate() {
+                    if guess.0[ind] == answer.0[ind] {
+                        *char_hint = CharHint::Correct;
+                    } else if answer.0.contains(&guess.0[ind]) {
+                        *char_hint = CharHint::Elsewhere;
+                    }
+                }
+            }
+            DuplicateScheme::Strict => {
+                let mut missed_answer_char_counts: HashMap<u8, usize> = HashMap::new();
+                for (ind, char_hint) in char_hints.iter_mut().enumerate() {
+                    if guess.0[ind] == answer.0[ind] {
+                        *char_hint = CharHint::Correct;
+                    } else {
+                        *missed_answer_char_counts.entry(answer.0[ind]).or_insert(0) += 1;
+                    }
+                }
+
+                // Only the first missed copy of each guess character can be marked
+                let mut seen_missed_chars: HashSet<u8> = HashSet::new();
+                for (ind, char_hint) in char_hints.iter_mut().enumerate() {
+                    let guess_char = guess.0[ind];
+                    if *char_hint == CharHint::Correct || !seen_missed_chars.insert(guess_char) {
+                        continue;
+                    }
+                    if missed_answer_char_counts.contains_key(&guess_char) {
+                        *char_hint = CharHint::Elsewhere;
+                    }
+                }
+            }
+        }
+        Self(char_hints)
+    }
+
     /// Get all possible hints for this word size
     pub fn all_possible() -> Vec<Self> {
         (0..3usize.pow(WORD_SIZE as u32))
@@ -98,6 +189,19 @@ impl<const WORD_SIZE: usize> WordHint<WORD_SIZE> {
         &self,
         guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
     ) -> String {
+        self.color_guess_with_mode(guess, render_mode())
+    }
+
+    /// Color a guess word based on this hint, or in ASCII mode write the hint symbols after it
+    pub fn color_guess_with_mode<const ALPHABET_SIZE: u8>(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        mode: RenderMode,
+    ) -> String {
+        if mode == RenderMode::Ascii {
+            let letters: String = guess.0.iter().map(|chr| (b'A' + chr) as char).collect();
+            return format!("{} {}", letters, self.render(mode));
+        }
         let mut out: Vec<String> = vec![];
         for ind in 0..WORD_SIZE {
             match self.0[ind] {
@@ -113,7 +217,7 @@ impl<const WORD_SIZE: usize> WordHint<WORD_SIZE> {
 
     /// Is this hint all correct
     pub fn all_correct(&self) -> bool {
-        return self.0 == [CharHint::Correct; WORD_SIZE];
+        self.0 == [CharHint::Correct; WORD_SIZE]
     }
 
     /// Get the constant id for this hint (little-endian).
@@ -154,22 +258,43 @@ impl<const WORD_SIZE: usize> Default for WordHint<WORD_SIZE> {
     }
 }
 
+impl<const WORD_SIZE: usize> WordHint<WORD_SIZE> {
+    /// Write out the symbol of each character hint in the given render mode.
+    pub fn render(&self, mode: RenderMode) -> String {
+        self.0.iter().map(|hint| hint.symbol(mode)).collect()
+    }
+}
+
 impl<const WORD_SIZE: usize> Display for WordHint<WORD_SIZE> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for hint in self.0 {
-            hint.fmt(f)?
-        }
-        Ok(())
+        write!(f, "{}", self.render(render_mode()))
     }
 }
 
-impl<const WORD_SIZE: usize> From<&str> for WordHint<WORD_SIZE> {
-    fn from(value: &str) -> Self {
+impl<const WORD_SIZE: usize> TryFrom<&str> for WordHint<WORD_SIZE> {
+    type Error = WordGameError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let num_chars = value.chars().count();
+        if num_chars != WORD_SIZE {
+            return Err(WordGameError::InvalidHint {
+                hint: value.to_string(),
+                reason: format!("expected {} chars, found {}", WORD_SIZE, num_chars),
+            });
+        }
         let mut char_hints = [CharHint::Correct; WORD_SIZE];
         for (ind, char_value) in value.chars().enumerate() {
-            char_hints[ind] = CharHint::from(char_value)
+            char_hints[ind] = CharHint::try_from(char_value)?;
         }
-        Self(char_hints)
+        Ok(Self(char_hints))
+    }
+}
+
+impl<const WORD_SIZE: usize> FromStr for WordHint<WORD_SIZE> {
+    type Err = WordGameError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::try_from(value)
     }
 }
 
@@ -178,7 +303,8 @@ impl<const WORD_SIZE: usize> Serialize for WordHint<WORD_SIZE> {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&format!("{}", self))
+        // Stored hints keep the same symbols whatever the terminal is rendering
+        serializer.serialize_str(&self.render(RenderMode::Unicode))
     }
 }
 
@@ -195,7 +321,7 @@ impl<'de, const WORD_SIZE: usize> Visitor<'de> for WordHintVisitor<WORD_SIZE> {
     where
         E: serde::de::Error,
     {
-        Ok(WordHint::from(v))
+        WordHint::try_from(v).map_err(E::custom)
     }
 }
 
@@ -213,9 +339,12 @@ mod tests {
     use super::*;
 
     fn assert_word_hint<const WORD_SIZE: usize>(answer: &str, guess: &str, word_hint: &str) {
-        let word_hint: WordHint<WORD_SIZE> = WordHint::from(word_hint);
+        let word_hint: WordHint<WORD_SIZE> = WordHint::try_from(word_hint).unwrap();
         assert_eq!(
-            WordHint::from_guess_and_answer::<26>(&Word::from_str(guess), &Word::from_str(answer)),
+            WordHint::from_guess_and_answer::<26>(
+                &Word::try_from(guess).unwrap(),
+                &Word::try_from(answer).unwrap()
+            ),
             word_hint,
         )
     }
@@ -250,14 +379,47 @@ mod tests {
         assert_word_hint::<5>("aabab", "bbbcc", "~X√XX");
     }
 
+    fn assert_word_hint_with_scheme(
+        scheme: DuplicateScheme,
+        answer: &str,
+        guess: &str,
+        word_hint: &str,
+    ) {
+        assert_eq!(
+            WordHint::<5>::from_guess_and_answer_with_scheme::<26>(
+                &Word::try_from(guess).unwrap(),
+                &Word::try_from(answer).unwrap(),
+                scheme,
+            ),
+            WordHint::try_from(word_hint).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_duplicate_schemes() {
+        assert_word_hint_with_scheme(DuplicateScheme::Standard, "ababa", "ccbbc", "XX~√X");
+        assert_word_hint_with_scheme(DuplicateScheme::Lenient, "ababa", "ccbbc", "XX~√X");
+        assert_word_hint_with_scheme(DuplicateScheme::Strict, "ababa", "ccbbc", "XX~√X");
+
+        // The answer has one spare B, shown twice by lenient but once by the others
+        assert_word_hint_with_scheme(DuplicateScheme::Standard, "abcba", "bbbxx", "~√XXX");
+        assert_word_hint_with_scheme(DuplicateScheme::Lenient, "abcba", "bbbxx", "~√~XX");
+        assert_word_hint_with_scheme(DuplicateScheme::Strict, "abcba", "bbbxx", "~√XXX");
+
+        // The answer has spare As and Zs, but strict only reveals one of each
+        assert_word_hint_with_scheme(DuplicateScheme::Standard, "aazzz", "zzaab", "~~~~X");
+        assert_word_hint_with_scheme(DuplicateScheme::Lenient, "aazzz", "zzaab", "~~~~X");
+        assert_word_hint_with_scheme(DuplicateScheme::Strict, "aazzz", "zzaab", "~X~XX");
+    }
+
     #[test]
     fn test_all_hints_1() {
         assert_eq!(
             WordHint::<1>::all_possible(),
             vec![
-                WordHint::from("√"),
-                WordHint::from("~"),
-                WordHint::from("X")
+                WordHint::try_from("√").unwrap(),
+                WordHint::try_from("~").unwrap(),
+                WordHint::try_from("X").unwrap()
             ]
         );
     }
@@ -267,15 +429,15 @@ mod tests {
         assert_eq!(
             WordHint::<2>::all_possible(),
             vec![
-                WordHint::from("√√"),
-                WordHint::from("√~"),
-                WordHint::from("√X"),
-                WordHint::from("~√"),
-                WordHint::from("~~"),
-                WordHint::from("~X"),
-                WordHint::from("X√"),
-                WordHint::from("X~"),
-                WordHint::from("XX"),
+                WordHint::try_from("√√").unwrap(),
+                WordHint::try_from("√~").unwrap(),
+                WordHint::try_from("√X").unwrap(),
+                WordHint::try_from("~√").unwrap(),
+                WordHint::try_from("~~").unwrap(),
+                WordHint::try_from("~X").unwrap(),
+                WordHint::try_from("X√").unwrap(),
+                WordHint::try_from("X~").unwrap(),
+                WordHint::try_from("XX").unwrap(),
             ]
         )
     }
@@ -285,33 +447,33 @@ mod tests {
         assert_eq!(
             WordHint::<3>::all_possible(),
             vec![
-                WordHint::from("√√√"),
-                WordHint::from("√√~"),
-                WordHint::from("√√X"),
-                WordHint::from("√~√"),
-                WordHint::from("√~~"),
-                WordHint::from("√~X"),
-                WordHint::from("√X√"),
-                WordHint::from("√X~"),
-                WordHint::from("√XX"),
-                WordHint::from("~√√"),
-                WordHint::from("~√~"),
-                WordHint::from("~√X"),
-                WordHint::from("~~√"),
-                WordHint::from("~~~"),
-                WordHint::from("~~X"),
-                WordHint::from("~X√"),
-                WordHint::from("~X~"),
-                WordHint::from("~XX"),
-                WordHint::from("X√√"),
-                WordHint::from("X√~"),
-                WordHint::from("X√X"),
-                WordHint::from("X~√"),
-                WordHint::from("X~~"),
-                WordHint::from("X~X"),
-                WordHint::from("XX√"),
-                WordHint::from("XX~"),
-                WordHint::from("XXX"),
+                WordHint::try_from("√√√").unwrap(),
+                WordHint::try_from("√√~").unwrap(),
+                WordHint::try_from("√√X").unwrap(),
+                WordHint::try_from("√~√").unwrap(),
+                WordHint::try_from("√~~").unwrap(),
+                WordHint::try_from("√~X").unwrap(),
+                WordHint::try_from("√X√").unwrap(),
+                WordHint::try_from("√X~").unwrap(),
+                WordHint::try_from("√XX").unwrap(),
+                WordHint::try_from("~√√").unwrap(),
+                WordHint::try_from("~√~").unwrap(),
+                WordHint::try_from("~√X").unwrap(),
+                WordHint::try_from("~~√").unwrap(),
+                WordHint::try_from("~~~").unwrap(),
+                WordHint::try_from("~~X").unwrap(),
+                WordHint::try_from("~X√").unwrap(),
+                WordHint::try_from("~X~").unwrap(),
+                WordHint::try_from("~XX").unwrap(),
+                WordHint::try_from("X√√").unwrap(),
+                WordHint::try_from("X√~").unwrap(),
+                WordHint::try_from("X√X").unwrap(),
+                WordHint::try_from("X~√").unwrap(),
+                WordHint::try_from("X~~").unwrap(),
+                WordHint::try_from("X~X").unwrap(),
+                WordHint::try_from("XX√").unwrap(),
+                WordHint::try_from("XX~").unwrap(),
+                WordHint::try_from("XXX").unwrap(),
             ]
         )
     }
@@ -319,7 +481,7 @@ mod tests {
     #[test]
     fn test_serialize() {
         assert_eq!(
-            serde_json::to_string(&WordHint::<5>::from("√~X√~")).unwrap(),
+            serde_json::to_string(&WordHint::<5>::try_from("√~X√~").unwrap()).unwrap(),
             "\"√~X√~\""
         );
     }
@@ -327,12 +489,12 @@ mod tests {
     #[test]
     fn test_deserialize() {
         let result: WordHint<5> = serde_json::from_str("\"√~X√~\"").unwrap();
-        assert_eq!(result, WordHint::<5>::from("√~X√~"),);
+        assert_eq!(result, WordHint::<5>::try_from("√~X√~").unwrap(),);
     }
 
     #[test]
     fn test_serde() {
-        let original = WordHint::<5>::from("X~X√~");
+        let original = WordHint::<5>::try_from("X~X√~").unwrap();
         let json = serde_json::to_string(&original).unwrap();
         let reconstructed = serde_json::from_str(&json).unwrap();
         assert_eq!(original, reconstructed);
@@ -341,9 +503,9 @@ mod tests {
     #[test]
     fn test_serde_as_map_key() {
         let original: HashMap<WordHint<5>, u64> = HashMap::from([
-            (WordHint::from("X~X√~"), 5),
-            (WordHint::from("~√~√X"), 3),
-            (WordHint::from("√√√√√"), 1),
+            (WordHint::try_from("X~X√~").unwrap(), 5),
+            (WordHint::try_from("~√~√X").unwrap(), 3),
+            (WordHint::try_from("√√√√√").unwrap(), 1),
         ]);
         let json = serde_json::to_string(&original).unwrap();
         let reconstructed = serde_json::from_str(&json).unwrap();
@@ -359,4 +521,39 @@ mod tests {
             assert_eq!(hint_id, hint_id_recov);
         }
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            CharHint::try_from('?'),
+            Err(WordGameError::InvalidCharHint('?'))
+        ));
+        assert!(WordHint::<5>::try_from("√~X").is_err());
+        assert!(WordHint::<3>::try_from("√?X").is_err());
+        assert_eq!(
+            "x~√".parse::<WordHint<3>>().unwrap(),
+            WordHint([CharHint::Nowhere, CharHint::Elsewhere, CharHint::Correct])
+        );
+    }
+
+    #[test]
+    fn test_ascii_rendering() {
+        let hint: WordHint<5> = WordHint::try_from("√X~XX").unwrap();
+        let guess: Word<5, 26> = Word::try_from("board").unwrap();
+        assert_eq!(hint.render(RenderMode::Unicode), "√X~XX");
+        assert_eq!(hint.render(RenderMode::Ascii), "+X~XX");
+        assert_eq!(
+            WordHint::try_from(hint.render(RenderMode::Ascii).as_str()).unwrap(),
+            hint
+        );
+        assert_eq!(
+            hint.color_guess_with_mode(&guess, RenderMode::Ascii),
+            "BOARD +X~XX"
+        );
+        assert!(
+            hint.color_guess_with_mode(&guess, RenderMode::Unicode)
+                .contains("\x1b[42mB")
+        );
+        assert_eq!(serde_json::to_string(&hint).unwrap(), "\"√X~XX\"");
+    }
 }
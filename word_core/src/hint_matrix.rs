@@ -0,0 +1,384 @@
+use std::{collections::HashSet, ops::Deref};
+
+use crate::{
+    column::Column,
+    error::WordGameError,
+    hint::WordHint,
+    query_generation::{clue_possible, clue_to_query},
+    ruleset::{Ruleset, Standard},
+    word::Word,
+    word_search::{IndexMapping, SearchableWords},
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+#[cfg(feature = "gpu")]
+pub mod gpu;
+
+/// Below this many answers, score each pair directly rather than building a search table and
+/// running one query per possible hint, as the table only pays off over many answers.
+const QUERY_ENGINE_MIN_ANSWERS: usize = 800;
+
+/// Hint ids for every (guess, answer) pair, indexed `[guess_ind][answer_ind]`.
+///
+/// Derefs to the rows, so it can be passed anywhere a `&[Vec<u8>]` is expected.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HintMatrix {
+    rows: Vec<Vec<u8>>,
+}
+
+impl HintMatrix {
+    /// Wrap rows of hint ids, one row per guess with one id per answer.
+    pub fn from_rows(rows: Vec<Vec<u8>>) -> Self {
+        debug_assert!(rows.windows(2).all(|pair| pair[0].len() == pair[1].len()));
+        Self { rows }
+    }
+
+    /// Take the rows back out.
+    pub fn into_rows(self) -> Vec<Vec<u8>> {
+        self.rows
+    }
+
+    /// The hint id a guess gets against an answer.
+    pub fn hint_id(&self, guess_ind: usize, answer_ind: usize) -> u8 {
+        self.rows[guess_ind][answer_ind]
+    }
+
+    pub fn num_guesses(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_answers(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    /// View the whole matrix, to be narrowed down to a subset of guesses and answers.
+    pub fn view(&self) -> HintMatrixView<'_> {
+        HintMatrixView {
+            matrix: self,
+            guesses: IndexMapping::identity(self.num_guesses()),
+            answers: IndexMapping::identity(self.num_answers()),
+        }
+    }
+}
+
+/// A subset of a [`HintMatrix`]'s guesses and answers, borrowing the matrix rather than copying
+/// it, so many workers can share one loaded matrix.
+///
+/// Indices into the view are positions within the subsets, which map back to the matrix's own
+/// indices in ascending order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintMatrixView<'a> {
+    matrix: &'a HintMatrix,
+    guesses: IndexMapping,
+    answers: IndexMapping,
+}
+
+impl<'a> HintMatrixView<'a> {
+    /// Keep only the given guesses, indexed within the current view.
+    pub fn with_guesses(mut self, guess_inds: &[usize]) -> Self {
+        self.guesses = self.guesses.filter(guess_inds);
+        self
+    }
+
+    /// Keep only the guesses set in a mask over the current view's guesses.
+    pub fn with_guess_mask(self, mask: &Column) -> Self {
+        self.with_guesses(&mask.true_inds())
+    }
+
+    /// Keep only the given answers, indexed within the current view.
+    pub fn with_answers(mut self, answer_inds: &[usize]) -> Self {
+        self.answers = self.answers.filter(answer_inds);
+        self
+    }
+
+    /// Keep only the answers set in a mask over the current view's answers.
+    pub fn with_answer_mask(self, mask: &Column) -> Self {
+        self.with_answers(&mask.true_inds())
+    }
+
+    /// The whole matrix the view borrows from.
+    pub fn matrix(&self) -> &'a HintMatrix {
+        self.matrix
+    }
+
+    /// The hint id a guess gets against an answer, both indexed within the view.
+    pub fn hint_id(&self, guess_ind: usize, answer_ind: usize) -> u8 {
+        self.matrix.rows[self.guesses.to_original(guess_ind)][self.answers.to_original(answer_ind)]
+    }
+
+    /// The hint ids a guess gets against each answer in the view.
+    pub fn row(&self, guess_ind: usize) -> impl ExactSizeIterator<Item = u8> + '_ {
+        let row = &self.matrix.rows[self.guesses.to_original(guess_ind)];
+        self.answers
+            .original_inds()
+            .iter()
+            .map(move |answer_ind| row[*answer_ind])
+    }
+
+    pub fn num_guesses(&self) -> usize {
+        self.guesses.original_inds().len()
+    }
+
+    pub fn num_answers(&self) -> usize {
+        self.answers.original_inds().len()
+    }
+
+    /// Where the view's guesses sit in the full matrix.
+    pub fn guess_mapping(&self) -> &IndexMapping {
+        &self.guesses
+    }
+
+    /// Where the view's answers sit in the full matrix.
+    pub fn answer_mapping(&self) -> &IndexMapping {
+        &self.answers
+    }
+
+    /// Copy the view out into a matrix of its own, for code that needs owned rows.
+    pub fn to_matrix(&self) -> HintMatrix {
+        HintMatrix::from_rows(
+            (0..self.num_guesses())
+                .map(|guess_ind| self.row(guess_ind).collect())
+                .collect(),
+        )
+    }
+}
+
+impl Deref for HintMatrix {
+    type Target = [Vec<u8>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.rows
+    }
+}
+
+/// List the possible answers followed by every other guess, without repeats, to compute a hint
+/// matrix over. The solvers index guesses by answer index too, so the answers must come first.
+pub fn answers_first<'a, const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    other_guesses: impl IntoIterator<Item = &'a Word<WORD_SIZE, ALPHABET_SIZE>>,
+) -> Vec<Word<WORD_SIZE, ALPHABET_SIZE>> {
+    let mut seen: HashSet<Word<WORD_SIZE, ALPHABET_SIZE>> =
+        possible_answers.iter().copied().collect();
+    let mut guesses = possible_answers.to_vec();
+    guesses.extend(
+        other_guesses
+            .into_iter()
+            .filter(|guess| seen.insert(**guess))
+            .copied(),
+    );
+    guesses
+}
+
+/// Precompute the hint id for every (guess, answer) pair under the standard rules.
+///
+/// This is the entry point callers should use. When built with the `gpu` feature it first
+/// attempts the GPU backend, and otherwise picks a CPU strategy by the number of answers.
+pub fn compute_hints<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> HintMatrix {
+    #[cfg(feature = "gpu")]
+    if let Some(rows) = gpu::compute_hint_matrix_gpu(guesses, answers) {
+        return HintMatrix::from_rows(rows);
+    }
+    if answers.len() < QUERY_ENGINE_MIN_ANSWERS {
+        compute_hint_matrix_simple(guesses, answers)
+    } else {
+        compute_hint_matrix_query_engine(guesses, answers)
+    }
+}
+
+/// Precompute the hint id for every (guess, answer) pair by scoring each pair directly.
+pub fn compute_hint_matrix_simple<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> HintMatrix {
+    compute_hint_matrix_for_ruleset(&Standard, allowed_guesses, possible_answers)
+}
+
+/// Precompute the hint id for every (guess, answer) pair by running the query for each possible
+/// hint against a search table of the answers.
+pub fn compute_hint_matrix_query_engine<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> HintMatrix {
+    let searchable_answers = SearchableWords::build(possible_answers.to_vec());
+    let rows = WorkerPool::new(PoolConfig::default()).map_or_unwind(allowed_guesses, |_, guess| {
+        let mut hints_for_guess = vec![0; possible_answers.len()];
+        for hint in WordHint::all_possible() {
+            if !clue_possible(*guess, hint) {
+                continue;
+            }
+            let answers_giving_this_hint_mask =
+                searchable_answers.eval_query(clue_to_query(*guess, hint));
+            let hint_id = hint.hint_id();
+            for answer_ind in answers_giving_this_hint_mask.true_inds() {
+                hints_for_guess[answer_ind] = hint_id;
+            }
+        }
+        hints_for_guess
+    });
+    HintMatrix::from_rows(rows)
+}
+
+/// Precompute the hint id for every (guess, answer) pair under an arbitrary ruleset's scoring.
+///
+/// Solvers only see the resulting ids, so this is all they need to handle a variant whose hints
+/// differ from the standard ones.
+pub fn compute_hint_matrix_for_ruleset<
+    const WORD_SIZE: usize,
+    const ALPHABET_SIZE: u8,
+    R: Ruleset<WORD_SIZE, ALPHABET_SIZE> + Sync,
+>(
+    ruleset: &R,
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> HintMatrix {
+    HintMatrix::from_rows(WorkerPool::new(PoolConfig::default()).map_or_unwind(
+        allowed_guesses,
+        |_, guess| {
+            possible_answers
+                .iter()
+                .map(|answer| ruleset.hint(guess, answer).hint_id())
+                .collect()
+        },
+    ))
+}
+
+/// Hints labelled separately for each guess, for words too long for every hint to have its own
+/// byte-sized id.
+///
+/// Each guess's hints are numbered in id order, with label 0 kept for the all-correct hint
+/// whether or not the guess can get it, so the labels can stand in for hint ids in the solvers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactHints<const WORD_SIZE: usize> {
+    /// The label of every (guess, answer) pair's hint, indexed `[guess_ind][answer_ind]`
+    pub matrix: HintMatrix,
+    /// The hint behind each label, indexed `[guess_ind][label]`
+    pub labels: Vec<Vec<WordHint<WORD_SIZE>>>,
+}
+
+impl<const WORD_SIZE: usize> CompactHints<WORD_SIZE> {
+    /// Get the hint a guess's label stands for.
+    pub fn hint(&self, guess_ind: usize, label: u8) -> WordHint<WORD_SIZE> {
+        self.labels[guess_ind][label as usize]
+    }
+}
+
+/// Precompute the hint label for every (guess, answer) pair under the standard rules, failing
+/// if any guess can get more distinct hints than a byte can label.
+pub fn compute_compact_hints<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Result<CompactHints<WORD_SIZE>, WordGameError> {
+    let (rows, labels) = WorkerPool::new(PoolConfig::default())
+        .map(guesses, |_, guess| {
+            let hints: Vec<WordHint<WORD_SIZE>> = answers
+                .iter()
+                .map(|answer| WordHint::from_guess_and_answer(guess, answer))
+                .collect();
+            // Hints compare in id order, which puts all-correct first
+            let mut labels = vec![WordHint::from_guess_and_answer(guess, guess)];
+            let mut distinct = hints.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            labels.extend(distinct.into_iter().filter(|hint| !hint.all_correct()));
+            if labels.len() > u8::MAX as usize + 1 {
+                return Err(WordGameError::TooManyHints {
+                    guess: format!("{:?}", guess.0),
+                    num_hints: labels.len(),
+                });
+            }
+            let row = hints
+                .iter()
+                .map(|hint| labels.binary_search(hint).unwrap() as u8)
+                .collect();
+            Ok((row, labels))
+        })?
+        .into_iter()
+        .collect::<Result<(Vec<Vec<u8>>, Vec<_>), WordGameError>>()?;
+    Ok(CompactHints {
+        matrix: HintMatrix::from_rows(rows),
+        labels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matches_direct_hints(compute: fn(&[Word<5, 26>], &[Word<5, 26>]) -> HintMatrix) {
+        let words: Vec<Word<5, 26>> = ["board", "bread", "brain", "aback", "eerie", "geese"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let all_hints = compute(&words, &words[1..]);
+        for (guess_ind, guess) in words.iter().enumerate() {
+            for (answer_ind, answer) in words[1..].iter().enumerate() {
+                assert_eq!(
+                    all_hints.hint_id(guess_ind, answer_ind),
+                    WordHint::from_guess_and_answer(guess, answer).hint_id()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simple_matches_direct_hints() {
+        assert_matches_direct_hints(compute_hint_matrix_simple);
+    }
+
+    #[test]
+    fn test_query_engine_matches_direct_hints() {
+        assert_matches_direct_hints(compute_hint_matrix_query_engine);
+    }
+
+    #[test]
+    fn test_matches_direct_hints() {
+        assert_matches_direct_hints(compute_hints);
+    }
+
+    #[test]
+    fn test_answers_first() {
+        let words: Vec<Word<5, 26>> = ["board", "bread", "brain", "aback"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let guesses = answers_first(&words[2..], [&words[0], &words[2], &words[1], &words[0]]);
+        assert_eq!(guesses, vec![words[2], words[3], words[0], words[1]]);
+    }
+
+    #[test]
+    fn test_view_matches_sliced_lists() {
+        let words: Vec<Word<5, 26>> = ["board", "bread", "brain", "aback", "eerie", "geese"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let all_hints = compute_hints(&words, &words);
+        let answer_mask = Column::from_bools(&[true, false, true, true, false, true]);
+        // Narrowing twice composes, so the second list indexes into the first
+        let view = all_hints
+            .view()
+            .with_guesses(&[1, 3, 4, 5])
+            .with_guesses(&[0, 2])
+            .with_answer_mask(&answer_mask);
+        assert_eq!((view.num_guesses(), view.num_answers()), (2, 4));
+        assert_eq!(view.guess_mapping().original_inds(), &[1, 4]);
+
+        let guesses = [words[1], words[4]];
+        let answers = [words[0], words[2], words[3], words[5]];
+        assert_eq!(view.to_matrix(), compute_hints(&guesses, &answers));
+        assert_eq!(
+            view.row(1).collect::<Vec<u8>>(),
+            compute_hints(&guesses, &answers)[1]
+        );
+        assert_eq!(view.hint_id(0, 3), all_hints.hint_id(1, 5));
+    }
+
+    #[test]
+    fn test_standard_ruleset_matches_direct_hints() {
+        assert_matches_direct_hints(|guesses, answers| {
+            compute_hint_matrix_for_ruleset(&crate::ruleset::Standard, guesses, answers)
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use std::mem::size_of;
+
+use wgpu::util::DeviceExt;
+
+use crate::word::Word;
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_DISPATCH_ROWS: usize = 65535;
+
+/// Must match the constants in `hints.wgsl`
+const MAX_WORD_SIZE: usize = 8;
+const MAX_ALPHABET_SIZE: u8 = 64;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    word_size: u32,
+    num_answers: u32,
+    num_guesses: u32,
+    alphabet_size: u32,
+}
+
+/// Precompute the hint id for every (guess, answer) pair with a wgpu compute shader.
+///
+/// Returns `None` if no suitable GPU adapter is available or the word shape isn't supported
+/// by the shader, so callers can fall back to the CPU path.
+pub fn compute_hint_matrix_gpu<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Option<Vec<Vec<u8>>> {
+    if WORD_SIZE > MAX_WORD_SIZE || ALPHABET_SIZE > MAX_ALPHABET_SIZE {
+        return None;
+    }
+    if allowed_guesses.is_empty() || possible_answers.is_empty() {
+        return Some(vec![vec![]; allowed_guesses.len()]);
+    }
+
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .ok()?;
+    let (device, queue) =
+        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()?;
+
+    let shader = device.create_shader_module(wgpu::include_wgsl!("hints.wgsl"));
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("hint matrix"),
+        layout: None,
+        module: &shader,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let answers_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("answers"),
+        contents: bytemuck::cast_slice(&flatten_words(possible_answers)),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+
+    // Split guesses into blocks of rows that fit both the buffer and dispatch size limits
+    let row_bytes = possible_answers.len() * size_of::<u32>();
+    let max_binding_bytes = device.limits().max_storage_buffer_binding_size as usize;
+    let rows_per_block = (max_binding_bytes / row_bytes).clamp(1, MAX_DISPATCH_ROWS);
+
+    let mut all_hints = Vec::with_capacity(allowed_guesses.len());
+    for guesses_block in allowed_guesses.chunks(rows_per_block) {
+        let params = Params {
+            word_size: WORD_SIZE as u32,
+            num_answers: possible_answers.len() as u32,
+            num_guesses: guesses_block.len() as u32,
+            alphabet_size: ALPHABET_SIZE as u32,
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let guesses_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("guesses"),
+            contents: bytemuck::cast_slice(&flatten_words(guesses_block)),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output_bytes = (guesses_block.len() * row_bytes) as u64;
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("hint ids"),
+            size: output_bytes,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("hint ids readback"),
+            size: output_bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("hint matrix"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: guesses_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: answers_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: output_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                (possible_answers.len() as u32).div_ceil(WORKGROUP_SIZE),
+                guesses_block.len() as u32,
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&output_buffer, 0, &readback_buffer, 0, output_bytes);
+        queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        device.poll(wgpu::PollType::Wait).ok()?;
+        receiver.recv().ok()?.ok()?;
+
+        let mapped = readback_buffer.slice(..).get_mapped_range();
+        let hint_ids: &[u32] = bytemuck::cast_slice(&mapped);
+        all_hints.extend(hint_ids.chunks(possible_answers.len()).map(|row| {
+            row.iter()
+                .map(|hint_id| *hint_id as u8)
+                .collect::<Vec<u8>>()
+        }));
+    }
+    Some(all_hints)
+}
+
+/// Lay words out as one u32 per character, as expected by the shader.
+fn flatten_words<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    words: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Vec<u32> {
+    words
+        .iter()
+        .flat_map(|word| word.0.iter().map(|chr| *chr as u32))
+        .collect()
+}
@@ -0,0 +1,63 @@
+// Compute the hint id for every (guess, answer) pair in a block of guess rows.
+//
+// Words are stored one character per u32, `word_size` characters per word. The output holds
+// one hint id per pair, row-major over (guess, answer).
+
+struct Params {
+    word_size: u32,
+    num_answers: u32,
+    num_guesses: u32,
+    alphabet_size: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> guesses: array<u32>;
+@group(0) @binding(2) var<storage, read> answers: array<u32>;
+@group(0) @binding(3) var<storage, read_write> hint_ids: array<u32>;
+
+const MAX_WORD_SIZE: u32 = 8u;
+const MAX_ALPHABET_SIZE: u32 = 64u;
+
+@compute @workgroup_size(64, 1, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let answer_ind = id.x;
+    let guess_ind = id.y;
+    if (answer_ind >= params.num_answers || guess_ind >= params.num_guesses) {
+        return;
+    }
+    let guess_start = guess_ind * params.word_size;
+    let answer_start = answer_ind * params.word_size;
+
+    // 0 = Correct, 1 = Elsewhere, 2 = Nowhere
+    var char_hints: array<u32, MAX_WORD_SIZE>;
+    var missed_answer_char_counts: array<u32, MAX_ALPHABET_SIZE>;
+
+    for (var ind = 0u; ind < params.word_size; ind++) {
+        let guess_char = guesses[guess_start + ind];
+        let answer_char = answers[answer_start + ind];
+        if (guess_char == answer_char) {
+            char_hints[ind] = 0u;
+        } else {
+            char_hints[ind] = 2u;
+            missed_answer_char_counts[answer_char] += 1u;
+        }
+    }
+
+    // Missed guess chars become Elsewhere left-to-right while the answer has some left over
+    for (var ind = 0u; ind < params.word_size; ind++) {
+        let guess_char = guesses[guess_start + ind];
+        if (char_hints[ind] == 2u && missed_answer_char_counts[guess_char] > 0u) {
+            char_hints[ind] = 1u;
+            missed_answer_char_counts[guess_char] -= 1u;
+        }
+    }
+
+    // Same little-endian encoding as `WordHint::hint_id`
+    var hint_id = 0u;
+    var factor = 1u;
+    for (var ind = params.word_size; ind > 0u; ind--) {
+        hint_id += char_hints[ind - 1u] * factor;
+        factor *= 3u;
+    }
+    hint_ids[guess_ind * params.num_answers + answer_ind] = hint_id;
+}
@@ -1,9 +1,55 @@
+pub mod adversarial;
+pub mod analysis;
+pub mod answer_set;
+pub mod artifact_diff;
+pub mod batch_build;
+pub mod bounded_cache;
+pub mod certificate;
 pub mod column;
 pub mod decision_tree;
 pub mod decision_tree_general;
 pub mod dumb_word_search;
+pub mod error;
+pub mod fast_path;
+pub mod flat_tree;
+pub mod game;
+pub mod grading;
 pub mod hint;
+pub mod hint_matrix;
 pub mod load_words;
+pub mod loss_analysis;
+pub mod mask_encoding;
+pub mod mastermind;
+pub mod multi_board;
+pub mod objectives;
+pub mod opening_book;
+pub mod play_out;
+pub mod possible_hints;
+pub mod precompute;
+pub mod preference;
+pub mod priors;
 pub mod query_generation;
+pub mod readable_tree;
+pub mod render;
+pub mod ruleset;
+pub mod run_manifest;
+pub mod schedule;
+pub mod search_stats;
+pub mod self_test;
+pub mod session;
+pub mod simulator;
+pub mod starter_pack;
+pub mod stats;
+pub mod strategy;
+pub mod subtree_log;
+pub mod suggestion;
+#[cfg(test)]
+mod test_words;
+pub mod text_plot;
+pub mod transcript;
+pub mod tree_report;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod word;
 pub mod word_search;
+pub mod worker_pool;
@@ -1,15 +1,18 @@
 use std::{env::args, fs};
 
-use crate::word::Word;
+use crate::{error::WordGameError, word::Word};
 
 pub fn load_words<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
     file_path: &str,
-) -> Vec<Word<WORD_SIZE, ALPHABET_SIZE>> {
-    let file = fs::read_to_string(file_path).unwrap();
+) -> Result<Vec<Word<WORD_SIZE, ALPHABET_SIZE>>, WordGameError> {
+    let file = fs::read_to_string(file_path).map_err(|source| WordGameError::Io {
+        path: file_path.into(),
+        source,
+    })?;
     file.split("\n")
         .map(|row| row.trim())
-        .filter(|row| row.len() > 0)
-        .map(|word| Word::from_str(word))
+        .filter(|row| !row.is_empty())
+        .map(Word::try_from)
         .collect()
 }
 
@@ -17,15 +20,18 @@ pub fn load_guesses_and_answers<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>
     allowed_guesses_file_path: &str,
     possible_answers_file_path: &str,
     do_print: bool,
-) -> (
-    Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
-    Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
-) {
-    let mut allowed_guesses = load_words(allowed_guesses_file_path);
+) -> Result<
+    (
+        Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ),
+    WordGameError,
+> {
+    let mut allowed_guesses = load_words(allowed_guesses_file_path)?;
     if do_print {
         println!("loaded {} allowed guesses", allowed_guesses.len());
     }
-    let possible_answers = load_words(possible_answers_file_path);
+    let possible_answers = load_words(possible_answers_file_path)?;
     if do_print {
         println!("loaded {} possible answers", possible_answers.len());
     }
@@ -43,22 +49,25 @@ pub fn load_guesses_and_answers<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>
         );
         println!("now {} allowed guesses", allowed_guesses.len());
     }
-    (allowed_guesses, possible_answers)
+    Ok((allowed_guesses, possible_answers))
 }
 
 pub fn load_guesses_and_answers_from_args<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
     do_print: bool,
-) -> (
-    Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
-    Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
-) {
+) -> Result<
+    (
+        Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ),
+    WordGameError,
+> {
     load_guesses_and_answers(
-        &args()
-            .nth(1)
-            .expect("Must supply allowed guesses word list file as first arg"),
-        &args()
-            .nth(2)
-            .expect("Must supply possible answers word list file as second arg"),
+        &args().nth(1).ok_or(WordGameError::MissingArgument(
+            "allowed guesses word list file as first arg",
+        ))?,
+        &args().nth(2).ok_or(WordGameError::MissingArgument(
+            "possible answers word list file as second arg",
+        ))?,
         do_print,
     )
 }
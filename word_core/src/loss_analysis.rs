@@ -0,0 +1,255 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::{
+    decision_tree_general::{NoPrinter, SearchOptions, compute_decision_tree_aggressive},
+    error::WordGameError,
+    hint::WordHint,
+    hint_matrix::{answers_first, compute_hints},
+    play_out::resolve_guess,
+    readable_tree::ReadableTreeNode,
+    transcript::Transcript,
+    word::Word,
+};
+
+/// How one turn of a game compares to the best play from the same position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TurnAnalysis<const WORD_SIZE: usize> {
+    pub guess: Word<WORD_SIZE, 26>,
+    pub hint: WordHint<WORD_SIZE>,
+
+    /// How many answers were still possible before the guess
+    pub num_candidates: usize,
+
+    /// What the solver would have guessed, if it can guarantee a win in the guesses left
+    pub optimal_guess: Option<Word<WORD_SIZE, 26>>,
+
+    /// Expected guesses from here, counting this one, when playing best throughout
+    pub optimal_cost: Option<f64>,
+
+    /// Expected guesses from here, counting this one, when playing the actual guess and then
+    /// playing best, or `None` if that can't guarantee a win in the guesses left
+    pub actual_cost: Option<f64>,
+}
+
+impl<const WORD_SIZE: usize> TurnAnalysis<WORD_SIZE> {
+    /// How many more guesses the actual guess is expected to cost than the best one, or
+    /// infinity if it gave up a guaranteed win.
+    pub fn cost_delta(&self) -> Option<f64> {
+        let optimal_cost = self.optimal_cost?;
+        Some(
+            self.actual_cost
+                .map_or(f64::INFINITY, |actual_cost| actual_cost - optimal_cost),
+        )
+    }
+}
+
+/// Where a finished game went wrong, turn by turn.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LossAnalysis<const WORD_SIZE: usize> {
+    pub answer: Word<WORD_SIZE, 26>,
+    pub max_guesses: u8,
+    pub turns: Vec<TurnAnalysis<WORD_SIZE>>,
+
+    /// The first turn whose guess was worse than the best one available
+    pub first_mistake: Option<usize>,
+}
+
+/// Costs closer than this are treated as equal
+const COST_EPSILON: f64 = 1e-9;
+
+/// Replay a finished game against its answer, comparing every guess to the best play from the
+/// same position.
+///
+/// The best play at each turn is found by the exhaustive solver over the candidates left, with
+/// however many of `max_guesses` remain. Searching the full answer list for the opening can be
+/// slow, so a precomputed tree over the same lists can be given instead, and is used for as long
+/// as the game follows it. Guesses outside both lists are still scored.
+pub fn explain_loss<const WORD_SIZE: usize>(
+    transcript: &Transcript<WORD_SIZE>,
+    answer: Word<WORD_SIZE, 26>,
+    allowed_guesses: &[Word<WORD_SIZE, 26>],
+    possible_answers: &[Word<WORD_SIZE, 26>],
+    max_guesses: u8,
+    tree: Option<&ReadableTreeNode<WORD_SIZE>>,
+) -> Result<LossAnalysis<WORD_SIZE>, WordGameError> {
+    if !possible_answers.contains(&answer) {
+        return Err(WordGameError::InconsistentHistory(format!(
+            "{} is not a possible answer",
+            answer
+        )));
+    }
+    Transcript {
+        answer: Some(answer),
+        ..transcript.clone()
+    }
+    .check_consistent()?;
+
+    let guesses = answers_first(
+        possible_answers,
+        allowed_guesses
+            .iter()
+            .chain(transcript.turns.iter().map(|turn| &turn.guess)),
+    );
+    let mut guess_inds: HashMap<Word<WORD_SIZE, 26>, u16> = HashMap::new();
+    for (guess_ind, guess) in guesses.iter().enumerate() {
+        guess_inds.entry(*guess).or_insert(guess_ind as u16);
+    }
+    let hints = compute_hints(&guesses, possible_answers);
+    let solve = |candidates: HashSet<u16>, depth: u8| {
+        compute_decision_tree_aggressive(
+            &hints,
+            candidates,
+            depth,
+            max_guesses,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+    };
+
+    let mut candidates: HashSet<u16> = (0..possible_answers.len() as u16).collect();
+    let mut tree_node = tree;
+    let mut turns = vec![];
+    for (depth, turn) in transcript.turns.iter().enumerate() {
+        let depth = depth as u8;
+        let guess_ind = guess_inds[&turn.guess] as usize;
+        let (optimal_guess, optimal_cost) = match tree_node {
+            Some(node) => (Some(node.should_guess), Some(node.est_cost)),
+            None => match solve(candidates.clone(), depth) {
+                Some(best) => (
+                    Some(resolve_guess(
+                        &best.should_guess,
+                        &guesses,
+                        possible_answers,
+                    )),
+                    Some(best.est_cost()),
+                ),
+                None => (None, None),
+            },
+        };
+
+        let actual_cost = if optimal_guess == Some(turn.guess) {
+            optimal_cost
+        } else {
+            let mut buckets: HashMap<u8, HashSet<u16>> = HashMap::new();
+            for answer_ind in &candidates {
+                buckets
+                    .entry(hints[guess_ind][*answer_ind as usize])
+                    .or_default()
+                    .insert(*answer_ind);
+            }
+            buckets
+                .into_iter()
+                .filter(|(hint, _)| *hint != 0)
+                .try_fold(candidates.len() as u32, |total, (_, bucket)| {
+                    solve(bucket, depth + 1).map(|child| total + child.total_guesses)
+                })
+                .map(|total| total as f64 / candidates.len() as f64)
+        };
+
+        turns.push(TurnAnalysis {
+            guess: turn.guess,
+            hint: turn.hint,
+            num_candidates: candidates.len(),
+            optimal_guess,
+            optimal_cost,
+            actual_cost,
+        });
+        if turn.hint.all_correct() {
+            break;
+        }
+        let hint_id = turn.hint.hint_id();
+        candidates.retain(|answer_ind| hints[guess_ind][*answer_ind as usize] == hint_id);
+        tree_node = tree_node
+            .filter(|node| node.should_guess == turn.guess)
+            .and_then(|node| node.next.get(&turn.hint));
+    }
+
+    let first_mistake = turns.iter().position(|turn| {
+        turn.cost_delta()
+            .is_some_and(|cost_delta| cost_delta > COST_EPSILON)
+    });
+    Ok(LossAnalysis {
+        answer,
+        max_guesses,
+        turns,
+        first_mistake,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words::{self, words};
+
+    #[test]
+    fn test_explain_loss() {
+        let possible_answers = words(&["fight", "light", "might", "night", "sight", "tight"]);
+        let allowed_guesses = words(&["flint", "msnst"]);
+        let answer = possible_answers[5];
+        // Guessing the candidates one at a time runs out of guesses
+        let played = words(&["fight", "light", "might", "night"]);
+        let pairs: Vec<(Word<5, 26>, WordHint<5>)> = played
+            .iter()
+            .map(|guess| (*guess, WordHint::from_guess_and_answer(guess, &answer)))
+            .collect();
+        let transcript = Transcript::from_pairs("standard", None, &pairs);
+
+        let analysis = explain_loss(
+            &transcript,
+            answer,
+            &allowed_guesses,
+            &possible_answers,
+            4,
+            None,
+        )
+        .unwrap();
+        assert_eq!(analysis.turns.len(), 4);
+        assert_eq!(analysis.turns[0].num_candidates, 6);
+        assert_eq!(analysis.turns[3].num_candidates, 3);
+        // A splitting guess would have done better from the start
+        assert_eq!(analysis.first_mistake, Some(0));
+        assert!(analysis.turns[0].cost_delta().unwrap() > 0.0);
+        // By the last guess the win could no longer be guaranteed at all
+        assert_eq!(analysis.turns[3].optimal_cost, None);
+
+        // The answer has to agree with the recorded hints
+        assert!(matches!(
+            explain_loss(
+                &transcript,
+                possible_answers[0],
+                &allowed_guesses,
+                &possible_answers,
+                4,
+                None,
+            ),
+            Err(WordGameError::InconsistentHistory(_))
+        ));
+    }
+
+    #[test]
+    fn test_explain_loss_follows_tree() {
+        let possible_answers = words(&["fight", "board", "bread"]);
+        let answer = possible_answers[2];
+        let tree =
+            test_words::guess_then_answers(possible_answers[1], &possible_answers, 5.0 / 3.0);
+        let pairs: Vec<(Word<5, 26>, WordHint<5>)> = words(&["board", "bread"])
+            .iter()
+            .map(|guess| (*guess, WordHint::from_guess_and_answer(guess, &answer)))
+            .collect();
+        let analysis = explain_loss(
+            &Transcript::from_pairs("standard", Some(answer), &pairs),
+            answer,
+            &[],
+            &possible_answers,
+            6,
+            Some(&tree),
+        )
+        .unwrap();
+        assert_eq!(analysis.turns[0].optimal_cost, Some(5.0 / 3.0));
+        assert_eq!(analysis.turns[1].optimal_guess, Some(answer));
+        assert_eq!(analysis.first_mistake, None);
+    }
+}
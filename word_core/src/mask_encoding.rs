@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{column::Column, error::WordGameError, word::Word};
+
+/// The encoded mask format version written by this crate
+pub const ENCODED_MASK_VERSION: u32 = 1;
+
+/// Identifies a word list by name and contents, so masks over it can't be applied to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordListId {
+    pub name: String,
+
+    /// FNV-1a hash of the list's words in order, as 16 hex digits
+    pub hash: String,
+
+    pub len: usize,
+}
+
+impl WordListId {
+    pub fn new<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        name: &str,
+        words: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            hash: format!("{:016x}", word_list_hash(words)),
+            len: words.len(),
+        }
+    }
+
+    /// Check that the words are exactly the list this identifies, in the same order.
+    pub fn check<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        &self,
+        words: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> Result<(), WordGameError> {
+        let actual = Self::new(&self.name, words);
+        if actual != *self {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("{} ({} words, {})", self.name, self.len, self.hash),
+                found: format!("{} ({} words, {})", actual.name, actual.len, actual.hash),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The starting state of an FNV-1a hash
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+/// Feed bytes into an FNV-1a hash, returning the new state.
+pub(crate) fn fnv1a(mut hash: u64, bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_PRIME: u64 = 0x100000001b3;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// A stable hash of a word list, independent of platform and compiler version.
+pub fn word_list_hash<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    words: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> u64 {
+    words.iter().fold(FNV_OFFSET_BASIS, |hash, word| {
+        // Separate words so lists of different word sizes can't collide by concatenation
+        fnv1a(hash, word.0.iter().copied().chain([u8::MAX]))
+    })
+}
+
+/// A `Column` mask over a named word list, in a compact form for files shared between tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodedMask {
+    pub version: u32,
+    pub list: String,
+    pub list_hash: String,
+
+    /// The mask's 64-bit chunks, each as 16 hex digits, lowest indices first
+    pub bits: String,
+}
+
+impl EncodedMask {
+    /// Encode a mask over the given word list.
+    pub fn encode(mask: &Column, list: &WordListId) -> Self {
+        assert_eq!(
+            mask.len(),
+            list.len,
+            "mask length must match word list length"
+        );
+        Self {
+            version: ENCODED_MASK_VERSION,
+            list: list.name.clone(),
+            list_hash: list.hash.clone(),
+            bits: mask
+                .to_chunks()
+                .iter()
+                .map(|chunk| format!("{:016x}", chunk))
+                .collect(),
+        }
+    }
+
+    /// Decode back into a mask, rejecting masks made for a different version or word list.
+    pub fn decode(&self, list: &WordListId) -> Result<Column, WordGameError> {
+        if self.version != ENCODED_MASK_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("encoded mask v{}", ENCODED_MASK_VERSION),
+                found: format!("encoded mask v{}", self.version),
+            });
+        }
+        if self.list_hash != list.hash {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("mask over {} ({})", list.name, list.hash),
+                found: format!("mask over {} ({})", self.list, self.list_hash),
+            });
+        }
+        let malformed = || WordGameError::FormatMismatch {
+            expected: format!("{} hex chunks", list.len.div_ceil(64)),
+            found: format!("{} hex digits", self.bits.len()),
+        };
+        if self.bits.len() != list.len.div_ceil(64) * 16 || !self.bits.is_ascii() {
+            return Err(malformed());
+        }
+        let chunks = (0..self.bits.len())
+            .step_by(16)
+            .map(|start| u64::from_str_radix(&self.bits[start..start + 16], 16))
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| malformed())?;
+        Ok(Column::from_chunks(list.len, chunks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words::words;
+
+    #[test]
+    fn test_round_trip() {
+        let answers: Vec<Word<5, 26>> = (0..100)
+            .map(|ind| Word([(ind % 26) as u8, (ind / 26) as u8, 0, 0, 0]))
+            .collect();
+        let list = WordListId::new("answers", &answers);
+        let mask = Column::from_bools(&(0..100).map(|ind| ind % 3 == 0).collect::<Vec<bool>>());
+
+        let encoded = EncodedMask::encode(&mask, &list);
+        assert_eq!(encoded.bits.len(), 32);
+        assert_eq!(encoded.decode(&list).unwrap(), mask);
+
+        let raw = serde_json::to_string(&encoded).unwrap();
+        let parsed: EncodedMask = serde_json::from_str(&raw).unwrap();
+        assert_eq!(parsed.decode(&list).unwrap(), mask);
+    }
+
+    #[test]
+    fn test_rejects_other_list() {
+        let list_a = WordListId::new("a", &words(&["fight", "light"]));
+        let list_b = WordListId::new("b", &words(&["light", "fight"]));
+        assert_ne!(list_a.hash, list_b.hash);
+        let encoded = EncodedMask::encode(&Column::from_true(2), &list_a);
+        assert!(matches!(
+            encoded.decode(&list_b),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use crate::{
+    decision_tree_general::{
+        GuessFrom, SearchObserver, SearchOptions, TreeNode, compute_decision_tree_aggressive,
+    },
+    hint_matrix::{HintMatrix, compute_hint_matrix_for_ruleset},
+    ruleset::{Mastermind, Ruleset},
+    word::{PositionAlphabets, Word},
+};
+
+/// Every code of length `CODE_LENGTH` over `NUM_COLORS` colors, in lexicographic order.
+pub fn all_codes<const CODE_LENGTH: usize, const NUM_COLORS: u8>(
+    allow_repeats: bool,
+) -> Vec<Word<CODE_LENGTH, NUM_COLORS>> {
+    PositionAlphabets::unrestricted()
+        .all_words()
+        .into_iter()
+        .filter(|code| {
+            allow_repeats || (1..CODE_LENGTH).all(|ind| !code.0[..ind].contains(&code.0[ind]))
+        })
+        .collect()
+}
+
+/// A Mastermind setting, with every possible code as both a guess and a candidate answer.
+pub struct MastermindSetting<const CODE_LENGTH: usize, const NUM_COLORS: u8> {
+    pub ruleset: Mastermind,
+    pub codes: Vec<Word<CODE_LENGTH, NUM_COLORS>>,
+
+    /// Hint ids for every (guess, answer) pair of codes, under unordered Mastermind feedback
+    pub hints: HintMatrix,
+}
+
+impl<const CODE_LENGTH: usize, const NUM_COLORS: u8> MastermindSetting<CODE_LENGTH, NUM_COLORS> {
+    /// Generate the codes and their hints, e.g. `MastermindSetting::<4, 6>::new(true)` for the
+    /// classic game.
+    pub fn new(allow_repeats: bool) -> Self {
+        let ruleset = Mastermind::default();
+        let codes = all_codes(allow_repeats);
+        let hints = compute_hint_matrix_for_ruleset(&ruleset, &codes, &codes);
+        Self {
+            ruleset,
+            codes,
+            hints,
+        }
+    }
+
+    /// Find the decision tree with the lowest expected number of guesses that always finishes
+    /// within `max_guesses`, or `None` if there isn't one.
+    pub fn solve<O: SearchObserver>(
+        &self,
+        max_guesses: u8,
+        max_cost: f64,
+        options: SearchOptions,
+        observer: Option<&O>,
+    ) -> Option<TreeNode> {
+        compute_decision_tree_aggressive(
+            &self.hints,
+            (0..self.codes.len() as u16).collect::<HashSet<u16>>(),
+            0,
+            max_guesses,
+            max_cost,
+            options,
+            observer,
+        )
+    }
+
+    /// Follow a tree against a specific answer, returning how many guesses it takes, or `None` if
+    /// the tree has no branch for some hint along the way.
+    pub fn guesses_needed(
+        &self,
+        tree: &TreeNode,
+        answer: &Word<CODE_LENGTH, NUM_COLORS>,
+    ) -> Option<usize> {
+        let mut node = tree;
+        let mut num_guesses = 1;
+        loop {
+            // Guesses and answers are the same list, so both index into the codes
+            let guess = match node.should_guess {
+                GuessFrom::Guess(ind) | GuessFrom::Answer(ind) => self.codes[ind as usize],
+            };
+            let hint = self.ruleset.hint(&guess, answer);
+            if Ruleset::<CODE_LENGTH, NUM_COLORS>::is_win(&self.ruleset, &hint) {
+                return Some(num_guesses);
+            }
+            node = node.next.get(&hint.hint_id())?;
+            num_guesses += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision_tree_general::{DepthSettings, GuessHeuristic, NoPrinter};
+
+    #[test]
+    fn test_all_codes() {
+        assert_eq!(all_codes::<4, 6>(true).len(), 1296);
+        assert_eq!(all_codes::<4, 6>(false).len(), 360);
+        assert_eq!(
+            all_codes::<2, 3>(false),
+            vec![
+                Word([0, 1]),
+                Word([0, 2]),
+                Word([1, 0]),
+                Word([1, 2]),
+                Word([2, 0]),
+                Word([2, 1])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solves_small_setting() {
+        let setting = MastermindSetting::<3, 4>::new(true);
+        let tree = setting
+            .solve(5, 10.0, SearchOptions::default(), None::<&NoPrinter>)
+            .unwrap();
+        let total_guesses: usize = setting
+            .codes
+            .iter()
+            .map(|code| {
+                let num_guesses = setting.guesses_needed(&tree, code).unwrap();
+                assert!(num_guesses <= 5);
+                num_guesses
+            })
+            .sum();
+        assert_eq!(tree.total_guesses as usize, total_guesses);
+    }
+
+    #[test]
+    fn test_per_depth_settings() {
+        let setting = MastermindSetting::<3, 4>::new(true);
+        let exact = setting
+            .solve(5, 10.0, SearchOptions::default(), None::<&NoPrinter>)
+            .unwrap();
+
+        // Reordering alone can't change the optimum
+        let entropy_options = SearchOptions {
+            depth_settings: vec![DepthSettings {
+                heuristic: GuessHeuristic::Entropy,
+                beam_width: None,
+                ..DepthSettings::default()
+            }],
+            ..SearchOptions::default()
+        };
+        let entropy = setting
+            .solve(5, 10.0, entropy_options, None::<&NoPrinter>)
+            .unwrap();
+        assert_eq!(entropy.total_guesses, exact.total_guesses);
+        assert!(!entropy.approximate);
+
+        // A narrow beam below the root still plays every code, but only approximately
+        let beam_options = SearchOptions {
+            depth_settings: vec![
+                DepthSettings::default(),
+                DepthSettings {
+                    heuristic: GuessHeuristic::MaxBucket,
+                    beam_width: Some(3),
+                    ..DepthSettings::default()
+                },
+            ],
+            ..SearchOptions::default()
+        };
+        assert_eq!(beam_options.at_depth(0), DepthSettings::default());
+        assert_eq!(beam_options.at_depth(4).beam_width, Some(3));
+        let beam = setting
+            .solve(5, 10.0, beam_options, None::<&NoPrinter>)
+            .unwrap();
+        assert!(beam.total_guesses >= exact.total_guesses);
+        assert!(beam.next.values().any(|child| child.approximate));
+        for code in &setting.codes {
+            assert!(setting.guesses_needed(&beam, code).unwrap() <= 5);
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use std::{collections::HashSet, mem::size_of};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounded_cache::{BoundedCache, MemoryFootprint},
+    column::Column,
+    decision_tree_general::{count_by_hint, split_by_hint},
+    mask_encoding::{FNV_OFFSET_BASIS, fnv1a},
+};
+
+/// A decision tree for solving several boards at once, as in Dordle or Quordle, where every
+/// guess is played on each board still unsolved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiBoardNode {
+    pub should_guess: u16,
+    /// The guesses it takes to solve every board, this guess included, summed over every
+    /// combination of answers under this node
+    pub total_guesses: u64,
+    /// How many combinations of one answer per board are still possible
+    pub num_answers: u64,
+    /// The subtree after each clue, keyed by the hint on every board in board order, in clue
+    /// order. Boards solved earlier keep getting the all-correct hint, and clues that solve the
+    /// last boards have no subtree.
+    pub next: Vec<(Vec<u8>, MultiBoardNode)>,
+}
+
+impl MultiBoardNode {
+    /// Get the expected number of guesses to solve every board from this node, over its
+    /// combinations of answers.
+    pub fn est_cost(&self) -> f64 {
+        self.total_guesses as f64 / self.num_answers as f64
+    }
+
+    /// Get the most guesses it takes to solve every board from this node.
+    pub fn worst_case(&self) -> u32 {
+        1 + self
+            .next
+            .iter()
+            .map(|(_, child)| child.worst_case())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Get the subtree after a clue, if the clue leaves any board unsolved.
+    pub fn child(&self, clue: &[u8]) -> Option<&Self> {
+        self.next
+            .binary_search_by(|(other, _)| other.as_slice().cmp(clue))
+            .ok()
+            .map(|ind| &self.next[ind].1)
+    }
+}
+
+impl MemoryFootprint for MultiBoardNode {
+    fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self
+                .next
+                .iter()
+                .map(|(clue, child)| clue.len() + child.memory_footprint())
+                .sum::<usize>()
+    }
+}
+
+/// Compute the tree that solves every board in the fewest guesses on average, each within
+/// `max_depth` guesses, or `None` if there's no such tree.
+///
+/// Each board starts with its own set of possible answers, and every combination of one answer
+/// per board is taken as equally likely. `hints` is indexed by guess then answer as for the
+/// single-board search, whose hint splitting this reuses. The number of combinations grows
+/// with every board, so this is for small answer sets and endgames rather than whole games.
+///
+/// Solved states of the boards are remembered within `memo_bytes` of memory, dropping the least
+/// recently used first, so a state reached through different guesses is usually only searched
+/// once.
+pub fn compute_multi_board_tree(
+    hints: &[Vec<u8>],
+    boards: &[HashSet<u16>],
+    max_depth: u8,
+    memo_bytes: usize,
+) -> Option<MultiBoardNode> {
+    if boards.is_empty() || boards.iter().any(HashSet::is_empty) {
+        return None;
+    }
+    let num_answers = hints.first().map_or(0, Vec::len);
+    let boards: Vec<Option<Column>> = boards
+        .iter()
+        .map(|answers| {
+            let mut column = Column::from_false(num_answers);
+            for answer_ind in answers {
+                column.set(*answer_ind as usize, true);
+            }
+            Some(column)
+        })
+        .collect();
+    MultiBoardSearch {
+        hints,
+        memo: BoundedCache::with_memory_budget(memo_bytes),
+    }
+    .solve(&boards, max_depth, u64::MAX)
+}
+
+/// Identifies a state of the boards by the guesses left and a hash of every board's possible
+/// answers.
+type MemoKey = (u8, u64);
+
+/// A memo entry, keeping the state's exact boards so a lookup whose key collides with a
+/// different state's is treated as a miss.
+struct MemoEntry {
+    boards: Vec<Option<Vec<u64>>>,
+    result: Memo,
+}
+
+impl MemoEntry {
+    fn matches(&self, boards: &[Option<Column>]) -> bool {
+        self.boards.len() == boards.len()
+            && self
+                .boards
+                .iter()
+                .zip(boards)
+                .all(|(chunks, board)| *chunks == board.as_ref().map(Column::to_chunks))
+    }
+}
+
+impl MemoryFootprint for MemoEntry {
+    fn memory_footprint(&self) -> usize {
+        let chunks: usize = self.boards.iter().flatten().map(Vec::len).sum();
+        let result = match &self.result {
+            Memo::Solved(tree) => tree.memory_footprint(),
+            Memo::NoneBelow(_) => 0,
+        };
+        self.boards.len() * size_of::<Option<Vec<u64>>>() + chunks * size_of::<u64>() + result
+    }
+}
+
+/// What a finished search found for one state of the boards.
+enum Memo {
+    Solved(MultiBoardNode),
+    /// No tree for the state comes in under this total
+    NoneBelow(u64),
+}
+
+fn memo_key(boards: &[Option<Column>], guesses_left: u8) -> MemoKey {
+    let hash = boards
+        .iter()
+        .fold(FNV_OFFSET_BASIS, |hash, board| match board {
+            Some(answers) => fnv1a(
+                fnv1a(hash, [1]),
+                answers
+                    .to_chunks()
+                    .iter()
+                    .flat_map(|chunk| chunk.to_le_bytes()),
+            ),
+            None => fnv1a(hash, [0]),
+        });
+    (guesses_left, hash)
+}
+
+/// The search over states of the boards, each board's possible answers or `None` once solved.
+struct MultiBoardSearch<'a> {
+    hints: &'a [Vec<u8>],
+    memo: BoundedCache<MemoKey, MemoEntry>,
+}
+
+impl MultiBoardSearch<'_> {
+    /// Find the best tree for a state that comes in under `max_total`.
+    fn solve(
+        &mut self,
+        boards: &[Option<Column>],
+        guesses_left: u8,
+        max_total: u64,
+    ) -> Option<MultiBoardNode> {
+        if guesses_left == 0 {
+            return None;
+        }
+        let key = memo_key(boards, guesses_left);
+        let cached = self
+            .memo
+            .get(&key)
+            .filter(|entry| entry.matches(boards))
+            .map(|entry| &entry.result);
+        match cached {
+            Some(Memo::Solved(tree)) if tree.total_guesses < max_total => {
+                return Some(tree.clone());
+            }
+            Some(Memo::Solved(_)) => return None,
+            Some(Memo::NoneBelow(below)) if max_total <= *below => return None,
+            _ => {}
+        }
+        let result = self.search(boards, guesses_left, max_total);
+        self.memo.insert(
+            key,
+            MemoEntry {
+                boards: boards
+                    .iter()
+                    .map(|board| board.as_ref().map(Column::to_chunks))
+                    .collect(),
+                result: match &result {
+                    Some(tree) => Memo::Solved(tree.clone()),
+                    None => Memo::NoneBelow(max_total),
+                },
+            },
+        );
+        result
+    }
+
+    fn search(
+        &mut self,
+        boards: &[Option<Column>],
+        guesses_left: u8,
+        max_total: u64,
+    ) -> Option<MultiBoardNode> {
+        let num_answers = num_combinations(boards);
+        let mut best: Option<MultiBoardNode> = None;
+        for guess_ind in self.guess_order(boards) {
+            let guess_max_total = best.as_ref().map_or(max_total, |best| best.total_guesses);
+            // Split every unsolved board by hint, and combine the splits into clues
+            let mut clues: Vec<(Vec<u8>, Vec<Option<Column>>)> = vec![(vec![], vec![])];
+            for board in boards {
+                let splits = match board {
+                    Some(answers) => split_by_hint(&self.hints[guess_ind as usize], answers),
+                    None => vec![(0, Column::from_false(0))],
+                };
+                clues = clues
+                    .into_iter()
+                    .flat_map(|(clue, state)| {
+                        splits.iter().map(move |(hint, answers)| {
+                            let mut clue = clue.clone();
+                            let mut state = state.clone();
+                            clue.push(*hint);
+                            state.push((*hint != 0).then(|| answers.clone()));
+                            (clue, state)
+                        })
+                    })
+                    .collect();
+            }
+            let mut children: Vec<(Vec<u8>, Vec<Option<Column>>, u64)> = clues
+                .into_iter()
+                .filter(|(_, state)| state.iter().any(Option::is_some))
+                .map(|(clue, state)| {
+                    let num_child_answers = num_combinations(&state);
+                    (clue, state, num_child_answers)
+                })
+                .collect();
+            children.sort_by(|(clue_a, _, num_a), (clue_b, _, num_b)| {
+                num_a.cmp(num_b).then(clue_a.cmp(clue_b))
+            });
+
+            let mut total = children.iter().fold(num_answers, |total, (_, _, num)| {
+                total + total_lower_bound(*num)
+            });
+            if total >= guess_max_total {
+                continue;
+            }
+            let mut next = Vec::with_capacity(children.len());
+            for (clue, state, num_child_answers) in children {
+                let child_lower_bound = total_lower_bound(num_child_answers);
+                let child_max_total = guess_max_total - total + child_lower_bound;
+                let Some(child) = self.solve(&state, guesses_left - 1, child_max_total) else {
+                    total = u64::MAX;
+                    break;
+                };
+                total = total - child_lower_bound + child.total_guesses;
+                if total >= guess_max_total {
+                    break;
+                }
+                next.push((clue, child));
+            }
+            if total < guess_max_total {
+                next.sort_by(|(clue_a, _), (clue_b, _)| clue_a.cmp(clue_b));
+                best = Some(MultiBoardNode {
+                    should_guess: guess_ind,
+                    total_guesses: total,
+                    num_answers,
+                    next,
+                });
+            }
+        }
+        best
+    }
+
+    /// Get the guesses worth trying in a state, those leaving the fewest combinations under
+    /// their most common clue first. Guesses that neither split nor solve any board are left
+    /// out.
+    fn guess_order(&self, boards: &[Option<Column>]) -> Vec<u16> {
+        let mut guess_order: Vec<(u16, u64)> = (0..self.hints.len())
+            .filter_map(|guess_ind| {
+                let mut useful = false;
+                let mut most_combinations: u64 = 1;
+                for answers in boards.iter().flatten() {
+                    let counts = count_by_hint(&self.hints[guess_ind], answers);
+                    let most_answers = *counts.iter().max().unwrap();
+                    useful |= counts[0] > 0 || most_answers < answers.count_true() as usize;
+                    most_combinations = most_combinations.saturating_mul(most_answers as u64);
+                }
+                useful.then_some((guess_ind as u16, most_combinations))
+            })
+            .collect();
+        guess_order.sort_by_key(|(guess_ind, most_combinations)| (*most_combinations, *guess_ind));
+        guess_order
+            .into_iter()
+            .map(|(guess_ind, _)| guess_ind)
+            .collect()
+    }
+}
+
+/// Count the combinations of one answer per unsolved board.
+fn num_combinations(boards: &[Option<Column>]) -> u64 {
+    boards
+        .iter()
+        .flatten()
+        .fold(1, |num, answers| num.saturating_mul(answers.count_true()))
+}
+
+/// Get a lower bound on the total guesses to solve some combinations of answers. A guess can
+/// only solve every board at once for the one combination with it as each board's answer, so
+/// every other combination takes at least two.
+fn total_lower_bound(num_combinations: u64) -> u64 {
+    (2 * num_combinations).saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decision_tree_general::{NoPrinter, SearchOptions, compute_decision_tree_aggressive},
+        hint_matrix::compute_hints,
+        test_words,
+    };
+
+    #[test]
+    fn test_multi_board_tree() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+
+        // One board is just the single-board search
+        let answers: HashSet<u16> = (0..8).collect();
+        let single = compute_decision_tree_aggressive(
+            &hints,
+            answers.clone(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let tree =
+            compute_multi_board_tree(&hints, std::slice::from_ref(&answers), 6, 1 << 20).unwrap();
+        assert_eq!(tree.total_guesses, single.total_guesses as u64);
+
+        // Playing out every pair of answers takes as many guesses as the tree says
+        let boards: Vec<HashSet<u16>> = vec![(0..5).collect(), (10..16).collect()];
+        let tree = compute_multi_board_tree(&hints, &boards, 7, 1 << 20).unwrap();
+        assert_eq!(tree.num_answers, 30);
+        let mut total = 0;
+        for first in &boards[0] {
+            for second in &boards[1] {
+                let mut solved = [false; 2];
+                let mut node = &tree;
+                loop {
+                    total += 1;
+                    let clue: Vec<u8> = [*first, *second]
+                        .iter()
+                        .zip(&mut solved)
+                        .map(|(answer, solved)| {
+                            let hint = if *solved {
+                                0
+                            } else {
+                                hints[node.should_guess as usize][*answer as usize]
+                            };
+                            *solved = hint == 0;
+                            hint
+                        })
+                        .collect();
+                    if solved.iter().all(|solved| *solved) {
+                        break;
+                    }
+                    node = node.child(&clue).unwrap();
+                }
+            }
+        }
+        assert_eq!(total, tree.total_guesses);
+        assert!(tree.worst_case() <= 7);
+
+        // Forgetting solved states only costs time
+        let small_boards: Vec<HashSet<u16>> = vec![(0..3).collect(), (10..13).collect()];
+        let forgetful = compute_multi_board_tree(&hints, &small_boards, 7, 0).unwrap();
+        assert_eq!(
+            Some(forgetful),
+            compute_multi_board_tree(&hints, &small_boards, 7, 1 << 20)
+        );
+
+        // Two boards can't both be solved in a single guess
+        assert!(compute_multi_board_tree(&hints, &boards, 1, 1 << 20).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::decision_tree_general::{
+    GuessFrom, NoPrinter, SearchOptions, TreeNode, compute_decision_tree_with_openers,
+};
+
+/// Several measures of how well a tree plays, so they can be compared without re-running the
+/// solver once per objective.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TreeObjectives {
+    /// Mean number of guesses over the answers the tree solves
+    pub expected_guesses: f64,
+
+    /// Most guesses needed for any answer the tree solves
+    pub worst_case: usize,
+
+    /// Fraction of all answers solved within `win_within` guesses
+    pub win_rate: f64,
+    pub win_within: usize,
+
+    /// Number of answers the tree has no path to
+    pub num_unsolved: usize,
+}
+
+/// How a root guess that lost out to the best one would have played.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RejectedGuessObjectives {
+    pub guess_ind: u16,
+
+    /// The cost the search assigned the guess, only a lower bound if it wasn't `complete`
+    pub est_cost: f64,
+    pub complete: bool,
+
+    /// Measures of the best tree opening with the guess, solved afresh if the search cut it
+    /// short, or `None` if no such tree fits within the max depth
+    pub objectives: Option<TreeObjectives>,
+}
+
+/// Every objective for the chosen tree, and for each rejected root guess the search kept.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectiveReport {
+    pub best: TreeObjectives,
+    pub rejected: Vec<RejectedGuessObjectives>,
+}
+
+/// Play every answer through the tree, measuring it against each objective.
+///
+/// Answer indices double as guess indices, as throughout the solver, so `all_hints` must have
+/// the possible answers as its first guesses.
+pub fn tree_objectives(
+    tree: &TreeNode,
+    all_hints: &[Vec<u8>],
+    possible_answers: &HashSet<u16>,
+    win_within: usize,
+) -> TreeObjectives {
+    let mut num_solved_by_depth: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut num_unsolved = 0;
+    for answer_ind in possible_answers {
+        match guesses_needed(tree, all_hints, *answer_ind) {
+            Some(num_guesses) => *num_solved_by_depth.entry(num_guesses).or_insert(0) += 1,
+            None => num_unsolved += 1,
+        }
+    }
+
+    let num_solved: usize = num_solved_by_depth.values().sum();
+    let total_guesses: usize = num_solved_by_depth
+        .iter()
+        .map(|(num_guesses, count)| num_guesses * count)
+        .sum();
+    let num_won: usize = num_solved_by_depth
+        .range(..=win_within)
+        .map(|(_, count)| count)
+        .sum();
+    TreeObjectives {
+        expected_guesses: if num_solved == 0 {
+            0.0
+        } else {
+            total_guesses as f64 / num_solved as f64
+        },
+        worst_case: num_solved_by_depth.keys().next_back().copied().unwrap_or(0),
+        win_rate: if possible_answers.is_empty() {
+            0.0
+        } else {
+            num_won as f64 / possible_answers.len() as f64
+        },
+        win_within,
+        num_unsolved,
+    }
+}
+
+/// Measure the tree and the rejected root guesses kept as its alternatives.
+///
+/// The search only keeps as many alternatives as `SearchOptions::num_alternatives` allows, so
+/// raise that to report on more of the rejected guesses. Those it pruned before finishing their
+/// trees are searched again, opening with the guess and within `max_depth` guesses.
+pub fn objective_report(
+    tree: &TreeNode,
+    all_hints: &[Vec<u8>],
+    possible_answers: &HashSet<u16>,
+    win_within: usize,
+    max_depth: u8,
+) -> ObjectiveReport {
+    ObjectiveReport {
+        best: tree_objectives(tree, all_hints, possible_answers, win_within),
+        rejected: tree
+            .alternatives
+            .iter()
+            .map(|alternative| RejectedGuessObjectives {
+                guess_ind: guess_ind(&alternative.tree_node.should_guess),
+                est_cost: alternative.tree_node.est_cost(),
+                complete: alternative.complete,
+                objectives: if alternative.complete {
+                    Some(tree_objectives(
+                        &alternative.tree_node,
+                        all_hints,
+                        possible_answers,
+                        win_within,
+                    ))
+                } else {
+                    compute_decision_tree_with_openers(
+                        all_hints,
+                        possible_answers.clone(),
+                        0,
+                        max_depth,
+                        f64::INFINITY,
+                        &[guess_ind(&alternative.tree_node.should_guess)],
+                        SearchOptions::default(),
+                        None::<&NoPrinter>,
+                    )
+                    .map(|guess_tree| {
+                        tree_objectives(&guess_tree, all_hints, possible_answers, win_within)
+                    })
+                },
+            })
+            .collect(),
+    }
+}
+
+fn guess_ind(should_guess: &GuessFrom) -> u16 {
+    match should_guess {
+        GuessFrom::Guess(ind) | GuessFrom::Answer(ind) => *ind,
+    }
+}
+
+/// Follow the tree against an answer, returning how many guesses it takes if it gets there.
+fn guesses_needed(tree: &TreeNode, all_hints: &[Vec<u8>], answer_ind: u16) -> Option<usize> {
+    let mut node = tree;
+    let mut num_guesses = 1;
+    loop {
+        let hint = all_hints[guess_ind(&node.should_guess) as usize][answer_ind as usize];
+        if hint == 0 {
+            return Some(num_guesses);
+        }
+        node = node.next.get(&hint)?;
+        num_guesses += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        decision_tree_general::compute_decision_tree_aggressive, hint_matrix::compute_hints,
+        readable_tree::ReadableTreeNode, tree_report::compare_trees, word::Word,
+    };
+
+    #[test]
+    fn test_objectives_match_tree_report() {
+        let answers: Vec<Word<5, 26>> = ["fight", "light", "might", "night", "sight", "board"]
+            .iter()
+            .map(|raw| Word::try_from(*raw).unwrap())
+            .collect();
+        let all_hints = compute_hints(&answers, &answers);
+        let possible_answers: HashSet<u16> = (0..answers.len() as u16).collect();
+        let tree = compute_decision_tree_aggressive(
+            &all_hints,
+            possible_answers.clone(),
+            0,
+            6,
+            10.0,
+            SearchOptions {
+                num_alternatives: 100,
+                ..SearchOptions::default()
+            },
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+
+        let report = objective_report(&tree, &all_hints, &possible_answers, 3, 6);
+        let readable = ReadableTreeNode::from_generalized_tree_node(&tree, &answers, &answers);
+        let evaluation = &compare_trees(&[("best", &readable)], &answers).evaluations[0];
+        assert!((report.best.expected_guesses - tree.est_cost()).abs() < 1e-9);
+        assert!((report.best.expected_guesses - evaluation.expected_guesses).abs() < 1e-9);
+        assert_eq!(report.best.worst_case, evaluation.worst_case);
+        assert_eq!(report.best.num_unsolved, 0);
+
+        // Every other guess gets pruned, with only a lower bound, so each is solved again
+        assert_eq!(report.rejected.len(), answers.len() - 1);
+        for rejected in &report.rejected {
+            assert!(!rejected.complete);
+            let objectives = rejected.objectives.as_ref().unwrap();
+            assert_eq!(objectives.num_unsolved, 0);
+            assert!(objectives.expected_guesses >= report.best.expected_guesses);
+            assert!(objectives.expected_guesses >= rejected.est_cost - 1e-9);
+        }
+        // BOARD tells none of the _IGHT words apart
+        let board = report
+            .rejected
+            .iter()
+            .find(|rejected| rejected.guess_ind == 5)
+            .unwrap();
+        assert!(board.objectives.as_ref().unwrap().worst_case > report.best.worst_case);
+
+        // A tree that only knows how to finish one answer leaves the rest unsolved
+        let stub = TreeNode {
+            should_guess: GuessFrom::Answer(0),
+            total_guesses: 1,
+            num_answers: 1,
+            next: HashMap::new(),
+            approximate: false,
+            alternatives: Vec::new(),
+        };
+        let objectives = tree_objectives(&stub, &all_hints, &possible_answers, 6);
+        assert_eq!(objectives.num_unsolved, 5);
+        assert_eq!(objectives.worst_case, 1);
+        assert_eq!(objectives.win_rate, 1.0 / 6.0);
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    column::Column,
+    decision_tree_general::{
+        GuessFrom, SearchObserver, SearchOptions, SearchStep, TreeNode,
+        compute_decision_tree_aggressive,
+    },
+    error::WordGameError,
+    hint::WordHint,
+    mask_encoding::{EncodedMask, WordListId},
+    readable_tree::ReadableTreeNode,
+    word::Word,
+};
+
+/// The opening book format version written by this crate
+pub const OPENING_BOOK_VERSION: u32 = 1;
+
+/// One position in an opening book: what to guess, and where each hint leads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookNode<const WORD_SIZE: usize> {
+    pub guess: Word<WORD_SIZE, 26>,
+    pub est_cost: f64,
+
+    /// Follow-up positions keyed by hint id, absent past the book's last ply
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub next: BTreeMap<u8, BookNode<WORD_SIZE>>,
+
+    /// Which answers remain at this position, if the book was built with them
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub remaining: Option<EncodedMask>,
+}
+
+/// The first few plies of a decision tree, small enough to ship to lightweight clients that
+/// solve deeper positions themselves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpeningBook<const WORD_SIZE: usize> {
+    pub version: u32,
+    pub num_plies: u8,
+    pub root: BookNode<WORD_SIZE>,
+}
+
+impl<const WORD_SIZE: usize> OpeningBook<WORD_SIZE> {
+    /// Build a book from the first `num_plies` guesses of a full tree.
+    pub fn from_tree(tree: &ReadableTreeNode<WORD_SIZE>, num_plies: u8) -> Self {
+        Self {
+            version: OPENING_BOOK_VERSION,
+            num_plies,
+            root: truncate(tree, num_plies.saturating_sub(1)),
+        }
+    }
+
+    /// Record at every position which answers from the given list remain, so clients can pick
+    /// up the search from there without replaying the hints.
+    pub fn with_remaining_masks(
+        mut self,
+        list: &WordListId,
+        answers: &[Word<WORD_SIZE, 26>],
+    ) -> Self {
+        fill_remaining(
+            &mut self.root,
+            list,
+            answers,
+            Column::from_true(answers.len()),
+        );
+        self
+    }
+
+    /// Find the position reached after receiving the given hints, if it's still in the book.
+    pub fn lookup(&self, hints: &[WordHint<WORD_SIZE>]) -> Option<&BookNode<WORD_SIZE>> {
+        hints.iter().try_fold(&self.root, |node, hint| {
+            if hint.all_correct() {
+                None
+            } else {
+                node.next.get(&hint.hint_id())
+            }
+        })
+    }
+
+    /// The guess to play after receiving the given hints, if it's still in the book.
+    pub fn next_guess(&self, hints: &[WordHint<WORD_SIZE>]) -> Option<Word<WORD_SIZE, 26>> {
+        self.lookup(hints).map(|node| node.guess)
+    }
+
+    /// Search exhaustively below the book's last ply, keeping the book's own guesses above it,
+    /// and stitch the results into a complete tree.
+    ///
+    /// `all_hints` must be indexed `[guess_ind][answer_ind]` over the given lists, and every
+    /// book guess must be in `allowed_guesses`.
+    pub fn solve_below<O: SearchObserver>(
+        &self,
+        all_hints: &[Vec<u8>],
+        allowed_guesses: &[Word<WORD_SIZE, 26>],
+        possible_answers: &[Word<WORD_SIZE, 26>],
+        max_depth: u8,
+        options: SearchOptions,
+        observer: Option<&O>,
+    ) -> Result<TreeNode, WordGameError> {
+        BelowBookSearch {
+            all_hints,
+            allowed_guesses,
+            max_depth,
+            options,
+        }
+        .solve(
+            &self.root,
+            (0..possible_answers.len() as u16).collect(),
+            0,
+            observer,
+        )
+    }
+
+    /// Serialize to compact JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Parse from JSON, rejecting books written in an unknown format version.
+    pub fn from_json(raw: &str) -> Result<Self, WordGameError> {
+        let book: Self = serde_json::from_str(raw)?;
+        if book.version != OPENING_BOOK_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("opening book v{}", OPENING_BOOK_VERSION),
+                found: format!("opening book v{}", book.version),
+            });
+        }
+        Ok(book)
+    }
+}
+
+/// Copy a tree node, keeping only `remaining_plies` levels of follow-ups beneath it.
+fn truncate<const WORD_SIZE: usize>(
+    tree: &ReadableTreeNode<WORD_SIZE>,
+    remaining_plies: u8,
+) -> BookNode<WORD_SIZE> {
+    BookNode {
+        guess: tree.should_guess,
+        est_cost: tree.est_cost,
+        next: if remaining_plies == 0 {
+            BTreeMap::new()
+        } else {
+            tree.next
+                .iter()
+                .map(|(hint, child)| (hint.hint_id(), truncate(child, remaining_plies - 1)))
+                .collect()
+        },
+        remaining: None,
+    }
+}
+
+/// What stays the same while solving every position below a book.
+struct BelowBookSearch<'a, const WORD_SIZE: usize> {
+    all_hints: &'a [Vec<u8>],
+    allowed_guesses: &'a [Word<WORD_SIZE, 26>],
+    max_depth: u8,
+    options: SearchOptions,
+}
+
+impl<const WORD_SIZE: usize> BelowBookSearch<'_, WORD_SIZE> {
+    /// Play a book node's guess against the remaining answers, then solve each resulting
+    /// position from the book if it goes that deep, or with the exhaustive search otherwise.
+    fn solve<O: SearchObserver>(
+        &self,
+        node: &BookNode<WORD_SIZE>,
+        possible_answers: HashSet<u16>,
+        depth: u8,
+        observer: Option<&O>,
+    ) -> Result<TreeNode, WordGameError> {
+        if depth >= self.max_depth {
+            return Err(WordGameError::Infeasible(format!(
+                "book is deeper than the max depth of {}",
+                self.max_depth
+            )));
+        }
+        let guess_ind = self
+            .allowed_guesses
+            .iter()
+            .position(|guess| *guess == node.guess)
+            .ok_or_else(|| WordGameError::InvalidWord {
+                word: node.guess.to_string(),
+                reason: "book guess isn't an allowed guess".to_string(),
+            })? as u16;
+
+        let mut answers_by_hint: BTreeMap<u8, HashSet<u16>> = BTreeMap::new();
+        for answer_ind in &possible_answers {
+            answers_by_hint
+                .entry(self.all_hints[guess_ind as usize][*answer_ind as usize])
+                .or_default()
+                .insert(*answer_ind);
+        }
+
+        let mut tree_node = TreeNode {
+            should_guess: GuessFrom::Guess(guess_ind),
+            total_guesses: possible_answers.len() as u32,
+            num_answers: possible_answers.len() as u32,
+            next: HashMap::new(),
+            approximate: false,
+            alternatives: Vec::new(),
+        };
+        for (hint_id, hint_possible_answers) in answers_by_hint {
+            // If we happened to guess correctly, there is no additional cost
+            if hint_id == 0 {
+                continue;
+            }
+            let hint_observer = observer.map(|observer| {
+                observer.descend(SearchStep::Clue {
+                    guess_ind,
+                    hint: hint_id,
+                })
+            });
+            let child = match node.next.get(&hint_id) {
+                Some(book_child) => self.solve(
+                    book_child,
+                    hint_possible_answers,
+                    depth + 1,
+                    hint_observer.as_ref(),
+                )?,
+                None => compute_decision_tree_aggressive(
+                    self.all_hints,
+                    hint_possible_answers,
+                    depth + 1,
+                    self.max_depth,
+                    f64::INFINITY,
+                    self.options.clone(),
+                    hint_observer.as_ref(),
+                )
+                .ok_or_else(|| {
+                    WordGameError::Infeasible(format!(
+                        "no tree below the book within {} guesses",
+                        self.max_depth
+                    ))
+                })?,
+            };
+            tree_node.total_guesses += child.total_guesses;
+            tree_node.approximate |= child.approximate;
+            tree_node.next.insert(hint_id, child);
+        }
+        Ok(tree_node)
+    }
+}
+
+/// Fill in the remaining-answers mask of a node and everything below it.
+fn fill_remaining<const WORD_SIZE: usize>(
+    node: &mut BookNode<WORD_SIZE>,
+    list: &WordListId,
+    answers: &[Word<WORD_SIZE, 26>],
+    mask: Column,
+) {
+    for (hint_id, child) in node.next.iter_mut() {
+        let mut child_mask = mask.clone();
+        for answer_ind in mask.true_inds() {
+            if WordHint::from_guess_and_answer(&node.guess, &answers[answer_ind]).hint_id()
+                != *hint_id
+            {
+                child_mask.set(answer_ind, false);
+            }
+        }
+        fill_remaining(child, list, answers, child_mask);
+    }
+    node.remaining = Some(EncodedMask::encode(&mask, list));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decision_tree_general::NoPrinter, hint_matrix::compute_hints, test_words};
+
+    fn word(raw: &str) -> Word<5, 26> {
+        Word::try_from(raw).unwrap()
+    }
+
+    fn node(raw: &str, next: Vec<(&str, ReadableTreeNode<5>)>) -> ReadableTreeNode<5> {
+        let guess = word(raw);
+        test_words::tree_node(
+            guess,
+            1.0,
+            next.into_iter()
+                .map(|(answer, child)| {
+                    (
+                        WordHint::from_guess_and_answer(&guess, &word(answer)),
+                        child,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_truncate_and_lookup() {
+        let tree = node(
+            "crane",
+            vec![(
+                "fight",
+                node(
+                    "sight",
+                    vec![
+                        ("fight", node("fight", vec![])),
+                        ("light", node("light", vec![])),
+                    ],
+                ),
+            )],
+        );
+        let book = OpeningBook::from_tree(&tree, 2);
+        let first_hint = WordHint::from_guess_and_answer(&word("crane"), &word("fight"));
+        let second_hint = WordHint::from_guess_and_answer(&word("sight"), &word("light"));
+
+        assert_eq!(book.next_guess(&[]), Some(word("crane")));
+        assert_eq!(book.next_guess(&[first_hint]), Some(word("sight")));
+        assert_eq!(book.next_guess(&[first_hint, second_hint]), None);
+        assert_eq!(book.lookup(&[first_hint]).unwrap().next, BTreeMap::new());
+
+        let round_tripped = OpeningBook::from_json(&book.to_json()).unwrap();
+        assert_eq!(round_tripped, book);
+    }
+
+    #[test]
+    fn test_remaining_masks() {
+        let answers: Vec<Word<5, 26>> = ["fight", "light", "board"]
+            .iter()
+            .map(|raw| word(raw))
+            .collect();
+        let tree = node(
+            "crane",
+            vec![(
+                "fight",
+                node(
+                    "sight",
+                    vec![
+                        ("fight", node("fight", vec![])),
+                        ("light", node("light", vec![])),
+                    ],
+                ),
+            )],
+        );
+        let list = WordListId::new("answers", &answers);
+        let book = OpeningBook::from_tree(&tree, 2).with_remaining_masks(&list, &answers);
+        let first_hint = WordHint::from_guess_and_answer(&word("crane"), &word("fight"));
+        let remaining = book
+            .lookup(&[first_hint])
+            .unwrap()
+            .remaining
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            remaining.decode(&list).unwrap(),
+            Column::from_bools(&[true, true, false])
+        );
+        assert_eq!(OpeningBook::from_json(&book.to_json()).unwrap(), book);
+    }
+
+    #[test]
+    fn test_solve_below() {
+        let answers: Vec<Word<5, 26>> = ["fight", "light", "might", "night", "sight", "board"]
+            .iter()
+            .map(|raw| word(raw))
+            .collect();
+        let mut guesses = answers.clone();
+        guesses.extend([word("crane"), word("films")]);
+        let all_hints = compute_hints(&guesses, &answers);
+
+        // The book only fixes the opening, leaving the _IGHT words to the search
+        let book = OpeningBook::from_tree(&node("crane", vec![]), 1);
+        let tree = book
+            .solve_below(
+                &all_hints,
+                &guesses,
+                &answers,
+                6,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+            .unwrap();
+        let readable = ReadableTreeNode::from_generalized_tree_node(&tree, &guesses, &answers);
+        assert_eq!(readable.should_guess, word("crane"));
+
+        let total_guesses: usize = answers
+            .iter()
+            .map(|answer| readable.play_out(answer).unwrap().len())
+            .sum();
+        assert_eq!(tree.total_guesses as usize, total_guesses);
+
+        // Fixing a guess that isn't allowed is an error rather than a panic
+        let bad_book = OpeningBook::from_tree(&node("zebra", vec![]), 1);
+        assert!(
+            bad_book
+                .solve_below(
+                    &all_hints,
+                    &guesses,
+                    &answers,
+                    6,
+                    SearchOptions::default(),
+                    None::<&NoPrinter>,
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let mut book = OpeningBook::from_tree(&node("crane", vec![]), 1);
+        book.version += 1;
+        assert!(matches!(
+            OpeningBook::<5>::from_json(&book.to_json()),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+    }
+}
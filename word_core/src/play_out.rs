@@ -0,0 +1,132 @@
+use crate::{
+    decision_tree_general::{GuessFrom, TreeNode},
+    error::WordGameError,
+    hint::WordHint,
+    word::Word,
+};
+
+/// Look up the word a tree node asks to guess.
+pub fn resolve_guess<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    should_guess: &GuessFrom,
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Word<WORD_SIZE, ALPHABET_SIZE> {
+    match should_guess {
+        GuessFrom::Guess(guess_ind) => allowed_guesses[*guess_ind as usize],
+        GuessFrom::Answer(answer_ind) => possible_answers[*answer_ind as usize],
+    }
+}
+
+/// Follow a decision tree against a specific answer, returning every guess and its hint.
+///
+/// The last entry is the all-correct hint for the answer, unless the tree runs out of nodes
+/// before reaching it, in which case an error is returned instead.
+pub fn play_out<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    tree: &TreeNode,
+    answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Result<Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)>, WordGameError> {
+    play_out_with(
+        tree,
+        answer,
+        |node| resolve_guess(&node.should_guess, allowed_guesses, possible_answers),
+        |node, hint| node.next.get(&hint.hint_id()),
+    )
+}
+
+/// Follow any kind of tree against a specific answer, as `play_out` does, given how to find the
+/// guess at a node and the node under it for a hint.
+pub fn play_out_with<'a, N, const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    tree: &'a N,
+    answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    guess_at: impl Fn(&'a N) -> Word<WORD_SIZE, ALPHABET_SIZE>,
+    next: impl Fn(&'a N, &WordHint<WORD_SIZE>) -> Option<&'a N>,
+) -> Result<Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)>, WordGameError> {
+    let mut transcript = vec![];
+    let mut node = tree;
+    loop {
+        let guess = guess_at(node);
+        let hint = WordHint::from_guess_and_answer(&guess, answer);
+        transcript.push((guess, hint));
+        if hint.all_correct() {
+            return Ok(transcript);
+        }
+        node = next(node, &hint).ok_or_else(|| WordGameError::IncompleteTree {
+            num_guesses: transcript.len(),
+            hint: hint.to_string(),
+        })?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::test_words::words;
+
+    fn leaf(should_guess: GuessFrom) -> TreeNode {
+        TreeNode {
+            should_guess,
+            total_guesses: 1,
+            num_answers: 1,
+            next: HashMap::new(),
+            approximate: false,
+            alternatives: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_play_out() {
+        let allowed_guesses = words(&["lemon"]);
+        let possible_answers = words(&["fight", "light", "sight"]);
+        let hint_for = |answer_ind: usize| {
+            WordHint::from_guess_and_answer(&allowed_guesses[0], &possible_answers[answer_ind])
+                .hint_id()
+        };
+        let tree = TreeNode {
+            should_guess: GuessFrom::Guess(0),
+            total_guesses: 6,
+            num_answers: 3,
+            next: HashMap::from([
+                (hint_for(0), leaf(GuessFrom::Answer(0))),
+                (hint_for(1), leaf(GuessFrom::Answer(1))),
+            ]),
+            approximate: false,
+            alternatives: Vec::new(),
+        };
+
+        let transcript = play_out(
+            &tree,
+            &possible_answers[1],
+            &allowed_guesses,
+            &possible_answers,
+        )
+        .unwrap();
+        assert_eq!(
+            transcript,
+            vec![
+                (allowed_guesses[0], WordHint::try_from("√XXXX").unwrap()),
+                (possible_answers[1], WordHint::try_from("√√√√√").unwrap()),
+            ]
+        );
+
+        // FIGHT and SIGHT give the same hint for LEMON, so the tree misses SIGHT
+        assert_eq!(hint_for(0), hint_for(2));
+        let err = play_out(
+            &tree,
+            &possible_answers[2],
+            &allowed_guesses,
+            &possible_answers,
+        )
+        .unwrap_err();
+        // The hint renders in whichever mode is set, so compare it parsed back
+        assert!(matches!(
+            err,
+            WordGameError::IncompleteTree { num_guesses: 2, ref hint }
+                if WordHint::<5>::try_from(hint.as_str()).unwrap()
+                    == WordHint::try_from("X√√√√").unwrap()
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{
+    column::Column,
+    hint::WordHint,
+    query_generation::{clue_possible, clue_to_query},
+    word::Word,
+    word_search::SearchableWords,
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+/// For every guess, which hints some remaining answer would still give it, as a bitmask over
+/// hint ids.
+///
+/// Solvers can iterate only the feasible hints of a guess rather than all `3^WORD_SIZE`. The
+/// sets are kept up to date as answers are ruled out, without rescanning the answers that
+/// remain.
+pub struct PossibleHints<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    answers: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+
+    /// Which answers are still possible
+    remaining: Column,
+
+    /// Number of remaining answers giving each hint id, indexed `[guess_ind][hint_id]`
+    counts: Vec<Vec<u32>>,
+
+    /// Hint ids with a nonzero count, one mask per guess
+    feasible: Vec<Column>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> PossibleHints<WORD_SIZE, ALPHABET_SIZE> {
+    /// Find the feasible hints of every guess with all answers still possible, running one
+    /// query per hint that the guess could ever receive.
+    pub fn build(
+        guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        answers: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ) -> Self {
+        let num_hints = 3usize.pow(WORD_SIZE as u32);
+        let searchable_answers = SearchableWords::build(answers.clone());
+        let counts: Vec<Vec<u32>> =
+            WorkerPool::new(PoolConfig::default()).map_or_unwind(&guesses, |_, guess| {
+                let mut counts = vec![0; num_hints];
+                for hint in WordHint::all_possible() {
+                    if !clue_possible(*guess, hint) {
+                        continue;
+                    }
+                    counts[hint.hint_id() as usize] = searchable_answers
+                        .eval_query(clue_to_query(*guess, hint))
+                        .count_true() as u32;
+                }
+                counts
+            });
+        let feasible = counts
+            .iter()
+            .map(|counts| {
+                Column::from_bools(&counts.iter().map(|count| *count > 0).collect::<Vec<_>>())
+            })
+            .collect();
+        Self {
+            remaining: Column::from_true(answers.len()),
+            guesses,
+            answers,
+            counts,
+            feasible,
+        }
+    }
+
+    /// The feasible hint ids of a guess, as a mask over all `3^WORD_SIZE` hint ids.
+    pub fn feasible(&self, guess_ind: usize) -> &Column {
+        &self.feasible[guess_ind]
+    }
+
+    /// Iterate the hints of a guess that some remaining answer would give.
+    pub fn iter_feasible(&self, guess_ind: usize) -> impl Iterator<Item = WordHint<WORD_SIZE>> {
+        self.feasible[guess_ind]
+            .iter_true()
+            .map(|hint_id| WordHint::from_id(hint_id as u8))
+    }
+
+    /// How many remaining answers would give a guess this hint.
+    pub fn num_answers_giving(&self, guess_ind: usize, hint: WordHint<WORD_SIZE>) -> u32 {
+        self.counts[guess_ind][hint.hint_id() as usize]
+    }
+
+    /// Rule out a single answer, updating every guess's feasible hints. Does nothing if it was
+    /// already ruled out.
+    pub fn remove_answer(&mut self, answer_ind: usize) {
+        if !self.remaining.get(answer_ind) {
+            return;
+        }
+        self.remaining.set(answer_ind, false);
+        let answer = self.answers[answer_ind];
+        let hint_ids = WorkerPool::new(PoolConfig::default())
+            .map_or_unwind(&self.guesses, |_, guess| {
+                WordHint::from_guess_and_answer(guess, &answer).hint_id() as usize
+            });
+        for ((counts, feasible), hint_id) in self
+            .counts
+            .iter_mut()
+            .zip(self.feasible.iter_mut())
+            .zip(hint_ids)
+        {
+            counts[hint_id] -= 1;
+            if counts[hint_id] == 0 {
+                feasible.set(hint_id, false);
+            }
+        }
+    }
+
+    /// Rule out every answer outside the mask, such as after receiving a hint.
+    pub fn retain(&mut self, mask: &Column) {
+        let removed: Vec<usize> = self
+            .remaining
+            .iter_true()
+            .filter(|answer_ind| !mask.get(*answer_ind))
+            .collect();
+        for answer_ind in removed {
+            self.remove_answer(answer_ind);
+        }
+    }
+
+    /// Which answers are still possible.
+    pub fn remaining(&self) -> &Column {
+        &self.remaining
+    }
+
+    pub fn guesses(&self) -> &[Word<WORD_SIZE, ALPHABET_SIZE>] {
+        &self.guesses
+    }
+
+    pub fn answers(&self) -> &[Word<WORD_SIZE, ALPHABET_SIZE>] {
+        &self.answers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::test_words::words;
+
+    fn assert_matches_direct_scan(possible_hints: &PossibleHints<5, 26>) {
+        for (guess_ind, guess) in possible_hints.guesses().iter().enumerate() {
+            let expected: HashSet<WordHint<5>> = possible_hints
+                .remaining()
+                .iter_true()
+                .map(|answer_ind| {
+                    WordHint::from_guess_and_answer(guess, &possible_hints.answers()[answer_ind])
+                })
+                .collect();
+            let found: HashSet<WordHint<5>> = possible_hints.iter_feasible(guess_ind).collect();
+            assert_eq!(found, expected);
+            for hint in &expected {
+                let num_answers = possible_hints
+                    .remaining()
+                    .iter_true()
+                    .filter(|answer_ind| {
+                        WordHint::from_guess_and_answer(
+                            guess,
+                            &possible_hints.answers()[*answer_ind],
+                        ) == *hint
+                    })
+                    .count();
+                assert_eq!(
+                    possible_hints.num_answers_giving(guess_ind, *hint),
+                    num_answers as u32
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_feasible_hints_track_remaining_answers() {
+        let answers = words(&[
+            "bread", "board", "abbey", "eerie", "geese", "fight", "light",
+        ]);
+        let guesses = words(&["board", "eerie", "might", "pumps"]);
+        let mut possible_hints = PossibleHints::build(guesses, answers);
+        assert_matches_direct_scan(&possible_hints);
+
+        possible_hints.remove_answer(3);
+        possible_hints.remove_answer(3);
+        assert_matches_direct_scan(&possible_hints);
+
+        possible_hints.retain(&Column::from_bools(&[
+            true, false, true, true, false, false, true,
+        ]));
+        assert_eq!(possible_hints.remaining().true_inds(), [0, 2, 6]);
+        assert_matches_direct_scan(&possible_hints);
+
+        // PUMPS shares nothing with the answers, so only the all-nowhere hint is left
+        assert_eq!(possible_hints.feasible(3).count_true(), 1);
+    }
+}
@@ -0,0 +1,463 @@
+use std::{
+    collections::BTreeMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use crate::{
+    decision_tree_general::{NoPrinter, SearchOptions, compute_decision_tree_aggressive},
+    error::WordGameError,
+    hint::WordHint,
+    hint_matrix::{HintMatrix, answers_first, compute_hints},
+    opening_book::OpeningBook,
+    readable_tree::ReadableTreeNode,
+    suggestion::{partition_by_hint, rank_suggestions},
+    word::Word,
+    worker_pool::panic_message,
+};
+
+/// What to warm, and how.
+#[derive(Debug, Clone)]
+pub struct PrecomputeConfig<const WORD_SIZE: usize> {
+    /// Openers to build second-guess tables for
+    pub openers: Vec<Word<WORD_SIZE, 26>>,
+
+    /// Plies of the solved tree to keep in the opening book, or 0 to skip solving
+    pub book_plies: u8,
+
+    /// Search settings for the tree the opening book comes from
+    pub max_depth: u8,
+    pub options: SearchOptions,
+}
+
+impl<const WORD_SIZE: usize> Default for PrecomputeConfig<WORD_SIZE> {
+    fn default() -> Self {
+        Self {
+            openers: vec![],
+            book_plies: 2,
+            max_depth: 6,
+            options: SearchOptions::default(),
+        }
+    }
+}
+
+/// The progress of one precomputation stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StageStatus {
+    Pending,
+    Running,
+
+    /// Swapped in, after taking this long to compute
+    Ready {
+        millis: u64,
+    },
+
+    /// Not requested by the config
+    Skipped,
+
+    Failed(String),
+}
+
+impl StageStatus {
+    /// Whether the stage won't change any further.
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Ready { .. } | Self::Skipped | Self::Failed(_))
+    }
+}
+
+/// The progress of every precomputation stage, in the order they run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecomputeStatus {
+    pub hint_matrix: StageStatus,
+    pub second_guesses: StageStatus,
+    pub opening_book: StageStatus,
+}
+
+impl PrecomputeStatus {
+    /// Whether every stage is done.
+    pub fn is_finished(&self) -> bool {
+        self.hint_matrix.is_done() && self.second_guesses.is_done() && self.opening_book.is_done()
+    }
+}
+
+/// The best second guess after each hint to an opener, keyed by hint id. The all-correct hint
+/// has no entry.
+pub type SecondGuessTable<const WORD_SIZE: usize> = BTreeMap<u8, Word<WORD_SIZE, 26>>;
+
+/// Whatever caches are ready so far. Each is `None` until its stage finishes.
+#[derive(Debug, Clone)]
+pub struct WarmCaches<const WORD_SIZE: usize> {
+    /// Hints over the answers-first guess list, as the solvers expect
+    pub hint_matrix: Option<Arc<HintMatrix>>,
+
+    /// The allowed guesses reordered to put the answers first, indexing the hint matrix rows
+    pub guesses: Arc<Vec<Word<WORD_SIZE, 26>>>,
+
+    pub second_guesses: Option<Arc<BTreeMap<Word<WORD_SIZE, 26>, SecondGuessTable<WORD_SIZE>>>>,
+    pub opening_book: Option<Arc<OpeningBook<WORD_SIZE>>>,
+}
+
+struct Shared<const WORD_SIZE: usize> {
+    caches: RwLock<Arc<WarmCaches<WORD_SIZE>>>,
+    status: Mutex<PrecomputeStatus>,
+}
+
+impl<const WORD_SIZE: usize> Shared<WORD_SIZE> {
+    /// A stage's panic is caught before it can leave a lock poisoned, but a poisoned lock
+    /// still holds a whole value, as every update replaces it in one step, so it's recovered
+    /// rather than spreading the panic to readers.
+    fn set_status(&self, update: impl FnOnce(&mut PrecomputeStatus)) {
+        update(&mut self.status.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// Publish a new set of caches built from the current one, so readers see either all of
+    /// an update or none of it.
+    fn swap_in(&self, update: impl FnOnce(&mut WarmCaches<WORD_SIZE>)) {
+        let mut caches = self.caches.write().unwrap_or_else(PoisonError::into_inner);
+        let mut next = WarmCaches::clone(&caches);
+        update(&mut next);
+        *caches = Arc::new(next);
+    }
+}
+
+/// Warms caches on a background thread, so a server can start answering right away and pick
+/// up each cache as soon as it's ready.
+///
+/// Readers take a [`WarmCaches`] snapshot, which never changes under them. Finished caches
+/// are swapped in by replacing the snapshot, so each read sees a consistent set.
+pub struct Precomputer<const WORD_SIZE: usize> {
+    shared: Arc<Shared<WORD_SIZE>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<const WORD_SIZE: usize> Precomputer<WORD_SIZE> {
+    /// Start warming caches for the given lists in the background.
+    pub fn start(
+        allowed_guesses: Vec<Word<WORD_SIZE, 26>>,
+        possible_answers: Vec<Word<WORD_SIZE, 26>>,
+        config: PrecomputeConfig<WORD_SIZE>,
+    ) -> Self {
+        let guesses = answers_first(&possible_answers, &allowed_guesses);
+        let shared = Arc::new(Shared {
+            caches: RwLock::new(Arc::new(WarmCaches {
+                hint_matrix: None,
+                guesses: Arc::new(guesses),
+                second_guesses: None,
+                opening_book: None,
+            })),
+            status: Mutex::new(PrecomputeStatus {
+                hint_matrix: StageStatus::Pending,
+                second_guesses: StageStatus::Pending,
+                opening_book: if config.book_plies == 0 {
+                    StageStatus::Skipped
+                } else {
+                    StageStatus::Pending
+                },
+            }),
+        });
+        let handle = {
+            let shared = shared.clone();
+            thread::spawn(move || run(&shared, &allowed_guesses, &possible_answers, &config))
+        };
+        Self {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    /// Get the caches that are ready right now.
+    pub fn snapshot(&self) -> Arc<WarmCaches<WORD_SIZE>> {
+        self.shared
+            .caches
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the progress of each stage.
+    pub fn status(&self) -> PrecomputeStatus {
+        self.shared
+            .status
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Block until every stage is done, then get the final caches.
+    pub fn wait(&mut self) -> Arc<WarmCaches<WORD_SIZE>> {
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("precomputation thread panicked");
+        }
+        self.snapshot()
+    }
+}
+
+/// Run every stage in turn, cheapest first, swapping each result in as it's ready.
+fn run<const WORD_SIZE: usize>(
+    shared: &Shared<WORD_SIZE>,
+    allowed_guesses: &[Word<WORD_SIZE, 26>],
+    possible_answers: &[Word<WORD_SIZE, 26>],
+    config: &PrecomputeConfig<WORD_SIZE>,
+) {
+    let guesses = shared
+        .caches
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .guesses
+        .clone();
+
+    let hints = run_stage(
+        shared,
+        |status| &mut status.hint_matrix,
+        || Ok(Arc::new(compute_hints(&guesses, possible_answers))),
+        |caches, hints| caches.hint_matrix = Some(hints.clone()),
+    );
+
+    run_stage(
+        shared,
+        |status| &mut status.second_guesses,
+        || {
+            let tables = config
+                .openers
+                .iter()
+                .map(|opener| {
+                    Ok((
+                        *opener,
+                        second_guess_table(opener, allowed_guesses, possible_answers)?,
+                    ))
+                })
+                .collect::<Result<BTreeMap<_, _>, WordGameError>>()?;
+            Ok(Arc::new(tables))
+        },
+        |caches, tables| caches.second_guesses = Some(tables.clone()),
+    );
+
+    if config.book_plies == 0 {
+        return;
+    }
+    let Some(hints) = hints else {
+        shared.set_status(|status| {
+            status.opening_book = StageStatus::Failed("no hint matrix to solve over".to_string())
+        });
+        return;
+    };
+    run_stage(
+        shared,
+        |status| &mut status.opening_book,
+        || solve_book(&hints, &guesses, possible_answers, config).map(Arc::new),
+        |caches, book| caches.opening_book = Some(book.clone()),
+    );
+}
+
+/// Run one stage, publishing its result and marking it ready, or marking it failed if it
+/// errors or panics. A panic is contained to its stage, so the status never stays running.
+fn run_stage<const WORD_SIZE: usize, T>(
+    shared: &Shared<WORD_SIZE>,
+    stage: fn(&mut PrecomputeStatus) -> &mut StageStatus,
+    compute: impl FnOnce() -> Result<T, WordGameError>,
+    publish: impl FnOnce(&mut WarmCaches<WORD_SIZE>, &T),
+) -> Option<T> {
+    shared.set_status(|status| *stage(status) = StageStatus::Running);
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(compute)).unwrap_or_else(|payload| {
+        Err(WordGameError::WorkerPanicked(panic_message(
+            payload.as_ref(),
+        )))
+    });
+    match result {
+        Ok(value) => {
+            shared.swap_in(|caches| publish(caches, &value));
+            shared.set_status(|status| *stage(status) = ready(start));
+            Some(value)
+        }
+        Err(err) => {
+            shared.set_status(|status| *stage(status) = StageStatus::Failed(err.to_string()));
+            None
+        }
+    }
+}
+
+fn ready(start: Instant) -> StageStatus {
+    StageStatus::Ready {
+        millis: start.elapsed().as_millis() as u64,
+    }
+}
+
+/// Pick the best second guess for each hint the opener can get, by expected remaining
+/// candidates. Fails if a hint leaves several candidates and there are no allowed guesses to
+/// tell them apart with.
+fn second_guess_table<const WORD_SIZE: usize>(
+    opener: &Word<WORD_SIZE, 26>,
+    allowed_guesses: &[Word<WORD_SIZE, 26>],
+    possible_answers: &[Word<WORD_SIZE, 26>],
+) -> Result<SecondGuessTable<WORD_SIZE>, WordGameError> {
+    partition_by_hint(opener, possible_answers)
+        .into_iter()
+        .filter(|(hint_id, _)| !WordHint::<WORD_SIZE>::from_id(*hint_id).all_correct())
+        .map(|(hint_id, bucket)| {
+            let guess = match bucket.as_slice() {
+                [only] => *only,
+                bucket => {
+                    rank_suggestions(allowed_guesses, bucket, 1)
+                        .first()
+                        .ok_or_else(|| {
+                            WordGameError::Infeasible(format!(
+                                "no allowed guesses to follow {opener} with"
+                            ))
+                        })?
+                        .guess
+                }
+            };
+            Ok((hint_id, guess))
+        })
+        .collect()
+}
+
+fn solve_book<const WORD_SIZE: usize>(
+    hints: &HintMatrix,
+    guesses: &[Word<WORD_SIZE, 26>],
+    possible_answers: &[Word<WORD_SIZE, 26>],
+    config: &PrecomputeConfig<WORD_SIZE>,
+) -> Result<OpeningBook<WORD_SIZE>, WordGameError> {
+    let tree = compute_decision_tree_aggressive(
+        hints,
+        (0..possible_answers.len() as u16).collect(),
+        0,
+        config.max_depth,
+        f64::INFINITY,
+        config.options.clone(),
+        None::<&NoPrinter>,
+    )
+    .ok_or_else(|| {
+        WordGameError::Infeasible(format!("no tree within depth {}", config.max_depth))
+    })?;
+    let tree = ReadableTreeNode::from_generalized_tree_node(&tree, guesses, possible_answers);
+    Ok(OpeningBook::from_tree(&tree, config.book_plies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words::words;
+
+    #[test]
+    fn test_precompute_in_background() {
+        let answers = words(&["fight", "light", "might", "night", "sight", "board"]);
+        let mut allowed = words(&["flint", "moist"]);
+        allowed.extend(&answers);
+        let opener = Word::try_from("flint").unwrap();
+        let mut precomputer = Precomputer::start(
+            allowed.clone(),
+            answers.clone(),
+            PrecomputeConfig {
+                openers: vec![opener],
+                ..PrecomputeConfig::default()
+            },
+        );
+        // Whatever has or hasn't been swapped in yet, the guess order is there from the start
+        assert_eq!(
+            &precomputer.snapshot().guesses[..answers.len()],
+            &answers[..]
+        );
+
+        let caches = precomputer.wait();
+        assert!(precomputer.status().is_finished());
+        assert!(matches!(
+            precomputer.status().opening_book,
+            StageStatus::Ready { .. }
+        ));
+        let hints = caches.hint_matrix.as_ref().unwrap();
+        assert_eq!(hints.num_guesses(), allowed.len());
+        assert_eq!(hints.num_answers(), answers.len());
+        assert_eq!(
+            caches.opening_book.as_ref().unwrap().num_plies,
+            PrecomputeConfig::<5>::default().book_plies
+        );
+
+        // Flint leaves only might and sight together, so every other answer is guessed directly
+        let table = &caches.second_guesses.as_ref().unwrap()[&opener];
+        let light_hint = WordHint::from_guess_and_answer(&opener, &answers[1]).hint_id();
+        assert_eq!(table[&light_hint], answers[1]);
+        let board_hint = WordHint::from_guess_and_answer(&opener, &answers[5]).hint_id();
+        assert_eq!(table[&board_hint], answers[5]);
+        assert_eq!(table.len(), 5);
+    }
+
+    #[test]
+    fn test_failed_stage_is_reported() {
+        let answers = words(&["fight", "light", "might", "night", "sight"]);
+        let mut precomputer = Precomputer::start(
+            answers.clone(),
+            answers,
+            PrecomputeConfig {
+                max_depth: 1,
+                ..PrecomputeConfig::default()
+            },
+        );
+        let caches = precomputer.wait();
+        assert!(caches.hint_matrix.is_some());
+        assert!(caches.opening_book.is_none());
+        let status = precomputer.status();
+        assert!(status.is_finished());
+        assert!(matches!(status.opening_book, StageStatus::Failed(_)));
+    }
+
+    #[test]
+    fn test_failed_second_guesses_are_reported() {
+        // With no allowed guesses, there's no second guess for a shared bucket
+        let answers = words(&["fight", "light", "might", "night", "sight"]);
+        let mut precomputer = Precomputer::start(
+            vec![],
+            answers,
+            PrecomputeConfig {
+                openers: vec![Word::try_from("flint").unwrap()],
+                ..PrecomputeConfig::default()
+            },
+        );
+        let caches = precomputer.wait();
+        let status = precomputer.status();
+        assert!(status.is_finished());
+        assert_eq!(
+            status.second_guesses,
+            StageStatus::Failed(
+                "no solution found: no allowed guesses to follow FLINT with".to_string()
+            )
+        );
+        assert!(caches.second_guesses.is_none());
+        // Later stages still run
+        assert!(matches!(status.opening_book, StageStatus::Ready { .. }));
+    }
+
+    #[test]
+    fn test_panicked_stage_is_reported() {
+        let mut precomputer = Precomputer::<5>::start(
+            vec![],
+            vec![],
+            PrecomputeConfig {
+                book_plies: 0,
+                ..PrecomputeConfig::default()
+            },
+        );
+        precomputer.wait();
+        let shared = &precomputer.shared;
+
+        // A panic marks only its own stage failed, and leaves no lock poisoned
+        let result = run_stage(
+            shared,
+            |status| &mut status.opening_book,
+            || -> Result<(), WordGameError> { panic!("book exploded") },
+            |_, _| unreachable!(),
+        );
+        assert!(result.is_none());
+        let status = precomputer.status();
+        assert_eq!(
+            status.opening_book,
+            StageStatus::Failed("worker panicked: book exploded".to_string())
+        );
+        assert!(matches!(status.hint_matrix, StageStatus::Ready { .. }));
+        assert!(!shared.status.is_poisoned());
+        assert!(!shared.caches.is_poisoned());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use crate::word::Word;
+
+/// A soft preference between guesses, consulted only to break ties between guesses that are
+/// otherwise equally good.
+pub trait Preference<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    /// How much the guess goes against this preference, lower is preferred.
+    fn penalty(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> f64;
+}
+
+/// Prefer guesses from a list of common words over obscure ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvoidRareWords<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    common: HashSet<Word<WORD_SIZE, ALPHABET_SIZE>>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> AvoidRareWords<WORD_SIZE, ALPHABET_SIZE> {
+    pub fn new(common: &[Word<WORD_SIZE, ALPHABET_SIZE>]) -> Self {
+        Self {
+            common: common.iter().copied().collect(),
+        }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Preference<WORD_SIZE, ALPHABET_SIZE>
+    for AvoidRareWords<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn penalty(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> f64 {
+        if self.common.contains(guess) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Prefer guesses that try letters no previous guess has, penalizing each distinct letter
+/// already tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreferUnusedLetters {
+    used: HashSet<u8>,
+}
+
+impl PreferUnusedLetters {
+    pub fn new<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        previous_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> Self {
+        Self {
+            used: previous_guesses.iter().flat_map(|guess| guess.0).collect(),
+        }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Preference<WORD_SIZE, ALPHABET_SIZE>
+    for PreferUnusedLetters
+{
+    fn penalty(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> f64 {
+        let letters: HashSet<u8> = guess.0.into_iter().collect();
+        letters.intersection(&self.used).count() as f64
+    }
+}
+
+/// Prefer guesses that could be an answer, so a lucky guess can still win.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreferAnswers<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    answers: HashSet<Word<WORD_SIZE, ALPHABET_SIZE>>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> PreferAnswers<WORD_SIZE, ALPHABET_SIZE> {
+    pub fn new(answers: &[Word<WORD_SIZE, ALPHABET_SIZE>]) -> Self {
+        Self {
+            answers: answers.iter().copied().collect(),
+        }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Preference<WORD_SIZE, ALPHABET_SIZE>
+    for PreferAnswers<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn penalty(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> f64 {
+        if self.answers.contains(guess) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Order two guesses by each preference in turn, earlier preferences taking priority.
+pub fn compare_preferences<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    preferences: &[&dyn Preference<WORD_SIZE, ALPHABET_SIZE>],
+    a: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    b: &Word<WORD_SIZE, ALPHABET_SIZE>,
+) -> Ordering {
+    preferences
+        .iter()
+        .map(|preference| preference.penalty(a).total_cmp(&preference.penalty(b)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Rank every guess by the preferences, lower is preferred and equally preferred guesses share
+/// a rank. This is the form the solvers take preferences in, as `SearchOptions::guess_ranks`.
+pub fn preference_ranks<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    preferences: &[&dyn Preference<WORD_SIZE, ALPHABET_SIZE>],
+    guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Vec<u32> {
+    let mut order: Vec<usize> = (0..guesses.len()).collect();
+    order.sort_by(|a, b| compare_preferences(preferences, &guesses[*a], &guesses[*b]));
+    let mut ranks = vec![0; guesses.len()];
+    let mut rank = 0;
+    for (pos, guess_ind) in order.iter().enumerate() {
+        if pos > 0
+            && compare_preferences(preferences, &guesses[order[pos - 1]], &guesses[*guess_ind])
+                .is_ne()
+        {
+            rank += 1;
+        }
+        ranks[*guess_ind] = rank;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words::words;
+
+    #[test]
+    fn test_preference_ranks() {
+        let guesses = words(&["fight", "xylyl", "board", "night"]);
+        let answers = PreferAnswers::new(&guesses[..1]);
+        let common = AvoidRareWords::new(&words(&["fight", "board", "night"]));
+        let unused = PreferUnusedLetters::new(&words(&["light"]));
+
+        assert_eq!(unused.penalty(&guesses[0]), 4.0);
+        assert_eq!(unused.penalty(&guesses[1]), 1.0);
+        assert_eq!(preference_ranks(&[], &guesses), vec![0, 0, 0, 0]);
+        assert_eq!(preference_ranks(&[&common], &guesses), vec![0, 1, 0, 0]);
+        // Earlier preferences come first, later ones only split their ties
+        assert_eq!(
+            preference_ranks(&[&common, &answers], &guesses),
+            vec![0, 2, 1, 1]
+        );
+        assert_eq!(
+            preference_ranks(&[&common, &answers, &unused], &guesses),
+            vec![0, 3, 1, 2]
+        );
+    }
+}
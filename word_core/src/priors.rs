@@ -0,0 +1,89 @@
+use std::{collections::HashMap, fs};
+
+use crate::{error::WordGameError, word::Word};
+
+/// Relative likelihoods of words being the answer, e.g. from word frequencies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnswerPriors<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    weights: HashMap<Word<WORD_SIZE, ALPHABET_SIZE>, f64>,
+
+    /// Weight given to words with no explicit weight
+    default_weight: f64,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> AnswerPriors<WORD_SIZE, ALPHABET_SIZE> {
+    /// Priors where every word is equally likely.
+    pub fn uniform() -> Self {
+        Self {
+            weights: HashMap::new(),
+            default_weight: 1.0,
+        }
+    }
+
+    /// Priors from explicit weights, with `default_weight` for any word not listed.
+    pub fn from_weights(
+        weights: impl IntoIterator<Item = (Word<WORD_SIZE, ALPHABET_SIZE>, f64)>,
+        default_weight: f64,
+    ) -> Self {
+        Self {
+            weights: weights.into_iter().collect(),
+            default_weight,
+        }
+    }
+
+    /// Load priors from a file with one `word weight` pair per line.
+    pub fn load(file_path: &str, default_weight: f64) -> Result<Self, WordGameError> {
+        let file = fs::read_to_string(file_path).map_err(|source| WordGameError::Io {
+            path: file_path.into(),
+            source,
+        })?;
+        let weights = file
+            .split("\n")
+            .map(|row| row.trim())
+            .filter(|row| !row.is_empty())
+            .map(|row| {
+                let (raw_word, raw_weight) =
+                    row.split_once(char::is_whitespace).ok_or_else(|| {
+                        WordGameError::FormatMismatch {
+                            expected: "word and weight".to_string(),
+                            found: row.to_string(),
+                        }
+                    })?;
+                let weight: f64 =
+                    raw_weight
+                        .trim()
+                        .parse()
+                        .map_err(|_| WordGameError::FormatMismatch {
+                            expected: "numeric weight".to_string(),
+                            found: raw_weight.to_string(),
+                        })?;
+                Ok((Word::try_from(raw_word)?, weight))
+            })
+            .collect::<Result<HashMap<_, _>, WordGameError>>()?;
+        Ok(Self {
+            weights,
+            default_weight,
+        })
+    }
+
+    /// Get the weight of a single word.
+    pub fn weight(&self, word: &Word<WORD_SIZE, ALPHABET_SIZE>) -> f64 {
+        self.weights
+            .get(word)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+
+    /// Get the weight of each of the given words, in order.
+    pub fn weights_for(&self, words: &[Word<WORD_SIZE, ALPHABET_SIZE>]) -> Vec<f64> {
+        words.iter().map(|word| self.weight(word)).collect()
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Default
+    for AnswerPriors<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
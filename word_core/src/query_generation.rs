@@ -1,7 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::{
-    hint::{CharHint, WordHint},
+    hint::{CharHint, DuplicateScheme, WordHint},
     word::Word,
     word_search::Query,
 };
@@ -35,9 +35,44 @@ pub fn clue_possible<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
     true
 }
 
+/// How a clue query states that a letter appears an exact number of times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CountForm {
+    /// A single `CountExact`, read straight from its precomputed column
+    #[default]
+    Exact,
+
+    /// A `CountAtLeast` and `CountAtMost` pair, for evaluators without exact count columns
+    Bounds,
+}
+
+impl CountForm {
+    /// The query for exactly `count` copies of `chr` in this form.
+    pub fn count_query(self, count: usize, chr: u8) -> Query {
+        match self {
+            CountForm::Exact => Query::CountExact { count, chr },
+            CountForm::Bounds if count == 0 => Query::CountAtMost { count, chr },
+            CountForm::Bounds => Query::And(vec![
+                Query::CountAtLeast { count, chr },
+                Query::CountAtMost { count, chr },
+            ]),
+        }
+    }
+}
+
 pub fn clue_to_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
     guess: Word<WORD_SIZE, ALPHABET_SIZE>,
     word_hint: WordHint<WORD_SIZE>,
+) -> Query {
+    clue_to_query_with_count_form(guess, word_hint, CountForm::Exact)
+}
+
+/// Build the query an answer must satisfy to produce a clue, stating exact letter counts in the
+/// given form.
+pub fn clue_to_query_with_count_form<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    word_hint: WordHint<WORD_SIZE>,
+    count_form: CountForm,
 ) -> Query {
     let mut sub_queries = vec![];
 
@@ -67,7 +102,7 @@ pub fn clue_to_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
             CharHint::Nowhere => {
                 incorrect_chars.insert(guess_char);
                 sub_queries.push(Query::Not(Box::new(Query::Match {
-                    ind: ind,
+                    ind,
                     chr: guess_char,
                 })))
             }
@@ -94,10 +129,7 @@ pub fn clue_to_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
 
         if num_nowhere > 0 {
             // If some showed as Nowhere, we know exactly how many of this char are present
-            sub_queries.push(Query::CountExact {
-                count: num_correct + num_elsewhere,
-                chr: incorrect_char,
-            });
+            sub_queries.push(count_form.count_query(num_correct + num_elsewhere, incorrect_char));
         } else if num_elsewhere > 0 {
             // In this case we have a lower bound on the number of this char that are present
             sub_queries.push(Query::CountAtLeast {
@@ -110,15 +142,416 @@ pub fn clue_to_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
     Query::And(sub_queries)
 }
 
+/// Build one query for every clue in a history at once, using what the clues say together to
+/// drop implied facts and tighten the rest.
+///
+/// The result matches exactly the words that `clue_to_query` would match for every clue, but
+/// combining the rounds lets it bound each letter's count by the positions still open to it.
+/// A letter shown Elsewhere that only fits in as many open positions as it must fill is placed
+/// outright, which can in turn close positions to other letters. A letter excluded from several
+/// positions becomes one `NotAtPositions` leaf. Histories that contradict themselves give a
+/// query matching nothing.
+pub fn history_to_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    history: &[(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)],
+) -> Query {
+    let contradiction = Query::Or(vec![]);
+    let mut known: [Option<u8>; WORD_SIZE] = [None; WORD_SIZE];
+    let mut excluded: [HashSet<u8>; WORD_SIZE] = std::array::from_fn(|_| HashSet::new());
+    let mut min_counts: HashMap<u8, usize> = HashMap::new();
+    let mut exact_counts: HashMap<u8, usize> = HashMap::new();
+
+    for (guess, word_hint) in history {
+        let mut num_present: HashMap<u8, usize> = HashMap::new();
+        let mut any_nowhere: HashSet<u8> = HashSet::new();
+        for ind in 0..WORD_SIZE {
+            let chr = guess.0[ind];
+            match word_hint.0[ind] {
+                CharHint::Correct => {
+                    if known[ind].is_some_and(|known_chr| known_chr != chr) {
+                        return contradiction;
+                    }
+                    known[ind] = Some(chr);
+                    *num_present.entry(chr).or_insert(0) += 1;
+                }
+                CharHint::Elsewhere => {
+                    excluded[ind].insert(chr);
+                    *num_present.entry(chr).or_insert(0) += 1;
+                }
+                CharHint::Nowhere => {
+                    excluded[ind].insert(chr);
+                    any_nowhere.insert(chr);
+                }
+            }
+        }
+        for &chr in guess.0.iter() {
+            let count = num_present.get(&chr).copied().unwrap_or(0);
+            let min_count = min_counts.entry(chr).or_insert(0);
+            *min_count = (*min_count).max(count);
+            if any_nowhere.contains(&chr) && *exact_counts.entry(chr).or_insert(count) != count {
+                return contradiction;
+            }
+        }
+    }
+    for (chr, exact_count) in &exact_counts {
+        min_counts.insert(*chr, *exact_count.max(&min_counts[chr]));
+        if min_counts[chr] > *exact_count {
+            return contradiction;
+        }
+    }
+    if (0..WORD_SIZE).any(|ind| known[ind].is_some_and(|chr| excluded[ind].contains(&chr))) {
+        return contradiction;
+    }
+
+    // Place any letter that has exactly as many open positions as copies it must fill, until
+    // nothing more can be placed
+    let open_positions = |known: &[Option<u8>; WORD_SIZE], chr: u8| -> Vec<usize> {
+        (0..WORD_SIZE)
+            .filter(|ind| match known[*ind] {
+                Some(known_chr) => known_chr == chr,
+                None => !excluded[*ind].contains(&chr),
+            })
+            .collect()
+    };
+    loop {
+        let mut placed_any = false;
+        for (&chr, &min_count) in &min_counts {
+            let positions = open_positions(&known, chr);
+            if positions.len() < min_count {
+                return contradiction;
+            }
+            if min_count > 0 && positions.len() == min_count {
+                for ind in positions {
+                    placed_any |= known[ind].replace(chr).is_none();
+                }
+            }
+        }
+        if !placed_any {
+            break;
+        }
+    }
+
+    let num_known = |chr: u8| known.iter().filter(|known| **known == Some(chr)).count();
+    let mut sub_queries = vec![];
+    let mut excluded_inds: BTreeMap<u8, Vec<usize>> = BTreeMap::new();
+    for ind in 0..WORD_SIZE {
+        match known[ind] {
+            // Every exclusion at a known position is implied by the match
+            Some(chr) => sub_queries.push(Query::Match { ind, chr }),
+            None => {
+                for &chr in &excluded[ind] {
+                    // Implied if the exact count leaves no copies beyond known positions
+                    if exact_counts.get(&chr) != Some(&num_known(chr)) {
+                        excluded_inds.entry(chr).or_default().push(ind);
+                    }
+                }
+            }
+        }
+    }
+    // Fold each letter's exclusions into a single leaf, since deep games pile them up
+    sub_queries.extend(excluded_inds.into_iter().map(|(chr, inds)| {
+        if let [ind] = inds[..] {
+            Query::Not(Box::new(Query::Match { ind, chr }))
+        } else {
+            Query::NotAtPositions { inds, chr }
+        }
+    }));
+    let mut chrs: Vec<u8> = min_counts.keys().copied().collect();
+    chrs.sort_unstable();
+    for chr in chrs {
+        if let Some(&count) = exact_counts.get(&chr) {
+            sub_queries.push(Query::CountExact { count, chr });
+        } else if min_counts[&chr] > num_known(chr) {
+            sub_queries.push(Query::CountAtLeast {
+                count: min_counts[&chr],
+                chr,
+            });
+        }
+    }
+    Query::And(sub_queries)
+}
+
+/// Check whether a clue is possible for a given word when repeated letters are scored by the
+/// given scheme.
+pub fn clue_possible_with_scheme<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    word_hint: WordHint<WORD_SIZE>,
+    scheme: DuplicateScheme,
+) -> bool {
+    match scheme {
+        DuplicateScheme::Standard => clue_possible(guess, word_hint),
+        DuplicateScheme::Lenient => {
+            // Any copy of a letter shown to be present means every copy is shown as present
+            let mut present_chars: HashSet<u8> = HashSet::new();
+            let mut nowhere_chars: HashSet<u8> = HashSet::new();
+            for ind in 0..WORD_SIZE {
+                match word_hint.0[ind] {
+                    CharHint::Nowhere => nowhere_chars.insert(guess.0[ind]),
+                    _ => present_chars.insert(guess.0[ind]),
+                };
+            }
+            present_chars.is_disjoint(&nowhere_chars)
+        }
+        DuplicateScheme::Strict => {
+            // Only the first missed copy of a letter can be shown as Elsewhere
+            let mut missed_chars: HashSet<u8> = HashSet::new();
+            for ind in 0..WORD_SIZE {
+                let guess_char = guess.0[ind];
+                match word_hint.0[ind] {
+                    CharHint::Correct => {}
+                    CharHint::Elsewhere => {
+                        if !missed_chars.insert(guess_char) {
+                            return false;
+                        }
+                    }
+                    CharHint::Nowhere => {
+                        missed_chars.insert(guess_char);
+                    }
+                }
+            }
+            true
+        }
+    }
+}
+
+/// Build the query an answer must satisfy to produce a clue when repeated letters are scored
+/// by the given scheme.
+pub fn clue_to_query_with_scheme<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    word_hint: WordHint<WORD_SIZE>,
+    scheme: DuplicateScheme,
+) -> Query {
+    if scheme == DuplicateScheme::Standard {
+        return clue_to_query(guess, word_hint);
+    }
+
+    let mut sub_queries = vec![];
+    let mut num_per_char_by_hint: HashMap<(u8, CharHint), usize> = HashMap::new();
+    for ind in 0..WORD_SIZE {
+        let guess_char = guess.0[ind];
+        let char_hint = word_hint.0[ind];
+        *num_per_char_by_hint
+            .entry((guess_char, char_hint))
+            .or_insert(0) += 1;
+        let position_query = Query::Match {
+            ind,
+            chr: guess_char,
+        };
+        sub_queries.push(match char_hint {
+            CharHint::Correct => position_query,
+            _ => Query::Not(Box::new(position_query)),
+        });
+    }
+
+    let chars: HashSet<u8> = guess.0.iter().copied().collect();
+    for chr in chars {
+        let num_with_hint = |char_hint| {
+            num_per_char_by_hint
+                .get(&(chr, char_hint))
+                .cloned()
+                .unwrap_or(0)
+        };
+        let num_correct = num_with_hint(CharHint::Correct);
+        let num_elsewhere = num_with_hint(CharHint::Elsewhere);
+        let num_nowhere = num_with_hint(CharHint::Nowhere);
+        match scheme {
+            DuplicateScheme::Standard => unreachable!(),
+            DuplicateScheme::Lenient => {
+                if num_nowhere > 0 {
+                    // Nowhere is only shown for letters missing from the answer entirely
+                    sub_queries.push(Query::CountExact { count: 0, chr });
+                } else if num_elsewhere > 0 && num_correct == 0 {
+                    // Otherwise all we learn is that the letter is present somewhere
+                    sub_queries.push(Query::CountAtLeast { count: 1, chr });
+                }
+            }
+            DuplicateScheme::Strict => {
+                if num_elsewhere > 0 {
+                    // There's at least one more copy, but maybe more that weren't shown
+                    sub_queries.push(Query::CountAtLeast {
+                        count: num_correct + 1,
+                        chr,
+                    });
+                } else if num_nowhere > 0 {
+                    // Any spare copy would have been shown, so there are none
+                    sub_queries.push(Query::CountExact {
+                        count: num_correct,
+                        chr,
+                    });
+                }
+            }
+        }
+    }
+
+    Query::And(sub_queries)
+}
+
+/// Build the query a later guess must satisfy to respect a clue under hard mode.
+///
+/// Correct letters must stay in place, and every revealed letter must appear at least as many
+/// times as it was shown to be present.
+pub fn hard_mode_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    word_hint: WordHint<WORD_SIZE>,
+) -> Query {
+    let mut sub_queries = vec![];
+    let mut num_revealed_per_char: HashMap<u8, usize> = HashMap::new();
+    for ind in 0..WORD_SIZE {
+        let guess_char = guess.0[ind];
+        match word_hint.0[ind] {
+            CharHint::Correct => {
+                sub_queries.push(Query::Match {
+                    ind,
+                    chr: guess_char,
+                });
+                *num_revealed_per_char.entry(guess_char).or_insert(0) += 1;
+            }
+            CharHint::Elsewhere => {
+                *num_revealed_per_char.entry(guess_char).or_insert(0) += 1;
+            }
+            CharHint::Nowhere => {}
+        }
+    }
+    sub_queries.extend(
+        num_revealed_per_char
+            .into_iter()
+            .map(|(chr, count)| Query::CountAtLeast { count, chr }),
+    );
+    Query::And(sub_queries)
+}
+
+/// Build the query a later guess must satisfy to respect a clue under ultra hard mode.
+///
+/// On top of hard mode, letters shown to be absent may not be played again, and a letter shown
+/// both present and absent must appear exactly as many times as it was shown present.
+pub fn ultra_hard_mode_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    word_hint: WordHint<WORD_SIZE>,
+) -> Query {
+    let mut num_revealed_per_char: HashMap<u8, usize> = HashMap::new();
+    let mut nowhere_chars: HashSet<u8> = HashSet::new();
+    for ind in 0..WORD_SIZE {
+        let guess_char = guess.0[ind];
+        match word_hint.0[ind] {
+            CharHint::Correct | CharHint::Elsewhere => {
+                *num_revealed_per_char.entry(guess_char).or_insert(0) += 1;
+            }
+            CharHint::Nowhere => {
+                nowhere_chars.insert(guess_char);
+            }
+        }
+    }
+    let mut sub_queries = vec![hard_mode_query(guess, word_hint)];
+    // A nowhere tile means every copy of the letter was already shown
+    sub_queries.extend(nowhere_chars.into_iter().map(|chr| Query::CountExact {
+        count: num_revealed_per_char.get(&chr).copied().unwrap_or(0),
+        chr,
+    }));
+    Query::And(sub_queries)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{column::Column, word_search::SearchableWords};
+
+    #[test]
+    fn test_scheme_queries_match_hints() {
+        let words: Vec<Word<5, 26>> = [
+            "aazzz", "zzaab", "abcba", "bbbxx", "ababa", "ccbbc", "board", "bread", "eerie",
+            "there", "geese", "sheep", "llama", "allay", "xxxxx", "bzazb",
+        ]
+        .iter()
+        .map(|raw| Word::try_from(*raw).unwrap())
+        .collect();
+        let searchable = SearchableWords::build(words.clone());
+
+        for scheme in [
+            DuplicateScheme::Standard,
+            DuplicateScheme::Lenient,
+            DuplicateScheme::Strict,
+        ] {
+            for guess in &words {
+                for answer in &words {
+                    let hint = WordHint::from_guess_and_answer_with_scheme(guess, answer, scheme);
+                    assert!(clue_possible_with_scheme(*guess, hint, scheme));
+
+                    // The query must match exactly the words that would give the same hint
+                    let mask =
+                        searchable.eval_query(clue_to_query_with_scheme(*guess, hint, scheme));
+                    for (ind, other) in words.iter().enumerate() {
+                        assert_eq!(
+                            mask.get(ind),
+                            WordHint::from_guess_and_answer_with_scheme(guess, other, scheme)
+                                == hint,
+                            "{:?} {} {} {}",
+                            scheme,
+                            guess,
+                            answer,
+                            other
+                        );
+                    }
+                }
+            }
+        }
+
+        let guess: Word<5, 26> = Word::try_from("zzaab").unwrap();
+        let hint = WordHint::try_from("~~XXX").unwrap();
+        assert!(clue_possible_with_scheme(
+            guess,
+            hint,
+            DuplicateScheme::Standard
+        ));
+        assert!(!clue_possible_with_scheme(
+            guess,
+            hint,
+            DuplicateScheme::Strict
+        ));
+        let hint = WordHint::try_from("~X~~X").unwrap();
+        assert!(!clue_possible_with_scheme(
+            guess,
+            hint,
+            DuplicateScheme::Lenient
+        ));
+    }
+
+    #[test]
+    fn test_count_forms_match_same_words() {
+        let words: Vec<Word<5, 26>> = [
+            "board", "bread", "eerie", "geese", "sheep", "there", "llama", "allay", "abbey",
+            "babes", "ebbed",
+        ]
+        .iter()
+        .map(|raw| Word::try_from(*raw).unwrap())
+        .collect();
+        let searchable = SearchableWords::build(words.clone());
+        for guess in &words {
+            for answer in &words {
+                let hint = WordHint::from_guess_and_answer(guess, answer);
+                // Compare true indices, since negated columns may differ in their unused bits
+                assert_eq!(
+                    searchable
+                        .eval_query(clue_to_query_with_count_form(
+                            *guess,
+                            hint,
+                            CountForm::Bounds
+                        ))
+                        .true_inds(),
+                    searchable
+                        .eval_query(clue_to_query(*guess, hint))
+                        .true_inds(),
+                    "{} {}",
+                    guess,
+                    answer
+                );
+            }
+        }
+    }
 
     #[test]
     fn test_query_has_all_facts() {
         // Guess is board, answer is bread
-        let guess: Word<5, 26> = Word::from_str("board");
-        let word_hint = WordHint::from("√X~~√");
+        let guess: Word<5, 26> = Word::try_from("board").unwrap();
+        let word_hint = WordHint::try_from("√X~~√").unwrap();
         let query = clue_to_query(guess, word_hint);
         let Query::And(sub_queries) = query else {
             panic!("non-And returned");
@@ -134,4 +567,97 @@ mod tests {
         assert!(sub_queries.contains(&Query::CountAtLeast { count: 1, chr: 0 }));
         assert!(sub_queries.contains(&Query::CountAtLeast { count: 1, chr: 17 }));
     }
+
+    #[test]
+    fn test_history_query_matches_clue_queries() {
+        let words: Vec<Word<5, 26>> = [
+            "board", "bread", "brain", "eerie", "geese", "sheep", "there", "llama", "allay",
+            "fight", "light", "night", "abbey", "babes", "ebbed", "xxxxa",
+        ]
+        .iter()
+        .map(|raw| Word::try_from(*raw).unwrap())
+        .collect();
+        let searchable = SearchableWords::build(words.clone());
+        let combined_mask = |history: &[(Word<5, 26>, WordHint<5>)]| {
+            let mut mask = Column::from_true(words.len());
+            for (guess, hint) in history {
+                mask &= searchable.eval_query(clue_to_query(*guess, *hint));
+            }
+            mask
+        };
+
+        // Histories consistent with some answer, then pairs of clues from different answers
+        for answer in &words {
+            for first in &words {
+                for second in &words {
+                    let history = [
+                        (*first, WordHint::from_guess_and_answer(first, answer)),
+                        (*second, WordHint::from_guess_and_answer(second, answer)),
+                    ];
+                    let mask = searchable.eval_query(history_to_query(&history));
+                    assert_eq!(
+                        mask,
+                        combined_mask(&history),
+                        "{} {} {}",
+                        answer,
+                        first,
+                        second
+                    );
+                    assert!(mask.get(words.iter().position(|word| word == answer).unwrap()));
+                }
+            }
+        }
+        for (first, second) in [("board", "light"), ("eerie", "geese"), ("abbey", "babes")] {
+            let first: Word<5, 26> = Word::try_from(first).unwrap();
+            let second: Word<5, 26> = Word::try_from(second).unwrap();
+            for first_answer in &words {
+                for second_answer in &words {
+                    let history = [
+                        (first, WordHint::from_guess_and_answer(&first, first_answer)),
+                        (
+                            second,
+                            WordHint::from_guess_and_answer(&second, second_answer),
+                        ),
+                    ];
+                    assert_eq!(
+                        searchable.eval_query(history_to_query(&history)),
+                        combined_mask(&history)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_history_query_places_letters_shown_elsewhere() {
+        // A shown elsewhere at every position but the last must be at the last
+        let answer: Word<5, 26> = Word::try_from("bbbba").unwrap();
+        let history: Vec<(Word<5, 26>, WordHint<5>)> = ["azzzz", "zazzz", "zzazz", "zzzaz"]
+            .iter()
+            .map(|raw| {
+                let guess = Word::try_from(*raw).unwrap();
+                (guess, WordHint::from_guess_and_answer(&guess, &answer))
+            })
+            .collect();
+        let Query::And(sub_queries) = history_to_query(&history) else {
+            panic!("expected an and-group");
+        };
+        assert!(sub_queries.contains(&Query::Match { ind: 4, chr: 0 }));
+        assert!(sub_queries.contains(&Query::CountExact { count: 0, chr: 25 }));
+        // The four exclusions of A remain, folded together, but nothing else about Z or the
+        // count of A does
+        assert!(sub_queries.contains(&Query::NotAtPositions {
+            inds: vec![0, 1, 2, 3],
+            chr: 0
+        }));
+        assert_eq!(sub_queries.len(), 3);
+
+        // Contradictory clues give a query matching nothing
+        let guess: Word<5, 26> = Word::try_from("azzzz").unwrap();
+        let history = [
+            (guess, WordHint::try_from("√XXXX").unwrap()),
+            (guess, WordHint::try_from("XXXXX").unwrap()),
+        ];
+        assert_eq!(history_to_query(&history), Query::Or(vec![]));
+    }
 }
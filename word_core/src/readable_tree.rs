@@ -0,0 +1,341 @@
+use std::{collections::HashMap, fs};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    column::Column,
+    decision_tree_general::{
+        Alternative, GuessFrom, NoPrinter, SearchOptions, TreeNode,
+        compute_decision_tree_aggressive,
+    },
+    error::WordGameError,
+    hint::WordHint,
+    hint_matrix::{answers_first, compute_hints},
+    mask_encoding::{EncodedMask, FNV_OFFSET_BASIS, WordListId, fnv1a},
+    play_out::{play_out_with, resolve_guess},
+    word::Word,
+};
+
+/// A decision tree keyed by words and hints rather than indices, as written to / read from JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadableTreeNode<const WORD_SIZE: usize> {
+    pub should_guess: Word<WORD_SIZE, 26>,
+    #[serde(default)]
+    pub est_cost: f64,
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_by_hint",
+        default
+    )]
+    pub next: HashMap<WordHint<WORD_SIZE>, ReadableTreeNode<WORD_SIZE>>,
+    /// Hints whose subtrees were left out to keep the tree small, each with a mask over the
+    /// possible answers still left under it, to be solved by `expand` once reached
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_by_hint",
+        default
+    )]
+    pub unexpanded: HashMap<WordHint<WORD_SIZE>, EncodedMask>,
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub approximate: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub alternatives: Vec<Alternative<ReadableTreeNode<WORD_SIZE>>>,
+}
+
+/// Serialize a map keyed by hint in hint id order, so the same tree always writes the same JSON.
+fn serialize_by_hint<const WORD_SIZE: usize, V: Serialize, S: Serializer>(
+    map: &HashMap<WordHint<WORD_SIZE>, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut entries: Vec<(&WordHint<WORD_SIZE>, &V)> = map.iter().collect();
+    // Hints compare character by character, which matches id order without overflowing it
+    entries.sort_unstable_by_key(|(hint, _)| **hint);
+    serializer.collect_map(entries)
+}
+
+impl<const WORD_SIZE: usize> ReadableTreeNode<WORD_SIZE> {
+    /// Convert an index-based tree into one keyed by the actual words and hints.
+    pub fn from_generalized_tree_node(
+        tree_node: &TreeNode,
+        allowed_guesses: &[Word<WORD_SIZE, 26>],
+        possible_answers: &[Word<WORD_SIZE, 26>],
+    ) -> Self {
+        Self::from_generalized_tree_node_with_hints(
+            tree_node,
+            allowed_guesses,
+            possible_answers,
+            &|_, hint_id| WordHint::from_id(hint_id),
+        )
+    }
+
+    /// Convert an index-based tree whose hints are labelled per guess, as by
+    /// `compute_compact_hints`. `hint_for` gets the index of the hint row the guess was searched
+    /// with and the label, and returns the hint it stands for.
+    pub fn from_generalized_tree_node_with_hints(
+        tree_node: &TreeNode,
+        allowed_guesses: &[Word<WORD_SIZE, 26>],
+        possible_answers: &[Word<WORD_SIZE, 26>],
+        hint_for: &impl Fn(usize, u8) -> WordHint<WORD_SIZE>,
+    ) -> Self {
+        let (GuessFrom::Guess(hint_row) | GuessFrom::Answer(hint_row)) = tree_node.should_guess;
+        Self {
+            should_guess: resolve_guess(&tree_node.should_guess, allowed_guesses, possible_answers),
+            est_cost: tree_node.est_cost(),
+            next: tree_node
+                .next
+                .iter()
+                .map(|(hint_id, next_node)| {
+                    (
+                        hint_for(hint_row as usize, *hint_id),
+                        Self::from_generalized_tree_node_with_hints(
+                            next_node,
+                            allowed_guesses,
+                            possible_answers,
+                            hint_for,
+                        ),
+                    )
+                })
+                .collect(),
+            unexpanded: HashMap::new(),
+            approximate: tree_node.approximate,
+            alternatives: tree_node
+                .alternatives
+                .iter()
+                .map(|alternative| Alternative {
+                    tree_node: Self::from_generalized_tree_node_with_hints(
+                        &alternative.tree_node,
+                        allowed_guesses,
+                        possible_answers,
+                        hint_for,
+                    ),
+                    complete: alternative.complete,
+                })
+                .collect(),
+        }
+    }
+
+    /// A stable hash of which guess the tree plays after every sequence of hints, as 16 hex
+    /// digits.
+    ///
+    /// Costs, alternatives and the order branches were serialized in are ignored, so two trees
+    /// hash the same exactly when they play the same game against every answer. Subtrees left
+    /// unexpanded hash by the answers left under them, so a collapsed tree doesn't hash the same
+    /// as the full one.
+    pub fn content_hash(&self) -> String {
+        format!("{:016x}", self.fold_content_hash(FNV_OFFSET_BASIS))
+    }
+
+    fn fold_content_hash(&self, hash: u64) -> u64 {
+        let mut hash = fnv1a(hash, self.should_guess.0);
+        let mut next: Vec<(&WordHint<WORD_SIZE>, &Self)> = self.next.iter().collect();
+        next.sort_unstable_by_key(|(hint, _)| *hint);
+        hash = fnv1a(hash, (next.len() as u32).to_le_bytes());
+        for (hint, child) in next {
+            // Hashed by character rather than by id, which only fits a byte for short words
+            hash = child.fold_content_hash(fnv1a(hash, hint.0.map(|char_hint| char_hint as u8)));
+        }
+        // Skipped when empty, so fully expanded trees keep the hashes they always had
+        if !self.unexpanded.is_empty() {
+            let mut unexpanded: Vec<(&WordHint<WORD_SIZE>, &EncodedMask)> =
+                self.unexpanded.iter().collect();
+            unexpanded.sort_unstable_by_key(|(hint, _)| **hint);
+            hash = fnv1a(hash, (unexpanded.len() as u32).to_le_bytes());
+            for (hint, mask) in unexpanded {
+                hash = fnv1a(hash, hint.0.map(|char_hint| char_hint as u8));
+                hash = fnv1a(hash, mask.list_hash.bytes().chain(mask.bits.bytes()));
+            }
+        }
+        hash
+    }
+
+    /// Replace every subtree past the first `max_depth` guesses with an unexpanded marker, so the
+    /// tree can be shipped without its deep branches and `expand`ed where it's actually played.
+    ///
+    /// Leaves are kept, as they're smaller than a marker.
+    pub fn collapse_below(&mut self, max_depth: usize, possible_answers: &[Word<WORD_SIZE, 26>]) {
+        let list = WordListId::new("possible answers", possible_answers);
+        self.collapse_from(
+            1,
+            max_depth,
+            (0..possible_answers.len()).collect(),
+            possible_answers,
+            &list,
+        );
+    }
+
+    fn collapse_from(
+        &mut self,
+        depth: usize,
+        max_depth: usize,
+        answer_inds: Vec<usize>,
+        possible_answers: &[Word<WORD_SIZE, 26>],
+        list: &WordListId,
+    ) {
+        let mut inds_by_hint: HashMap<WordHint<WORD_SIZE>, Vec<usize>> = HashMap::new();
+        for answer_ind in answer_inds {
+            inds_by_hint
+                .entry(WordHint::from_guess_and_answer(
+                    &self.should_guess,
+                    &possible_answers[answer_ind],
+                ))
+                .or_default()
+                .push(answer_ind);
+        }
+        let hints: Vec<WordHint<WORD_SIZE>> = self.next.keys().copied().collect();
+        for hint in hints {
+            let hint_answer_inds = inds_by_hint.remove(&hint).unwrap_or_default();
+            let child = self.next.get_mut(&hint).unwrap();
+            if depth < max_depth {
+                child.collapse_from(
+                    depth + 1,
+                    max_depth,
+                    hint_answer_inds,
+                    possible_answers,
+                    list,
+                );
+            } else if !child.next.is_empty() || !child.unexpanded.is_empty() {
+                let mut mask = vec![false; possible_answers.len()];
+                for answer_ind in hint_answer_inds {
+                    mask[answer_ind] = true;
+                }
+                self.next.remove(&hint);
+                self.unexpanded
+                    .insert(hint, EncodedMask::encode(&Column::from_bools(&mask), list));
+            }
+        }
+    }
+
+    /// Get the child under a hint, first solving its subtree if it was left unexpanded.
+    ///
+    /// `num_guesses` counts the guesses up to and including this node's, and the solved subtree
+    /// must finish within `max_depth` guesses in total. The lists must be the ones the tree was
+    /// collapsed over.
+    pub fn expand(
+        &mut self,
+        hint: &WordHint<WORD_SIZE>,
+        allowed_guesses: &[Word<WORD_SIZE, 26>],
+        possible_answers: &[Word<WORD_SIZE, 26>],
+        num_guesses: usize,
+        max_depth: u8,
+    ) -> Result<&mut Self, WordGameError> {
+        if !self.next.contains_key(hint) {
+            let mask = self
+                .unexpanded
+                .get(hint)
+                .ok_or_else(|| WordGameError::IncompleteTree {
+                    num_guesses,
+                    hint: hint.to_string(),
+                })?
+                .decode(&WordListId::new("possible answers", possible_answers))?;
+            let answers: Vec<Word<WORD_SIZE, 26>> = mask
+                .iter_true()
+                .map(|answer_ind| possible_answers[answer_ind])
+                .collect();
+            let guesses = answers_first(&answers, allowed_guesses);
+            let remaining_depth = max_depth.saturating_sub(num_guesses as u8);
+            let tree = compute_decision_tree_aggressive(
+                &compute_hints(&guesses, &answers),
+                (0..answers.len() as u16).collect(),
+                0,
+                remaining_depth,
+                f64::INFINITY,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+            .ok_or_else(|| {
+                WordGameError::Infeasible(format!(
+                    "no subtree for hint {} within {} more guesses",
+                    hint, remaining_depth
+                ))
+            })?;
+            self.unexpanded.remove(hint);
+            self.next.insert(
+                *hint,
+                Self::from_generalized_tree_node(&tree, &guesses, &answers),
+            );
+        }
+        Ok(self.next.get_mut(hint).unwrap())
+    }
+
+    /// Load a tree from a JSON file.
+    pub fn load(file_path: &str) -> Result<Self, WordGameError> {
+        let raw = fs::read_to_string(file_path).map_err(|source| WordGameError::Io {
+            path: file_path.into(),
+            source,
+        })?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    /// Follow this tree against a specific answer, returning every guess and its hint.
+    pub fn play_out(
+        &self,
+        answer: &Word<WORD_SIZE, 26>,
+    ) -> Result<Vec<(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)>, WordGameError> {
+        play_out_with(
+            self,
+            answer,
+            |node| node.should_guess,
+            |node, hint| node.next.get(hint),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapse_and_expand() {
+        let words: Vec<Word<5, 26>> = [
+            "board", "bread", "brain", "fight", "light", "sight", "might", "grain",
+        ]
+        .iter()
+        .map(|word| Word::try_from(*word).unwrap())
+        .collect();
+        let tree = compute_decision_tree_aggressive(
+            &compute_hints(&words, &words),
+            (0..words.len() as u16).collect(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let full = ReadableTreeNode::from_generalized_tree_node(&tree, &words, &words);
+        let mut collapsed = full.clone();
+        collapsed.collapse_below(1, &words);
+        assert!(!collapsed.unexpanded.is_empty());
+        assert!(collapsed.next.values().all(|child| child.next.is_empty()));
+        assert_ne!(collapsed.content_hash(), full.content_hash());
+        let raw = serde_json::to_string(&collapsed).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ReadableTreeNode<5>>(&raw).unwrap(),
+            collapsed
+        );
+        // Hints are written in order, so a reloaded tree writes the same JSON
+        assert_eq!(
+            serde_json::to_string(&serde_json::from_str::<ReadableTreeNode<5>>(&raw).unwrap())
+                .unwrap(),
+            raw
+        );
+
+        // Each expanded subtree is as good as the one it replaced
+        let hints: Vec<WordHint<5>> = collapsed.unexpanded.keys().copied().collect();
+        for hint in hints {
+            let child = collapsed.expand(&hint, &words, &words, 1, 6).unwrap();
+            assert!((child.est_cost - full.next[&hint].est_cost).abs() < 1e-9);
+        }
+        assert!(collapsed.unexpanded.is_empty());
+        assert_eq!(collapsed.content_hash(), full.content_hash());
+        for answer in &words {
+            assert!(collapsed.play_out(answer).is_ok());
+        }
+
+        // Hints that can't happen have nothing to expand
+        assert!(matches!(
+            collapsed.expand(&WordHint::try_from("√√√√~").unwrap(), &words, &words, 1, 6),
+            Err(WordGameError::IncompleteTree { .. })
+        ));
+    }
+}
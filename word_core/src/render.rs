@@ -0,0 +1,99 @@
+use std::{
+    env,
+    fmt::Display,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Environment variable that switches the default render mode to ASCII when set to anything but
+/// an empty string or `0`.
+pub const ASCII_ENV_VAR: &str = "WORD_GAME_ASCII";
+
+/// How hints, trees and debug output are drawn in the terminal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    /// Unicode symbols and ANSI colors
+    #[default]
+    Unicode,
+
+    /// Plain ASCII with no escape sequences, for logs and terminals that mangle anything else
+    Ascii,
+}
+
+impl RenderMode {
+    /// Read the mode from `WORD_GAME_ASCII`, defaulting to Unicode.
+    pub fn from_env() -> Self {
+        match env::var(ASCII_ENV_VAR) {
+            Ok(value) if !value.is_empty() && value != "0" => Self::Ascii,
+            _ => Self::Unicode,
+        }
+    }
+}
+
+const MODE_UNSET: u8 = 0;
+const MODE_UNICODE: u8 = 1;
+const MODE_ASCII: u8 = 2;
+
+static MODE: AtomicU8 = AtomicU8::new(MODE_UNSET);
+
+/// Choose how every display path renders from now on, overriding the environment.
+pub fn set_render_mode(mode: RenderMode) {
+    let value = match mode {
+        RenderMode::Unicode => MODE_UNICODE,
+        RenderMode::Ascii => MODE_ASCII,
+    };
+    MODE.store(value, Ordering::Relaxed);
+}
+
+/// The mode display paths should render in, read from the environment until one is set.
+pub fn render_mode() -> RenderMode {
+    match MODE.load(Ordering::Relaxed) {
+        MODE_UNICODE => RenderMode::Unicode,
+        MODE_ASCII => RenderMode::Ascii,
+        _ => {
+            let mode = RenderMode::from_env();
+            set_render_mode(mode);
+            mode
+        }
+    }
+}
+
+/// Displays its contents in bold, or unchanged in ASCII mode.
+pub struct Bold<T>(pub T);
+
+impl<T: Display> Display for Bold<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match render_mode() {
+            RenderMode::Unicode => write!(f, "\x1b[1m{}\x1b[0m", self.0),
+            RenderMode::Ascii => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// The letters of a QWERTY keyboard, row by row
+pub const KEYBOARD_ROWS: [&str; 3] = ["QWERTYUIOP", "ASDFGHJKL", "ZXCVBNM"];
+
+/// Draw a bar of `level` eighths, from 0 (empty) to 8 (full), for inline histograms.
+pub fn histogram_bar(level: usize, mode: RenderMode) -> &'static str {
+    const UNICODE: [&str; 9] = [" ", "⡀", "⣀", "⣄", "⣤", "⣦", "⣶", "⣷", "⣿"];
+    const ASCII: [&str; 9] = [" ", ".", ",", ":", ";", "+", "*", "#", "@"];
+    match mode {
+        RenderMode::Unicode => UNICODE[level],
+        RenderMode::Ascii => ASCII[level],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_bars_are_ascii_in_ascii_mode() {
+        for level in 0..=8 {
+            assert!(histogram_bar(level, RenderMode::Ascii).is_ascii());
+            assert_eq!(
+                histogram_bar(level, RenderMode::Unicode) == " ",
+                histogram_bar(level, RenderMode::Ascii) == " "
+            );
+        }
+    }
+}
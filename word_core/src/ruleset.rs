@@ -0,0 +1,550 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    column::Column,
+    decision_tree_general::ClueLegality,
+    hint::{CharHint, DuplicateScheme, WordHint},
+    query_generation::{clue_to_query_with_scheme, hard_mode_query, ultra_hard_mode_query},
+    word::Word,
+    word_search::{Query, SearchableWords},
+};
+
+/// Which guesses may be played given the clues so far, shared by the rulesets, the exhaustive
+/// search and the interactive solver.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuessLegality {
+    /// Any allowed word may be guessed
+    #[default]
+    Any,
+
+    /// Correct letters must stay in place, and every revealed letter must appear at least as
+    /// many times as it has been shown to be present
+    Hard,
+
+    /// Hard, and letters shown to be absent may not be played again, and a letter shown both
+    /// present and absent must appear exactly as many times as it was shown present
+    UltraHard,
+}
+
+impl GuessLegality {
+    /// A short name for the policy.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Hard => "hard",
+            Self::UltraHard => "ultra hard",
+        }
+    }
+
+    /// Whether a guess may be played after the given history, by the queries of
+    /// `legality_query`.
+    pub fn is_legal_guess<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        history: &[(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)],
+    ) -> bool {
+        history.iter().all(|(prev_guess, prev_hint)| {
+            self.legality_query(*prev_guess, *prev_hint)
+                .is_none_or(|query| query.matches(guess))
+        })
+    }
+
+    /// A query that later guesses must satisfy after the given clue, or `None` if the clue
+    /// doesn't restrict them.
+    pub fn legality_query<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        self,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+        hint: WordHint<WORD_SIZE>,
+    ) -> Option<Query> {
+        match self {
+            Self::Any => None,
+            Self::Hard => Some(hard_mode_query(guess, hint)),
+            Self::UltraHard => Some(ultra_hard_mode_query(guess, hint)),
+        }
+    }
+}
+
+/// The rules of a word game variant: which guesses are legal, how hints are produced, and when
+/// the game is won.
+pub trait Ruleset<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    /// A short name for the variant, as recorded in transcripts.
+    fn name(&self) -> &str;
+
+    /// The most guesses allowed before the game is lost, if limited.
+    fn max_guesses(&self) -> Option<usize> {
+        Some(6)
+    }
+
+    /// Which guesses the variant allows given the clues so far.
+    fn guess_legality(&self) -> GuessLegality {
+        GuessLegality::Any
+    }
+
+    /// Whether a guess may be played after the given history.
+    fn is_legal_guess(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        history: &[(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)],
+    ) -> bool {
+        self.guess_legality().is_legal_guess(guess, history)
+    }
+
+    /// A query that later guesses must satisfy after the given clue, or `None` if the clue
+    /// doesn't restrict them.
+    ///
+    /// This must agree with `is_legal_guess`, and lets legality be tracked incrementally as a
+    /// mask over the guess list rather than re-checked against the whole history.
+    fn legality_query(
+        &self,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+        hint: WordHint<WORD_SIZE>,
+    ) -> Option<Query> {
+        self.guess_legality().legality_query(guess, hint)
+    }
+
+    /// How the default `hint` scores repeated letters.
+    fn duplicate_scheme(&self) -> DuplicateScheme {
+        DuplicateScheme::Standard
+    }
+
+    /// The hint a guess receives against a specific answer.
+    fn hint(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> WordHint<WORD_SIZE> {
+        WordHint::from_guess_and_answer_with_scheme(guess, answer, self.duplicate_scheme())
+    }
+
+    /// The query an answer must satisfy to produce the given clue.
+    ///
+    /// This follows the default `hint`, so rulesets that score clues some other way can't rely
+    /// on it to filter candidates.
+    fn clue_query(
+        &self,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+        hint: WordHint<WORD_SIZE>,
+    ) -> Query {
+        clue_to_query_with_scheme(guess, hint, self.duplicate_scheme())
+    }
+
+    /// The hint the game actually gives for a guess, given the answer and every answer still
+    /// consistent with the history.
+    ///
+    /// Fixed-answer variants just score against the answer. Adversarial variants may ignore it
+    /// and pick among the remaining candidates instead.
+    fn respond(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        _candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> WordHint<WORD_SIZE> {
+        self.hint(guess, answer)
+    }
+
+    /// Whether a hint ends the game in a win.
+    fn is_win(&self, hint: &WordHint<WORD_SIZE>) -> bool {
+        hint.all_correct()
+    }
+}
+
+/// Standard rules: any allowed word may be guessed, six guesses to win.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Standard;
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Ruleset<WORD_SIZE, ALPHABET_SIZE>
+    for Standard
+{
+    fn name(&self) -> &str {
+        "standard"
+    }
+}
+
+/// Standard rules, but with repeated letters scored by a different scheme.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DuplicateVariant {
+    pub scheme: DuplicateScheme,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Ruleset<WORD_SIZE, ALPHABET_SIZE>
+    for DuplicateVariant
+{
+    fn name(&self) -> &str {
+        self.scheme.name()
+    }
+
+    fn duplicate_scheme(&self) -> DuplicateScheme {
+        self.scheme
+    }
+}
+
+/// Hard mode: revealed hints must be used in every later guess.
+///
+/// Correct letters must stay in place, and every revealed letter must appear at least as many
+/// times as it has been shown to be present.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HardMode;
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Ruleset<WORD_SIZE, ALPHABET_SIZE>
+    for HardMode
+{
+    fn name(&self) -> &str {
+        "hard"
+    }
+
+    fn guess_legality(&self) -> GuessLegality {
+        GuessLegality::Hard
+    }
+}
+
+/// Ultra hard mode: every clue must be used in every later guess.
+///
+/// On top of hard mode, letters shown to be absent may not be played again, and letters whose
+/// exact count has been revealed must be played exactly that many times.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UltraHardMode;
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Ruleset<WORD_SIZE, ALPHABET_SIZE>
+    for UltraHardMode
+{
+    fn name(&self) -> &str {
+        "ultra hard"
+    }
+
+    fn guess_legality(&self) -> GuessLegality {
+        GuessLegality::UltraHard
+    }
+}
+
+/// Which guesses from a fixed list are legal, tracked as a mask that's narrowed one clue at a time.
+pub struct LegalGuesses<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    guesses: SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> LegalGuesses<WORD_SIZE, ALPHABET_SIZE> {
+    pub fn new(allowed_guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>) -> Self {
+        Self {
+            guesses: SearchableWords::build(allowed_guesses),
+        }
+    }
+
+    /// The mask before any guesses, where everything is legal.
+    pub fn root_mask(&self) -> Column {
+        Column::from_true(self.guesses.len())
+    }
+
+    /// Narrow a parent node's mask by the clue that leads to the child node.
+    pub fn child_mask<R: Ruleset<WORD_SIZE, ALPHABET_SIZE> + ?Sized>(
+        &self,
+        ruleset: &R,
+        parent_mask: &Column,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+        hint: WordHint<WORD_SIZE>,
+    ) -> Column {
+        self.narrow(parent_mask, ruleset.legality_query(guess, hint))
+    }
+
+    /// Narrow a mask by a legality query, as from `GuessLegality::legality_query`.
+    pub fn narrow(&self, parent_mask: &Column, query: Option<Query>) -> Column {
+        let mut mask = parent_mask.clone();
+        if let Some(query) = query {
+            mask &= self.guesses.eval_query(query);
+        }
+        mask
+    }
+
+    /// The guesses allowed by a mask.
+    pub fn words(&self, mask: &Column) -> Vec<Word<WORD_SIZE, ALPHABET_SIZE>> {
+        self.guesses.filter_words(mask)
+    }
+}
+
+/// The guesses each clue leaves legal under a policy, for limiting the exhaustive search to
+/// legal play.
+///
+/// The guesses must be those of the searched hint matrix, in the same order.
+pub struct ClueLegalityMasks<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    legality: GuessLegality,
+    guesses: SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> ClueLegalityMasks<WORD_SIZE, ALPHABET_SIZE> {
+    pub fn new(legality: GuessLegality, guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>) -> Self {
+        Self {
+            legality,
+            guesses: SearchableWords::build(guesses),
+        }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> std::fmt::Debug
+    for ClueLegalityMasks<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClueLegalityMasks")
+            .field("legality", &self.legality)
+            .field("num_guesses", &self.guesses.len())
+            .finish()
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> ClueLegality
+    for ClueLegalityMasks<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn legal_after(&self, guess_ind: u16, hint: u8) -> Option<Column> {
+        self.legality
+            .legality_query(
+                self.guesses.words()[guess_ind as usize],
+                WordHint::from_id(hint),
+            )
+            .map(|query| self.guesses.eval_query(query))
+    }
+
+    fn name(&self) -> String {
+        self.legality.name().to_string()
+    }
+}
+
+/// Absurdle: there's no fixed answer, and every hint keeps as many candidates alive as possible.
+///
+/// Ties between equally large buckets go to the hint revealing less, i.e. the one with the
+/// fewest correct then fewest elsewhere tiles.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Absurdle;
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Ruleset<WORD_SIZE, ALPHABET_SIZE>
+    for Absurdle
+{
+    fn name(&self) -> &str {
+        "absurdle"
+    }
+
+    fn max_guesses(&self) -> Option<usize> {
+        None
+    }
+
+    fn respond(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> WordHint<WORD_SIZE> {
+        let mut bucket_sizes: BTreeMap<WordHint<WORD_SIZE>, usize> = BTreeMap::new();
+        for candidate in candidates {
+            *bucket_sizes.entry(self.hint(guess, candidate)).or_default() += 1;
+        }
+        absurdle_response(bucket_sizes).unwrap_or_else(|| self.hint(guess, answer))
+    }
+}
+
+/// Pick the hint Absurdle responds with, given how many candidates each hint would keep.
+pub(crate) fn absurdle_response<const WORD_SIZE: usize>(
+    bucket_sizes: impl IntoIterator<Item = (WordHint<WORD_SIZE>, usize)>,
+) -> Option<WordHint<WORD_SIZE>> {
+    let reveals = |hint: &WordHint<WORD_SIZE>| {
+        let count = |target: CharHint| hint.0.iter().filter(|chr| **chr == target).count();
+        (count(CharHint::Correct), count(CharHint::Elsewhere))
+    };
+    bucket_sizes
+        .into_iter()
+        .max_by(|(hint_a, size_a), (hint_b, size_b)| {
+            size_a
+                .cmp(size_b)
+                .then(reveals(hint_b).cmp(&reveals(hint_a)))
+                .then(hint_a.cmp(hint_b))
+        })
+        .map(|(hint, _)| hint)
+}
+
+/// Mastermind: feedback only counts exact and misplaced symbols, not where they are.
+///
+/// The counts are reported as a hint with all the correct tiles first, then the elsewhere
+/// tiles, then the rest, so equal feedback always compares equal.
+#[derive(Debug, Clone, Copy)]
+pub struct Mastermind {
+    pub max_guesses: usize,
+}
+
+impl Default for Mastermind {
+    fn default() -> Self {
+        Self { max_guesses: 10 }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Ruleset<WORD_SIZE, ALPHABET_SIZE>
+    for Mastermind
+{
+    fn name(&self) -> &str {
+        "mastermind"
+    }
+
+    fn max_guesses(&self) -> Option<usize> {
+        Some(self.max_guesses)
+    }
+
+    fn hint(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        answer: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> WordHint<WORD_SIZE> {
+        // Positional hints already count misplaced duplicates the same way Mastermind pegs do
+        let mut hint = WordHint::from_guess_and_answer(guess, answer);
+        hint.0.sort();
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(raw: &str) -> Word<5, 26> {
+        Word::try_from(raw).unwrap()
+    }
+
+    fn hint(raw: &str) -> WordHint<5> {
+        WordHint::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn test_hard_mode_legality() {
+        let history = [(word("board"), hint("√X~XX"))];
+        assert!(Ruleset::<5, 26>::is_legal_guess(
+            &HardMode,
+            &word("beach"),
+            &history
+        ));
+        // Drops the green B
+        assert!(!HardMode.is_legal_guess(&word("cable"), &history));
+        // Drops the yellow A
+        assert!(!HardMode.is_legal_guess(&word("bison"), &history));
+        assert!(Standard.is_legal_guess(&word("bison"), &history));
+    }
+
+    #[test]
+    fn test_legal_guesses_match_direct_checks() {
+        let allowed: Vec<Word<5, 26>> = ["beach", "cable", "bison", "bread", "abbey", "board"]
+            .iter()
+            .map(|raw| word(raw))
+            .collect();
+        let history = [
+            (word("board"), hint("√X~XX")),
+            (word("beach"), hint("√X~XX")),
+        ];
+        let legal_guesses = LegalGuesses::new(allowed.clone());
+        let mask = history
+            .iter()
+            .fold(legal_guesses.root_mask(), |mask, (guess, hint)| {
+                legal_guesses.child_mask(&HardMode, &mask, *guess, *hint)
+            });
+        let expected: Vec<Word<5, 26>> = allowed
+            .iter()
+            .filter(|guess| HardMode.is_legal_guess(guess, &history))
+            .copied()
+            .collect();
+        assert_eq!(legal_guesses.words(&mask), expected);
+        assert_eq!(
+            legal_guesses.child_mask(&Standard, &mask, word("cable"), hint("XXXXX")),
+            mask
+        );
+    }
+
+    #[test]
+    fn test_ultra_hard_mode_legality() {
+        let history = [(word("board"), hint("√X~XX"))];
+        assert!(UltraHardMode.is_legal_guess(&word("beach"), &history));
+        // Plays the absent R and D, which hard mode allows
+        assert!(HardMode.is_legal_guess(&word("bread"), &history));
+        assert!(!UltraHardMode.is_legal_guess(&word("bread"), &history));
+        // Only one E was shown present, and the others were shown absent
+        let history = [(word("geese"), hint("X~XXX"))];
+        assert!(UltraHardMode.is_legal_guess(&word("beach"), &history));
+        assert!(HardMode.is_legal_guess(&word("where"), &history));
+        assert!(!UltraHardMode.is_legal_guess(&word("where"), &history));
+
+        // The masks agree with checking guesses one at a time after every clue between these words
+        let words: Vec<Word<5, 26>> = [
+            "board", "bread", "beach", "geese", "there", "where", "eerie", "abbey", "babes",
+            "llama", "allay", "sheep",
+        ]
+        .iter()
+        .map(|raw| word(raw))
+        .collect();
+        let searchable = SearchableWords::build(words.clone());
+        for legality in [GuessLegality::Hard, GuessLegality::UltraHard] {
+            for guess in &words {
+                for answer in &words {
+                    let clue = (*guess, WordHint::from_guess_and_answer(guess, answer));
+                    let mask =
+                        searchable.eval_query(legality.legality_query(clue.0, clue.1).unwrap());
+                    for (ind, later) in words.iter().enumerate() {
+                        assert_eq!(
+                            mask.get(ind),
+                            legality.is_legal_guess(later, &[clue]),
+                            "{} {} {} {}",
+                            legality.name(),
+                            guess,
+                            answer,
+                            later
+                        );
+                    }
+                    // The answer itself is always still legal
+                    assert!(legality.is_legal_guess(answer, &[clue]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_absurdle_keeps_largest_bucket() {
+        let candidates: Vec<Word<5, 26>> = ["fight", "light", "might", "board"]
+            .iter()
+            .map(|raw| word(raw))
+            .collect();
+        // BOARD as the nominal answer is ignored in favor of the three _IGHT words
+        let response = Absurdle.respond(&word("crown"), &word("board"), &candidates);
+        assert_eq!(response, hint("XXXXX"));
+    }
+
+    #[test]
+    fn test_duplicate_variant_queries() {
+        let answers: Vec<Word<5, 26>> = ["abcba", "bbbxx", "board", "cbcbc"]
+            .iter()
+            .map(|raw| word(raw))
+            .collect();
+        let searchable = SearchableWords::build(answers.clone());
+        let lenient = DuplicateVariant {
+            scheme: DuplicateScheme::Lenient,
+        };
+        let guess = word("bbbxx");
+        let clue = lenient.hint(&guess, &answers[0]);
+        assert_eq!(clue, hint("~√~XX"));
+        assert_eq!(
+            searchable.filter_words(&searchable.eval_query(lenient.clue_query(guess, clue))),
+            vec![answers[0], answers[3]]
+        );
+    }
+
+    #[test]
+    fn test_mastermind_counts_only() {
+        let mastermind = Mastermind::default();
+        let guess: Word<4, 6> = Word([0, 0, 1, 2]);
+        let answer: Word<4, 6> = Word([1, 0, 3, 0]);
+        assert_eq!(
+            mastermind.hint(&guess, &answer),
+            WordHint([
+                CharHint::Correct,
+                CharHint::Elsewhere,
+                CharHint::Elsewhere,
+                CharHint::Nowhere
+            ])
+        );
+        assert!(Ruleset::<4, 6>::is_win(
+            &mastermind,
+            &mastermind.hint(&answer, &answer)
+        ));
+    }
+}
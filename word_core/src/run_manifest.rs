@@ -0,0 +1,459 @@
+use std::{collections::BTreeMap, sync::Arc, time::Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    column::Column,
+    decision_tree_general::{
+        DepthSettings, Objective, SearchObserver, SearchOptions, compute_decision_tree_aggressive,
+    },
+    error::WordGameError,
+    hint_matrix::{answers_first, compute_compact_hints},
+    mask_encoding::{FNV_OFFSET_BASIS, WordListId, fnv1a},
+    readable_tree::ReadableTreeNode,
+    word::Word,
+    word_search::{LetterScreen, SearchableWords},
+};
+
+/// The run manifest format version written by this crate
+pub const RUN_MANIFEST_VERSION: u32 = 1;
+
+/// The settings a search ran with, in a form that can be saved and compared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchConfig {
+    pub max_depth: u8,
+
+    /// The est cost limit, or `None` for no limit
+    pub max_cost: Option<f64>,
+
+    pub tolerance: f64,
+    pub num_alternatives: usize,
+    pub max_nodes: Option<u64>,
+    pub max_millis: Option<u64>,
+    pub depth_settings: Vec<DepthSettings>,
+
+    /// Guess indices excluded at the root, ascending
+    pub root_exclusions: Vec<u16>,
+
+    /// A hash of the guess ranks as 16 hex digits, or empty if there were none
+    pub guess_ranks_hash: String,
+
+    /// Whether the root guesses were searched across threads, which can settle ties
+    /// differently
+    #[serde(default)]
+    pub parallel_root: bool,
+
+    /// The transposition table's memory budget, which can change which tied subtrees and
+    /// runner-ups are kept
+    #[serde(default)]
+    pub transposition_bytes: usize,
+
+    #[serde(default)]
+    pub objective: Objective,
+
+    /// The wall-clock limit on the whole search, whose result depends on how far it got
+    #[serde(default)]
+    pub time_limit_millis: Option<u64>,
+
+    /// Whether guesses were limited to words on the answer list
+    #[serde(default)]
+    pub answers_only: bool,
+
+    /// The answer count below which only possible answers were guessed
+    #[serde(default)]
+    pub candidates_only_below: Option<usize>,
+
+    /// Whether the root's alternatives were evaluated in full
+    #[serde(default)]
+    pub complete_alternatives: bool,
+
+    /// The name of the custom guess ordering, if there was one
+    #[serde(default)]
+    pub guess_ordering: Option<String>,
+
+    /// The name of the guess legality policy, if guesses had to keep to one
+    #[serde(default)]
+    pub guess_legality: Option<String>,
+
+    /// A hash of the guesses legal at the root as 16 hex digits, or empty if every guess was
+    #[serde(default)]
+    pub root_legal_guesses_hash: String,
+
+    /// A hash of the guesses the search was limited to as 16 hex digits, or empty if it wasn't
+    #[serde(default)]
+    pub guess_subset_hash: String,
+}
+
+impl SearchConfig {
+    pub fn new(max_depth: u8, max_cost: f64, options: &SearchOptions) -> Self {
+        let mut root_exclusions: Vec<u16> = options.root_exclusions.iter().copied().collect();
+        root_exclusions.sort_unstable();
+        Self {
+            max_depth,
+            max_cost: max_cost.is_finite().then_some(max_cost),
+            tolerance: options.tolerance,
+            num_alternatives: options.num_alternatives,
+            max_nodes: options.budget.max_nodes,
+            max_millis: options.budget.max_millis,
+            depth_settings: options.depth_settings.clone(),
+            root_exclusions,
+            guess_ranks_hash: if options.guess_ranks.is_empty() {
+                String::new()
+            } else {
+                let hash = options
+                    .guess_ranks
+                    .iter()
+                    .fold(FNV_OFFSET_BASIS, |hash, rank| {
+                        fnv1a(hash, rank.to_le_bytes())
+                    });
+                format!("{:016x}", hash)
+            },
+            parallel_root: options.parallel_root,
+            transposition_bytes: options.transposition_bytes,
+            objective: options.objective,
+            time_limit_millis: options.time_limit_millis,
+            answers_only: options.answers_only,
+            candidates_only_below: options.candidates_only_below,
+            complete_alternatives: options.complete_alternatives,
+            guess_ordering: options
+                .guess_ordering
+                .as_ref()
+                .map(|ordering| ordering.name()),
+            guess_legality: options
+                .guess_legality
+                .as_ref()
+                .map(|legality| legality.name()),
+            root_legal_guesses_hash: mask_hash(options.root_legal_guesses.as_ref()),
+            guess_subset_hash: mask_hash(options.guess_subset.as_ref()),
+        }
+    }
+}
+
+/// Hash the indices a mask allows as 16 hex digits, or give an empty string if there's no mask.
+fn mask_hash(mask: Option<&Column>) -> String {
+    mask.map_or(String::new(), |mask| {
+        let hash = mask.iter_true().fold(FNV_OFFSET_BASIS, |hash, ind| {
+            fnv1a(hash, (ind as u64).to_le_bytes())
+        });
+        format!("{:016x}", hash)
+    })
+}
+
+/// Everything that went into a solver run and what came out, so two runs that disagree can be
+/// told apart by their inputs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub version: u32,
+    pub crate_version: String,
+    pub allowed_guesses: WordListId,
+    pub possible_answers: WordListId,
+    pub search: SearchConfig,
+
+    /// The random seed, for runs that take one. The exhaustive search doesn't, as it breaks ties
+    /// between equally good guesses by guess rank, then guess index.
+    pub seed: Option<u64>,
+
+    /// What the run produced, or `None` if it's yet to finish or recorded alongside the
+    /// manifest instead, as in an artifact header
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub result: Option<RunResult>,
+}
+
+/// What a solver run produced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunResult {
+    /// The resulting tree's `content_hash`
+    pub tree_hash: String,
+
+    pub est_cost: f64,
+    pub approximate: bool,
+    pub wall_millis: u64,
+}
+
+impl RunManifest {
+    /// Describe a run of this crate over the given lists, with no result recorded yet.
+    pub fn new(
+        allowed_guesses: WordListId,
+        possible_answers: WordListId,
+        search: SearchConfig,
+        seed: Option<u64>,
+    ) -> Self {
+        Self {
+            version: RUN_MANIFEST_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            allowed_guesses,
+            possible_answers,
+            search,
+            seed,
+            result: None,
+        }
+    }
+
+    /// Record the tree the run produced and how long it took.
+    pub fn record_result<const WORD_SIZE: usize>(
+        &mut self,
+        tree: &ReadableTreeNode<WORD_SIZE>,
+        wall_millis: u64,
+    ) {
+        self.result = Some(RunResult {
+            tree_hash: tree.content_hash(),
+            est_cost: tree.est_cost,
+            approximate: tree.approximate,
+            wall_millis,
+        });
+    }
+
+    /// List every input that differs between two runs, as `(field, left, right)` with dotted
+    /// field paths and JSON values. The result is left out, since that's what the inputs are
+    /// meant to explain.
+    pub fn input_differences(&self, other: &Self) -> Vec<(String, String, String)> {
+        let left = self.input_fields();
+        let right = other.input_fields();
+        let mut fields: Vec<&String> = left.keys().chain(right.keys()).collect();
+        fields.sort_unstable();
+        fields.dedup();
+        let missing = || "-".to_string();
+        fields
+            .into_iter()
+            .filter(|field| left.get(*field) != right.get(*field))
+            .map(|field| {
+                (
+                    field.clone(),
+                    left.get(field).cloned().unwrap_or_else(missing),
+                    right.get(field).cloned().unwrap_or_else(missing),
+                )
+            })
+            .collect()
+    }
+
+    /// Every input as a leaf JSON value keyed by its dotted path.
+    fn input_fields(&self) -> BTreeMap<String, String> {
+        let mut fields = BTreeMap::new();
+        let Value::Object(object) = serde_json::to_value(self).unwrap() else {
+            unreachable!("a manifest always serializes to an object");
+        };
+        for (key, value) in object {
+            if key != "result" {
+                flatten(&key, value, &mut fields);
+            }
+        }
+        fields
+    }
+}
+
+/// Solve for the best tree over a pair of named word lists with the general solver, recording
+/// the run in a manifest alongside it.
+///
+/// The search plays the possible answers followed by the other allowed guesses, as from
+/// `answers_first`, which is what guess indices in the options index. It screens guesses by
+/// their letters itself, in place of any screen in the options. Hints are labelled per guess,
+/// so words too long for every hint to have a byte-sized id still solve, as long as no guess
+/// can get more than 256 distinct hints from the answers.
+///
+/// The tree is `None` if there's none within the limits, in which case the manifest has no
+/// result.
+pub fn solve_recorded<const WORD_SIZE: usize, O: SearchObserver>(
+    allowed_guesses: (&str, &[Word<WORD_SIZE, 26>]),
+    possible_answers: (&str, &[Word<WORD_SIZE, 26>]),
+    max_depth: u8,
+    max_cost: f64,
+    options: SearchOptions,
+    seed: Option<u64>,
+    observer: Option<&O>,
+) -> Result<(Option<ReadableTreeNode<WORD_SIZE>>, RunManifest), WordGameError> {
+    let (allowed_name, allowed_guesses) = allowed_guesses;
+    let (answers_name, possible_answers) = possible_answers;
+    let guesses = answers_first(possible_answers, allowed_guesses);
+    let options = SearchOptions {
+        letter_screen: Some(Arc::new(LetterScreen::new(
+            &guesses,
+            &SearchableWords::build(possible_answers.to_vec()),
+        ))),
+        ..options
+    };
+    let mut manifest = RunManifest::new(
+        WordListId::new(allowed_name, allowed_guesses),
+        WordListId::new(answers_name, possible_answers),
+        SearchConfig::new(max_depth, max_cost, &options),
+        seed,
+    );
+
+    let start = Instant::now();
+    let hints = compute_compact_hints(&guesses, possible_answers)?;
+    let Some(tree) = compute_decision_tree_aggressive(
+        &hints.matrix,
+        (0..possible_answers.len() as u16).collect(),
+        0,
+        max_depth,
+        max_cost,
+        options,
+        observer,
+    ) else {
+        return Ok((None, manifest));
+    };
+    let wall_millis = start.elapsed().as_millis() as u64;
+
+    let tree = ReadableTreeNode::from_generalized_tree_node_with_hints(
+        &tree,
+        &guesses,
+        possible_answers,
+        &|guess_ind, label| hints.hint(guess_ind, label),
+    );
+    manifest.record_result(&tree, wall_millis);
+    Ok((Some(tree), manifest))
+}
+
+fn flatten(path: &str, value: Value, fields: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten(&format!("{}.{}", path, key), value, fields);
+            }
+        }
+        value => {
+            fields.insert(path.to_string(), value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        decision_tree_general::{GuessHeuristic, NoPrinter},
+        ruleset::{ClueLegalityMasks, GuessLegality},
+        test_words,
+        word::Word,
+    };
+
+    #[test]
+    fn test_input_differences() {
+        let words: Vec<Word<5, 26>> = ["fight", "light", "board"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        let manifest = |answers: &[Word<5, 26>], options: &SearchOptions| {
+            RunManifest::new(
+                WordListId::new("words", &words),
+                WordListId::new("answers", answers),
+                SearchConfig::new(6, f64::INFINITY, options),
+                None,
+            )
+        };
+        let left = manifest(&words, &SearchOptions::default());
+        let mut right = left.clone();
+        right.result = Some(RunResult {
+            tree_hash: "0123456789abcdef".to_string(),
+            est_cost: 2.0,
+            approximate: false,
+            wall_millis: 100,
+        });
+        assert!(left.input_differences(&right).is_empty());
+
+        let right = manifest(
+            &words[..2],
+            &SearchOptions {
+                root_exclusions: HashSet::from([2, 0]),
+                depth_settings: vec![DepthSettings {
+                    heuristic: GuessHeuristic::Entropy,
+                    beam_width: None,
+                    ..DepthSettings::default()
+                }],
+                guess_ordering: Some(Arc::new(GuessHeuristic::Entropy)),
+                ..SearchOptions::default()
+            },
+        );
+        assert_eq!(right.search.root_exclusions, vec![0, 2]);
+        assert_eq!(right.search.guess_ordering.as_deref(), Some("Entropy"));
+        assert_eq!(right.search.guess_legality, None);
+        let fields: Vec<String> = left
+            .input_differences(&right)
+            .into_iter()
+            .map(|(field, _, _)| field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "possible_answers.hash",
+                "possible_answers.len",
+                "search.depth_settings",
+                "search.guess_ordering",
+                "search.root_exclusions",
+            ]
+        );
+
+        // Runs keeping to different rules or guess lists are told apart
+        let mut legal_guesses = Column::from_true(words.len());
+        legal_guesses.set(1, false);
+        let ruled = manifest(
+            &words,
+            &SearchOptions {
+                guess_legality: Some(Arc::new(ClueLegalityMasks::new(
+                    GuessLegality::Hard,
+                    words.clone(),
+                ))),
+                root_legal_guesses: Some(legal_guesses.clone()),
+                guess_subset: Some(legal_guesses),
+                ..SearchOptions::default()
+            },
+        );
+        assert_eq!(ruled.search.guess_legality.as_deref(), Some("hard"));
+        let fields: Vec<String> = left
+            .input_differences(&ruled)
+            .into_iter()
+            .map(|(field, _, _)| field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "search.guess_legality",
+                "search.guess_subset_hash",
+                "search.root_legal_guesses_hash",
+            ]
+        );
+
+        // Infinite costs are kept out of the JSON, which can't represent them
+        let raw = serde_json::to_string(&left).unwrap();
+        assert_eq!(serde_json::from_str::<RunManifest>(&raw).unwrap(), left);
+    }
+
+    #[test]
+    fn test_solve_recorded() {
+        let words = test_words::small_list();
+        let (tree, manifest) = solve_recorded(
+            ("words", &words),
+            ("words", &words[..7]),
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None,
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let tree = tree.unwrap();
+        let result = manifest.result.unwrap();
+        assert_eq!(result.tree_hash, tree.content_hash());
+        assert_eq!(result.est_cost, tree.est_cost);
+        assert_eq!(manifest.possible_answers.len, 7);
+        for answer in &words[..7] {
+            assert!(tree.play_out(answer).is_ok());
+        }
+
+        // With no tree in reach, the inputs are still recorded
+        let (tree, manifest) = solve_recorded(
+            ("words", &words),
+            ("words", &words[..7]),
+            1,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None,
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        assert!(tree.is_none());
+        assert_eq!((manifest.search.max_depth, manifest.result), (1, None));
+    }
+}
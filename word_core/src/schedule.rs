@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{analysis::AnswerCluster, error::WordGameError, mask_encoding::WordListId, word::Word};
+
+/// The schedule format version written by this crate
+pub const SCHEDULE_VERSION: u32 = 1;
+
+/// What a schedule has to respect when picking each day's answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleConstraints {
+    /// Days that must pass before an answer can be used again
+    pub min_repeat_gap: usize,
+
+    /// Length of the window, in days, in which no two answers from the same cluster may appear
+    pub similar_window: usize,
+
+    /// Target difficulty for each day of a repeating cycle, or empty to ignore difficulty
+    pub difficulty_cycle: Vec<f64>,
+}
+
+/// A day-by-day rotation of answers, which can be saved and extended later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub version: u32,
+    pub answers: WordListId,
+    pub constraints: ScheduleConstraints,
+
+    /// The answer index chosen for each day so far
+    pub days: Vec<u16>,
+}
+
+impl Schedule {
+    /// Start an empty schedule over an answer list.
+    pub fn new(answers: WordListId, constraints: ScheduleConstraints) -> Self {
+        Self {
+            version: SCHEDULE_VERSION,
+            answers,
+            constraints,
+            days: vec![],
+        }
+    }
+
+    /// Schedule `num_days` more days, continuing from the days already scheduled.
+    ///
+    /// Each day takes, from the answers allowed by the constraints, the one closest to that
+    /// day's target difficulty, breaking ties by the longest time since last use (never used
+    /// first) and then by answer index. `difficulties` holds a score per answer, and
+    /// `clusters` groups answers too similar to appear close together, like those from
+    /// `cluster_answers_by_signature`. If no answer fits some day, the days before it are kept
+    /// and an error is returned.
+    pub fn extend(
+        &mut self,
+        num_days: usize,
+        difficulties: &[f64],
+        clusters: &[AnswerCluster],
+    ) -> Result<(), WordGameError> {
+        if difficulties.len() != self.answers.len {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("{} difficulty scores", self.answers.len),
+                found: format!("{} difficulty scores", difficulties.len()),
+            });
+        }
+        let mut cluster_by_answer: HashMap<u16, usize> = HashMap::new();
+        // A lone answer has nothing to be confused with, and repeats are handled separately
+        for (cluster_ind, cluster) in clusters
+            .iter()
+            .enumerate()
+            .filter(|(_, cluster)| cluster.len() > 1)
+        {
+            for answer_ind in &cluster.answer_inds {
+                cluster_by_answer.insert(*answer_ind, cluster_ind);
+            }
+        }
+        let mut last_used: HashMap<u16, usize> = HashMap::new();
+        for (day, answer_ind) in self.days.iter().enumerate() {
+            last_used.insert(*answer_ind, day);
+        }
+
+        for _ in 0..num_days {
+            let day = self.days.len();
+            let recent_clusters: Vec<usize> = self.days
+                [day.saturating_sub(self.constraints.similar_window.saturating_sub(1))..]
+                .iter()
+                .filter_map(|answer_ind| cluster_by_answer.get(answer_ind).copied())
+                .collect();
+            let target = match self.constraints.difficulty_cycle.len() {
+                0 => None,
+                cycle_len => Some(self.constraints.difficulty_cycle[day % cycle_len]),
+            };
+            let best = (0..self.answers.len as u16)
+                .filter(|answer_ind| {
+                    last_used
+                        .get(answer_ind)
+                        .is_none_or(|used| day - used >= self.constraints.min_repeat_gap)
+                })
+                .filter(|answer_ind| {
+                    cluster_by_answer
+                        .get(answer_ind)
+                        .is_none_or(|cluster_ind| !recent_clusters.contains(cluster_ind))
+                })
+                .min_by(|a, b| {
+                    let distance = |answer_ind: &u16| {
+                        target.map_or(0.0, |target| {
+                            (difficulties[*answer_ind as usize] - target).abs()
+                        })
+                    };
+                    let last_use = |answer_ind: &u16| last_used.get(answer_ind).map(|day| day + 1);
+                    distance(a)
+                        .total_cmp(&distance(b))
+                        .then(last_use(a).cmp(&last_use(b)))
+                        .then(a.cmp(b))
+                })
+                .ok_or_else(|| {
+                    WordGameError::Infeasible(format!(
+                        "no answer fits the constraints on day {}",
+                        day
+                    ))
+                })?;
+            self.days.push(best);
+            last_used.insert(best, day);
+        }
+        Ok(())
+    }
+
+    /// The scheduled answers as words, checking they come from the list the schedule was made
+    /// for.
+    pub fn words<const WORD_SIZE: usize>(
+        &self,
+        answers: &[Word<WORD_SIZE, 26>],
+    ) -> Result<Vec<Word<WORD_SIZE, 26>>, WordGameError> {
+        self.answers.check(answers)?;
+        Ok(self
+            .days
+            .iter()
+            .map(|answer_ind| answers[*answer_ind as usize])
+            .collect())
+    }
+
+    /// Serialize to compact JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Parse from JSON, failing if it was written by an incompatible version.
+    pub fn from_json(raw: &str) -> Result<Self, WordGameError> {
+        let schedule: Self = serde_json::from_str(raw)?;
+        if schedule.version != SCHEDULE_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("schedule v{}", SCHEDULE_VERSION),
+                found: format!("schedule v{}", schedule.version),
+            });
+        }
+        Ok(schedule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers() -> Vec<Word<5, 26>> {
+        ["fight", "light", "board", "bread", "brain", "sheep"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect()
+    }
+
+    fn constraints(difficulty_cycle: Vec<f64>) -> ScheduleConstraints {
+        ScheduleConstraints {
+            min_repeat_gap: 4,
+            similar_window: 3,
+            difficulty_cycle,
+        }
+    }
+
+    #[test]
+    fn test_schedule_respects_constraints() {
+        let answers = answers();
+        let clusters = vec![AnswerCluster {
+            answer_inds: vec![0, 1],
+            signature: vec![],
+        }];
+        let difficulties = [1.0, 1.0, 3.0, 3.0, 3.0, 5.0];
+        let mut schedule = Schedule::new(
+            WordListId::new("test", &answers),
+            constraints(vec![1.0, 3.0]),
+        );
+        schedule.extend(8, &difficulties, &clusters).unwrap();
+        assert_eq!(schedule.days, vec![0, 2, 3, 4, 1, 2, 3, 4]);
+        for (day, answer_ind) in schedule.days.iter().enumerate() {
+            // No repeats within the last three days
+            assert!(!schedule.days[day.saturating_sub(3)..day].contains(answer_ind));
+            // Fight and light are too similar to both appear in any three day window
+            assert!(
+                *answer_ind > 1
+                    || schedule.days[day.saturating_sub(2)..day]
+                        .iter()
+                        .all(|other| *other > 1)
+            );
+        }
+
+        // Words are only given for the list the schedule was made for
+        assert_eq!(schedule.words(&answers).unwrap()[1], answers[2]);
+        assert!(schedule.words(&answers[..5]).is_err());
+    }
+
+    #[test]
+    fn test_schedule_resumes() {
+        let answers = answers();
+        let difficulties = [2.0; 6];
+        let mut whole = Schedule::new(WordListId::new("test", &answers), constraints(vec![]));
+        whole.extend(10, &difficulties, &[]).unwrap();
+
+        let mut resumed = Schedule::new(WordListId::new("test", &answers), constraints(vec![]));
+        resumed.extend(4, &difficulties, &[]).unwrap();
+        let mut resumed = Schedule::from_json(&resumed.to_json()).unwrap();
+        resumed.extend(6, &difficulties, &[]).unwrap();
+        assert_eq!(resumed, whole);
+        // With difficulty ignored, answers rotate through the whole list
+        assert_eq!(whole.days, vec![0, 1, 2, 3, 4, 5, 0, 1, 2, 3]);
+
+        // Six answers can't go eight days without repeats
+        let mut strict = Schedule::new(
+            WordListId::new("test", &answers),
+            ScheduleConstraints {
+                min_repeat_gap: 8,
+                ..constraints(vec![])
+            },
+        );
+        assert!(matches!(
+            strict.extend(8, &difficulties, &[]),
+            Err(WordGameError::Infeasible(_))
+        ));
+        assert_eq!(strict.days.len(), 6);
+    }
+}
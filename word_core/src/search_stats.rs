@@ -0,0 +1,228 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::decision_tree_general::{
+    AbandonReason, PruneReason, SearchEvent, SearchObserver, SearchStep,
+};
+
+/// Counts of what an exhaustive search did, for comparing pruning rules and heuristics by more
+/// than their run times.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchStats {
+    /// Nodes whose guesses were evaluated, by depth below where the search started
+    pub nodes_by_depth: Vec<u64>,
+
+    /// Time spent searching nodes at each depth. A node's time includes the nodes below it, so
+    /// each depth covers the ones deeper than it too.
+    pub elapsed_by_depth: Vec<Duration>,
+
+    /// Nodes given up on without evaluating any guesses, because the depth limit was reached
+    /// or couldn't be avoided
+    pub abandoned_by_depth_limit: u64,
+
+    /// Nodes given up on without evaluating any guesses, because the max total was exceeded or
+    /// couldn't be avoided
+    pub abandoned_by_cost_limit: u64,
+
+    /// Guesses evaluated in full
+    pub guesses_evaluated: u64,
+
+    /// Guesses skipped because every answer gives them the same hint
+    pub pruned_useless: u64,
+
+    /// Guesses skipped because their lower bound reached the most they may total
+    pub pruned_by_lower_bound: u64,
+
+    /// Guesses dropped because some child node had no tree within its constraints
+    pub pruned_no_guarantee: u64,
+
+    /// Guesses dropped because the children solved so far reached the most they may total
+    pub pruned_over_max: u64,
+
+    /// Guesses skipped because another guess got the same hint for every possible answer
+    pub pruned_duplicate: u64,
+
+    /// Guesses skipped because another guess split the answers at least as finely
+    pub pruned_dominated: u64,
+
+    /// Nodes that ran over budget and fell back on the greedy tree
+    pub budget_exceeded: u64,
+
+    pub transposition_hits: u64,
+    pub transposition_misses: u64,
+}
+
+impl SearchStats {
+    /// Get the fraction of transposition table lookups that found a result.
+    pub fn transposition_hit_rate(&self) -> f64 {
+        let lookups = self.transposition_hits + self.transposition_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.transposition_hits as f64 / lookups as f64
+        }
+    }
+
+    /// Get the total number of guesses dropped before being fully evaluated, by any rule.
+    pub fn guesses_pruned(&self) -> u64 {
+        self.pruned_useless
+            + self.pruned_by_lower_bound
+            + self.pruned_no_guarantee
+            + self.pruned_over_max
+            + self.pruned_duplicate
+            + self.pruned_dominated
+    }
+
+    fn record(&mut self, depth: usize, event: SearchEvent) {
+        match event {
+            SearchEvent::GuessOrder(_) => {
+                if self.nodes_by_depth.len() <= depth {
+                    self.nodes_by_depth.resize(depth + 1, 0);
+                }
+                self.nodes_by_depth[depth] += 1;
+            }
+            SearchEvent::NodeAbandoned(
+                AbandonReason::DepthLimitReached | AbandonReason::DepthLimitUnavoidable,
+            ) => self.abandoned_by_depth_limit += 1,
+            SearchEvent::NodeAbandoned(
+                AbandonReason::CostLimitExceeded | AbandonReason::CostLimitUnavoidable,
+            ) => self.abandoned_by_cost_limit += 1,
+            SearchEvent::GuessEvaluated { .. } => self.guesses_evaluated += 1,
+            SearchEvent::GuessPruned { reason, .. } => match reason {
+                PruneReason::Useless => self.pruned_useless += 1,
+                PruneReason::LowerBound { .. } => self.pruned_by_lower_bound += 1,
+                PruneReason::NoGuarantee => self.pruned_no_guarantee += 1,
+                PruneReason::OverMax { .. } => self.pruned_over_max += 1,
+                PruneReason::Duplicate { .. } => self.pruned_duplicate += 1,
+                PruneReason::Dominated { .. } => self.pruned_dominated += 1,
+            },
+            SearchEvent::NodeFinished { elapsed, .. } => self.add_elapsed(depth, elapsed),
+            SearchEvent::BudgetExceeded { elapsed, .. } => {
+                self.budget_exceeded += 1;
+                self.add_elapsed(depth, elapsed);
+            }
+            SearchEvent::TranspositionLookup { hit: true } => self.transposition_hits += 1,
+            SearchEvent::TranspositionLookup { hit: false } => self.transposition_misses += 1,
+            _ => {}
+        }
+    }
+
+    fn add_elapsed(&mut self, depth: usize, elapsed: Duration) {
+        if self.elapsed_by_depth.len() <= depth {
+            self.elapsed_by_depth.resize(depth + 1, Duration::ZERO);
+        }
+        self.elapsed_by_depth[depth] += elapsed;
+    }
+}
+
+/// An observer that gathers `SearchStats` over every node of a search.
+///
+/// Copies share one set of stats, so keep one to read them after the search. Workers of a
+/// parallel root search aren't observed, so their nodes aren't counted.
+#[derive(Debug, Default, Clone)]
+pub struct StatsObserver {
+    /// The depth of this copy's node below where the search started
+    depth: usize,
+    stats: Arc<Mutex<SearchStats>>,
+}
+
+impl StatsObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the stats gathered so far.
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl SearchObserver for StatsObserver {
+    fn descend(&self, step: SearchStep) -> Self {
+        Self {
+            // A guess's events still belong to its node, and only its clues lead deeper
+            depth: match step {
+                SearchStep::Guess(_) => self.depth,
+                SearchStep::Clue { .. } => self.depth + 1,
+            },
+            stats: self.stats.clone(),
+        }
+    }
+
+    fn on_event(&self, event: SearchEvent) {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .record(self.depth, event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        decision_tree_general::{SearchOptions, compute_decision_tree_aggressive},
+        hint_matrix::compute_hints,
+        test_words,
+    };
+
+    #[test]
+    fn test_stats_observer() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let observer = StatsObserver::new();
+        compute_decision_tree_aggressive(
+            &hints,
+            answers,
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions {
+                transposition_bytes: 1 << 20,
+                ..SearchOptions::default()
+            },
+            Some(&observer),
+        )
+        .unwrap();
+
+        let stats = observer.stats();
+        assert_eq!(stats.nodes_by_depth[0], 1);
+        assert!(stats.nodes_by_depth.len() > 1);
+        assert!(stats.guesses_evaluated > 0);
+        assert!(stats.guesses_pruned() > 0);
+        assert!(stats.transposition_hits + stats.transposition_misses > 0);
+        // The root's time covers every node below it
+        assert!(stats.elapsed_by_depth[0] >= stats.elapsed_by_depth[1]);
+    }
+
+    #[test]
+    fn test_duplicates_counted_apart() {
+        let mut words = test_words::medium_list();
+        words.push(words[0]);
+        let hints = compute_hints(&words, &words[..30]);
+        let observer = StatsObserver::new();
+        compute_decision_tree_aggressive(
+            &hints,
+            (0..30).collect(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            Some(&observer),
+        )
+        .unwrap();
+
+        // The repeated "fight" is skipped as a duplicate, without dominance pruning on
+        let stats = observer.stats();
+        assert!(stats.pruned_duplicate > 0);
+        assert_eq!(stats.pruned_dominated, 0);
+    }
+}
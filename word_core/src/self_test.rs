@@ -0,0 +1,302 @@
+use std::{collections::HashSet, time::Instant};
+
+use serde::Serialize;
+
+use crate::{
+    dumb_word_search::dumb_search_words,
+    hint::WordHint,
+    hint_matrix::{HintMatrix, compute_hint_matrix_query_engine, compute_hint_matrix_simple},
+    load_words::load_guesses_and_answers,
+    query_generation::clue_to_query,
+    render::{RenderMode, render_mode},
+    word::Word,
+    word_search::{Query, SearchableWords},
+};
+
+/// How much of the word lists a self-test exercises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestConfig {
+    /// Guesses and answers to sample, spread evenly over each list
+    pub sample_size: usize,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        Self { sample_size: 50 }
+    }
+}
+
+/// Where the self-test ran, to attach to bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvironmentInfo {
+    pub crate_version: String,
+    pub os: String,
+    pub arch: String,
+    pub num_threads: usize,
+    pub render_mode: String,
+    pub gpu_feature: bool,
+    pub tui_feature: bool,
+}
+
+impl EnvironmentInfo {
+    /// Describe the running process.
+    pub fn current() -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            num_threads: rayon::current_num_threads(),
+            render_mode: format!("{:?}", render_mode()),
+            gpu_feature: cfg!(feature = "gpu"),
+            tui_feature: cfg!(feature = "tui"),
+        }
+    }
+}
+
+/// The outcome of one self-test check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub passed: bool,
+
+    /// What was checked, or the first thing that went wrong
+    pub detail: String,
+
+    pub millis: u64,
+}
+
+/// Every check a self-test ran, with the environment it ran in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelfTestReport {
+    pub environment: EnvironmentInfo,
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+}
+
+/// Exercise the real loading, search and hint code paths on a sample of the given word lists,
+/// as a first check when the solver seems to give wrong answers.
+///
+/// If the lists can't be loaded, that's the only check reported.
+pub fn run_self_test<const WORD_SIZE: usize>(
+    allowed_guesses_path: &str,
+    possible_answers_path: &str,
+    config: SelfTestConfig,
+) -> SelfTestReport {
+    let mut checks = vec![];
+    let mut lists = None;
+    checks.push(run_check("load word lists", || {
+        let (allowed_guesses, possible_answers) = load_guesses_and_answers::<WORD_SIZE, 26>(
+            allowed_guesses_path,
+            possible_answers_path,
+            false,
+        )
+        .map_err(|err| err.to_string())?;
+        let detail = format!(
+            "{} allowed guesses, {} possible answers",
+            allowed_guesses.len(),
+            possible_answers.len()
+        );
+        lists = Some((allowed_guesses, possible_answers));
+        Ok(detail)
+    }));
+
+    if let Some((allowed_guesses, possible_answers)) = lists {
+        let guesses = sample(&allowed_guesses, config.sample_size);
+        let answers = sample(&possible_answers, config.sample_size);
+        checks.push(run_check("hint id round trip", check_hint_ids::<WORD_SIZE>));
+        checks.push(run_check("search table", || check_search_table(&answers)));
+        checks.push(run_check("dumb vs smart parity", || {
+            check_parity(&guesses, &answers)
+        }));
+        checks.push(run_check("hint matrix", || {
+            check_hint_matrix(&guesses, &answers)
+        }));
+    }
+    SelfTestReport {
+        environment: EnvironmentInfo::current(),
+        checks,
+    }
+}
+
+fn run_check(name: &str, check: impl FnOnce() -> Result<String, String>) -> CheckResult {
+    let start = Instant::now();
+    let result = check();
+    let millis = start.elapsed().as_millis() as u64;
+    let (passed, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    CheckResult {
+        name: name.to_string(),
+        passed,
+        detail,
+        millis,
+    }
+}
+
+/// Take up to `sample_size` words spread evenly through the list.
+fn sample<const WORD_SIZE: usize>(
+    words: &[Word<WORD_SIZE, 26>],
+    sample_size: usize,
+) -> Vec<Word<WORD_SIZE, 26>> {
+    let step = words.len().div_ceil(sample_size.max(1)).max(1);
+    words.iter().step_by(step).copied().collect()
+}
+
+/// Every hint must survive conversion to an id and to its symbols and back.
+fn check_hint_ids<const WORD_SIZE: usize>() -> Result<String, String> {
+    let hints = WordHint::<WORD_SIZE>::all_possible();
+    let mut seen_ids = HashSet::new();
+    for hint in &hints {
+        let hint_id = hint.hint_id();
+        if !seen_ids.insert(hint_id) || WordHint::<WORD_SIZE>::from_id(hint_id) != *hint {
+            return Err(format!(
+                "hint {} doesn't round trip through id {}",
+                hint, hint_id
+            ));
+        }
+        let rendered = hint.render(RenderMode::Unicode);
+        if WordHint::<WORD_SIZE>::try_from(rendered.as_str()).ok() != Some(*hint) {
+            return Err(format!("hint {} doesn't round trip through text", rendered));
+        }
+    }
+    Ok(format!("{} hints", hints.len()))
+}
+
+/// Every word must be found by a query matching all of its letters, and only its duplicates
+/// alongside it.
+fn check_search_table<const WORD_SIZE: usize>(
+    answers: &[Word<WORD_SIZE, 26>],
+) -> Result<String, String> {
+    let table = SearchableWords::build(answers.to_vec());
+    for answer in answers {
+        let query = Query::And(
+            (0..WORD_SIZE)
+                .map(|ind| Query::Match {
+                    ind,
+                    chr: answer.0[ind],
+                })
+                .collect(),
+        );
+        let found = table.eval_query(query).true_inds();
+        let expected: Vec<usize> = (0..answers.len())
+            .filter(|other_ind| answers[*other_ind] == *answer)
+            .collect();
+        if found != expected {
+            return Err(format!(
+                "{} found at rows {:?}, expected {:?}",
+                answer, found, expected
+            ));
+        }
+    }
+    Ok(format!("{} words", answers.len()))
+}
+
+/// The search table must agree with scoring every answer directly, for every sampled clue.
+fn check_parity<const WORD_SIZE: usize>(
+    guesses: &[Word<WORD_SIZE, 26>],
+    answers: &[Word<WORD_SIZE, 26>],
+) -> Result<String, String> {
+    let table = SearchableWords::build(answers.to_vec());
+    for guess in guesses {
+        for answer in answers {
+            let hint = WordHint::from_guess_and_answer(guess, answer);
+            let dumb: HashSet<Word<WORD_SIZE, 26>> = dumb_search_words(answers, *guess, hint)
+                .into_iter()
+                .collect();
+            let smart: HashSet<Word<WORD_SIZE, 26>> = table
+                .filter_words(&table.eval_query(clue_to_query(*guess, hint)))
+                .into_iter()
+                .collect();
+            if dumb != smart {
+                return Err(format!(
+                    "guess {} with hint {} matched {} words by search but {} by scoring",
+                    guess,
+                    hint,
+                    smart.len(),
+                    dumb.len()
+                ));
+            }
+        }
+    }
+    Ok(format!("{} clues", guesses.len() * answers.len()))
+}
+
+/// Both CPU hint matrix strategies must agree with scoring each pair directly.
+fn check_hint_matrix<const WORD_SIZE: usize>(
+    guesses: &[Word<WORD_SIZE, 26>],
+    answers: &[Word<WORD_SIZE, 26>],
+) -> Result<String, String> {
+    let strategies: [(&str, HintMatrix); 2] = [
+        ("simple", compute_hint_matrix_simple(guesses, answers)),
+        (
+            "query engine",
+            compute_hint_matrix_query_engine(guesses, answers),
+        ),
+    ];
+    for (strategy, matrix) in &strategies {
+        for (guess_ind, guess) in guesses.iter().enumerate() {
+            for (answer_ind, answer) in answers.iter().enumerate() {
+                let expected = WordHint::from_guess_and_answer(guess, answer).hint_id();
+                if matrix.hint_id(guess_ind, answer_ind) != expected {
+                    return Err(format!(
+                        "{} strategy gives {} against {} hint id {}, expected {}",
+                        strategy,
+                        guess,
+                        answer,
+                        matrix.hint_id(guess_ind, answer_ind),
+                        expected
+                    ));
+                }
+            }
+        }
+    }
+    Ok(format!("{} pairs", guesses.len() * answers.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+
+    #[test]
+    fn test_run_self_test() {
+        let dir = env::temp_dir().join(format!("word_core_self_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let list = dir.join("words.txt");
+        fs::write(&list, "fight\nlight\nboard\nbread\neerie\ngeese\nabbey").unwrap();
+        let list = list.to_string_lossy();
+
+        let report = run_self_test::<5>(&list, &list, SelfTestConfig { sample_size: 4 });
+        assert!(report.passed(), "{:#?}", report);
+        assert_eq!(report.checks.len(), 5);
+        assert_eq!(
+            report.checks[0].detail,
+            "7 allowed guesses, 7 possible answers"
+        );
+
+        let missing = dir.join("missing.txt");
+        let report =
+            run_self_test::<5>(&missing.to_string_lossy(), &list, SelfTestConfig::default());
+        assert!(!report.passed());
+        assert_eq!(report.checks.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sample_spreads_over_list() {
+        let words: Vec<Word<5, 26>> = ["fight", "light", "board", "bread", "eerie"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        assert_eq!(sample(&words, 2), vec![words[0], words[3]]);
+        assert_eq!(sample(&words, 10), words);
+    }
+}
@@ -0,0 +1,1105 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    column::Column,
+    decision_tree_general::{
+        ClueLegality, NoPrinter, SearchOptions, compute_decision_tree_aggressive,
+    },
+    error::WordGameError,
+    hint::{CharHint, WordHint},
+    hint_matrix::{answers_first, compute_hints},
+    mask_encoding::{EncodedMask, WordListId},
+    query_generation::{clue_possible, clue_to_query, history_to_query},
+    readable_tree::ReadableTreeNode,
+    ruleset::{ClueLegalityMasks, GuessLegality, LegalGuesses},
+    suggestion::{
+        PartitionStats, SacrificeDecision, Suggestion, analyze_sacrifice, partition_stats,
+        rank_suggestions,
+    },
+    word::Word,
+    word_search::{Elimination, SearchableWords},
+};
+
+/// A single-tile change to an entered hint that makes the history consistent again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HintCorrection<const WORD_SIZE: usize> {
+    /// Which entered row to change
+    pub row: usize,
+
+    /// Which tile in that row to change
+    pub position: usize,
+
+    /// The full hint for the row after the change
+    pub corrected_hint: WordHint<WORD_SIZE>,
+
+    /// How many candidates remain after the change
+    pub num_candidates: usize,
+}
+
+/// Which word list the current candidates are drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateSource {
+    /// The configured answer list
+    Primary,
+
+    /// The larger fallback vocabulary, used once no configured answer fits the hints
+    Fallback,
+}
+
+/// How one guess played from a recorded position would have gone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIfBranch<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    pub guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+
+    /// The hint the guess got or would have got, if the answer is known
+    pub hint: Option<WordHint<WORD_SIZE>>,
+
+    /// The candidates left after the hint, if it's known
+    pub candidates: Option<Vec<Word<WORD_SIZE, ALPHABET_SIZE>>>,
+
+    /// How the guess splits the candidates it was played against
+    pub stats: PartitionStats,
+
+    /// Expected guesses from the position, counting this one, when playing best afterwards, or
+    /// `None` if the guess can't guarantee a win in the guesses left
+    pub expected_cost: Option<f64>,
+}
+
+/// A recorded guess side by side with a different guess from the same position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIf<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    /// The row of the history the branch was taken at
+    pub row: usize,
+
+    /// The candidates before that row's guess
+    pub candidates_before: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+
+    pub actual: WhatIfBranch<WORD_SIZE, ALPHABET_SIZE>,
+    pub hypothetical: WhatIfBranch<WORD_SIZE, ALPHABET_SIZE>,
+}
+
+/// The session snapshot format version written by this crate
+pub const SESSION_SNAPSHOT_VERSION: u32 = 1;
+
+/// A saved `SolverSession`, identifying its word lists by hash rather than storing them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot<const WORD_SIZE: usize> {
+    pub version: u32,
+    pub allowed_guesses: WordListId,
+    pub possible_answers: WordListId,
+
+    /// The fallback vocabulary, if one was configured
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback_answers: Option<WordListId>,
+
+    pub history: Vec<(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)>,
+
+    /// Rows that could be redone, most recently undone last
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub redo: Vec<(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)>,
+
+    /// The possible answers still consistent with the history
+    pub candidates: EncodedMask,
+
+    /// Which guesses the session suggests after the entered hints
+    #[serde(skip_serializing_if = "is_any_legality", default)]
+    pub guess_legality: GuessLegality,
+}
+
+fn is_any_legality(guess_legality: &GuessLegality) -> bool {
+    *guess_legality == GuessLegality::Any
+}
+
+impl<const WORD_SIZE: usize> SessionSnapshot<WORD_SIZE> {
+    /// Serialize to compact JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Parse from JSON, rejecting snapshots written in an unknown format version.
+    pub fn from_json(raw: &str) -> Result<Self, WordGameError> {
+        let snapshot: Self = serde_json::from_str(raw)?;
+        if snapshot.version != SESSION_SNAPSHOT_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("session snapshot v{}", SESSION_SNAPSHOT_VERSION),
+                found: format!("session snapshot v{}", snapshot.version),
+            });
+        }
+        Ok(snapshot)
+    }
+}
+
+/// The state of one game being solved interactively: the word lists and the hints so far.
+pub struct SolverSession<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    allowed_guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    possible_answers: SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+    fallback_answers: Option<SearchableWords<WORD_SIZE, ALPHABET_SIZE>>,
+    history: Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)>,
+
+    /// Mask over the possible answers after each entered row, so undoing is a pop
+    step_masks: Vec<Column>,
+
+    /// Rows undone since the last new entry, most recently undone last, with their masks
+    redo_stack: Vec<(
+        (Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>),
+        Column,
+    )>,
+
+    /// A tree to follow, with the most guesses its subtrees may take, expanded as it's played
+    tree: Option<(ReadableTreeNode<WORD_SIZE>, u8)>,
+
+    /// Which guesses are suggested after the entered hints
+    guess_legality: GuessLegality,
+
+    /// The allowed guesses, searchable for which the guess policy leaves legal
+    legal_guesses: LegalGuesses<WORD_SIZE, ALPHABET_SIZE>,
+
+    /// Mask over the allowed guesses the policy leaves legal after each entered row
+    legal_masks: Vec<Column>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> SolverSession<WORD_SIZE, ALPHABET_SIZE> {
+    pub fn new(
+        allowed_guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        possible_answers: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ) -> Self {
+        Self {
+            legal_guesses: LegalGuesses::new(allowed_guesses.clone()),
+            legal_masks: vec![],
+            allowed_guesses,
+            possible_answers: SearchableWords::build(possible_answers),
+            fallback_answers: None,
+            history: vec![],
+            step_masks: vec![],
+            redo_stack: vec![],
+            tree: None,
+            guess_legality: GuessLegality::Any,
+        }
+    }
+
+    /// Fall back to candidates from a larger vocabulary if the answer turns out not to be in the
+    /// configured answer list.
+    pub fn with_fallback_vocabulary(
+        mut self,
+        vocabulary: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ) -> Self {
+        self.fallback_answers = Some(SearchableWords::build(vocabulary));
+        self
+    }
+
+    /// Fall back to candidates from the full allowed guess list if the answer turns out not to be
+    /// in the configured answer list.
+    pub fn with_guess_list_fallback(self) -> Self {
+        let vocabulary = self.allowed_guesses.clone();
+        self.with_fallback_vocabulary(vocabulary)
+    }
+
+    /// Follow a decision tree over the configured lists, solving any unexpanded subtrees within
+    /// `max_depth` guesses in total once they're reached.
+    pub fn with_tree(mut self, tree: ReadableTreeNode<WORD_SIZE>, max_depth: u8) -> Self {
+        self.tree = Some((tree, max_depth));
+        self
+    }
+
+    /// Suggest only guesses the policy allows after the entered hints, as when playing hard
+    /// mode.
+    pub fn with_guess_legality(mut self, guess_legality: GuessLegality) -> Self {
+        self.guess_legality = guess_legality;
+        self.legal_masks.clear();
+        for row in 0..self.history.len() {
+            let (guess, hint) = self.history[row];
+            let mask = self.legal_mask_after(guess, hint);
+            self.legal_masks.push(mask);
+        }
+        self
+    }
+
+    /// Get the tree being followed, including any subtrees expanded so far.
+    pub fn tree(&self) -> Option<&ReadableTreeNode<WORD_SIZE>> {
+        self.tree.as_ref().map(|(tree, _)| tree)
+    }
+
+    /// Record a guess and the hint it received, discarding anything that could be redone.
+    pub fn add_hint(&mut self, guess: Word<WORD_SIZE, ALPHABET_SIZE>, hint: WordHint<WORD_SIZE>) {
+        let mut mask = self.possible_answers.eval_query(clue_to_query(guess, hint));
+        if let Some(prev_mask) = self.step_masks.last() {
+            mask &= prev_mask.clone();
+        }
+        self.redo_stack.clear();
+        self.legal_masks.push(self.legal_mask_after(guess, hint));
+        self.history.push((guess, hint));
+        self.step_masks.push(mask);
+    }
+
+    /// Narrow the legal guesses after the entered rows by one more clue.
+    fn legal_mask_after(
+        &self,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+        hint: WordHint<WORD_SIZE>,
+    ) -> Column {
+        let parent_mask = match self.legal_masks.last() {
+            Some(parent_mask) => parent_mask.clone(),
+            None => Column::from_true(self.allowed_guesses.len()),
+        };
+        self.legal_guesses.narrow(
+            &parent_mask,
+            self.guess_legality.legality_query(guess, hint),
+        )
+    }
+
+    /// Remove the most recent guess and hint, returning it if there was one.
+    pub fn undo(&mut self) -> Option<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)> {
+        let row = self.history.pop()?;
+        let mask = self.step_masks.pop().unwrap();
+        self.legal_masks.pop();
+        self.redo_stack.push((row, mask));
+        Some(row)
+    }
+
+    /// Restore the most recently undone guess and hint, returning it if there was one.
+    pub fn redo(&mut self) -> Option<(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)> {
+        let (row, mask) = self.redo_stack.pop()?;
+        self.legal_masks.push(self.legal_mask_after(row.0, row.1));
+        self.history.push(row);
+        self.step_masks.push(mask);
+        Some(row)
+    }
+
+    /// Whether there's an entered row to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// Whether there's an undone row to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Get the guesses and hints entered so far.
+    pub fn history(&self) -> &[(Word<WORD_SIZE, ALPHABET_SIZE>, WordHint<WORD_SIZE>)] {
+        &self.history
+    }
+
+    /// Whether a guess is in the allowed guess list.
+    ///
+    /// Guesses outside the list can still be entered, and constrain the candidates as normal.
+    pub fn is_allowed_guess(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> bool {
+        self.allowed_guesses.contains(guess)
+    }
+
+    /// Whether the guess policy allows a guess after the entered hints.
+    pub fn is_legal_guess(&self, guess: &Word<WORD_SIZE, ALPHABET_SIZE>) -> bool {
+        self.guess_legality.is_legal_guess(guess, &self.history)
+    }
+
+    /// Get the allowed guesses the guess policy allows after the entered hints.
+    pub fn legal_guesses(&self) -> Vec<Word<WORD_SIZE, ALPHABET_SIZE>> {
+        match self.legal_masks.last() {
+            Some(mask) => self.legal_guesses.words(mask),
+            None => self.allowed_guesses.clone(),
+        }
+    }
+
+    /// Get the rows of the history whose guess isn't in the allowed guess list.
+    pub fn out_of_list_rows(&self) -> Vec<usize> {
+        self.history
+            .iter()
+            .enumerate()
+            .filter(|(_, (guess, _))| !self.is_allowed_guess(guess))
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Get the possible answers the given entered row ruled out beyond those ruled out before
+    /// it, each with the parts of that row's clue it failed. Returns `None` past the history.
+    pub fn eliminations_at(
+        &self,
+        row: usize,
+    ) -> Option<Vec<Elimination<WORD_SIZE, ALPHABET_SIZE>>> {
+        let (guess, hint) = *self.history.get(row)?;
+        let before = match row {
+            0 => Column::from_true(self.possible_answers.len()),
+            row => self.step_masks[row - 1].clone(),
+        };
+        Some(self.possible_answers.attribute_eliminations(
+            &before,
+            &self.step_masks[row],
+            clue_to_query(guess, hint),
+        ))
+    }
+
+    /// Compare the guess entered at a row with a different guess from the same position,
+    /// leaving the session as it is.
+    ///
+    /// If the history ends with the answer found, the hypothetical guess is also given the hint
+    /// it would have got. Expected costs assume at most `max_guesses` guesses in total, and
+    /// search exhaustively over the allowed guesses the guess policy leaves legal, so are only
+    /// cheap once few candidates are left. Returns `None` past the history.
+    pub fn what_if(
+        &self,
+        row: usize,
+        guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+        max_guesses: u8,
+    ) -> Option<WhatIf<WORD_SIZE, ALPHABET_SIZE>> {
+        let (actual_guess, actual_hint) = *self.history.get(row)?;
+        let before = match row {
+            0 => Column::from_true(self.possible_answers.len()),
+            row => self.step_masks[row - 1].clone(),
+        };
+        let candidates_before = self.possible_answers.filter_words(&before);
+        let answer = self
+            .history
+            .last()
+            .filter(|(_, hint)| hint.all_correct())
+            .map(|(guess, _)| *guess);
+
+        let guesses = answers_first(
+            &candidates_before,
+            [actual_guess, guess].iter().chain(&self.allowed_guesses),
+        );
+        let hints = compute_hints(&guesses, &candidates_before);
+        let options = self.search_options_before(row, &guesses);
+        let branch = |guess: Word<WORD_SIZE, ALPHABET_SIZE>, hint: Option<WordHint<WORD_SIZE>>| {
+            let guess_ind = guesses.iter().position(|other| *other == guess).unwrap();
+            WhatIfBranch {
+                guess,
+                hint,
+                candidates: hint.map(|hint| {
+                    candidates_before
+                        .iter()
+                        .enumerate()
+                        .filter(|(answer_ind, _)| hints[guess_ind][*answer_ind] == hint.hint_id())
+                        .map(|(_, candidate)| *candidate)
+                        .collect()
+                }),
+                stats: partition_stats(&guess, &candidates_before),
+                expected_cost: expected_cost(&hints, guess_ind, row as u8, max_guesses, &options),
+            }
+        };
+        Some(WhatIf {
+            row,
+            actual: branch(actual_guess, Some(actual_hint)),
+            hypothetical: branch(
+                guess,
+                answer.map(|answer| WordHint::from_guess_and_answer(&guess, &answer)),
+            ),
+            candidates_before,
+        })
+    }
+
+    /// Get the search options that keep to the guess policy from a row of the history on, over
+    /// the given guesses.
+    fn search_options_before(
+        &self,
+        row: usize,
+        guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> SearchOptions {
+        if self.guess_legality == GuessLegality::Any {
+            return SearchOptions::default();
+        }
+        let legal_guesses = LegalGuesses::new(guesses.to_vec());
+        let root_legal_guesses =
+            self.history[..row]
+                .iter()
+                .fold(legal_guesses.root_mask(), |mask, (guess, hint)| {
+                    legal_guesses.narrow(&mask, self.guess_legality.legality_query(*guess, *hint))
+                });
+        let guess_legality: Arc<dyn ClueLegality> = Arc::new(ClueLegalityMasks::new(
+            self.guess_legality,
+            guesses.to_vec(),
+        ));
+        SearchOptions {
+            guess_legality: Some(guess_legality),
+            root_legal_guesses: Some(root_legal_guesses),
+            ..SearchOptions::default()
+        }
+    }
+
+    /// Get the full list of allowed guesses.
+    pub fn allowed_guesses(&self) -> &[Word<WORD_SIZE, ALPHABET_SIZE>] {
+        &self.allowed_guesses
+    }
+
+    /// Get the full, unfiltered set of possible answers.
+    pub fn possible_answers(&self) -> &SearchableWords<WORD_SIZE, ALPHABET_SIZE> {
+        &self.possible_answers
+    }
+
+    /// Get a mask over the possible answers of those consistent with every entered hint.
+    pub fn candidates_mask(&self) -> Column {
+        self.step_masks
+            .last()
+            .cloned()
+            .unwrap_or_else(|| Column::from_true(self.possible_answers.len()))
+    }
+
+    /// Get the words consistent with every entered hint, and which list they came from.
+    ///
+    /// Candidates come from the configured answer list while any fit, switching over to the
+    /// fallback vocabulary (if configured) once none do. Returns `None` if nothing fits.
+    pub fn candidates_with_source(
+        &self,
+    ) -> Option<(CandidateSource, Vec<Word<WORD_SIZE, ALPHABET_SIZE>>)> {
+        let primary = self.possible_answers.filter_words(&self.candidates_mask());
+        if !primary.is_empty() {
+            return Some((CandidateSource::Primary, primary));
+        }
+        let fallback_answers = self.fallback_answers.as_ref()?;
+        let fallback = fallback_answers.filter_words(&self.candidates_mask_in(fallback_answers));
+        (!fallback.is_empty()).then_some((CandidateSource::Fallback, fallback))
+    }
+
+    /// Get the words consistent with every entered hint, using the fallback if needed.
+    pub fn candidates(&self) -> Vec<Word<WORD_SIZE, ALPHABET_SIZE>> {
+        self.candidates_with_source()
+            .map(|(_, candidates)| candidates)
+            .unwrap_or_default()
+    }
+
+    /// Get the candidates, or an error if the entered hints rule out every known word.
+    pub fn try_candidates(&self) -> Result<Vec<Word<WORD_SIZE, ALPHABET_SIZE>>, WordGameError> {
+        let candidates = self.candidates();
+        if candidates.is_empty() {
+            return Err(WordGameError::InconsistentHistory(format!(
+                "no possible answer matches all {} entered hints",
+                self.history.len()
+            )));
+        }
+        Ok(candidates)
+    }
+
+    /// Rank the legal guesses against the current candidates, best `top_n` first.
+    pub fn suggest(&self, top_n: usize) -> Vec<Suggestion<WORD_SIZE, ALPHABET_SIZE>> {
+        rank_suggestions(&self.legal_guesses(), &self.candidates(), top_n)
+    }
+
+    /// Decide whether to guess a remaining candidate or sacrifice a legal guess for information.
+    pub fn analyze_sacrifice(&self) -> Option<SacrificeDecision<WORD_SIZE, ALPHABET_SIZE>> {
+        analyze_sacrifice(&self.legal_guesses(), &self.candidates())
+    }
+
+    /// Find every single-tile change to one entered hint that leaves at least one candidate.
+    ///
+    /// This is meant for when the entered hints are inconsistent, most likely from a typo. The
+    /// corrections are checked against the configured answer list only, and are ordered by how
+    /// many candidates they leave, most first. Nothing is suggested if the history is already
+    /// consistent with that list.
+    pub fn suggest_hint_corrections(&self) -> Vec<HintCorrection<WORD_SIZE>> {
+        let row_masks = self.row_masks(&self.possible_answers);
+        let num_answers = self.possible_answers.len();
+        let candidates =
+            row_masks
+                .iter()
+                .fold(Column::from_true(num_answers), |mut acc, row_mask| {
+                    acc &= row_mask.clone();
+                    acc
+                });
+        if candidates.count_true() != 0 {
+            return vec![];
+        }
+
+        // For each row, the mask from every other row, via prefix and suffix products
+        let mut prefixes = vec![Column::from_true(num_answers)];
+        for row_mask in &row_masks {
+            let mut next = prefixes.last().unwrap().clone();
+            next &= row_mask.clone();
+            prefixes.push(next);
+        }
+        let mut suffixes = vec![Column::from_true(num_answers)];
+        for row_mask in row_masks.iter().rev() {
+            let mut next = suffixes.last().unwrap().clone();
+            next &= row_mask.clone();
+            suffixes.push(next);
+        }
+        suffixes.reverse();
+
+        // Gather every plausible single-tile flip so they can be evaluated as one batch
+        let mut flips: Vec<(usize, usize, WordHint<WORD_SIZE>)> = vec![];
+        let mut queries = vec![];
+        for (row, (guess, hint)) in self.history.iter().enumerate() {
+            for position in 0..WORD_SIZE {
+                for char_hint in [CharHint::Correct, CharHint::Elsewhere, CharHint::Nowhere] {
+                    if char_hint == hint.0[position] {
+                        continue;
+                    }
+                    let mut corrected_hint = *hint;
+                    corrected_hint.0[position] = char_hint;
+                    if !clue_possible(*guess, corrected_hint) {
+                        continue;
+                    }
+                    flips.push((row, position, corrected_hint));
+                    queries.push(clue_to_query(*guess, corrected_hint));
+                }
+            }
+        }
+
+        let mut corrections: Vec<HintCorrection<WORD_SIZE>> = flips
+            .into_iter()
+            .zip(self.possible_answers.eval_queries(queries))
+            .filter_map(|((row, position, corrected_hint), mut mask)| {
+                mask &= prefixes[row].clone();
+                mask &= suffixes[row + 1].clone();
+                let num_candidates = mask.count_true() as usize;
+                (num_candidates != 0).then_some(HintCorrection {
+                    row,
+                    position,
+                    corrected_hint,
+                    num_candidates,
+                })
+            })
+            .collect();
+        corrections.sort_by_key(|correction| {
+            (
+                Reverse(correction.num_candidates),
+                correction.row,
+                correction.position,
+            )
+        });
+        corrections
+    }
+
+    /// Get a mask over the given words of those consistent with every entered hint.
+    fn candidates_mask_in(&self, words: &SearchableWords<WORD_SIZE, ALPHABET_SIZE>) -> Column {
+        words.eval_query(history_to_query(&self.history))
+    }
+
+    /// Get the mask of the given words consistent with each entered row on its own.
+    fn row_masks(&self, words: &SearchableWords<WORD_SIZE, ALPHABET_SIZE>) -> Vec<Column> {
+        words.eval_queries(
+            self.history
+                .iter()
+                .map(|(guess, hint)| clue_to_query(*guess, *hint))
+                .collect(),
+        )
+    }
+}
+
+impl<const WORD_SIZE: usize> SolverSession<WORD_SIZE, 26> {
+    /// Get the tree's guess after the entered hints, solving and keeping any unexpanded subtree
+    /// on the way.
+    ///
+    /// Returns `None` if there's no tree, the entered guesses strayed from it, or the answer was
+    /// already found.
+    pub fn tree_guess(&mut self) -> Result<Option<Word<WORD_SIZE, 26>>, WordGameError> {
+        let Some((tree, max_depth)) = &mut self.tree else {
+            return Ok(None);
+        };
+        let mut node = tree;
+        for (num_guesses, (guess, hint)) in self.history.iter().enumerate() {
+            if node.should_guess != *guess || hint.all_correct() {
+                return Ok(None);
+            }
+            node = node.expand(
+                hint,
+                &self.allowed_guesses,
+                self.possible_answers.words(),
+                num_guesses + 1,
+                *max_depth,
+            )?;
+        }
+        Ok(Some(node.should_guess))
+    }
+
+    /// Save the session's history and configuration, to be restored later over the same lists.
+    pub fn snapshot(&self) -> SessionSnapshot<WORD_SIZE> {
+        let possible_answers = WordListId::new("possible answers", self.possible_answers.words());
+        SessionSnapshot {
+            version: SESSION_SNAPSHOT_VERSION,
+            allowed_guesses: WordListId::new("allowed guesses", &self.allowed_guesses),
+            fallback_answers: self
+                .fallback_answers
+                .as_ref()
+                .map(|fallback| WordListId::new("fallback answers", fallback.words())),
+            history: self.history.clone(),
+            redo: self.redo_stack.iter().map(|(row, _)| *row).collect(),
+            candidates: EncodedMask::encode(&self.candidates_mask(), &possible_answers),
+            possible_answers,
+            guess_legality: self.guess_legality,
+        }
+    }
+
+    /// Rebuild a session from a snapshot and the word lists it was taken over.
+    ///
+    /// The lists must be the same words in the same order as when the snapshot was taken, and
+    /// a fallback vocabulary must be given exactly when the snapshot had one.
+    pub fn restore(
+        snapshot: &SessionSnapshot<WORD_SIZE>,
+        allowed_guesses: Vec<Word<WORD_SIZE, 26>>,
+        possible_answers: Vec<Word<WORD_SIZE, 26>>,
+        fallback_vocabulary: Option<Vec<Word<WORD_SIZE, 26>>>,
+    ) -> Result<Self, WordGameError> {
+        snapshot.allowed_guesses.check(&allowed_guesses)?;
+        snapshot.possible_answers.check(&possible_answers)?;
+        let mut session = Self::new(allowed_guesses, possible_answers)
+            .with_guess_legality(snapshot.guess_legality);
+        match (&snapshot.fallback_answers, fallback_vocabulary) {
+            (Some(list), Some(vocabulary)) => {
+                list.check(&vocabulary)?;
+                session = session.with_fallback_vocabulary(vocabulary);
+            }
+            (None, None) => {}
+            (expected, _) => {
+                return Err(WordGameError::FormatMismatch {
+                    expected: format!(
+                        "{} fallback vocabulary",
+                        if expected.is_some() { "a" } else { "no" }
+                    ),
+                    found: format!(
+                        "{} fallback vocabulary",
+                        if expected.is_some() { "no" } else { "a" }
+                    ),
+                });
+            }
+        }
+
+        // Replay the undone rows too, so each keeps its mask for a later redo
+        for (guess, hint) in snapshot.history.iter().chain(snapshot.redo.iter().rev()) {
+            session.add_hint(*guess, *hint);
+        }
+        for _ in &snapshot.redo {
+            session.undo();
+        }
+        let candidates = snapshot.candidates.decode(&snapshot.possible_answers)?;
+        if candidates.true_inds() != session.candidates_mask().true_inds() {
+            return Err(WordGameError::InconsistentHistory(
+                "saved candidates don't match the saved history".to_string(),
+            ));
+        }
+        Ok(session)
+    }
+}
+
+/// Expected guesses to finish from a position, counting the given guess, when playing best
+/// afterwards under the options' guess policy. `hints` covers every answer still possible,
+/// indexed by the answers first.
+fn expected_cost(
+    hints: &[Vec<u8>],
+    guess_ind: usize,
+    depth: u8,
+    max_guesses: u8,
+    options: &SearchOptions,
+) -> Option<f64> {
+    let num_answers = hints.first().map_or(0, Vec::len);
+    let mut buckets: HashMap<u8, HashSet<u16>> = HashMap::new();
+    for (answer_ind, hint) in hints[guess_ind].iter().enumerate() {
+        buckets.entry(*hint).or_default().insert(answer_ind as u16);
+    }
+    buckets
+        .into_iter()
+        .filter(|(hint, _)| *hint != 0)
+        .try_fold(num_answers as u32, |total, (hint, bucket)| {
+            let root_legal_guesses =
+                options.legal_after(options.root_legal_guesses.as_ref(), guess_ind as u16, hint);
+            compute_decision_tree_aggressive(
+                hints,
+                bucket,
+                depth + 1,
+                max_guesses,
+                f64::INFINITY,
+                SearchOptions {
+                    root_legal_guesses,
+                    ..options.clone()
+                },
+                None::<&NoPrinter>,
+            )
+            .map(|child| total + child.total_guesses)
+        })
+        .map(|total| total as f64 / num_answers as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_words::{self, words},
+        word_search::Query,
+    };
+
+    fn session() -> SolverSession<5, 26> {
+        let words = words(&["board", "bread", "brain", "fight", "light", "sight"]);
+        SolverSession::new(words.clone(), words)
+    }
+
+    #[test]
+    fn test_candidates() {
+        let mut session = session();
+        session.add_hint(
+            Word::try_from("lemon").unwrap(),
+            WordHint::try_from("XXXXX").unwrap(),
+        );
+        assert_eq!(session.candidates(), words(&["fight", "sight"]));
+        assert!(session.suggest_hint_corrections().is_empty());
+    }
+
+    #[test]
+    fn test_eliminations_at() {
+        let mut session = session();
+        session.add_hint(
+            Word::try_from("lemon").unwrap(),
+            WordHint::try_from("XXXXX").unwrap(),
+        );
+        session.add_hint(
+            Word::try_from("fight").unwrap(),
+            WordHint::try_from("X√√√√").unwrap(),
+        );
+        let first = session.eliminations_at(0).unwrap();
+        assert_eq!(
+            first.iter().map(|elim| elim.word).collect::<Vec<_>>(),
+            words(&["board", "bread", "brain", "light"])
+        );
+        let light = Word::<5, 26>::try_from("light").unwrap();
+        let mut light_reasons = first[3].sub_queries.clone();
+        light_reasons.sort();
+        assert_eq!(
+            light_reasons,
+            vec![
+                Query::CountExact {
+                    count: 0,
+                    chr: light.0[0]
+                },
+                Query::Not(Box::new(Query::Match {
+                    ind: 0,
+                    chr: light.0[0]
+                })),
+            ]
+        );
+        // E is only ruled out by its count, since bread has it somewhere other than the guess
+        let bread = Word::<5, 26>::try_from("bread").unwrap();
+        assert_eq!(
+            first[1].sub_queries,
+            vec![Query::CountExact {
+                count: 0,
+                chr: bread.0[2]
+            }]
+        );
+
+        // Later rows only report what was still a candidate before them
+        let second = session.eliminations_at(1).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].word, words(&["fight"])[0]);
+        assert!(session.eliminations_at(2).is_none());
+    }
+
+    #[test]
+    fn test_what_if() {
+        let mut session = session();
+        for (guess, hint) in [("lemon", "XXXXX"), ("fight", "X√√√√"), ("sight", "√√√√√")]
+        {
+            session.add_hint(
+                Word::try_from(guess).unwrap(),
+                WordHint::try_from(hint).unwrap(),
+            );
+        }
+        let what_if = session
+            .what_if(0, Word::try_from("fight").unwrap(), 6)
+            .unwrap();
+        assert_eq!(what_if.candidates_before.len(), 6);
+        assert_eq!(what_if.actual.candidates, Some(words(&["fight", "sight"])));
+        // The game was won, so the hypothetical guess gets the hint it would have seen
+        assert_eq!(
+            what_if.hypothetical.hint,
+            Some(WordHint::try_from("X√√√√").unwrap())
+        );
+        assert_eq!(
+            what_if.hypothetical.candidates,
+            Some(words(&["light", "sight"]))
+        );
+        // Fight leaves light or sight, board or bread, or pins down brain
+        let expected_cost = what_if.hypothetical.expected_cost.unwrap();
+        assert!((expected_cost - 13.0 / 6.0).abs() < 1e-9);
+        // Lemon splits finer but can never win outright, so they come out the same
+        assert_eq!(what_if.actual.stats.num_buckets, 5);
+        assert_eq!(what_if.hypothetical.stats.num_buckets, 4);
+        assert!((what_if.actual.expected_cost.unwrap() - expected_cost).abs() < 1e-9);
+
+        // Too few guesses left to guarantee anything
+        let cramped = session
+            .what_if(1, Word::try_from("board").unwrap(), 2)
+            .unwrap();
+        assert_eq!(cramped.hypothetical.expected_cost, None);
+        assert_eq!(cramped.actual.expected_cost, None);
+
+        assert!(
+            session
+                .what_if(3, Word::try_from("board").unwrap(), 6)
+                .is_none()
+        );
+        assert_eq!(session.history().len(), 3);
+    }
+
+    #[test]
+    fn test_what_if_keeps_to_guess_legality() {
+        let words = test_words::small_list();
+        let fight = Word::try_from("fight").unwrap();
+        let light = Word::try_from("light").unwrap();
+        let mut free = SolverSession::new(words.clone(), words.clone());
+        let mut hard =
+            SolverSession::new(words.clone(), words).with_guess_legality(GuessLegality::Hard);
+        for session in [&mut free, &mut hard] {
+            session.add_hint(fight, WordHint::from_guess_and_answer(&fight, &light));
+            session.add_hint(light, WordHint::from_guess_and_answer(&light, &light));
+        }
+        // Hard mode only guesses words ending in IGHT after FIGHT, so can't promise to finish
+        // within six guesses
+        let free_cost = free.what_if(1, light, 6).unwrap().actual.expected_cost;
+        assert!(free_cost.is_some());
+        assert_eq!(
+            hard.what_if(1, light, 6).unwrap().actual.expected_cost,
+            None
+        );
+        let hard_cost = hard.what_if(1, light, 8).unwrap().actual.expected_cost;
+        assert!(hard_cost.unwrap() > free_cost.unwrap());
+    }
+
+    #[test]
+    fn test_suggest_hint_corrections() {
+        let mut session = session();
+        session.add_hint(
+            Word::try_from("fight").unwrap(),
+            WordHint::try_from("X√√√√").unwrap(),
+        );
+        // Typo: the B should have been gray
+        session.add_hint(
+            Word::try_from("board").unwrap(),
+            WordHint::try_from("√XXXX").unwrap(),
+        );
+        assert!(session.candidates().is_empty());
+        assert!(matches!(
+            session.try_candidates(),
+            Err(WordGameError::InconsistentHistory(_))
+        ));
+
+        let corrections = session.suggest_hint_corrections();
+        assert!(corrections.contains(&HintCorrection {
+            row: 1,
+            position: 0,
+            corrected_hint: WordHint::try_from("XXXXX").unwrap(),
+            num_candidates: 2,
+        }));
+        // Every suggestion must leave exactly as many candidates as it claims
+        for correction in &corrections {
+            let mut fixed = session.history().to_vec();
+            fixed[correction.row].1 = correction.corrected_hint;
+            let mut fixed_session = self::session();
+            for (guess, hint) in fixed {
+                fixed_session.add_hint(guess, hint);
+            }
+            assert_eq!(fixed_session.candidates().len(), correction.num_candidates);
+        }
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut session = session();
+        let lemon = (
+            Word::try_from("lemon").unwrap(),
+            WordHint::try_from("XXXXX").unwrap(),
+        );
+        let fight = (
+            Word::try_from("fight").unwrap(),
+            WordHint::try_from("X√√√√").unwrap(),
+        );
+        session.add_hint(lemon.0, lemon.1);
+        session.add_hint(fight.0, fight.1);
+        assert_eq!(session.candidates(), words(&["sight"]));
+
+        assert_eq!(session.undo(), Some(fight));
+        assert_eq!(session.candidates(), words(&["fight", "sight"]));
+        assert_eq!(session.undo(), Some(lemon));
+        assert_eq!(session.undo(), None);
+        assert_eq!(session.candidates().len(), 6);
+
+        assert_eq!(session.redo(), Some(lemon));
+        assert_eq!(session.history(), &[lemon]);
+        assert_eq!(session.candidates(), words(&["fight", "sight"]));
+
+        // A new entry replaces whatever was left to redo
+        session.add_hint(
+            Word::try_from("light").unwrap(),
+            WordHint::try_from("X√√√√").unwrap(),
+        );
+        assert!(!session.can_redo());
+        assert_eq!(session.redo(), None);
+        assert_eq!(session.candidates(), words(&["fight", "sight"]));
+        assert_eq!(
+            session.candidates_mask().true_inds(),
+            session
+                .possible_answers()
+                .eval_query(history_to_query(session.history()))
+                .true_inds()
+        );
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut session = session().with_guess_list_fallback();
+        session.add_hint(
+            Word::try_from("lemon").unwrap(),
+            WordHint::try_from("XXXXX").unwrap(),
+        );
+        session.add_hint(
+            Word::try_from("fight").unwrap(),
+            WordHint::try_from("X√√√√").unwrap(),
+        );
+        session.undo();
+
+        let snapshot = SessionSnapshot::from_json(&session.snapshot().to_json()).unwrap();
+        let allowed = self::session().allowed_guesses().to_vec();
+        let mut restored = SolverSession::restore(
+            &snapshot,
+            allowed.clone(),
+            allowed.clone(),
+            Some(allowed.clone()),
+        )
+        .unwrap();
+        assert_eq!(restored.history(), session.history());
+        assert_eq!(restored.candidates(), session.candidates());
+        assert!(restored.redo().is_some());
+        assert_eq!(restored.candidates(), words(&["sight"]));
+
+        // Restoring over different lists or without the fallback is refused
+        let mut reordered = allowed.clone();
+        reordered.reverse();
+        assert!(matches!(
+            SolverSession::restore(&snapshot, allowed.clone(), reordered, Some(allowed.clone())),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+        assert!(matches!(
+            SolverSession::restore(&snapshot, allowed.clone(), allowed, None),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_guess_legality() {
+        let words = words(&["board", "bread", "brain", "fight", "light", "sight"]);
+        let mut session = session().with_guess_legality(GuessLegality::UltraHard);
+        session.add_hint(
+            words[0],
+            WordHint::from_guess_and_answer(&words[0], &words[1]),
+        );
+        // BOARD plays the absent O again, and BRAIN drops the D
+        assert_eq!(session.legal_guesses(), vec![words[1]]);
+        assert!(
+            session
+                .suggest(10)
+                .iter()
+                .all(|suggestion| session.is_legal_guess(&suggestion.guess))
+        );
+        // The legal guesses follow undo and redo, and a policy set after the hints
+        session.undo();
+        assert_eq!(session.legal_guesses(), words);
+        session.redo();
+        assert_eq!(session.legal_guesses(), vec![words[1]]);
+        let mut hard = self::session();
+        hard.add_hint(
+            words[0],
+            WordHint::from_guess_and_answer(&words[0], &words[1]),
+        );
+        let hard = hard.with_guess_legality(GuessLegality::Hard);
+        assert_eq!(
+            hard.legal_guesses(),
+            words
+                .iter()
+                .filter(|guess| hard.is_legal_guess(guess))
+                .copied()
+                .collect::<Vec<_>>()
+        );
+
+        let snapshot = SessionSnapshot::from_json(&session.snapshot().to_json()).unwrap();
+        assert_eq!(snapshot.guess_legality, GuessLegality::UltraHard);
+        let restored = SolverSession::restore(&snapshot, words.clone(), words, None).unwrap();
+        assert_eq!(restored.legal_guesses(), session.legal_guesses());
+    }
+
+    #[test]
+    fn test_tree_guess_expands_collapsed_tree() {
+        let words = words(&["board", "bread", "brain", "fight", "light", "sight"]);
+        let hints = compute_hints(&words, &words);
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            (0..words.len() as u16).collect(),
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            None::<&NoPrinter>,
+        )
+        .unwrap();
+        let full = ReadableTreeNode::from_generalized_tree_node(&tree, &words, &words);
+        let mut collapsed = full.clone();
+        collapsed.collapse_below(1, &words);
+        assert!(!collapsed.unexpanded.is_empty());
+
+        // Expanded subtrees may break ties differently, but must cost the same in total. Each
+        // game picks up the tree as the last one left it, so subtrees are only solved once.
+        let mut tree = collapsed.clone();
+        let mut total_guesses = 0;
+        for answer in &words {
+            let mut session = SolverSession::new(words.clone(), words.clone()).with_tree(tree, 6);
+            while let Some(guess) = session.tree_guess().unwrap() {
+                session.add_hint(guess, WordHint::from_guess_and_answer(&guess, answer));
+                total_guesses += 1;
+            }
+            assert_eq!(session.history().last().unwrap().0, *answer);
+            tree = session.tree().unwrap().clone();
+            assert_eq!(tree.play_out(answer).unwrap().as_slice(), session.history());
+        }
+        assert!(tree.unexpanded.is_empty());
+        let full_total_guesses: usize = words
+            .iter()
+            .map(|answer| full.play_out(answer).unwrap().len())
+            .sum();
+        assert_eq!(total_guesses, full_total_guesses);
+
+        // Straying from the tree leaves it without a suggestion
+        let mut session = SolverSession::new(words.clone(), words).with_tree(collapsed, 6);
+        let stray = Word::try_from("lemon").unwrap();
+        assert_ne!(session.tree_guess().unwrap(), Some(stray));
+        session.add_hint(stray, WordHint::try_from("XXXXX").unwrap());
+        assert_eq!(session.tree_guess().unwrap(), None);
+    }
+
+    #[test]
+    fn test_out_of_list_guess() {
+        let mut session = session();
+        let guess = Word::try_from("tight").unwrap();
+        assert!(!session.is_allowed_guess(&guess));
+        session.add_hint(guess, WordHint::try_from("X√√√√").unwrap());
+        assert_eq!(session.out_of_list_rows(), vec![0]);
+        assert_eq!(session.candidates(), words(&["fight", "light", "sight"]));
+    }
+
+    #[test]
+    fn test_fallback_vocabulary() {
+        let answers = words(&["fight", "light"]);
+        let guesses = words(&["fight", "light", "night", "sight", "lemon"]);
+        let mut session = SolverSession::new(guesses.clone(), answers).with_guess_list_fallback();
+        session.add_hint(
+            Word::try_from("lemon").unwrap(),
+            WordHint::try_from("XXXX~").unwrap(),
+        );
+        assert_eq!(
+            session.candidates_with_source(),
+            Some((CandidateSource::Fallback, words(&["night"])))
+        );
+        assert_eq!(
+            session.suggest(1)[0].guess,
+            Word::try_from("night").unwrap()
+        );
+
+        // Without the fallback there's nothing left
+        let mut session = SolverSession::new(guesses, words(&["fight", "light"]));
+        session.add_hint(
+            Word::try_from("lemon").unwrap(),
+            WordHint::try_from("XXXX~").unwrap(),
+        );
+        assert_eq!(session.candidates_with_source(), None);
+    }
+}
@@ -0,0 +1,549 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Write},
+    sync::mpsc,
+    thread,
+};
+
+use crate::{
+    game::{Game, GameStatus},
+    ruleset::{LegalGuesses, Ruleset},
+    stats::{GameResult, StatsAggregator, StatsReport},
+    strategy::Strategy,
+    word::Word,
+    worker_pool::{CancelToken, PoolConfig, WorkerPool},
+};
+
+/// Cap on guesses per simulated game, for rulesets that don't limit them themselves
+pub const MAX_SIMULATED_GUESSES: usize = 32;
+
+/// How a simulation spreads its games across threads and seeds its strategies.
+///
+/// Results come out the same for a given master seed however many threads play the games.
+#[derive(Default, Clone)]
+pub struct SimulationConfig<'a> {
+    /// Threads to play games on
+    pub pool: PoolConfig,
+
+    /// Seed every game's seed is derived from
+    pub master_seed: u64,
+
+    /// Called with the number of games finished and the total after each game of a
+    /// simulation, from the threads playing them
+    pub progress: Option<&'a (dyn Fn(usize, usize) + Sync)>,
+
+    /// Stops starting new games once cancelled, failing the simulation
+    pub cancel: Option<CancelToken>,
+}
+
+impl fmt::Debug for SimulationConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulationConfig")
+            .field("pool", &self.pool)
+            .field("master_seed", &self.master_seed)
+            .field("progress", &self.progress.is_some())
+            .field("cancel", &self.cancel)
+            .finish()
+    }
+}
+
+impl<'a> SimulationConfig<'a> {
+    /// The seed for the game played against `possible_answers[answer_ind]`.
+    pub fn game_seed(&self, answer_ind: usize) -> u64 {
+        mix_seed(self.master_seed, answer_ind as u64)
+    }
+
+    /// Get a worker pool for playing games, reporting progress and watching for cancellation.
+    fn worker_pool(&self) -> WorkerPool<'a> {
+        let pool = WorkerPool::new(self.pool);
+        let pool = match self.progress {
+            Some(progress) => pool.with_progress(progress),
+            None => pool,
+        };
+        match &self.cancel {
+            Some(cancel) => pool.with_cancel(cancel.clone()),
+            None => pool,
+        }
+    }
+}
+
+/// Derive an independent seed from a parent seed and a counter, with the splitmix64 finalizer.
+fn mix_seed(seed: u64, counter: u64) -> u64 {
+    let mut z = seed ^ counter.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Play one game against a specific answer, returning the result.
+///
+/// Guesses are drawn from `legal_guesses`, narrowed after every clue by the ruleset. Each turn
+/// hands the strategy its own seed derived from `seed`.
+pub fn simulate_game<
+    const WORD_SIZE: usize,
+    const ALPHABET_SIZE: u8,
+    R: Ruleset<WORD_SIZE, ALPHABET_SIZE> + Clone,
+    S: Strategy<WORD_SIZE, ALPHABET_SIZE> + ?Sized,
+>(
+    ruleset: &R,
+    strategy: &S,
+    legal_guesses: &LegalGuesses<WORD_SIZE, ALPHABET_SIZE>,
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    answer: Word<WORD_SIZE, ALPHABET_SIZE>,
+    seed: u64,
+) -> GameResult {
+    let mut legal_mask = legal_guesses.root_mask();
+    let mut game = Game::new(ruleset.clone(), answer, possible_answers.to_vec());
+    while game.status() == GameStatus::InProgress && game.history().len() < MAX_SIMULATED_GUESSES {
+        let turn_seed = mix_seed(seed, game.history().len() as u64);
+        let Some(guess) = strategy.choose_guess_seeded(
+            &legal_guesses.words(&legal_mask),
+            game.candidates(),
+            turn_seed,
+        ) else {
+            break;
+        };
+        let Ok(hint) = game.guess(guess) else {
+            break;
+        };
+        legal_mask = legal_guesses.child_mask(ruleset, &legal_mask, guess, hint);
+    }
+    GameResult {
+        strategy: strategy.name(),
+        num_guesses: game.history().len(),
+        solved: game.status() == GameStatus::Won,
+    }
+}
+
+/// Play a game against every possible answer in parallel, returning the results in answer
+/// order.
+pub fn simulate<
+    const WORD_SIZE: usize,
+    const ALPHABET_SIZE: u8,
+    R: Ruleset<WORD_SIZE, ALPHABET_SIZE> + Clone + Sync,
+    S: Strategy<WORD_SIZE, ALPHABET_SIZE> + Sync + ?Sized,
+>(
+    ruleset: &R,
+    strategy: &S,
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    config: &SimulationConfig,
+) -> io::Result<Vec<GameResult>> {
+    let legal_guesses = LegalGuesses::new(allowed_guesses.to_vec());
+    config
+        .worker_pool()
+        .map(possible_answers, |answer_ind, answer| {
+            simulate_game(
+                ruleset,
+                strategy,
+                &legal_guesses,
+                possible_answers,
+                *answer,
+                config.game_seed(answer_ind),
+            )
+        })
+        .map_err(io::Error::other)
+}
+
+/// Receives simulated games one at a time, so large sweeps never hold every result at once.
+pub trait ResultSink {
+    /// Consume the result of the game played against `possible_answers[answer_ind]`.
+    fn accept(&mut self, answer_ind: usize, result: GameResult) -> io::Result<()>;
+}
+
+impl ResultSink for StatsAggregator {
+    fn accept(&mut self, _answer_ind: usize, result: GameResult) -> io::Result<()> {
+        self.add(result);
+        Ok(())
+    }
+}
+
+/// Writes each game as a CSV row of strategy, answer index, guesses and whether it was solved.
+pub struct CsvSink<W: Write> {
+    writer: W,
+    wrote_header: bool,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            wrote_header: false,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> ResultSink for CsvSink<W> {
+    fn accept(&mut self, answer_ind: usize, result: GameResult) -> io::Result<()> {
+        if !self.wrote_header {
+            writeln!(self.writer, "strategy,answer_ind,num_guesses,solved")?;
+            self.wrote_header = true;
+        }
+        writeln!(
+            self.writer,
+            "{},{},{},{}",
+            result.strategy, answer_ind, result.num_guesses, result.solved
+        )
+    }
+}
+
+/// The order in which a streaming simulation hands results to its sink.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// In the order of the possible answers, buffering any games that finish early
+    #[default]
+    Ordered,
+
+    /// As soon as each game finishes
+    Unordered,
+}
+
+/// Play a game against every possible answer in parallel, passing each result to the sink as
+/// it's ready rather than collecting them.
+///
+/// Stops at the first error from the sink, though games already underway still run to completion.
+pub fn simulate_streaming<
+    const WORD_SIZE: usize,
+    const ALPHABET_SIZE: u8,
+    R: Ruleset<WORD_SIZE, ALPHABET_SIZE> + Clone + Sync,
+    S: Strategy<WORD_SIZE, ALPHABET_SIZE> + Sync + ?Sized,
+    K: ResultSink + ?Sized,
+>(
+    ruleset: &R,
+    strategy: &S,
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    config: &SimulationConfig,
+    delivery: Delivery,
+    sink: &mut K,
+) -> io::Result<()> {
+    let legal_guesses = LegalGuesses::new(allowed_guesses.to_vec());
+    let (sender, receiver) = mpsc::channel();
+    let stop = CancelToken::new();
+    thread::scope(|scope| {
+        // The sender moves into the games thread, so the receiver sees the channel close once
+        // every game is done
+        let games = scope.spawn({
+            let stop = stop.clone();
+            move || {
+                config.worker_pool().with_cancel(stop.clone()).map(
+                    possible_answers,
+                    |answer_ind, answer| {
+                        let result = simulate_game(
+                            ruleset,
+                            strategy,
+                            &legal_guesses,
+                            possible_answers,
+                            *answer,
+                            config.game_seed(answer_ind),
+                        );
+                        // Sending only fails once the sink has errored, so no more games are
+                        // wanted
+                        if sender.send((answer_ind, result)).is_err() {
+                            stop.cancel();
+                        }
+                    },
+                )
+            }
+        });
+
+        // Don't start any more games once the sink fails
+        let mut accept = |answer_ind, result| {
+            sink.accept(answer_ind, result)
+                .inspect_err(|_| stop.cancel())
+        };
+        let mut pending: BTreeMap<usize, GameResult> = BTreeMap::new();
+        let mut next_ind = 0;
+        for (answer_ind, result) in receiver {
+            match delivery {
+                Delivery::Unordered => accept(answer_ind, result)?,
+                Delivery::Ordered => {
+                    pending.insert(answer_ind, result);
+                    while let Some(result) = pending.remove(&next_ind) {
+                        accept(next_ind, result)?;
+                        next_ind += 1;
+                    }
+                }
+            }
+        }
+        games
+            .join()
+            .expect("the pool catches panics from games")
+            .map(|_| ())
+            .map_err(io::Error::other)
+    })
+}
+
+/// Simulate every strategy against every possible answer and compare the results.
+///
+/// Fails only if the configured thread pool can't be built, a game panics or the config's cancel
+/// token is cancelled.
+pub fn compare_strategies<
+    const WORD_SIZE: usize,
+    const ALPHABET_SIZE: u8,
+    R: Ruleset<WORD_SIZE, ALPHABET_SIZE> + Clone + Sync,
+>(
+    ruleset: &R,
+    strategies: &[&(dyn Strategy<WORD_SIZE, ALPHABET_SIZE> + Sync)],
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    possible_answers: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    config: &SimulationConfig,
+) -> io::Result<StatsReport> {
+    let mut aggregator = StatsAggregator::new();
+    for strategy in strategies {
+        simulate_streaming(
+            ruleset,
+            *strategy,
+            allowed_guesses,
+            possible_answers,
+            config,
+            Delivery::Ordered,
+            &mut aggregator,
+        )?;
+    }
+    Ok(aggregator.report())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        priors::AnswerPriors,
+        ruleset::Standard,
+        strategy::{GreedyObjective, GreedyStrategy},
+        test_words::words,
+    };
+
+    #[test]
+    fn test_compare_weighted_and_unweighted() {
+        let answers = words(&["fight", "light", "might", "sight", "board"]);
+        let unweighted = GreedyStrategy::unweighted(GreedyObjective::ExpectedRemaining);
+        let weighted = GreedyStrategy::weighted(
+            GreedyObjective::ExpectedRemaining,
+            AnswerPriors::from_weights([(answers[2], 10.0)], 1.0),
+        );
+        let report = compare_strategies(
+            &Standard,
+            &[&unweighted, &weighted],
+            &answers,
+            &answers,
+            &SimulationConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(report.strategies.len(), 2);
+        assert_eq!(report.comparisons.len(), 1);
+        for stats in &report.strategies {
+            assert_eq!(stats.num_games, answers.len());
+            assert_eq!(stats.num_solved, answers.len());
+        }
+    }
+
+    #[test]
+    fn test_simulate_streaming() {
+        let answers = words(&["fight", "light", "might", "sight", "board"]);
+        let strategy = GreedyStrategy::unweighted(GreedyObjective::Entropy);
+        let config = SimulationConfig::default();
+        let expected = simulate(&Standard, &strategy, &answers, &answers, &config).unwrap();
+
+        let mut sink = CsvSink::new(vec![]);
+        simulate_streaming(
+            &Standard,
+            &strategy,
+            &answers,
+            &answers,
+            &config,
+            Delivery::Ordered,
+            &mut sink,
+        )
+        .unwrap();
+        let csv = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "strategy,answer_ind,num_guesses,solved");
+        assert_eq!(lines.len(), answers.len() + 1);
+        for (answer_ind, (line, result)) in lines[1..].iter().zip(&expected).enumerate() {
+            assert_eq!(
+                *line,
+                format!(
+                    "{},{},{},{}",
+                    result.strategy, answer_ind, result.num_guesses, result.solved
+                )
+            );
+        }
+
+        let mut aggregator = StatsAggregator::new();
+        simulate_streaming(
+            &Standard,
+            &strategy,
+            &answers,
+            &answers,
+            &config,
+            Delivery::Unordered,
+            &mut aggregator,
+        )
+        .unwrap();
+        assert_eq!(aggregator.report().strategies[0].num_games, answers.len());
+    }
+
+    #[test]
+    fn test_streaming_stops_on_sink_error() {
+        /// Fails on the first result it's given
+        struct FailingSink;
+
+        impl ResultSink for FailingSink {
+            fn accept(&mut self, _answer_ind: usize, _result: GameResult) -> io::Result<()> {
+                Err(io::Error::other("disk full"))
+            }
+        }
+
+        /// Guesses the first candidate, slowly, counting the games it starts
+        #[derive(Default)]
+        struct SlowStrategy(std::sync::atomic::AtomicUsize);
+
+        impl Strategy<5, 26> for SlowStrategy {
+            fn name(&self) -> String {
+                "slow".to_string()
+            }
+
+            fn choose_guess(
+                &self,
+                allowed: &[Word<5, 26>],
+                candidates: &[Word<5, 26>],
+            ) -> Option<Word<5, 26>> {
+                if candidates.len() == allowed.len() {
+                    self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+                thread::sleep(std::time::Duration::from_millis(20));
+                candidates.first().copied()
+            }
+        }
+
+        let answers = words(&[
+            "fight", "light", "might", "sight", "tight", "right", "night", "board",
+        ]);
+        let strategy = SlowStrategy::default();
+        let config = SimulationConfig {
+            pool: PoolConfig {
+                num_threads: Some(1),
+            },
+            ..SimulationConfig::default()
+        };
+        let err = simulate_streaming(
+            &Standard,
+            &strategy,
+            &answers,
+            &answers,
+            &config,
+            Delivery::Unordered,
+            &mut FailingSink,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        assert!(strategy.0.load(std::sync::atomic::Ordering::Relaxed) < answers.len());
+    }
+
+    /// Guesses whichever candidate the seed points at
+    struct SeededStrategy;
+
+    impl Strategy<5, 26> for SeededStrategy {
+        fn name(&self) -> String {
+            "seeded".to_string()
+        }
+
+        fn choose_guess(
+            &self,
+            allowed: &[Word<5, 26>],
+            candidates: &[Word<5, 26>],
+        ) -> Option<Word<5, 26>> {
+            self.choose_guess_seeded(allowed, candidates, 0)
+        }
+
+        fn choose_guess_seeded(
+            &self,
+            _allowed: &[Word<5, 26>],
+            candidates: &[Word<5, 26>],
+            seed: u64,
+        ) -> Option<Word<5, 26>> {
+            if candidates.is_empty() {
+                return None;
+            }
+            Some(candidates[(seed % candidates.len() as u64) as usize])
+        }
+    }
+
+    #[test]
+    fn test_progress_and_cancel() {
+        let answers = words(&["fight", "light", "might", "sight", "board"]);
+        let strategy = GreedyStrategy::unweighted(GreedyObjective::ExpectedRemaining);
+        let finished = std::sync::atomic::AtomicUsize::new(0);
+        let progress = |done: usize, total: usize| {
+            assert_eq!(total, answers.len());
+            finished.fetch_max(done, std::sync::atomic::Ordering::Relaxed);
+        };
+        let config = SimulationConfig {
+            progress: Some(&progress),
+            ..SimulationConfig::default()
+        };
+        simulate(&Standard, &strategy, &answers, &answers, &config).unwrap();
+        assert_eq!(
+            finished.load(std::sync::atomic::Ordering::Relaxed),
+            answers.len()
+        );
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let config = SimulationConfig {
+            cancel: Some(cancel),
+            ..SimulationConfig::default()
+        };
+        assert!(simulate(&Standard, &strategy, &answers, &answers, &config).is_err());
+        let mut aggregator = StatsAggregator::new();
+        assert!(
+            simulate_streaming(
+                &Standard,
+                &strategy,
+                &answers,
+                &answers,
+                &config,
+                Delivery::Unordered,
+                &mut aggregator,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_seeded_results_independent_of_threads() {
+        let answers = words(&[
+            "fight", "light", "might", "sight", "tight", "right", "night", "board",
+        ]);
+        let play = |num_threads, master_seed| {
+            let config = SimulationConfig {
+                pool: PoolConfig {
+                    num_threads: Some(num_threads),
+                },
+                master_seed,
+                ..SimulationConfig::default()
+            };
+            simulate(&Standard, &SeededStrategy, &answers, &answers, &config)
+                .unwrap()
+                .into_iter()
+                .map(|result| (result.num_guesses, result.solved))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(play(1, 7), play(3, 7));
+        assert_eq!(
+            SimulationConfig::default().game_seed(3),
+            SimulationConfig::default().game_seed(3)
+        );
+        assert_ne!(
+            SimulationConfig::default().game_seed(3),
+            SimulationConfig::default().game_seed(4)
+        );
+    }
+}
@@ -0,0 +1,397 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decision_tree_general::{NoPrinter, SearchOptions},
+    error::WordGameError,
+    hint::WordHint,
+    mask_encoding::{FNV_OFFSET_BASIS, WordListId, fnv1a},
+    opening_book::OpeningBook,
+    readable_tree::ReadableTreeNode,
+    render::{KEYBOARD_ROWS, RenderMode, histogram_bar},
+    run_manifest::{RunManifest, solve_recorded},
+    word::Word,
+};
+
+/// The starter pack manifest format version written by this crate
+pub const STARTER_PACK_VERSION: u32 = 1;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const OPENING_BOOK_FILE: &str = "opening_book.json";
+pub const SECOND_GUESSES_FILE: &str = "second_guesses.csv";
+pub const DIFFICULTY_FILE: &str = "difficulty.csv";
+pub const KEYBOARD_HEATMAP_FILE: &str = "keyboard_heatmap.json";
+pub const KEYBOARD_HEATMAP_TEXT_FILE: &str = "keyboard_heatmap.txt";
+
+/// How to build a starter pack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StarterPackConfig {
+    /// Plies of the tree to keep in the opening book
+    pub book_plies: u8,
+
+    /// Search limits, only used when no tree is given
+    pub max_depth: u8,
+    pub max_cost: f64,
+
+    /// How the text keyboard heatmaps and the second guesses' hints are drawn
+    pub mode: RenderMode,
+}
+
+impl Default for StarterPackConfig {
+    fn default() -> Self {
+        Self {
+            book_plies: 2,
+            max_depth: 6,
+            max_cost: f64::INFINITY,
+            mode: RenderMode::Unicode,
+        }
+    }
+}
+
+/// One file written into a starter pack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackFile {
+    pub name: String,
+
+    /// FNV-1a hash of the file's bytes, as 16 hex digits
+    pub hash: String,
+}
+
+/// What went into a starter pack and what it contains, written alongside the other files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StarterPackManifest {
+    pub version: u32,
+    pub allowed_guesses: WordListId,
+    pub possible_answers: WordListId,
+    pub tree_hash: String,
+    pub est_cost: f64,
+    pub book_plies: u8,
+    pub files: Vec<PackFile>,
+
+    /// What went into the search for the tree, if the pack ran one. Its result is left out, as
+    /// the pack records the tree itself and the search's wall time would keep packs from being
+    /// reproducible.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub run: Option<RunManifest>,
+}
+
+/// How often a letter appears across the answers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LetterFrequency {
+    /// Number of answers containing the letter anywhere
+    pub answers_containing: usize,
+
+    /// Number of answers with the letter at each position
+    pub by_position: Vec<usize>,
+}
+
+/// Letter frequencies across the answers, keyed by letter.
+pub type KeyboardHeatmap = BTreeMap<char, LetterFrequency>;
+
+/// Build every front-end artifact for a pair of word lists and write them to `out_dir`, with a
+/// manifest listing each file and its hash.
+///
+/// A tree already built over the same lists can be given to skip the search. Nothing else in
+/// the output depends on when or where it was built, and the search breaks ties between equally
+/// good guesses by guess index, so the same lists give byte-identical packs either way.
+pub fn build_starter_pack<const WORD_SIZE: usize>(
+    allowed_guesses: (&str, &[Word<WORD_SIZE, 26>]),
+    possible_answers: (&str, &[Word<WORD_SIZE, 26>]),
+    tree: Option<&ReadableTreeNode<WORD_SIZE>>,
+    config: &StarterPackConfig,
+    out_dir: &Path,
+) -> Result<StarterPackManifest, WordGameError> {
+    let (allowed_name, allowed_guesses) = allowed_guesses;
+    let (answers_name, possible_answers) = possible_answers;
+    let (tree, run) = match tree {
+        Some(tree) => (tree.clone(), None),
+        None => {
+            let (tree, mut run) = solve_recorded(
+                (allowed_name, allowed_guesses),
+                (answers_name, possible_answers),
+                config.max_depth,
+                config.max_cost,
+                SearchOptions::default(),
+                None,
+                None::<&NoPrinter>,
+            )?;
+            let tree = tree.ok_or_else(|| {
+                WordGameError::Infeasible(format!(
+                    "no tree within depth {} and cost {}",
+                    config.max_depth, config.max_cost
+                ))
+            })?;
+            run.result = None;
+            (tree, Some(run))
+        }
+    };
+    let answers_list = WordListId::new(answers_name, possible_answers);
+    let heatmap = keyboard_heatmap(possible_answers);
+
+    let contents = [
+        (
+            OPENING_BOOK_FILE,
+            OpeningBook::from_tree(&tree, config.book_plies)
+                .with_remaining_masks(&answers_list, possible_answers)
+                .to_json(),
+        ),
+        (
+            SECOND_GUESSES_FILE,
+            second_guesses_csv(&tree, possible_answers, config.mode),
+        ),
+        (DIFFICULTY_FILE, difficulty_csv(&tree, possible_answers)?),
+        (
+            KEYBOARD_HEATMAP_FILE,
+            serde_json::to_string_pretty(&heatmap).unwrap(),
+        ),
+        (
+            KEYBOARD_HEATMAP_TEXT_FILE,
+            render_keyboard_heatmaps(&heatmap, config.mode),
+        ),
+    ];
+
+    let write = |file_name: &str, raw: &str| {
+        let path: PathBuf = out_dir.join(file_name);
+        fs::write(&path, raw).map_err(|source| WordGameError::Io { path, source })
+    };
+    fs::create_dir_all(out_dir).map_err(|source| WordGameError::Io {
+        path: out_dir.to_path_buf(),
+        source,
+    })?;
+    let mut files = vec![];
+    for (file_name, raw) in &contents {
+        write(file_name, raw)?;
+        files.push(PackFile {
+            name: file_name.to_string(),
+            hash: format!("{:016x}", fnv1a(FNV_OFFSET_BASIS, raw.bytes())),
+        });
+    }
+    let manifest = StarterPackManifest {
+        version: STARTER_PACK_VERSION,
+        allowed_guesses: WordListId::new(allowed_name, allowed_guesses),
+        possible_answers: answers_list,
+        tree_hash: tree.content_hash(),
+        est_cost: tree.est_cost,
+        book_plies: config.book_plies,
+        files,
+        run,
+    };
+    write(
+        MANIFEST_FILE,
+        &serde_json::to_string_pretty(&manifest).unwrap(),
+    )?;
+    Ok(manifest)
+}
+
+/// What the tree guesses second after each hint to its opening, with how many answers give that
+/// hint, in hint id order.
+fn second_guesses_csv<const WORD_SIZE: usize>(
+    tree: &ReadableTreeNode<WORD_SIZE>,
+    possible_answers: &[Word<WORD_SIZE, 26>],
+    mode: RenderMode,
+) -> String {
+    let mut num_answers_by_hint: BTreeMap<u8, usize> = BTreeMap::new();
+    for answer in possible_answers {
+        *num_answers_by_hint
+            .entry(WordHint::from_guess_and_answer(&tree.should_guess, answer).hint_id())
+            .or_default() += 1;
+    }
+    let mut csv = "first_guess,hint,num_answers,second_guess,est_cost\n".to_string();
+    for (hint_id, num_answers) in num_answers_by_hint {
+        let hint = WordHint::<WORD_SIZE>::from_id(hint_id);
+        let (second_guess, est_cost) = match tree.next.get(&hint) {
+            Some(next) => (next.should_guess.to_string(), next.est_cost),
+            // Only the all-correct hint has nothing left to guess
+            None => (String::new(), 0.0),
+        };
+        writeln!(
+            csv,
+            "{},{},{},{},{:.4}",
+            tree.should_guess,
+            hint.render(mode),
+            num_answers,
+            second_guess,
+            est_cost
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// How many guesses the tree needs for each answer, in answer list order.
+fn difficulty_csv<const WORD_SIZE: usize>(
+    tree: &ReadableTreeNode<WORD_SIZE>,
+    possible_answers: &[Word<WORD_SIZE, 26>],
+) -> Result<String, WordGameError> {
+    let mut csv = "answer,num_guesses\n".to_string();
+    for answer in possible_answers {
+        writeln!(csv, "{},{}", answer, tree.play_out(answer)?.len()).unwrap();
+    }
+    Ok(csv)
+}
+
+/// Count every letter across the answers, overall and by position.
+pub fn keyboard_heatmap<const WORD_SIZE: usize>(
+    possible_answers: &[Word<WORD_SIZE, 26>],
+) -> KeyboardHeatmap {
+    let mut heatmap: KeyboardHeatmap = (b'A'..=b'Z')
+        .map(|letter| {
+            (
+                letter as char,
+                LetterFrequency {
+                    answers_containing: 0,
+                    by_position: vec![0; WORD_SIZE],
+                },
+            )
+        })
+        .collect();
+    for answer in possible_answers {
+        for (ind, chr) in answer.0.iter().enumerate() {
+            let frequency = heatmap.get_mut(&((b'A' + chr) as char)).unwrap();
+            frequency.by_position[ind] += 1;
+            if !answer.0[..ind].contains(chr) {
+                frequency.answers_containing += 1;
+            }
+        }
+    }
+    heatmap
+}
+
+/// Draw the heatmap as keyboards, one for letters anywhere in the answers and one per position,
+/// with each key's shade scaled to the most common letter in that keyboard.
+pub fn render_keyboard_heatmaps(heatmap: &KeyboardHeatmap, mode: RenderMode) -> String {
+    let num_positions = heatmap
+        .values()
+        .next()
+        .map_or(0, |frequency| frequency.by_position.len());
+    let mut rendered = String::new();
+    let mut render = |title: String, count: &dyn Fn(&LetterFrequency) -> usize| {
+        let max_count = heatmap.values().map(count).max().unwrap_or(0).max(1);
+        writeln!(rendered, "{}", title).unwrap();
+        for (row_ind, row) in KEYBOARD_ROWS.iter().enumerate() {
+            let keys: Vec<String> = row
+                .chars()
+                .map(|letter| {
+                    let level = (8 * count(&heatmap[&letter])).div_ceil(max_count);
+                    format!("{}{}", letter, histogram_bar(level, mode))
+                })
+                .collect();
+            writeln!(rendered, "{}{}", " ".repeat(row_ind), keys.join(" ")).unwrap();
+        }
+        writeln!(rendered).unwrap();
+    };
+    render("anywhere".to_string(), &|frequency| {
+        frequency.answers_containing
+    });
+    for ind in 0..num_positions {
+        render(format!("position {}", ind + 1), &|frequency| {
+            frequency.by_position[ind]
+        });
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::{
+        decision_tree_general::compute_decision_tree_aggressive, hint_matrix::compute_hints,
+        test_words::words,
+    };
+
+    #[test]
+    fn test_build_starter_pack() {
+        let root = env::temp_dir().join(format!("word_core_starter_pack_{}", std::process::id()));
+        let answers = words(&["fight", "light", "board", "bread", "eerie"]);
+        let allowed_guesses = words(&["flint", "fight", "light", "board", "bread", "eerie"]);
+        let config = StarterPackConfig {
+            mode: RenderMode::Ascii,
+            ..StarterPackConfig::default()
+        };
+        let manifest = build_starter_pack(
+            ("allowed", &allowed_guesses),
+            ("answers", &answers),
+            None,
+            &config,
+            &root.join("first"),
+        )
+        .unwrap();
+        assert_eq!(manifest.files.len(), 5);
+        for file in &manifest.files {
+            assert!(root.join("first").join(&file.name).is_file());
+        }
+        let saved: StarterPackManifest = serde_json::from_str(
+            &fs::read_to_string(root.join("first").join(MANIFEST_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved, manifest);
+        let run = manifest.run.unwrap();
+        assert_eq!(run.possible_answers, manifest.possible_answers);
+        assert_eq!(run.result, None);
+
+        let difficulty = fs::read_to_string(root.join("first").join(DIFFICULTY_FILE)).unwrap();
+        assert_eq!(difficulty.lines().count(), answers.len() + 1);
+        let book = OpeningBook::<5>::from_json(
+            &fs::read_to_string(root.join("first").join(OPENING_BOOK_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(book.num_plies, 2);
+        // Hints are drawn in the configured mode, and the all-correct one is in the table too
+        let second_guesses =
+            fs::read_to_string(root.join("first").join(SECOND_GUESSES_FILE)).unwrap();
+        let all_correct = WordHint::<5>::from_id(0).render(RenderMode::Ascii);
+        assert!(second_guesses.contains(&format!(",{all_correct},1,,")));
+        assert!(!second_guesses.contains(&WordHint::<5>::from_id(0).render(RenderMode::Unicode)));
+
+        // Building from the same tree gives the same pack every time
+        let tree = ReadableTreeNode::from_generalized_tree_node(
+            &compute_decision_tree_aggressive(
+                &compute_hints(&answers, &answers),
+                (0..answers.len() as u16).collect(),
+                0,
+                6,
+                f64::INFINITY,
+                SearchOptions::default(),
+                None::<&NoPrinter>,
+            )
+            .unwrap(),
+            &answers,
+            &answers,
+        );
+        let build = |dir: &str| {
+            build_starter_pack(
+                ("allowed", &allowed_guesses),
+                ("answers", &answers),
+                Some(&tree),
+                &config,
+                &root.join(dir),
+            )
+            .unwrap()
+        };
+        let rebuilt = build("second");
+        assert_eq!(rebuilt.tree_hash, tree.content_hash());
+        assert_eq!(rebuilt.run, None);
+        assert_eq!(build("third"), rebuilt);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_keyboard_heatmap() {
+        let heatmap = keyboard_heatmap(&words(&["eerie", "sheep"]));
+        assert_eq!(heatmap[&'E'].answers_containing, 2);
+        assert_eq!(heatmap[&'E'].by_position, vec![1, 1, 1, 1, 1]);
+        assert_eq!(heatmap[&'Z'].answers_containing, 0);
+
+        let rendered = render_keyboard_heatmaps(&heatmap, RenderMode::Ascii);
+        assert!(rendered.starts_with("anywhere\nQ  W  E@ R"));
+        assert_eq!(rendered.matches("position").count(), 5);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::transcript::Transcript;
+
+/// The z-score for a two-sided 95% confidence interval
+const Z_95: f64 = 1.959964;
+
+/// The outcome of one game, as played by a named strategy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GameResult {
+    pub strategy: String,
+    pub num_guesses: usize,
+    pub solved: bool,
+}
+
+impl GameResult {
+    /// Summarize a transcript as a game result.
+    pub fn from_transcript<const WORD_SIZE: usize>(
+        strategy: &str,
+        transcript: &Transcript<WORD_SIZE>,
+    ) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            num_guesses: transcript.num_guesses(),
+            solved: transcript.is_solved(),
+        }
+    }
+}
+
+/// Summary statistics for all the games played by one strategy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyStats {
+    pub strategy: String,
+    pub num_games: usize,
+    pub num_solved: usize,
+
+    /// Number of solved games by how many guesses they took
+    pub guess_distribution: BTreeMap<usize, usize>,
+
+    /// Fraction of games solved, with a 95% Wilson score interval
+    pub win_rate: f64,
+    pub win_rate_ci95: (f64, f64),
+
+    /// Mean guesses over solved games, with a 95% normal-approximation interval
+    pub mean_guesses: f64,
+    pub mean_guesses_ci95: (f64, f64),
+    pub std_dev_guesses: f64,
+
+    /// Longest run of consecutive solved games, in the order they were added
+    pub longest_win_streak: usize,
+
+    /// Run of solved games at the end of the sequence
+    pub current_win_streak: usize,
+}
+
+/// The difference in mean guesses between two strategies.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyComparison {
+    pub strategy_a: String,
+    pub strategy_b: String,
+
+    /// Mean guesses of `strategy_a` minus mean guesses of `strategy_b`, over solved games
+    pub mean_guesses_diff: f64,
+
+    /// 95% interval of the difference, using Welch's normal approximation
+    pub mean_guesses_diff_ci95: (f64, f64),
+}
+
+/// A full report over every strategy seen by an aggregator.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsReport {
+    pub strategies: Vec<StrategyStats>,
+    pub comparisons: Vec<StrategyComparison>,
+}
+
+/// Collects game results and summarizes them per strategy.
+#[derive(Debug, Clone, Default)]
+pub struct StatsAggregator {
+    results_by_strategy: BTreeMap<String, Vec<GameResult>>,
+}
+
+impl StatsAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a single game result.
+    pub fn add(&mut self, result: GameResult) {
+        self.results_by_strategy
+            .entry(result.strategy.clone())
+            .or_default()
+            .push(result);
+    }
+
+    /// Record many game results.
+    pub fn extend(&mut self, results: impl IntoIterator<Item = GameResult>) {
+        for result in results {
+            self.add(result);
+        }
+    }
+
+    /// Summarize every strategy, plus a comparison of each pair of strategies.
+    pub fn report(&self) -> StatsReport {
+        let strategies: Vec<StrategyStats> = self
+            .results_by_strategy
+            .iter()
+            .map(|(strategy, results)| summarize(strategy, results))
+            .collect();
+        let mut comparisons = vec![];
+        for (ind_a, stats_a) in strategies.iter().enumerate() {
+            for stats_b in &strategies[ind_a + 1..] {
+                comparisons.push(compare(stats_a, stats_b));
+            }
+        }
+        StatsReport {
+            strategies,
+            comparisons,
+        }
+    }
+}
+
+fn summarize(strategy: &str, results: &[GameResult]) -> StrategyStats {
+    let mut guess_distribution: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut longest_win_streak = 0;
+    let mut current_win_streak = 0;
+    for result in results {
+        if result.solved {
+            *guess_distribution.entry(result.num_guesses).or_default() += 1;
+            current_win_streak += 1;
+            longest_win_streak = longest_win_streak.max(current_win_streak);
+        } else {
+            current_win_streak = 0;
+        }
+    }
+
+    let num_games = results.len();
+    let num_solved: usize = guess_distribution.values().sum();
+    let win_rate = if num_games == 0 {
+        0.0
+    } else {
+        num_solved as f64 / num_games as f64
+    };
+
+    let (mean_guesses, variance) = mean_and_variance(&guess_distribution, num_solved);
+    let std_dev_guesses = variance.sqrt();
+    let half_width = if num_solved == 0 {
+        0.0
+    } else {
+        Z_95 * std_dev_guesses / (num_solved as f64).sqrt()
+    };
+
+    StrategyStats {
+        strategy: strategy.to_string(),
+        num_games,
+        num_solved,
+        guess_distribution,
+        win_rate,
+        win_rate_ci95: wilson_interval(num_solved, num_games),
+        mean_guesses,
+        mean_guesses_ci95: (mean_guesses - half_width, mean_guesses + half_width),
+        std_dev_guesses,
+        longest_win_streak,
+        current_win_streak,
+    }
+}
+
+/// Compute the mean and sample variance of a histogram of guess counts.
+fn mean_and_variance(guess_distribution: &BTreeMap<usize, usize>, total: usize) -> (f64, f64) {
+    if total == 0 {
+        return (0.0, 0.0);
+    }
+    let mean = guess_distribution
+        .iter()
+        .map(|(num_guesses, count)| (num_guesses * count) as f64)
+        .sum::<f64>()
+        / total as f64;
+    if total == 1 {
+        return (mean, 0.0);
+    }
+    let sum_squares: f64 = guess_distribution
+        .iter()
+        .map(|(num_guesses, count)| (*num_guesses as f64 - mean).powi(2) * *count as f64)
+        .sum();
+    (mean, sum_squares / (total - 1) as f64)
+}
+
+/// Compute the 95% Wilson score interval for a binomial proportion.
+fn wilson_interval(successes: usize, trials: usize) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z_sq = Z_95 * Z_95;
+    let denom = 1.0 + z_sq / n;
+    let center = (p + z_sq / (2.0 * n)) / denom;
+    let half_width = Z_95 * (p * (1.0 - p) / n + z_sq / (4.0 * n * n)).sqrt() / denom;
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+fn compare(stats_a: &StrategyStats, stats_b: &StrategyStats) -> StrategyComparison {
+    let diff = stats_a.mean_guesses - stats_b.mean_guesses;
+    let std_err_sq = |stats: &StrategyStats| {
+        if stats.num_solved == 0 {
+            0.0
+        } else {
+            stats.std_dev_guesses.powi(2) / stats.num_solved as f64
+        }
+    };
+    let half_width = Z_95 * (std_err_sq(stats_a) + std_err_sq(stats_b)).sqrt();
+    StrategyComparison {
+        strategy_a: stats_a.strategy.clone(),
+        strategy_b: stats_b.strategy.clone(),
+        mean_guesses_diff: diff,
+        mean_guesses_diff_ci95: (diff - half_width, diff + half_width),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(strategy: &str, num_guesses: usize, solved: bool) -> GameResult {
+        GameResult {
+            strategy: strategy.to_string(),
+            num_guesses,
+            solved,
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut aggregator = StatsAggregator::new();
+        aggregator.extend([
+            result("a", 3, true),
+            result("a", 4, true),
+            result("a", 6, false),
+            result("a", 3, true),
+            result("a", 2, true),
+            result("a", 4, true),
+        ]);
+        let report = aggregator.report();
+        assert_eq!(report.strategies.len(), 1);
+        assert!(report.comparisons.is_empty());
+
+        let stats = &report.strategies[0];
+        assert_eq!(stats.num_games, 6);
+        assert_eq!(stats.num_solved, 5);
+        assert_eq!(
+            stats.guess_distribution,
+            BTreeMap::from([(2, 1), (3, 2), (4, 2)])
+        );
+        assert_eq!(stats.mean_guesses, 3.2);
+        assert!((stats.std_dev_guesses - 0.7f64.sqrt()).abs() < 1e-12);
+        assert!(stats.mean_guesses_ci95.0 < 3.2 && stats.mean_guesses_ci95.1 > 3.2);
+        assert!(stats.win_rate_ci95.0 < stats.win_rate && stats.win_rate_ci95.1 > stats.win_rate);
+        assert_eq!(stats.longest_win_streak, 3);
+        assert_eq!(stats.current_win_streak, 3);
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut aggregator = StatsAggregator::new();
+        for _ in 0..50 {
+            aggregator.add(result("fast", 3, true));
+            aggregator.add(result("fast", 4, true));
+            aggregator.add(result("slow", 4, true));
+            aggregator.add(result("slow", 5, true));
+        }
+        let report = aggregator.report();
+        assert_eq!(report.comparisons.len(), 1);
+        let comparison = &report.comparisons[0];
+        assert_eq!(comparison.strategy_a, "fast");
+        assert_eq!(comparison.strategy_b, "slow");
+        assert_eq!(comparison.mean_guesses_diff, -1.0);
+        assert!(comparison.mean_guesses_diff_ci95.1 < 0.0);
+    }
+
+    #[test]
+    fn test_wilson_interval_bounds() {
+        assert_eq!(wilson_interval(0, 0), (0.0, 1.0));
+        let (low, high) = wilson_interval(10, 10);
+        assert!(low > 0.6 && (high - 1.0).abs() < 1e-9);
+    }
+}
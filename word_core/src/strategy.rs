@@ -0,0 +1,212 @@
+use crate::{
+    hint::WordHint,
+    priors::AnswerPriors,
+    word::Word,
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+/// A way of picking the next guess from the remaining candidates.
+pub trait Strategy<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    /// A short name for the strategy, as recorded in game results.
+    fn name(&self) -> String;
+
+    /// Pick a guess, or `None` if no allowed guess can make progress.
+    fn choose_guess(
+        &self,
+        allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+        candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> Option<Word<WORD_SIZE, ALPHABET_SIZE>>;
+
+    /// Pick a guess, drawing any random choices from `seed` so that simulations are
+    /// reproducible. Deterministic strategies can ignore the seed, as the default does.
+    fn choose_guess_seeded(
+        &self,
+        allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+        candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+        _seed: u64,
+    ) -> Option<Word<WORD_SIZE, ALPHABET_SIZE>> {
+        self.choose_guess(allowed_guesses, candidates)
+    }
+}
+
+/// What a greedy strategy optimizes when choosing a single guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GreedyObjective {
+    /// Maximize the expected information gained
+    Entropy,
+
+    /// Minimize the expected number of candidates left
+    ExpectedRemaining,
+
+    /// Minimize the largest bucket of candidates left
+    Minimax,
+}
+
+impl GreedyObjective {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Entropy => "entropy",
+            Self::ExpectedRemaining => "expected-remaining",
+            Self::Minimax => "minimax",
+        }
+    }
+}
+
+/// Pick whichever guess looks best one move ahead, optionally weighting candidates by priors.
+///
+/// Without priors every candidate counts equally. With them, each candidate counts in
+/// proportion to its weight, so likely answers dominate the score.
+#[derive(Debug, Clone)]
+pub struct GreedyStrategy<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    pub objective: GreedyObjective,
+    pub priors: Option<AnswerPriors<WORD_SIZE, ALPHABET_SIZE>>,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> GreedyStrategy<WORD_SIZE, ALPHABET_SIZE> {
+    pub fn unweighted(objective: GreedyObjective) -> Self {
+        Self {
+            objective,
+            priors: None,
+        }
+    }
+
+    pub fn weighted(
+        objective: GreedyObjective,
+        priors: AnswerPriors<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> Self {
+        Self {
+            objective,
+            priors: Some(priors),
+        }
+    }
+
+    /// Score a guess against the candidates and their weights. Lower is better.
+    pub fn score(
+        &self,
+        guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+        candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+        weights: &[f64],
+    ) -> f64 {
+        let mut bucket_sizes = [0usize; 256];
+        let mut bucket_weights = [0f64; 256];
+        for (candidate, weight) in candidates.iter().zip(weights) {
+            let hint_id = WordHint::from_guess_and_answer(guess, candidate).hint_id() as usize;
+            bucket_sizes[hint_id] += 1;
+            bucket_weights[hint_id] += weight;
+        }
+        let total_weight: f64 = bucket_weights.iter().sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        let buckets = bucket_sizes
+            .iter()
+            .zip(bucket_weights.iter())
+            .filter(|(size, _)| **size != 0);
+        match self.objective {
+            GreedyObjective::Entropy => buckets
+                .filter(|(_, weight)| **weight > 0.0)
+                .map(|(_, weight)| {
+                    let prob = weight / total_weight;
+                    prob * prob.log2()
+                })
+                .sum(),
+            GreedyObjective::ExpectedRemaining => buckets
+                .map(|(size, weight)| *size as f64 * weight / total_weight)
+                .sum(),
+            GreedyObjective::Minimax => buckets.map(|(_, weight)| *weight).fold(0.0, f64::max),
+        }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Strategy<WORD_SIZE, ALPHABET_SIZE>
+    for GreedyStrategy<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn name(&self) -> String {
+        match self.priors {
+            Some(_) => format!("weighted-{}", self.objective.name()),
+            None => self.objective.name().to_string(),
+        }
+    }
+
+    fn choose_guess(
+        &self,
+        allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+        candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    ) -> Option<Word<WORD_SIZE, ALPHABET_SIZE>> {
+        if candidates.len() == 1 {
+            return Some(candidates[0]);
+        }
+        let weights = match &self.priors {
+            Some(priors) => priors.weights_for(candidates),
+            None => vec![1.0; candidates.len()],
+        };
+        // Ties go to guesses that could be the answer, then to the earliest word
+        WorkerPool::new(PoolConfig::default())
+            .map_or_unwind(allowed_guesses, |_, guess| {
+                let first_hint = WordHint::from_guess_and_answer(guess, &candidates[0]);
+                let splits = candidates[1..].iter().any(|candidate| {
+                    WordHint::from_guess_and_answer(guess, candidate) != first_hint
+                });
+                splits.then(|| {
+                    (
+                        self.score(guess, candidates, &weights),
+                        !candidates.contains(guess),
+                        *guess,
+                    )
+                })
+            })
+            .into_iter()
+            .flatten()
+            .min_by(
+                |(score_a, sacrifice_a, guess_a), (score_b, sacrifice_b, guess_b)| {
+                    score_a
+                        .total_cmp(score_b)
+                        .then(sacrifice_a.cmp(sacrifice_b))
+                        .then(guess_a.cmp(guess_b))
+                },
+            )
+            .map(|(_, _, guess)| guess)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words::words;
+
+    #[test]
+    fn test_weights_change_choice() {
+        // Each guess only splits itself off from the rest, so weights decide which to pick
+        let candidates = words(&["fight", "light", "might", "sight"]);
+        let allowed = candidates.clone();
+        let unweighted = GreedyStrategy::unweighted(GreedyObjective::Minimax);
+        assert_eq!(
+            unweighted.choose_guess(&allowed, &candidates),
+            Some(candidates[0])
+        );
+
+        let priors = AnswerPriors::from_weights([(candidates[2], 100.0)], 1.0);
+        let weighted = GreedyStrategy::weighted(GreedyObjective::Minimax, priors);
+        assert_eq!(weighted.name(), "weighted-minimax");
+        assert_eq!(
+            weighted.choose_guess(&allowed, &candidates),
+            Some(candidates[2])
+        );
+    }
+
+    #[test]
+    fn test_objectives_agree_on_clear_split() {
+        let candidates = words(&["board", "fight", "lemon"]);
+        let allowed = words(&["aaaaa", "board"]);
+        for objective in [
+            GreedyObjective::Entropy,
+            GreedyObjective::ExpectedRemaining,
+            GreedyObjective::Minimax,
+        ] {
+            assert_eq!(
+                GreedyStrategy::unweighted(objective).choose_guess(&allowed, &candidates),
+                Some(candidates[0])
+            );
+        }
+    }
+}
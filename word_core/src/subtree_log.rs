@@ -0,0 +1,252 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decision_tree_general::{SearchEvent, SearchObserver, SearchStep, TreeNode, WorkerObserver},
+    error::WordGameError,
+};
+
+/// A subtree the search finished, as written to a subtree log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtreeRecord {
+    /// The guess and hint at each step from where the search started down to this subtree
+    pub path: Vec<(u16, u8)>,
+    pub tree: TreeNode,
+}
+
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    /// The first write that failed, after which nothing more is written
+    error: Option<io::Error>,
+}
+
+/// An observer that appends every subtree the search finishes to a file, one JSON record per
+/// line, so a long search's partial results can be read while it runs and survive a crash.
+///
+/// Subtrees are written as soon as their node is solved, so most belong to guesses that later
+/// lose out. The search's own tree comes last, under the empty path. Nodes answered from the
+/// transposition table aren't written again. Workers of a parallel root search write to the
+/// same log, so their records interleave.
+#[derive(Debug, Clone)]
+pub struct SubtreeLogObserver {
+    /// The deepest nodes whose subtrees are written, counted as in `max_depth`
+    max_depth: u8,
+    path: Vec<(u16, u8)>,
+    log: Arc<Mutex<LogFile>>,
+}
+
+impl SubtreeLogObserver {
+    /// Start a new log at a file path, replacing anything already there.
+    pub fn create(file_path: &Path, max_depth: u8) -> Result<Self, WordGameError> {
+        let file = File::create(file_path).map_err(|source| WordGameError::Io {
+            path: file_path.into(),
+            source,
+        })?;
+        Ok(Self {
+            max_depth,
+            path: Vec::new(),
+            log: Arc::new(Mutex::new(LogFile {
+                path: file_path.into(),
+                file,
+                error: None,
+            })),
+        })
+    }
+
+    /// Get the first error hit writing the log, if any.
+    pub fn check(&self) -> Result<(), WordGameError> {
+        let mut log = self.lock();
+        match log.error.take() {
+            Some(source) => Err(WordGameError::Io {
+                path: log.path.clone(),
+                source,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LogFile> {
+        self.log
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SearchObserver for SubtreeLogObserver {
+    fn observes_depth(&self, depth: u8) -> bool {
+        depth <= self.max_depth
+    }
+
+    fn descend(&self, step: SearchStep) -> Self {
+        let mut path = self.path.clone();
+        if let SearchStep::Clue { guess_ind, hint } = step {
+            path.push((guess_ind, hint));
+        }
+        Self {
+            max_depth: self.max_depth,
+            path,
+            log: self.log.clone(),
+        }
+    }
+
+    fn on_event(&self, event: SearchEvent) {
+        let SearchEvent::NodeFinished {
+            best: Some(tree), ..
+        } = event
+        else {
+            return;
+        };
+        let mut line = serde_json::to_vec(&SubtreeRecord {
+            path: self.path.clone(),
+            tree: tree.clone(),
+        })
+        .expect("trees always serialize");
+        line.push(b'\n');
+        let mut log = self.lock();
+        if log.error.is_none() {
+            // Each record goes out in a single write, so a crash can only cut off the last line
+            if let Err(source) = log.file.write_all(&line) {
+                log.error = Some(source);
+            }
+        }
+    }
+
+    fn for_worker(&self) -> Option<WorkerObserver> {
+        Some(WorkerObserver::new(self.clone()))
+    }
+}
+
+/// Read the records of a subtree log, which may still be being written.
+///
+/// A last line that doesn't parse is taken to be cut off mid-write and left out, but a bad line
+/// anywhere else is an error.
+pub fn read_subtree_log(file_path: &Path) -> Result<Vec<SubtreeRecord>, WordGameError> {
+    let raw = fs::read_to_string(file_path).map_err(|source| WordGameError::Io {
+        path: file_path.into(),
+        source,
+    })?;
+    let lines: Vec<&str> = raw.lines().filter(|line| !line.is_empty()).collect();
+    let mut records = Vec::with_capacity(lines.len());
+    for (ind, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) if ind + 1 == lines.len() && !raw.ends_with('\n') => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, env};
+
+    use super::*;
+    use crate::{
+        decision_tree_general::{GuessFrom, SearchOptions, compute_decision_tree_aggressive},
+        hint_matrix::compute_hints,
+        test_words,
+    };
+
+    #[test]
+    fn test_subtree_log() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let file_path =
+            env::temp_dir().join(format!("word_core_subtree_log_{}", std::process::id()));
+        let observer = SubtreeLogObserver::create(&file_path, 1).unwrap();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            answers,
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions::default(),
+            Some(&observer),
+        )
+        .unwrap();
+        observer.check().unwrap();
+
+        let records = read_subtree_log(&file_path).unwrap();
+        assert!(records.len() > 1);
+        assert!(records.iter().all(|record| record.path.len() <= 1));
+        let last = records.last().unwrap();
+        assert!(last.path.is_empty());
+        assert_eq!(
+            serde_json::to_string(&last.tree).unwrap(),
+            serde_json::to_string(&tree).unwrap()
+        );
+        // Each subtree under the first guess is one that guess's child was solved with
+        let (GuessFrom::Guess(guess_ind) | GuessFrom::Answer(guess_ind)) = tree.should_guess;
+        for (hint, child) in &tree.next {
+            if child.num_answers > 1 {
+                assert!(records.iter().any(|record| {
+                    record.path == [(guess_ind, *hint)]
+                        && record.tree.total_guesses == child.total_guesses
+                }));
+            }
+        }
+
+        // A record cut off by a crash is left out
+        let raw = fs::read_to_string(&file_path).unwrap();
+        fs::write(&file_path, &raw[..raw.len() - 10]).unwrap();
+        assert_eq!(
+            read_subtree_log(&file_path).unwrap().len(),
+            records.len() - 1
+        );
+        fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_subtree_log_parallel_root() {
+        let words = test_words::small_list();
+        let hints = compute_hints(&words, &words);
+        let answers: HashSet<u16> = (0..words.len() as u16).collect();
+        let file_path = env::temp_dir().join(format!(
+            "word_core_subtree_log_parallel_{}",
+            std::process::id()
+        ));
+        let observer = SubtreeLogObserver::create(&file_path, 1).unwrap();
+        let tree = compute_decision_tree_aggressive(
+            &hints,
+            answers,
+            0,
+            6,
+            f64::INFINITY,
+            SearchOptions {
+                parallel_root: true,
+                ..SearchOptions::default()
+            },
+            Some(&observer),
+        )
+        .unwrap();
+        observer.check().unwrap();
+
+        // The workers log the subtrees under the root's guesses, and only the root logs itself
+        let records = read_subtree_log(&file_path).unwrap();
+        let root_guesses: HashSet<u16> = records
+            .iter()
+            .filter_map(|record| record.path.first().map(|(guess_ind, _)| *guess_ind))
+            .collect();
+        assert!(root_guesses.len() > 1);
+        let roots: Vec<&SubtreeRecord> = records
+            .iter()
+            .filter(|record| record.path.is_empty())
+            .collect();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(
+            serde_json::to_string(&roots[0].tree).unwrap(),
+            serde_json::to_string(&tree).unwrap()
+        );
+        fs::remove_file(&file_path).unwrap();
+    }
+}
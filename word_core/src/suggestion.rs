@@ -0,0 +1,541 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BTreeMap,
+};
+
+use serde::Serialize;
+
+use crate::{
+    bounded_cache::MemoryFootprint,
+    hint::WordHint,
+    preference::{Preference, compare_preferences},
+    readable_tree::ReadableTreeNode,
+    word::Word,
+    word_search::SearchableWords,
+    worker_pool::{PoolConfig, WorkerPool},
+};
+
+/// How a guess splits the remaining candidates, summarized.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PartitionStats {
+    /// Number of distinct hints the guess can produce
+    pub num_buckets: usize,
+
+    /// Expected number of candidates left after the guess, for a uniformly random answer
+    pub expected_remaining: f64,
+
+    /// Expected information gained by the guess, in bits
+    pub entropy: f64,
+
+    /// Number of candidates left in the largest bucket
+    pub worst_bucket: usize,
+}
+
+impl PartitionStats {
+    /// Summarize a partition from the sizes of its (non-empty) buckets.
+    pub fn from_bucket_sizes(bucket_sizes: impl IntoIterator<Item = usize>) -> Self {
+        let bucket_sizes: Vec<usize> = bucket_sizes.into_iter().filter(|size| *size != 0).collect();
+        let total: usize = bucket_sizes.iter().sum();
+        if total == 0 {
+            return Self {
+                num_buckets: 0,
+                expected_remaining: 0.0,
+                entropy: 0.0,
+                worst_bucket: 0,
+            };
+        }
+        let total = total as f64;
+        Self {
+            num_buckets: bucket_sizes.len(),
+            expected_remaining: bucket_sizes
+                .iter()
+                .map(|size| (size * size) as f64)
+                .sum::<f64>()
+                / total,
+            entropy: Self::entropy_of(&bucket_sizes),
+            worst_bucket: bucket_sizes.iter().copied().max().unwrap_or(0),
+        }
+    }
+
+    /// Get just the entropy of a partition in bits, from the sizes of its buckets, any of which
+    /// may be empty. Cheaper than the full summary for ranking many guesses.
+    pub fn entropy_of(bucket_sizes: &[usize]) -> f64 {
+        let total: usize = bucket_sizes.iter().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let total = total as f64;
+        bucket_sizes
+            .iter()
+            .filter(|size| **size != 0)
+            .map(|size| {
+                let prob = *size as f64 / total;
+                -prob * prob.log2()
+            })
+            .sum()
+    }
+}
+
+/// Group the candidates by the hint id the guess would receive if each were the answer.
+pub fn partition_by_hint<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> BTreeMap<u8, Vec<Word<WORD_SIZE, ALPHABET_SIZE>>> {
+    let mut buckets: BTreeMap<u8, Vec<Word<WORD_SIZE, ALPHABET_SIZE>>> = BTreeMap::new();
+    for candidate in candidates {
+        buckets
+            .entry(WordHint::from_guess_and_answer(guess, candidate).hint_id())
+            .or_default()
+            .push(*candidate);
+    }
+    buckets
+}
+
+/// Summarize how the guess would split the candidates.
+pub fn partition_stats<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> PartitionStats {
+    let mut bucket_sizes = [0; 256];
+    for candidate in candidates {
+        bucket_sizes[WordHint::from_guess_and_answer(guess, candidate).hint_id() as usize] += 1;
+    }
+    PartitionStats::from_bucket_sizes(bucket_sizes)
+}
+
+/// The candidates that would all receive the same hint for a guess.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    pub hint: WordHint<WORD_SIZE>,
+
+    /// Chance of receiving this hint, for a uniformly random answer
+    pub probability: f64,
+
+    /// Number of candidates in the bucket, including any left out of `candidates`
+    pub num_candidates: usize,
+
+    /// The candidates in the bucket, possibly capped
+    pub candidates: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+}
+
+/// The full breakdown of how a guess splits the candidates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketReport<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    pub guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+    pub stats: PartitionStats,
+
+    /// Every feasible hint's bucket, largest first
+    pub buckets: Vec<Bucket<WORD_SIZE, ALPHABET_SIZE>>,
+}
+
+/// Break down how the guess would split the candidates, listing at most
+/// `max_candidates_per_bucket` words per bucket if given.
+pub fn bucket_report<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    max_candidates_per_bucket: Option<usize>,
+) -> BucketReport<WORD_SIZE, ALPHABET_SIZE> {
+    let buckets = partition_by_hint(guess, candidates);
+    let stats = PartitionStats::from_bucket_sizes(buckets.values().map(Vec::len));
+    let mut buckets: Vec<Bucket<WORD_SIZE, ALPHABET_SIZE>> = buckets
+        .into_iter()
+        .map(|(hint_id, mut bucket_candidates)| {
+            let num_candidates = bucket_candidates.len();
+            if let Some(max_candidates) = max_candidates_per_bucket {
+                bucket_candidates.truncate(max_candidates);
+            }
+            Bucket {
+                hint: WordHint::from_id(hint_id),
+                probability: num_candidates as f64 / candidates.len() as f64,
+                num_candidates,
+                candidates: bucket_candidates,
+            }
+        })
+        .collect();
+    // Stable, so equal buckets stay in hint id order
+    buckets.sort_by_key(|bucket| Reverse(bucket.num_candidates));
+    BucketReport {
+        guess: *guess,
+        stats,
+        buckets,
+    }
+}
+
+/// A recommended guess along with the evidence for recommending it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suggestion<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    pub guess: Word<WORD_SIZE, ALPHABET_SIZE>,
+
+    /// Whether the guess could itself be the answer
+    pub is_candidate: bool,
+
+    pub stats: PartitionStats,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> MemoryFootprint
+    for Suggestion<WORD_SIZE, ALPHABET_SIZE>
+{
+    fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+    }
+}
+
+/// Rank guesses by expected remaining candidates, returning the best `top_n` with evidence.
+///
+/// Ties are broken in favor of guesses that could be the answer, then by smaller worst bucket.
+pub fn rank_suggestions<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    top_n: usize,
+) -> Vec<Suggestion<WORD_SIZE, ALPHABET_SIZE>> {
+    rank_suggestions_with_preferences(allowed_guesses, candidates, top_n, &[])
+}
+
+/// Rank guesses like [`rank_suggestions`], breaking any remaining ties by the preferences in
+/// order before falling back to alphabetical order.
+pub fn rank_suggestions_with_preferences<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    top_n: usize,
+    preferences: &[&dyn Preference<WORD_SIZE, ALPHABET_SIZE>],
+) -> Vec<Suggestion<WORD_SIZE, ALPHABET_SIZE>> {
+    let mut suggestions =
+        WorkerPool::new(PoolConfig::default()).map_or_unwind(allowed_guesses, |_, guess| {
+            Suggestion {
+                guess: *guess,
+                is_candidate: candidates.contains(guess),
+                stats: partition_stats(guess, candidates),
+            }
+        });
+    suggestions.sort_by(|a, b| compare_suggestions(a, b, preferences));
+    suggestions.truncate(top_n);
+    suggestions
+}
+
+/// Order suggestions best first.
+pub(crate) fn compare_suggestions<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    a: &Suggestion<WORD_SIZE, ALPHABET_SIZE>,
+    b: &Suggestion<WORD_SIZE, ALPHABET_SIZE>,
+    preferences: &[&dyn Preference<WORD_SIZE, ALPHABET_SIZE>],
+) -> Ordering {
+    a.stats
+        .expected_remaining
+        .total_cmp(&b.stats.expected_remaining)
+        .then(b.is_candidate.cmp(&a.is_candidate))
+        .then(a.stats.worst_bucket.cmp(&b.stats.worst_bucket))
+        .then_with(|| compare_preferences(preferences, &a.guess, &b.guess))
+        .then(a.guess.cmp(&b.guess))
+}
+
+/// How `score_guesses` rates each guess.
+#[derive(Debug, Clone, Copy)]
+pub enum Scorer<'a, const WORD_SIZE: usize> {
+    /// Expected information gained in bits, higher is better
+    Entropy,
+
+    /// Expected number of candidates left, lower is better
+    ExpectedRemaining,
+
+    /// Number of candidates left in the largest bucket, lower is better
+    Minimax,
+
+    /// The estimated cost a precomputed tree gives the guess at the current position, lower is
+    /// better. Its chosen guess and any alternatives it kept are scored, and every other guess
+    /// scores infinity.
+    TreeLookup(&'a ReadableTreeNode<WORD_SIZE>),
+}
+
+impl<const WORD_SIZE: usize> Scorer<'_, WORD_SIZE> {
+    /// Whether a higher score is better.
+    pub fn higher_is_better(&self) -> bool {
+        matches!(self, Self::Entropy)
+    }
+}
+
+/// Score every guess against the candidates, best first, without any session state.
+///
+/// Ties keep the order of `guesses`.
+pub fn score_guesses<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    candidates: &SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+    guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    scorer: Scorer<WORD_SIZE>,
+) -> Vec<(Word<WORD_SIZE, ALPHABET_SIZE>, f64)> {
+    let mut scores = WorkerPool::new(PoolConfig::default()).map_or_unwind(guesses, |_, guess| {
+        let score = match scorer {
+            Scorer::Entropy => partition_stats(guess, candidates.words()).entropy,
+            Scorer::ExpectedRemaining => {
+                partition_stats(guess, candidates.words()).expected_remaining
+            }
+            Scorer::Minimax => partition_stats(guess, candidates.words()).worst_bucket as f64,
+            Scorer::TreeLookup(tree) => std::iter::once(tree)
+                .chain(tree.alternatives.iter().map(|alt| &alt.tree_node))
+                .find(|tree_node| tree_node.should_guess.0 == guess.0)
+                .map_or(f64::INFINITY, |tree_node| tree_node.est_cost),
+        };
+        (*guess, score)
+    });
+    if scorer.higher_is_better() {
+        scores.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    } else {
+        scores.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    }
+    scores
+}
+
+/// Whether to spend a guess on a non-candidate purely for information, and why.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SacrificeDecision<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    /// The best guess that could itself be the answer
+    pub best_candidate: Suggestion<WORD_SIZE, ALPHABET_SIZE>,
+
+    /// The best guess that can't be the answer, if any are allowed
+    pub best_sacrifice: Option<Suggestion<WORD_SIZE, ALPHABET_SIZE>>,
+
+    /// Estimated total guesses if the best candidate is played
+    pub candidate_expected_guesses: f64,
+
+    /// Estimated total guesses if the best sacrifice is played
+    pub sacrifice_expected_guesses: Option<f64>,
+
+    /// Sacrificing pays off once more than this many candidates remain, given how well each
+    /// guess splits them. `None` if the sacrifice doesn't split them any better.
+    pub break_even_candidates: Option<f64>,
+
+    pub should_sacrifice: bool,
+}
+
+/// Estimate the guesses still needed after a guess, excluding that guess itself.
+///
+/// Each bucket of `k` candidates is assumed to be finished by guessing one of them in a way that
+/// identifies the rest, costing `(2k - 1) / k` guesses. This is optimistic for large buckets, but
+/// treats candidate and non-candidate guesses alike.
+fn expected_guesses_after<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    guess: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> f64 {
+    let mut bucket_sizes = [0usize; 256];
+    for candidate in candidates {
+        bucket_sizes[WordHint::from_guess_and_answer(guess, candidate).hint_id() as usize] += 1;
+    }
+    // Hint id 0 is all correct, which needs no more guesses
+    bucket_sizes[1..]
+        .iter()
+        .filter(|size| **size != 0)
+        .map(|size| (2 * size - 1) as f64 / candidates.len() as f64)
+        .sum()
+}
+
+/// Decide whether the best move is to guess a remaining candidate or to sacrifice a guess on a
+/// non-candidate for more information.
+///
+/// Returns `None` if there are no candidates or none of them are allowed guesses.
+pub fn analyze_sacrifice<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+    allowed_guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+    candidates: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+) -> Option<SacrificeDecision<WORD_SIZE, ALPHABET_SIZE>> {
+    let num_candidates = candidates.len() as f64;
+    let best_by_expected_guesses = |is_candidate: bool| {
+        WorkerPool::new(PoolConfig::default())
+            .map_or_unwind(allowed_guesses, |_, guess| {
+                (candidates.contains(guess) == is_candidate)
+                    .then(|| (expected_guesses_after(guess, candidates), *guess))
+            })
+            .into_iter()
+            .flatten()
+            .min_by(|(cost_a, guess_a), (cost_b, guess_b)| {
+                cost_a.total_cmp(cost_b).then(guess_a.cmp(guess_b))
+            })
+            .map(|(cost_after, guess)| {
+                (
+                    1.0 + cost_after,
+                    Suggestion {
+                        guess,
+                        is_candidate,
+                        stats: partition_stats(&guess, candidates),
+                    },
+                )
+            })
+    };
+
+    let (candidate_expected_guesses, best_candidate) = best_by_expected_guesses(true)?;
+    let sacrifice = best_by_expected_guesses(false);
+
+    // Conditional on the candidate not being the answer, how many more guesses each needs
+    let candidate_rest =
+        (candidate_expected_guesses - 1.0) * num_candidates / (num_candidates - 1.0).max(1.0);
+    let break_even_candidates = sacrifice.and_then(|(sacrifice_expected_guesses, _)| {
+        let sacrifice_rest = sacrifice_expected_guesses - 1.0;
+        (candidate_rest > sacrifice_rest)
+            .then(|| candidate_rest / (candidate_rest - sacrifice_rest))
+    });
+
+    Some(SacrificeDecision {
+        best_candidate,
+        best_sacrifice: sacrifice.map(|(_, suggestion)| suggestion),
+        candidate_expected_guesses,
+        sacrifice_expected_guesses: sacrifice.map(|(cost, _)| cost),
+        break_even_candidates,
+        should_sacrifice: sacrifice.is_some_and(|(cost, _)| cost < candidate_expected_guesses),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decision_tree_general::Alternative,
+        preference::{AvoidRareWords, PreferUnusedLetters},
+        test_words::{self, words},
+    };
+
+    #[test]
+    fn test_partition_stats() {
+        let stats = PartitionStats::from_bucket_sizes([2, 1, 1, 0]);
+        assert_eq!(stats.num_buckets, 3);
+        assert_eq!(stats.expected_remaining, 1.5);
+        assert_eq!(stats.entropy, 1.5);
+        assert_eq!(stats.worst_bucket, 2);
+    }
+
+    #[test]
+    fn test_partition_by_hint_matches_stats() {
+        let candidates = words(&["fight", "light", "might", "night", "sight"]);
+        let guess = Word::try_from("mints").unwrap();
+        let buckets = partition_by_hint(&guess, &candidates);
+        assert_eq!(
+            PartitionStats::from_bucket_sizes(buckets.values().map(Vec::len)),
+            partition_stats(&guess, &candidates)
+        );
+        assert_eq!(buckets.values().map(Vec::len).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn test_bucket_report() {
+        let candidates = words(&["fight", "light", "might", "night", "sight", "board"]);
+        let guess = Word::try_from("light").unwrap();
+        let report = bucket_report(&guess, &candidates, Some(2));
+        assert_eq!(report.stats, partition_stats(&guess, &candidates));
+
+        // Every _IGHT word other than LIGHT gets the same hint
+        assert_eq!(report.buckets.len(), 3);
+        assert_eq!(report.buckets[0].num_candidates, 4);
+        assert_eq!(report.buckets[0].candidates.len(), 2);
+        assert_eq!(report.buckets[0].probability, 4.0 / 6.0);
+        let total_probability: f64 = report.buckets.iter().map(|bucket| bucket.probability).sum();
+        assert!((total_probability - 1.0).abs() < 1e-9);
+        assert!(
+            report
+                .buckets
+                .iter()
+                .any(|bucket| { bucket.hint.all_correct() && bucket.candidates == vec![guess] })
+        );
+    }
+
+    #[test]
+    fn test_rank_suggestions() {
+        let candidates = words(&["fight", "light", "might", "night", "sight"]);
+        let mut allowed_guesses = words(&["board", "films", "mints"]);
+        allowed_guesses.extend(candidates.iter().copied());
+        let suggestions = rank_suggestions(&allowed_guesses, &candidates, 3);
+        assert_eq!(suggestions.len(), 3);
+
+        // FILMS tells every candidate apart
+        assert_eq!(suggestions[0].guess, Word::try_from("films").unwrap());
+        assert!(!suggestions[0].is_candidate);
+        assert_eq!(suggestions[0].stats.worst_bucket, 1);
+        assert_eq!(suggestions[0].stats.expected_remaining, 1.0);
+        for window in suggestions.windows(2) {
+            assert!(window[0].stats.expected_remaining <= window[1].stats.expected_remaining);
+        }
+        // BOARD splits nothing
+        let board = rank_suggestions(&allowed_guesses, &candidates, 100)
+            .into_iter()
+            .find(|suggestion| suggestion.guess == Word::try_from("board").unwrap())
+            .unwrap();
+        assert_eq!(board.stats.num_buckets, 1);
+        assert_eq!(board.stats.entropy, 0.0);
+    }
+
+    #[test]
+    fn test_rank_suggestions_with_preferences() {
+        let candidates = words(&["fight", "light", "might", "night", "sight"]);
+        // Every candidate splits the rest the same way, so they only differ by preference
+        assert_eq!(
+            rank_suggestions(&candidates, &candidates, 1)[0].guess,
+            candidates[0]
+        );
+        let common = AvoidRareWords::new(&candidates[3..]);
+        let unused = PreferUnusedLetters::new(&words(&["north"]));
+        let ranked = rank_suggestions_with_preferences(&candidates, &candidates, 5, &[&common]);
+        assert_eq!(ranked[0].guess, candidates[3]);
+        assert_eq!(ranked[2].guess, candidates[0]);
+        let ranked =
+            rank_suggestions_with_preferences(&candidates, &candidates, 5, &[&common, &unused]);
+        assert_eq!(ranked[0].guess, candidates[4]);
+    }
+
+    #[test]
+    fn test_analyze_sacrifice() {
+        let candidates = words(&["fight", "light", "might", "night", "sight"]);
+        let mut allowed_guesses = words(&["board", "films"]);
+        allowed_guesses.extend(candidates.iter().copied());
+
+        // Any candidate leaves the other four together, while FILMS identifies every answer
+        let decision = analyze_sacrifice(&allowed_guesses, &candidates).unwrap();
+        assert!(decision.should_sacrifice);
+        assert_eq!(
+            decision.best_sacrifice.unwrap().guess,
+            Word::try_from("films").unwrap()
+        );
+        assert_eq!(decision.sacrifice_expected_guesses, Some(2.0));
+        assert_eq!(decision.candidate_expected_guesses, 1.0 + 7.0 / 5.0);
+        let break_even = decision.break_even_candidates.unwrap();
+        assert!(break_even < candidates.len() as f64);
+
+        // With only two candidates, guessing one of them is always at least as good
+        let decision = analyze_sacrifice(&allowed_guesses, &candidates[..2]).unwrap();
+        assert!(!decision.should_sacrifice);
+        assert!(decision.best_candidate.is_candidate);
+        assert_eq!(decision.candidate_expected_guesses, 1.5);
+
+        assert!(analyze_sacrifice(&allowed_guesses, &[]).is_none());
+    }
+
+    #[test]
+    fn test_score_guesses() {
+        let candidates =
+            SearchableWords::build(words(&["fight", "light", "might", "night", "sight"]));
+        let guesses = words(&["board", "fight", "films"]);
+        let [board, fight, films] = [guesses[0], guesses[1], guesses[2]];
+        let scores = |scorer| score_guesses(&candidates, &guesses, scorer);
+
+        assert_eq!(
+            scores(Scorer::Entropy),
+            [
+                (films, 5f64.log2()),
+                (fight, PartitionStats::from_bucket_sizes([1, 4]).entropy),
+                (board, 0.0)
+            ]
+        );
+        assert_eq!(
+            scores(Scorer::ExpectedRemaining),
+            [(films, 1.0), (fight, 17.0 / 5.0), (board, 5.0)]
+        );
+        assert_eq!(
+            scores(Scorer::Minimax),
+            [(films, 1.0), (fight, 4.0), (board, 5.0)]
+        );
+
+        // Only the tree's guess and the alternatives it kept get a finite score
+        let leaf = |guess, est_cost| test_words::tree_node(guess, est_cost, Default::default());
+        let mut tree = leaf(films, 2.0);
+        tree.alternatives.push(Alternative {
+            tree_node: leaf(fight, 2.6),
+            complete: true,
+        });
+        assert_eq!(
+            scores(Scorer::TreeLookup(&tree)),
+            [(films, 2.0), (fight, 2.6), (board, f64::INFINITY)]
+        );
+    }
+}
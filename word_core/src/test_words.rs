@@ -0,0 +1,73 @@
+//! Word lists and trees shared by the solver tests.
+
+use std::collections::HashMap;
+
+use crate::{hint::WordHint, readable_tree::ReadableTreeNode, word::Word};
+
+/// Eighteen words in a few tight families, where the _IGHT words take many guesses to tell apart.
+pub fn small_list() -> Vec<Word<5, 26>> {
+    words(&[
+        "fight", "light", "might", "sight", "tight", "night", "right", "board", "bread", "broad",
+        "beard", "bored", "brain", "drain", "grain", "train", "stain", "stair",
+    ])
+}
+
+/// The small list and a dozen more words, enough that searches don't finish instantly.
+pub fn medium_list() -> Vec<Word<5, 26>> {
+    let mut medium = small_list();
+    medium.extend(words(&[
+        "chair", "charm", "champ", "clamp", "cramp", "crane", "crate", "grate", "irate", "plate",
+        "slate", "skate",
+    ]));
+    medium
+}
+
+/// Parse each word, panicking on any that isn't valid.
+pub(crate) fn words(raw: &[&str]) -> Vec<Word<5, 26>> {
+    raw.iter()
+        .map(|word| Word::try_from(*word).unwrap())
+        .collect()
+}
+
+/// A tree node guessing `should_guess` with the given children, and nothing else set.
+pub fn tree_node(
+    should_guess: Word<5, 26>,
+    est_cost: f64,
+    next: HashMap<WordHint<5>, ReadableTreeNode<5>>,
+) -> ReadableTreeNode<5> {
+    ReadableTreeNode {
+        should_guess,
+        est_cost,
+        next,
+        unexpanded: HashMap::new(),
+        approximate: false,
+        alternatives: Vec::new(),
+    }
+}
+
+/// A node guessing the answer it was reached for.
+pub fn leaf(should_guess: Word<5, 26>) -> ReadableTreeNode<5> {
+    tree_node(should_guess, 1.0, HashMap::new())
+}
+
+/// A node guessing `guess`, then whichever other answer its hint leaves.
+pub fn guess_then_answers(
+    guess: Word<5, 26>,
+    answers: &[Word<5, 26>],
+    est_cost: f64,
+) -> ReadableTreeNode<5> {
+    tree_node(
+        guess,
+        est_cost,
+        answers
+            .iter()
+            .filter(|answer| **answer != guess)
+            .map(|answer| {
+                (
+                    WordHint::from_guess_and_answer(&guess, answer),
+                    leaf(*answer),
+                )
+            })
+            .collect(),
+    )
+}
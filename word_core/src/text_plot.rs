@@ -0,0 +1,84 @@
+use std::fmt::Display;
+
+use crate::render::{RenderMode, histogram_bar};
+
+/// Count how many times each size occurs, indexed by size from 0 up to the largest.
+///
+/// Handy for turning bucket sizes into the counts [`sparkline`] draws.
+pub fn size_counts(sizes: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    let mut counts = vec![];
+    for size in sizes {
+        if size >= counts.len() {
+            counts.resize(size + 1, 0);
+        }
+        counts[size] += 1;
+    }
+    counts
+}
+
+/// Draw counts as one character each, scaled so the largest is a full cell.
+///
+/// Any non-zero count gets at least the smallest mark, so rare values stay visible.
+pub fn sparkline(counts: &[usize], mode: RenderMode) -> String {
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|count| histogram_bar((8 * count).div_ceil(max_count.max(1)), mode))
+        .collect()
+}
+
+/// Draw a horizontal bar for `count`, scaled so `max_count` fills `width` cells.
+pub fn bar(count: usize, max_count: usize, width: usize, mode: RenderMode) -> String {
+    let eighths = (8 * width * count).div_ceil(max_count.max(1));
+    let mut bar = histogram_bar(8, mode).repeat(eighths / 8);
+    let partial = eighths % 8;
+    if partial != 0 {
+        bar.push_str(histogram_bar(partial, mode));
+    }
+    bar
+}
+
+/// Draw one labeled row per entry, with its count and a bar scaled so the largest count fills
+/// `width` cells.
+pub fn histogram<K: Display>(
+    rows: impl IntoIterator<Item = (K, usize)>,
+    width: usize,
+    mode: RenderMode,
+) -> String {
+    let rows: Vec<(K, usize)> = rows.into_iter().collect();
+    let max_count = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    rows.iter()
+        .map(|(label, count)| {
+            format!(
+                "{}\t{}\t| {}\n",
+                label,
+                count,
+                bar(*count, max_count, width, mode)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_counts_and_sparkline() {
+        let counts = size_counts([1, 1, 3, 1, 2, 1]);
+        assert_eq!(counts, vec![0, 4, 1, 1]);
+        assert_eq!(sparkline(&counts, RenderMode::Ascii), " @,,");
+        assert_eq!(sparkline(&counts, RenderMode::Unicode), " ⣿⣀⣀");
+        assert_eq!(sparkline(&[], RenderMode::Ascii), "");
+    }
+
+    #[test]
+    fn test_histogram() {
+        assert_eq!(bar(3, 4, 2, RenderMode::Ascii), "@;");
+        assert_eq!(bar(0, 0, 2, RenderMode::Ascii), "");
+        assert_eq!(
+            histogram([(2, 1), (3, 2)], 1, RenderMode::Ascii),
+            "2\t1\t| ;\n3\t2\t| @\n"
+        );
+    }
+}
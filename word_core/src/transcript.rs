@@ -0,0 +1,213 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::WordGameError, hint::WordHint, word::Word};
+
+/// The transcript format version written by this crate
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// The variant name used for standard games
+pub const DEFAULT_VARIANT: &str = "standard";
+
+/// A single guess and the hint it received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptTurn<const WORD_SIZE: usize> {
+    pub guess: Word<WORD_SIZE, 26>,
+    pub hint: WordHint<WORD_SIZE>,
+
+    /// When the hint was received, in milliseconds since the unix epoch
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub timestamp_ms: Option<u64>,
+}
+
+/// A record of one game, shareable between tools as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript<const WORD_SIZE: usize> {
+    pub version: u32,
+
+    /// Name of the variant / ruleset the game was played under
+    pub variant: String,
+
+    /// The answer, if known
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub answer: Option<Word<WORD_SIZE, 26>>,
+
+    /// When the game started, in milliseconds since the unix epoch
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub started_at_ms: Option<u64>,
+
+    pub turns: Vec<TranscriptTurn<WORD_SIZE>>,
+}
+
+impl<const WORD_SIZE: usize> Transcript<WORD_SIZE> {
+    /// Start an empty transcript for the given variant, stamped with the current time.
+    pub fn new(variant: &str) -> Self {
+        Self {
+            version: TRANSCRIPT_VERSION,
+            variant: variant.to_string(),
+            answer: None,
+            started_at_ms: Some(now_ms()),
+            turns: vec![],
+        }
+    }
+
+    /// Build an untimed transcript from a list of guesses and hints, e.g. from `play_out`.
+    pub fn from_pairs(
+        variant: &str,
+        answer: Option<Word<WORD_SIZE, 26>>,
+        pairs: &[(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)],
+    ) -> Self {
+        Self {
+            version: TRANSCRIPT_VERSION,
+            variant: variant.to_string(),
+            answer,
+            started_at_ms: None,
+            turns: pairs
+                .iter()
+                .map(|(guess, hint)| TranscriptTurn {
+                    guess: *guess,
+                    hint: *hint,
+                    timestamp_ms: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Record a guess and its hint, stamped with the current time.
+    pub fn push(&mut self, guess: Word<WORD_SIZE, 26>, hint: WordHint<WORD_SIZE>) {
+        self.turns.push(TranscriptTurn {
+            guess,
+            hint,
+            timestamp_ms: Some(now_ms()),
+        });
+    }
+
+    /// Get the guesses and hints as plain pairs.
+    pub fn pairs(&self) -> Vec<(Word<WORD_SIZE, 26>, WordHint<WORD_SIZE>)> {
+        self.turns
+            .iter()
+            .map(|turn| (turn.guess, turn.hint))
+            .collect()
+    }
+
+    /// Get the number of guesses made so far.
+    pub fn num_guesses(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// Whether the last hint was all correct.
+    pub fn is_solved(&self) -> bool {
+        self.turns
+            .last()
+            .is_some_and(|turn| turn.hint.all_correct())
+    }
+
+    /// Check that every hint is the one the answer would give, if the answer is known.
+    pub fn check_consistent(&self) -> Result<(), WordGameError> {
+        let Some(answer) = self.answer else {
+            return Ok(());
+        };
+        for (ind, turn) in self.turns.iter().enumerate() {
+            let expected = WordHint::from_guess_and_answer(&turn.guess, &answer);
+            if turn.hint != expected {
+                return Err(WordGameError::InconsistentHistory(format!(
+                    "guess {} ({}) got hint {} but answer {} gives {}",
+                    ind + 1,
+                    turn.guess,
+                    turn.hint,
+                    answer,
+                    expected
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Parse from JSON, rejecting transcripts written in an unknown format version.
+    pub fn from_json(raw: &str) -> Result<Self, WordGameError> {
+        let transcript: Self = serde_json::from_str(raw)?;
+        if transcript.version != TRANSCRIPT_VERSION {
+            return Err(WordGameError::FormatMismatch {
+                expected: format!("transcript v{}", TRANSCRIPT_VERSION),
+                found: format!("transcript v{}", transcript.version),
+            });
+        }
+        Ok(transcript)
+    }
+}
+
+/// Get the current time in milliseconds since the unix epoch.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(guess: &str, hint: &str) -> (Word<5, 26>, WordHint<5>) {
+        (
+            Word::try_from(guess).unwrap(),
+            WordHint::try_from(hint).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut transcript = Transcript::<5>::new(DEFAULT_VARIANT);
+        let (guess, hint) = pair("board", "XXXXX");
+        transcript.push(guess, hint);
+        let reconstructed = Transcript::from_json(&transcript.to_json()).unwrap();
+        assert_eq!(transcript, reconstructed);
+        assert!(!reconstructed.is_solved());
+    }
+
+    #[test]
+    fn test_untimed_json_is_compact() {
+        let transcript = Transcript::from_pairs(
+            DEFAULT_VARIANT,
+            None,
+            &[pair("board", "XXXXX"), pair("light", "√√√√√")],
+        );
+        assert_eq!(
+            serde_json::to_string(&transcript).unwrap(),
+            r#"{"version":1,"variant":"standard","turns":[{"guess":"BOARD","hint":"XXXXX"},{"guess":"LIGHT","hint":"√√√√√"}]}"#
+        );
+        assert!(transcript.is_solved());
+        assert_eq!(transcript.num_guesses(), 2);
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let raw = r#"{"version":99,"variant":"standard","turns":[]}"#;
+        assert!(matches!(
+            Transcript::<5>::from_json(raw),
+            Err(WordGameError::FormatMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_consistent() {
+        let answer = Word::try_from("light").unwrap();
+        let consistent = Transcript::from_pairs(
+            DEFAULT_VARIANT,
+            Some(answer),
+            &[pair("lemon", "√XXXX"), pair("light", "√√√√√")],
+        );
+        assert!(consistent.check_consistent().is_ok());
+        let inconsistent =
+            Transcript::from_pairs(DEFAULT_VARIANT, Some(answer), &[pair("lemon", "XXXXX")]);
+        assert!(matches!(
+            inconsistent.check_consistent(),
+            Err(WordGameError::InconsistentHistory(_))
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{readable_tree::ReadableTreeNode, word::Word};
+
+/// How a single tree performs against an answer list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TreeEvaluation {
+    pub name: String,
+
+    /// Mean number of guesses over the answers the tree solves
+    pub expected_guesses: f64,
+
+    /// Most guesses needed for any answer the tree solves
+    pub worst_case: usize,
+
+    /// Number of answers solved with each number of guesses
+    pub leaf_counts_by_depth: BTreeMap<usize, usize>,
+
+    /// Answers the tree has no path to
+    pub unsolved: Vec<String>,
+}
+
+/// An answer for which the trees don't all play the same guesses.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnswerDifference {
+    pub answer: String,
+
+    /// The guesses each tree plays for this answer, or `None` if it can't reach it
+    pub guesses_by_tree: Vec<Option<Vec<String>>>,
+}
+
+/// A side-by-side evaluation of several trees against the same answers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TreeComparisonReport {
+    pub evaluations: Vec<TreeEvaluation>,
+    pub differences: Vec<AnswerDifference>,
+}
+
+/// Play every answer through every tree, summarizing each tree and listing where they differ.
+pub fn compare_trees<const WORD_SIZE: usize>(
+    trees: &[(&str, &ReadableTreeNode<WORD_SIZE>)],
+    answers: &[Word<WORD_SIZE, 26>],
+) -> TreeComparisonReport {
+    // Guess sequences indexed [tree][answer]
+    let guesses: Vec<Vec<Option<Vec<Word<WORD_SIZE, 26>>>>> = trees
+        .iter()
+        .map(|(_, tree)| {
+            answers
+                .iter()
+                .map(|answer| {
+                    tree.play_out(answer)
+                        .ok()
+                        .map(|transcript| transcript.into_iter().map(|(guess, _)| guess).collect())
+                })
+                .collect()
+        })
+        .collect();
+
+    let evaluations = trees
+        .iter()
+        .zip(guesses.iter())
+        .map(|((name, _), tree_guesses)| {
+            let mut leaf_counts_by_depth: BTreeMap<usize, usize> = BTreeMap::new();
+            let mut unsolved = vec![];
+            for (answer, answer_guesses) in answers.iter().zip(tree_guesses.iter()) {
+                match answer_guesses {
+                    Some(answer_guesses) => {
+                        *leaf_counts_by_depth
+                            .entry(answer_guesses.len())
+                            .or_default() += 1
+                    }
+                    None => unsolved.push(answer.to_string()),
+                }
+            }
+            let num_solved: usize = leaf_counts_by_depth.values().sum();
+            let total_guesses: usize = leaf_counts_by_depth
+                .iter()
+                .map(|(depth, count)| depth * count)
+                .sum();
+            TreeEvaluation {
+                name: name.to_string(),
+                expected_guesses: if num_solved == 0 {
+                    0.0
+                } else {
+                    total_guesses as f64 / num_solved as f64
+                },
+                worst_case: leaf_counts_by_depth.keys().last().copied().unwrap_or(0),
+                leaf_counts_by_depth,
+                unsolved,
+            }
+        })
+        .collect();
+
+    let differences = answers
+        .iter()
+        .enumerate()
+        .filter(|(answer_ind, _)| {
+            guesses
+                .iter()
+                .any(|tree_guesses| tree_guesses[*answer_ind] != guesses[0][*answer_ind])
+        })
+        .map(|(answer_ind, answer)| AnswerDifference {
+            answer: answer.to_string(),
+            guesses_by_tree: guesses
+                .iter()
+                .map(|tree_guesses| {
+                    tree_guesses[answer_ind]
+                        .as_ref()
+                        .map(|answer_guesses| answer_guesses.iter().map(Word::to_string).collect())
+                })
+                .collect(),
+        })
+        .collect();
+
+    TreeComparisonReport {
+        evaluations,
+        differences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_words;
+
+    fn word(raw: &str) -> Word<5, 26> {
+        Word::try_from(raw).unwrap()
+    }
+
+    fn branch(raw: &str, answers: &[&str]) -> ReadableTreeNode<5> {
+        let answers: Vec<Word<5, 26>> = answers.iter().map(|answer| word(answer)).collect();
+        test_words::guess_then_answers(word(raw), &answers, 0.0)
+    }
+
+    #[test]
+    fn test_compare_trees() {
+        let answers = ["fight", "bread", "board"];
+        let tree_a = branch("board", &answers);
+        let tree_b = branch("fight", &answers[..2]);
+        let answer_words: Vec<Word<5, 26>> = answers.iter().map(|raw| word(raw)).collect();
+        let report = compare_trees(&[("a", &tree_a), ("b", &tree_b)], &answer_words);
+
+        assert_eq!(report.evaluations[0].name, "a");
+        assert_eq!(report.evaluations[0].expected_guesses, 5.0 / 3.0);
+        assert_eq!(report.evaluations[0].worst_case, 2);
+        assert_eq!(
+            report.evaluations[0].leaf_counts_by_depth,
+            BTreeMap::from([(1, 1), (2, 2)])
+        );
+        assert!(report.evaluations[0].unsolved.is_empty());
+        assert_eq!(report.evaluations[1].expected_guesses, 1.5);
+        assert_eq!(report.evaluations[1].unsolved, vec!["BOARD".to_string()]);
+
+        assert_eq!(report.differences.len(), 3);
+        assert_eq!(
+            report.differences[0].guesses_by_tree,
+            vec![
+                Some(vec!["BOARD".to_string(), "FIGHT".to_string()]),
+                Some(vec!["FIGHT".to_string()]),
+            ]
+        );
+        assert_eq!(report.differences[2].guesses_by_tree[1], None);
+    }
+
+    #[test]
+    fn test_identical_trees_have_no_differences() {
+        let answers = ["fight", "bread", "board"];
+        let tree = branch("board", &answers);
+        let answer_words: Vec<Word<5, 26>> = answers.iter().map(|raw| word(raw)).collect();
+        let report = compare_trees(&[("a", &tree), ("b", &tree)], &answer_words);
+        assert!(report.differences.is_empty());
+        assert_eq!(
+            report.evaluations[0],
+            TreeEvaluation {
+                name: "a".to_string(),
+                ..report.evaluations[1].clone()
+            }
+        );
+    }
+}
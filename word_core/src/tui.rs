@@ -0,0 +1,457 @@
+use std::io;
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Row, Table},
+};
+
+use crate::{
+    hint::{CharHint, WordHint},
+    render::{KEYBOARD_ROWS, render_mode},
+    session::{CandidateSource, SolverSession},
+    suggestion::{Suggestion, partition_by_hint},
+    text_plot::{size_counts, sparkline},
+    word::Word,
+};
+
+const NUM_SUGGESTIONS: usize = 5;
+
+/// Whether keystrokes are currently spelling a guess or entering the hint it received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputMode {
+    Guess,
+    Hint,
+}
+
+/// An interactive terminal front end over a [`SolverSession`].
+pub struct TuiApp<const WORD_SIZE: usize> {
+    session: SolverSession<WORD_SIZE, 26>,
+    mode: InputMode,
+    guess_input: String,
+    hint_input: Vec<CharHint>,
+    candidate_summary: String,
+    suggestions: Vec<Suggestion<WORD_SIZE, 26>>,
+
+    /// How each suggestion splits the candidates, as a sparkline of bucket sizes
+    bucket_sparklines: Vec<String>,
+    status: String,
+    should_quit: bool,
+}
+
+impl<const WORD_SIZE: usize> TuiApp<WORD_SIZE> {
+    pub fn new(session: SolverSession<WORD_SIZE, 26>) -> Self {
+        let mut app = Self {
+            session,
+            mode: InputMode::Guess,
+            guess_input: String::new(),
+            hint_input: vec![],
+            candidate_summary: String::new(),
+            suggestions: vec![],
+            bucket_sparklines: vec![],
+            status: String::new(),
+            should_quit: false,
+        };
+        app.refresh();
+        app
+    }
+
+    /// Get the underlying session.
+    pub fn session(&self) -> &SolverSession<WORD_SIZE, 26> {
+        &self.session
+    }
+
+    /// Whether the user has asked to exit.
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Update the app state for a single key press.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        match (self.mode, key.code) {
+            (_, KeyCode::Esc) => self.should_quit = true,
+            (InputMode::Guess, KeyCode::Char('z' | 'y'))
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                let changed = if key.code == KeyCode::Char('z') {
+                    self.session.undo()
+                } else {
+                    self.session.redo()
+                };
+                if changed.is_some() {
+                    self.refresh();
+                }
+            }
+            (InputMode::Guess, KeyCode::Char(chr))
+                if chr.is_ascii_alphabetic() && self.guess_input.len() < WORD_SIZE =>
+            {
+                self.guess_input.push(chr.to_ascii_uppercase());
+            }
+            (InputMode::Guess, KeyCode::Tab) => {
+                if let Some(suggestion) = self.suggestions.first() {
+                    self.guess_input = suggestion.guess.to_string();
+                }
+            }
+            (InputMode::Guess, KeyCode::Backspace) => {
+                self.guess_input.pop();
+            }
+            (InputMode::Guess, KeyCode::Enter) => {
+                match Word::<WORD_SIZE, 26>::try_from(self.guess_input.as_str()) {
+                    Ok(guess) => {
+                        self.mode = InputMode::Hint;
+                        self.status = if self.session.is_allowed_guess(&guess) {
+                            "enter the hint: g = green, y = yellow, x = gray".to_string()
+                        } else {
+                            format!(
+                                "{} is not in the word list, its hint will still be used: \
+                                 g = green, y = yellow, x = gray",
+                                guess
+                            )
+                        };
+                    }
+                    Err(err) => self.status = err.to_string(),
+                }
+            }
+            (InputMode::Hint, KeyCode::Char(chr)) => {
+                let char_hint = match chr.to_ascii_lowercase() {
+                    'g' | '√' => CharHint::Correct,
+                    'y' | '~' => CharHint::Elsewhere,
+                    'x' => CharHint::Nowhere,
+                    _ => return,
+                };
+                if self.hint_input.len() < WORD_SIZE {
+                    self.hint_input.push(char_hint);
+                }
+            }
+            (InputMode::Hint, KeyCode::Backspace) if self.hint_input.is_empty() => {
+                self.mode = InputMode::Guess;
+            }
+            (InputMode::Hint, KeyCode::Backspace) => {
+                self.hint_input.pop();
+            }
+            (InputMode::Hint, KeyCode::Enter) if self.hint_input.len() == WORD_SIZE => {
+                let guess = Word::try_from(self.guess_input.as_str()).unwrap();
+                let hint = WordHint(self.hint_input.clone().try_into().unwrap());
+                self.session.add_hint(guess, hint);
+                self.guess_input.clear();
+                self.hint_input.clear();
+                self.mode = InputMode::Guess;
+                self.refresh();
+            }
+            _ => {}
+        }
+    }
+
+    /// Recompute candidates and suggestions after the history changes.
+    fn refresh(&mut self) {
+        self.status.clear();
+        match self.session.candidates_with_source() {
+            Some((source, candidates)) => {
+                self.candidate_summary = match source {
+                    CandidateSource::Primary => format!("{} candidates", candidates.len()),
+                    CandidateSource::Fallback => format!(
+                        "{} candidates (none in the answer list, using fallback)",
+                        candidates.len()
+                    ),
+                };
+                if candidates.len() == 1 {
+                    self.status = format!("the answer is {}", candidates[0]);
+                }
+                self.suggestions = self.session.suggest(NUM_SUGGESTIONS);
+                self.bucket_sparklines = self
+                    .suggestions
+                    .iter()
+                    .map(|suggestion| {
+                        let buckets = partition_by_hint(&suggestion.guess, &candidates);
+                        sparkline(&size_counts(buckets.values().map(Vec::len)), render_mode())
+                    })
+                    .collect();
+            }
+            None => {
+                self.candidate_summary = "0 candidates".to_string();
+                self.suggestions.clear();
+                self.bucket_sparklines.clear();
+                self.status = self
+                    .session
+                    .suggest_hint_corrections()
+                    .first()
+                    .map(|correction| {
+                        format!(
+                            "no word fits, did row {} mean {}?",
+                            correction.row + 1,
+                            correction.corrected_hint
+                        )
+                    })
+                    .unwrap_or_else(|| "no word fits these hints".to_string());
+            }
+        }
+    }
+
+    /// Draw the board and keyboard on the left, candidates and suggestions on the right.
+    pub fn render(&self, frame: &mut Frame) {
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(frame.area());
+        let [board_area, keyboard_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(5)]).areas(left);
+        let [summary_area, suggestions_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(NUM_SUGGESTIONS as u16 + 3),
+            Constraint::Min(0),
+        ])
+        .areas(right);
+
+        self.render_board(frame, board_area);
+        self.render_keyboard(frame, keyboard_area);
+        frame.render_widget(
+            Paragraph::new(self.candidate_summary.as_str()).block(Block::bordered()),
+            summary_area,
+        );
+        self.render_suggestions(frame, suggestions_area);
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(self.status.as_str()),
+                Line::from(
+                    "tab: use top suggestion, enter: submit, ctrl-z/y: undo/redo, esc: quit",
+                )
+                .style(Style::new().fg(Color::DarkGray)),
+            ])
+            .block(Block::bordered()),
+            status_area,
+        );
+    }
+
+    fn render_board(&self, frame: &mut Frame, area: Rect) {
+        let mut lines: Vec<Line> = self
+            .session
+            .history()
+            .iter()
+            .map(|(guess, hint)| {
+                let mut tiles: Vec<Span> = guess
+                    .to_string()
+                    .chars()
+                    .zip(hint.0)
+                    .map(|(chr, char_hint)| tile(chr, Some(char_hint)))
+                    .collect();
+                if !self.session.is_allowed_guess(guess) {
+                    tiles.push(Span::styled(" *", Style::new().fg(Color::DarkGray)));
+                }
+                Line::from(tiles)
+            })
+            .collect();
+        let current_row: Vec<Span> = (0..WORD_SIZE)
+            .map(|ind| {
+                let chr = self.guess_input.chars().nth(ind).unwrap_or('_');
+                tile(chr, self.hint_input.get(ind).copied())
+            })
+            .collect();
+        lines.push(Line::from(current_row));
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("board")),
+            area,
+        );
+    }
+
+    fn render_keyboard(&self, frame: &mut Frame, area: Rect) {
+        let mut best_hints: [Option<CharHint>; 26] = [None; 26];
+        for (guess, hint) in self.session.history() {
+            for (chr, char_hint) in guess.0.iter().zip(hint.0) {
+                let best = &mut best_hints[*chr as usize];
+                // CharHint orders Correct first, so the minimum is the most informative
+                *best = Some(best.map_or(char_hint, |best| best.min(char_hint)));
+            }
+        }
+        let lines: Vec<Line> = KEYBOARD_ROWS
+            .iter()
+            .map(|row| {
+                Line::from(
+                    row.chars()
+                        .map(|chr| tile(chr, best_hints[(chr as u8 - b'A') as usize]))
+                        .collect::<Vec<Span>>(),
+                )
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("keyboard")),
+            area,
+        );
+    }
+
+    fn render_suggestions(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.suggestions.iter().zip(&self.bucket_sparklines).map(
+            |(suggestion, bucket_sparkline)| {
+                Row::new(vec![
+                    suggestion.guess.to_string(),
+                    format!("{:.2}", suggestion.stats.expected_remaining),
+                    format!("{:.2}", suggestion.stats.entropy),
+                    suggestion.stats.worst_bucket.to_string(),
+                    if suggestion.is_candidate { "yes" } else { "" }.to_string(),
+                    bucket_sparkline.clone(),
+                ])
+            },
+        );
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(WORD_SIZE as u16 + 2),
+                Constraint::Length(9),
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "guess",
+                "expected",
+                "entropy",
+                "worst",
+                "candidate",
+                "buckets",
+            ])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title("suggestions"));
+        frame.render_widget(table, area);
+    }
+}
+
+/// Render a single letter as a colored tile.
+fn tile(chr: char, char_hint: Option<CharHint>) -> Span<'static> {
+    let style = match char_hint {
+        Some(CharHint::Correct) => Style::new().fg(Color::Black).bg(Color::Green),
+        Some(CharHint::Elsewhere) => Style::new().fg(Color::Black).bg(Color::Yellow),
+        Some(CharHint::Nowhere) => Style::new().fg(Color::White).bg(Color::DarkGray),
+        None => Style::new(),
+    };
+    Span::styled(format!(" {} ", chr), style)
+}
+
+/// Run the interactive front end until the user quits.
+pub fn run<const WORD_SIZE: usize>(session: SolverSession<WORD_SIZE, 26>) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, TuiApp::new(session));
+    ratatui::restore();
+    result
+}
+
+fn run_app<const WORD_SIZE: usize>(
+    terminal: &mut DefaultTerminal,
+    mut app: TuiApp<WORD_SIZE>,
+) -> io::Result<()> {
+    while !app.should_quit() {
+        terminal.draw(|frame| app.render(frame))?;
+        if let Event::Key(key) = event::read()? {
+            app.handle_key(key);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+
+    fn press(app: &mut TuiApp<5>, keys: &str) {
+        for chr in keys.chars() {
+            app.handle_key(KeyEvent::new(KeyCode::Char(chr), KeyModifiers::NONE));
+        }
+    }
+
+    fn press_code(app: &mut TuiApp<5>, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn app() -> TuiApp<5> {
+        let words: Vec<Word<5, 26>> = ["fight", "light", "might", "sight", "films"]
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect();
+        TuiApp::new(SolverSession::new(words.clone(), words[..4].to_vec()))
+    }
+
+    #[test]
+    fn test_enter_guess_and_hint() {
+        let mut app = app();
+        assert_eq!(app.candidate_summary, "4 candidates");
+        press(&mut app, "films");
+        press_code(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, InputMode::Hint);
+        press(&mut app, "xgyxx");
+        press_code(&mut app, KeyCode::Enter);
+        assert_eq!(app.session().history().len(), 1);
+        assert_eq!(app.mode, InputMode::Guess);
+        assert_eq!(app.candidate_summary, "1 candidates");
+        assert_eq!(app.status, "the answer is LIGHT");
+    }
+
+    #[test]
+    fn test_out_of_list_guess() {
+        let mut app = app();
+        press(&mut app, "tight");
+        press_code(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, InputMode::Hint);
+        assert!(app.status.contains("not in the word list"));
+        press(&mut app, "xgggg");
+        press_code(&mut app, KeyCode::Enter);
+        assert_eq!(app.session().out_of_list_rows(), vec![0]);
+        assert_eq!(app.candidate_summary, "4 candidates");
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut app = app();
+        press(&mut app, "films");
+        press_code(&mut app, KeyCode::Enter);
+        press(&mut app, "xgyxx");
+        press_code(&mut app, KeyCode::Enter);
+        assert_eq!(app.candidate_summary, "1 candidates");
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert!(app.session().history().is_empty());
+        assert_eq!(app.candidate_summary, "4 candidates");
+        assert!(app.guess_input.is_empty());
+        app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL));
+        assert_eq!(app.session().history().len(), 1);
+        assert_eq!(app.candidate_summary, "1 candidates");
+    }
+
+    #[test]
+    fn test_backspace_leaves_hint_mode() {
+        let mut app = app();
+        press_code(&mut app, KeyCode::Tab);
+        assert_eq!(app.guess_input.len(), 5);
+        press_code(&mut app, KeyCode::Enter);
+        press(&mut app, "g");
+        press_code(&mut app, KeyCode::Backspace);
+        press_code(&mut app, KeyCode::Backspace);
+        assert_eq!(app.mode, InputMode::Guess);
+        press_code(&mut app, KeyCode::Esc);
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_render() {
+        let app = app();
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let rendered: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(rendered.contains("4 candidates"));
+        assert!(rendered.contains("FILMS"));
+    }
+}
@@ -1,26 +1,144 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 use serde::{Deserialize, Serialize, Serializer, de::Visitor};
 
+use crate::error::WordGameError;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Word<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(pub [u8; WORD_SIZE]);
 
 impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> Word<WORD_SIZE, ALPHABET_SIZE> {
-    /// Convert from the given raw string. Panics if invalid.
-    pub fn from_str(raw: &str) -> Self {
-        assert_eq!(raw.bytes().len(), WORD_SIZE);
+    /// Count how many of the given char are in the word.
+    pub fn count_chr(&self, chr: u8) -> usize {
+        self.0.iter().filter(|self_chr| **self_chr == chr).count()
+    }
+}
+
+/// Which characters are allowed at each position, for variants where some positions only take
+/// part of the alphabet (e.g. a digit or an operator).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionAlphabets<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    allowed: [Vec<bool>; WORD_SIZE],
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> PositionAlphabets<WORD_SIZE, ALPHABET_SIZE> {
+    /// Allow every character at every position.
+    pub fn unrestricted() -> Self {
+        Self {
+            allowed: std::array::from_fn(|_| vec![true; ALPHABET_SIZE as usize]),
+        }
+    }
+
+    /// Only allow the given characters at a position.
+    pub fn restrict(mut self, ind: usize, chrs: &[u8]) -> Self {
+        self.allowed[ind] = (0..ALPHABET_SIZE).map(|chr| chrs.contains(&chr)).collect();
+        self
+    }
+
+    /// Whether the character is allowed at the position.
+    pub fn allows(&self, ind: usize, chr: u8) -> bool {
+        self.allowed[ind][chr as usize]
+    }
+
+    /// How many positions allow the character, i.e. the most copies of it a word can have.
+    pub fn max_count(&self, chr: u8) -> usize {
+        (0..WORD_SIZE).filter(|ind| self.allows(*ind, chr)).count()
+    }
+
+    /// Check that every character of the word is allowed where it is.
+    pub fn validate(&self, word: &Word<WORD_SIZE, ALPHABET_SIZE>) -> Result<(), WordGameError> {
+        match (0..WORD_SIZE).find(|ind| !self.allows(*ind, word.0[*ind])) {
+            Some(ind) => Err(WordGameError::InvalidWord {
+                word: format!("{:?}", word.0),
+                reason: format!(
+                    "character {} isn't allowed at position {}",
+                    word.0[ind], ind
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse a word, rejecting it if any character isn't allowed where it is.
+    pub fn parse(&self, raw: &str) -> Result<Word<WORD_SIZE, ALPHABET_SIZE>, WordGameError> {
+        let word = Word::try_from(raw)?;
+        self.validate(&word)?;
+        Ok(word)
+    }
+
+    /// Every word the restrictions allow, in lexicographic order.
+    pub fn all_words(&self) -> Vec<Word<WORD_SIZE, ALPHABET_SIZE>> {
+        let choices: Vec<Vec<u8>> = (0..WORD_SIZE)
+            .map(|ind| {
+                (0..ALPHABET_SIZE)
+                    .filter(|chr| self.allows(ind, *chr))
+                    .collect()
+            })
+            .collect();
+        if choices.iter().any(Vec::is_empty) {
+            return vec![];
+        }
+
+        // Step through the choices like an odometer, finishing once every digit has rolled over
+        let mut words = vec![];
+        let mut choice_inds = [0usize; WORD_SIZE];
+        loop {
+            words.push(Word(std::array::from_fn(|ind| {
+                choices[ind][choice_inds[ind]]
+            })));
+            let mut ind = WORD_SIZE;
+            loop {
+                if ind == 0 {
+                    return words;
+                }
+                ind -= 1;
+                choice_inds[ind] += 1;
+                if choice_inds[ind] < choices[ind].len() {
+                    break;
+                }
+                choice_inds[ind] = 0;
+            }
+        }
+    }
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> TryFrom<&str>
+    for Word<WORD_SIZE, ALPHABET_SIZE>
+{
+    type Error = WordGameError;
+
+    /// Convert from the given raw string, case-insensitively.
+    fn try_from(raw: &str) -> Result<Self, Self::Error> {
+        let invalid = |reason: String| WordGameError::InvalidWord {
+            word: raw.to_string(),
+            reason,
+        };
+        if raw.len() != WORD_SIZE {
+            return Err(invalid(format!("expected {} letters", WORD_SIZE)));
+        }
         let mut out = [0; WORD_SIZE];
         for (ind, byte) in raw.bytes().enumerate() {
-            let value = byte.to_ascii_uppercase() - 65;
-            assert!(value < 26);
+            if !byte.is_ascii_alphabetic() {
+                return Err(invalid(format!("{:?} is not a letter", byte as char)));
+            }
+            let value = byte.to_ascii_uppercase() - b'A';
+            if value >= ALPHABET_SIZE {
+                return Err(invalid(format!(
+                    "{:?} is outside the {}-letter alphabet",
+                    byte as char, ALPHABET_SIZE
+                )));
+            }
             out[ind] = value;
         }
-        Self(out)
+        Ok(Self(out))
     }
+}
 
-    /// Count how many of the given char are in the word.
-    pub fn count_chr(&self, chr: u8) -> usize {
-        self.0.iter().filter(|self_chr| **self_chr == chr).count()
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> FromStr for Word<WORD_SIZE, ALPHABET_SIZE> {
+    type Err = WordGameError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        Self::try_from(raw)
     }
 }
 
@@ -55,7 +173,7 @@ impl<'de, const WORD_SIZE: usize> Visitor<'de> for WordVisitor<WORD_SIZE> {
     where
         E: serde::de::Error,
     {
-        Ok(Word::from_str(v))
+        Word::try_from(v).map_err(E::custom)
     }
 }
 
@@ -77,7 +195,7 @@ mod tests {
     #[test]
     fn test_serialize() {
         assert_eq!(
-            serde_json::to_string(&Word::<5, 26>::from_str("abcdz")).unwrap(),
+            serde_json::to_string(&Word::<5, 26>::try_from("abcdz").unwrap()).unwrap(),
             "\"ABCDZ\""
         );
     }
@@ -85,12 +203,12 @@ mod tests {
     #[test]
     fn test_deserialize() {
         let result: Word<5, 26> = serde_json::from_str("\"zdcba\"").unwrap();
-        assert_eq!(result, Word::<5, 26>::from_str("zdcba"),);
+        assert_eq!(result, Word::<5, 26>::try_from("zdcba").unwrap(),);
     }
 
     #[test]
     fn test_serde() {
-        let original = Word::<5, 26>::from_str("azbyc");
+        let original = Word::<5, 26>::try_from("azbyc").unwrap();
         let json = serde_json::to_string(&original).unwrap();
         let reconstructed = serde_json::from_str(&json).unwrap();
         assert_eq!(original, reconstructed);
@@ -99,12 +217,55 @@ mod tests {
     #[test]
     fn test_serde_as_map_key() {
         let original: HashMap<Word<5, 26>, u64> = HashMap::from([
-            (Word::from_str("abcde"), 5),
-            (Word::from_str("fghij"), 3),
-            (Word::from_str("vwxyz"), 1),
+            (Word::try_from("abcde").unwrap(), 5),
+            (Word::try_from("fghij").unwrap(), 3),
+            (Word::try_from("vwxyz").unwrap(), 1),
         ]);
         let json = serde_json::to_string(&original).unwrap();
         let reconstructed = serde_json::from_str(&json).unwrap();
         assert_eq!(original, reconstructed);
     }
+
+    #[test]
+    fn test_position_alphabets() {
+        // Vowel in the middle, no Z at the end
+        let alphabets = PositionAlphabets::<3, 26>::unrestricted()
+            .restrict(1, &[0, 4, 8, 14, 20])
+            .restrict(2, &(0..25).collect::<Vec<u8>>());
+        assert!(alphabets.parse("cat").is_ok());
+        assert!(alphabets.parse("cst").is_err());
+        assert_eq!(
+            alphabets.parse("caz").unwrap_err().to_string(),
+            alphabets
+                .validate(&Word::try_from("caz").unwrap())
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(
+            alphabets
+                .parse("caz")
+                .unwrap_err()
+                .to_string()
+                .contains("position 2")
+        );
+        assert_eq!(alphabets.max_count(0), 3);
+        assert_eq!(alphabets.max_count(25), 1);
+
+        let words = alphabets.all_words();
+        assert_eq!(words.len(), 26 * 5 * 25);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(words.iter().all(|word| alphabets.validate(word).is_ok()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Word::<5, 26>::try_from("abcd").is_err());
+        assert!(Word::<5, 26>::try_from("abc1e").is_err());
+        assert!(Word::<3, 4>::try_from("abe").is_err());
+        assert_eq!(
+            "ABCDE".parse::<Word<5, 26>>().unwrap(),
+            Word([0, 1, 2, 3, 4])
+        );
+        assert!(serde_json::from_str::<Word<5, 26>>("\"abc\"").is_err());
+    }
 }
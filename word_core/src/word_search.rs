@@ -1,5 +1,6 @@
 use crate::column::Column;
-use crate::word::Word;
+use crate::word::{PositionAlphabets, Word};
+use crate::worker_pool::{PoolConfig, WorkerPool};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Query {
@@ -12,6 +13,12 @@ pub enum Query {
     /// Filter for words that contain at least `count` instances of `chr`
     CountAtLeast { count: usize, chr: u8 },
 
+    /// Filter for words that contain at most `count` instances of `chr`
+    CountAtMost { count: usize, chr: u8 },
+
+    /// Filter for words that don't contain `chr` at any of the specified `inds`
+    NotAtPositions { inds: Vec<usize>, chr: u8 },
+
     /// Filter for words that do not satisfy the child query
     Not(Box<Query>),
 
@@ -22,14 +29,166 @@ pub enum Query {
     Or(Vec<Query>),
 }
 
+impl Query {
+    /// Whether a single word satisfies the query, as a mask from `eval_query` would show.
+    pub fn matches<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        &self,
+        word: &Word<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> bool {
+        match self {
+            Self::Match { ind, chr } => word.0.get(*ind) == Some(chr),
+            Self::CountExact { count, chr } => word.count_chr(*chr) == *count,
+            Self::CountAtLeast { count, chr } => word.count_chr(*chr) >= *count,
+            Self::CountAtMost { count, chr } => word.count_chr(*chr) <= *count,
+            Self::NotAtPositions { inds, chr } => {
+                inds.iter().all(|ind| word.0.get(*ind) != Some(chr))
+            }
+            Self::Not(query) => !query.matches(word),
+            Self::And(queries) => queries.iter().all(|query| query.matches(word)),
+            Self::Or(queries) => queries.iter().any(|query| query.matches(word)),
+        }
+    }
+}
+
+/// A word ruled out by a clue, with the parts of the clue's query it failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elimination<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    pub word: Word<WORD_SIZE, ALPHABET_SIZE>,
+
+    /// The conjuncts of the query this word doesn't satisfy, in query order
+    pub sub_queries: Vec<Query>,
+}
+
+/// Which families of single-fact columns a search table precomputes.
+///
+/// Queries on a family that isn't stored are answered by scanning the words instead, trading
+/// speed for memory. For small alphabets or long words the count families can dominate the
+/// table while rarely being queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnFamilies {
+    /// `Match` columns, one per position per character
+    pub matches: bool,
+
+    /// `CountExact` columns, one per count per character
+    pub count_exact: bool,
+
+    /// `CountAtLeast` columns, one per count per character
+    pub count_at_least: bool,
+}
+
+impl ColumnFamilies {
+    /// Store every family
+    pub const ALL: Self = Self {
+        matches: true,
+        count_exact: true,
+        count_at_least: true,
+    };
+}
+
+impl Default for ColumnFamilies {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
 pub struct SearchableWords<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
     words: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
-    columns: Vec<Column>,
+
+    /// Precomputed columns, or `None` for those that can never match any word or whose family
+    /// isn't stored
+    columns: Vec<Option<Column>>,
+
+    /// Which column families are stored rather than computed on demand
+    families: ColumnFamilies,
+
+    /// Number of words matching each column, whether or not it's stored
+    leaf_counts: Vec<u64>,
+
+    /// Where each word sits in the list the table was originally built from
+    index_mapping: IndexMapping,
+}
+
+/// Maps between row indices of a filtered table and indices into the original word list it was
+/// built from, so data indexed by the original list (like a hint matrix) stays usable after
+/// filtering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexMapping {
+    /// Original index of each filtered row, in ascending order
+    original_inds: Vec<usize>,
+}
+
+impl IndexMapping {
+    /// The mapping of an unfiltered list onto itself.
+    pub fn identity(len: usize) -> Self {
+        Self {
+            original_inds: (0..len).collect(),
+        }
+    }
+
+    /// Keep only the given rows, which must be in ascending order.
+    pub fn filter(&self, inds: &[usize]) -> Self {
+        Self {
+            original_inds: inds.iter().map(|ind| self.original_inds[*ind]).collect(),
+        }
+    }
+
+    /// Get the original index of a filtered row.
+    pub fn to_original(&self, filtered_ind: usize) -> usize {
+        self.original_inds[filtered_ind]
+    }
+
+    /// Get the filtered row of an original index, or `None` if it was filtered out.
+    pub fn to_filtered(&self, original_ind: usize) -> Option<usize> {
+        self.original_inds.binary_search(&original_ind).ok()
+    }
+
+    /// Get the original index of every filtered row, in ascending order.
+    pub fn original_inds(&self) -> &[usize] {
+        &self.original_inds
+    }
+}
+
+/// Where the answer to a single-fact query is precomputed.
+enum Leaf {
+    Column(usize),
+    All,
+}
+
+/// The single fact a column records about each word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnFact {
+    Match { ind: usize, chr: u8 },
+    CountExact { count: usize, chr: u8 },
+    CountAtLeast { count: usize, chr: u8 },
 }
 
 impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> SearchableWords<WORD_SIZE, ALPHABET_SIZE> {
     /// Given a set of words and an alphabet size, build a search table of word data.
     pub fn build(words: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>) -> Self {
+        Self::build_with_alphabets(words, &PositionAlphabets::unrestricted())
+    }
+
+    /// Build a search table of words drawn from restricted per-position alphabets, skipping the
+    /// columns for characters and counts that the restrictions rule out.
+    ///
+    /// Every word must be allowed by the restrictions.
+    pub fn build_with_alphabets(
+        words: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        alphabets: &PositionAlphabets<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> Self {
+        Self::build_with_families(words, alphabets, ColumnFamilies::ALL)
+    }
+
+    /// Build a search table that only stores the given column families, computing the others
+    /// from the words whenever a query needs them.
+    ///
+    /// Every word must be allowed by the alphabet restrictions.
+    pub fn build_with_families(
+        words: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        alphabets: &PositionAlphabets<WORD_SIZE, ALPHABET_SIZE>,
+        families: ColumnFamilies,
+    ) -> Self {
+        debug_assert!(words.iter().all(|word| alphabets.validate(word).is_ok()));
         let num_cols = (ALPHABET_SIZE as usize) * WORD_SIZE * 3;
         let mut columns = Vec::with_capacity(num_cols);
 
@@ -37,66 +196,250 @@ impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> SearchableWords<WORD_SIZE,
         for chr in 0..ALPHABET_SIZE {
             // Push exact match columns
             let match_cols = (0..WORD_SIZE).map(|ind| {
-                Column::from_bools(
-                    &words
-                        .iter()
-                        .map(|word| word.0[ind] == chr)
-                        .collect::<Vec<bool>>(),
-                )
+                (families.matches && alphabets.allows(ind, chr)).then(|| {
+                    Column::from_bools(
+                        &words
+                            .iter()
+                            .map(|word| word.0[ind] == chr)
+                            .collect::<Vec<bool>>(),
+                    )
+                })
             });
             columns.extend(match_cols);
 
             // Compute counts for this character once
+            let max_count = alphabets.max_count(chr);
             let counts: Vec<u64> = words
                 .iter()
                 .map(|word| word.count_chr(chr) as u64)
                 .collect();
 
             // Push count-exact columns
-            let exact_count_cols = Column::one_hot_values(&counts, WORD_SIZE as u64 + 1);
-            columns.extend(exact_count_cols.into_iter());
+            if families.count_exact {
+                let exact_count_cols = Column::one_hot_values(&counts, max_count as u64 + 1);
+                columns.extend(exact_count_cols.into_iter().map(Some));
+                columns.extend((max_count..WORD_SIZE).map(|_| None));
+            } else {
+                columns.extend((0..=WORD_SIZE).map(|_| None));
+            }
 
             // Push count-at-least columns
             for threshold_count in 1..WORD_SIZE {
+                if !families.count_at_least || threshold_count > max_count {
+                    columns.push(None);
+                    continue;
+                }
                 let word_count_at_least_threshold: Vec<bool> = counts
                     .iter()
                     .map(|word_count| *word_count >= threshold_count as u64)
                     .collect();
                 let count_at_least_col = Column::from_bools(&word_count_at_least_threshold);
-                columns.push(count_at_least_col);
+                columns.push(Some(count_at_least_col));
             }
         }
 
-        Self { words, columns }
+        let index_mapping = IndexMapping::identity(words.len());
+        Self::from_columns(words, columns, families, index_mapping)
     }
 
-    /// Evaluate the query and produce an output column that represents a mask over rows.
-    pub fn eval_query(&self, query: Query) -> Column {
-        match query {
+    fn from_columns(
+        words: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        columns: Vec<Option<Column>>,
+        families: ColumnFamilies,
+        index_mapping: IndexMapping,
+    ) -> Self {
+        let mut stored_counts =
+            Column::count_true_each(&columns.iter().flatten().collect::<Vec<&Column>>())
+                .into_iter();
+        let leaf_counts = columns
+            .iter()
+            .enumerate()
+            .map(|(col_ind, col)| {
+                let fact = Self::column_fact(col_ind);
+                if Self::stores(families, fact) {
+                    col.as_ref().map_or(0, |_| stored_counts.next().unwrap())
+                } else {
+                    words
+                        .iter()
+                        .filter(|word| Self::fact_holds(fact, word))
+                        .count() as u64
+                }
+            })
+            .collect();
+        Self {
+            words,
+            columns,
+            families,
+            leaf_counts,
+            index_mapping,
+        }
+    }
+
+    /// Work out which fact a column index records, inverting the layout `leaf` maps into.
+    fn column_fact(col_ind: usize) -> ColumnFact {
+        let chr = (col_ind / (WORD_SIZE * 3)) as u8;
+        match col_ind % (WORD_SIZE * 3) {
+            ind if ind < WORD_SIZE => ColumnFact::Match { ind, chr },
+            offset if offset <= WORD_SIZE * 2 => ColumnFact::CountExact {
+                count: offset - WORD_SIZE,
+                chr,
+            },
+            offset => ColumnFact::CountAtLeast {
+                count: offset - WORD_SIZE * 2,
+                chr,
+            },
+        }
+    }
+
+    /// Whether the family a fact belongs to is stored.
+    fn stores(families: ColumnFamilies, fact: ColumnFact) -> bool {
+        match fact {
+            ColumnFact::Match { .. } => families.matches,
+            ColumnFact::CountExact { .. } => families.count_exact,
+            ColumnFact::CountAtLeast { .. } => families.count_at_least,
+        }
+    }
+
+    fn fact_holds(fact: ColumnFact, word: &Word<WORD_SIZE, ALPHABET_SIZE>) -> bool {
+        match fact {
+            ColumnFact::Match { ind, chr } => word.0[ind] == chr,
+            ColumnFact::CountExact { count, chr } => word.count_chr(chr) == count,
+            ColumnFact::CountAtLeast { count, chr } => word.count_chr(chr) >= count,
+        }
+    }
+
+    /// Get a column, which is all false if it was skipped, or scanned from the words if its
+    /// family isn't stored.
+    fn column(&self, col_ind: usize) -> Column {
+        let fact = Self::column_fact(col_ind);
+        if !Self::stores(self.families, fact) {
+            return Column::from_bools(
+                &self
+                    .words
+                    .iter()
+                    .map(|word| Self::fact_holds(fact, word))
+                    .collect::<Vec<bool>>(),
+            );
+        }
+        match &self.columns[col_ind] {
+            Some(column) => column.clone(),
+            None => Column::from_false(self.words.len()),
+        }
+    }
+
+    /// Find where the answer to a single-fact query is precomputed, or `None` for compound
+    /// queries.
+    fn leaf(query: &Query) -> Option<Leaf> {
+        match *query {
             Query::Match { ind, chr } => {
                 let chr_block_start = (WORD_SIZE * 3) * chr as usize;
-                let chr_block_match_cols_start = chr_block_start + 0;
-                let target_col = chr_block_match_cols_start + ind;
-                self.columns[target_col].clone()
+                let chr_block_match_cols_start = chr_block_start;
+                Some(Leaf::Column(chr_block_match_cols_start + ind))
             }
             Query::CountExact { count, chr } => {
                 let chr_block_start = (WORD_SIZE * 3) * chr as usize;
                 let chr_block_count_exact_cols_start = chr_block_start + WORD_SIZE;
-                let target_col = chr_block_count_exact_cols_start + count;
-                self.columns[target_col].clone()
+                Some(Leaf::Column(chr_block_count_exact_cols_start + count))
             }
             Query::CountAtLeast { count, chr } => {
                 if count == 0 {
-                    Column::from_true(self.words.len())
+                    Some(Leaf::All)
                 } else if count == WORD_SIZE {
-                    self.eval_query(Query::CountExact { count, chr })
+                    Self::leaf(&Query::CountExact { count, chr })
                 } else {
                     let chr_block_start = (WORD_SIZE * 3) * chr as usize;
                     let chr_block_count_at_least_cols_start = chr_block_start + WORD_SIZE * 2 + 1;
-                    let target_col = chr_block_count_at_least_cols_start + count - 1;
-                    self.columns[target_col].clone()
+                    Some(Leaf::Column(
+                        chr_block_count_at_least_cols_start + count - 1,
+                    ))
+                }
+            }
+            Query::CountAtMost { .. }
+            | Query::NotAtPositions { .. }
+            | Query::Not(_)
+            | Query::And(_)
+            | Query::Or(_) => None,
+        }
+    }
+
+    /// How many words satisfy a single-fact query, read from the counts cached at build time
+    /// without touching any column data. Returns `None` for compound queries.
+    pub fn leaf_count(&self, query: &Query) -> Option<u64> {
+        Self::leaf(query).map(|leaf| match leaf {
+            Leaf::Column(col_ind) => self.leaf_counts[col_ind],
+            Leaf::All => self.words.len() as u64,
+        })
+    }
+
+    /// Count how many of the masked rows have each character at each position, indexed
+    /// `[ind][chr]`. Stored Match columns are counted together in one pass.
+    pub fn match_counts(&self, mask: &Column) -> Vec<Vec<u64>> {
+        if !self.families.matches {
+            let mut counts = vec![vec![0; ALPHABET_SIZE as usize]; WORD_SIZE];
+            for word in self.filter_words(mask) {
+                for (ind, chr) in word.0.iter().enumerate() {
+                    counts[ind][*chr as usize] += 1;
+                }
+            }
+            return counts;
+        }
+        let match_cols: Vec<Option<&Column>> = (0..WORD_SIZE)
+            .flat_map(|ind| (0..ALPHABET_SIZE).map(move |chr| Query::Match { ind, chr }))
+            .map(|query| match Self::leaf(&query) {
+                Some(Leaf::Column(col_ind)) => self.columns[col_ind].as_ref(),
+                _ => None,
+            })
+            .collect();
+        let mut stored_counts = Column::count_true_each_within(
+            &match_cols.iter().flatten().copied().collect::<Vec<_>>(),
+            mask,
+        )
+        .into_iter();
+        let flat: Vec<u64> = match_cols
+            .iter()
+            // Skipped columns can never match
+            .map(|col| col.map_or(0, |_| stored_counts.next().unwrap()))
+            .collect();
+        flat.chunks(ALPHABET_SIZE as usize)
+            .map(<[u64]>::to_vec)
+            .collect()
+    }
+
+    /// Whether any word contains at least one of the letters of `word`, read from the cached
+    /// counts. A guess sharing no letters with any word gets the same all-nowhere hint from each
+    /// of them, so it can be discarded without partitioning.
+    pub fn shares_any_letter(&self, word: &Word<WORD_SIZE, ALPHABET_SIZE>) -> bool {
+        word.0
+            .iter()
+            .any(|&chr| self.leaf_count(&Query::CountAtLeast { count: 1, chr }) != Some(0))
+    }
+
+    /// Evaluate the query and produce an output column that represents a mask over rows.
+    pub fn eval_query(&self, query: Query) -> Column {
+        if let Some(leaf) = Self::leaf(&query) {
+            return match leaf {
+                Leaf::Column(col_ind) => self.column(col_ind),
+                Leaf::All => Column::from_true(self.words.len()),
+            };
+        }
+        match query {
+            Query::CountAtMost { count, chr } => {
+                if count >= WORD_SIZE {
+                    Column::from_true(self.words.len())
+                } else {
+                    !self.eval_query(Query::CountAtLeast {
+                        count: count + 1,
+                        chr,
+                    })
                 }
             }
+            Query::NotAtPositions { inds, chr } => {
+                inds.into_iter()
+                    .fold(Column::from_true(self.words.len()), |mut acc, ind| {
+                        acc &= !self.eval_query(Query::Match { ind, chr });
+                        acc
+                    })
+            }
             Query::Not(query) => !self.eval_query(*query),
             Query::And(queries) => {
                 queries
@@ -114,7 +457,55 @@ impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> SearchableWords<WORD_SIZE,
                         acc
                     })
             }
+            Query::Match { .. } | Query::CountExact { .. } | Query::CountAtLeast { .. } => {
+                unreachable!("leaf queries are handled above")
+            }
+        }
+    }
+
+    /// Evaluate many independent queries in parallel, producing one mask per query.
+    pub fn eval_queries(&self, queries: Vec<Query>) -> Vec<Column> {
+        WorkerPool::new(PoolConfig::default())
+            .map_or_unwind(&queries, |_, query| self.eval_query(query.clone()))
+    }
+
+    /// Evaluate each conjunct of the query on its own, looking through nested `And`s, so a row
+    /// missing from the combined mask can be traced to the conjuncts it fails.
+    pub fn eval_query_per_leaf(&self, query: Query) -> Vec<(Query, Column)> {
+        fn conjuncts(query: Query, out: &mut Vec<Query>) {
+            match query {
+                Query::And(queries) => queries.into_iter().for_each(|query| conjuncts(query, out)),
+                query => out.push(query),
+            }
         }
+        let mut leaves = vec![];
+        conjuncts(query, &mut leaves);
+        let masks = self.eval_queries(leaves.clone());
+        leaves.into_iter().zip(masks).collect()
+    }
+
+    /// Report the rows in `before` but not `after` and which conjuncts of `query` ruled each one
+    /// out, where `after` is `before` narrowed by `query`.
+    pub fn attribute_eliminations(
+        &self,
+        before: &Column,
+        after: &Column,
+        query: Query,
+    ) -> Vec<Elimination<WORD_SIZE, ALPHABET_SIZE>> {
+        let leaf_masks = self.eval_query_per_leaf(query);
+        let mut eliminated = before.clone();
+        eliminated &= !after.clone();
+        eliminated
+            .iter_true()
+            .map(|ind| Elimination {
+                word: self.words[ind],
+                sub_queries: leaf_masks
+                    .iter()
+                    .filter(|(_, mask)| !mask.get(ind))
+                    .map(|(query, _)| query.clone())
+                    .collect(),
+            })
+            .collect()
     }
 
     /// Given a mask over rows, extract the words filtered by that mask.
@@ -128,10 +519,21 @@ impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> SearchableWords<WORD_SIZE,
     /// Given a mask over rows, extract a new table filtered by that mask.
     pub fn filter(&self, mask: &Column) -> Self {
         let inds = mask.true_inds();
-        Self {
-            words: inds.iter().map(|ind| self.words[*ind]).collect(),
-            columns: self.columns.iter().map(|col| col.filter(&inds)).collect(),
-        }
+        Self::from_columns(
+            inds.iter().map(|ind| self.words[*ind]).collect(),
+            self.columns
+                .iter()
+                .map(|col| col.as_ref().map(|col| col.filter(&inds)))
+                .collect(),
+            self.families,
+            self.index_mapping.filter(&inds),
+        )
+    }
+
+    /// Get the mapping from this table's rows back to the list it was originally built from,
+    /// through any number of filters.
+    pub fn index_mapping(&self) -> &IndexMapping {
+        &self.index_mapping
     }
 
     /// Get a reference to the words contained in this data structure.
@@ -143,18 +545,134 @@ impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> SearchableWords<WORD_SIZE,
     pub fn len(&self) -> usize {
         self.words.len()
     }
+
+    /// Whether there are no possible answers left in this set.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// The letters of each guess alongside which answers contain each letter, so a search over a
+/// hint matrix can discard guesses sharing no letters with any answer still possible without
+/// partitioning the answers by them.
+#[derive(Debug, Clone)]
+pub struct LetterScreen {
+    /// The distinct letters of each guess, indexed like the hint matrix's rows
+    guess_letters: Vec<Vec<u8>>,
+    /// The answers containing each letter, indexed by letter
+    letter_answers: Vec<Column>,
+}
+
+impl LetterScreen {
+    /// Build a screen for the guesses against the answers table, reading each letter's answers
+    /// from the table's cached columns.
+    pub fn new<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>(
+        guesses: &[Word<WORD_SIZE, ALPHABET_SIZE>],
+        answers: &SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+    ) -> Self {
+        let guess_letters = guesses
+            .iter()
+            .map(|guess| {
+                let mut letters = guess.0.to_vec();
+                letters.sort_unstable();
+                letters.dedup();
+                letters
+            })
+            .collect();
+        let letter_answers = (0..ALPHABET_SIZE)
+            .map(|chr| answers.eval_query(Query::CountAtLeast { count: 1, chr }))
+            .collect();
+        Self {
+            guess_letters,
+            letter_answers,
+        }
+    }
+
+    /// Find which letters appear in any of the possible answers, by letter.
+    pub fn letters_within(&self, possible_answers: &Column) -> Vec<bool> {
+        let cols: Vec<&Column> = self.letter_answers.iter().collect();
+        Column::count_true_each_within(&cols, possible_answers)
+            .into_iter()
+            .map(|count| count != 0)
+            .collect()
+    }
+
+    /// Whether the guess shares a letter with those from `letters_within`. A guess sharing none
+    /// gets the same all-nowhere hint from every answer, so it can't split them. Guesses the
+    /// screen wasn't built with are kept.
+    pub fn shares_any_letter(&self, guess_ind: usize, letters: &[bool]) -> bool {
+        self.guess_letters
+            .get(guess_ind)
+            .is_none_or(|guess_letters| guess_letters.iter().any(|&chr| letters[chr as usize]))
+    }
+}
+
+/// Search tables over both a guess list and an answer list, so one clue query can be evaluated
+/// against both at once.
+///
+/// The two lists share a single table, so each query is evaluated in one pass and then split
+/// into a guess mask and an answer mask.
+pub struct JointSearchableWords<const WORD_SIZE: usize, const ALPHABET_SIZE: u8> {
+    table: SearchableWords<WORD_SIZE, ALPHABET_SIZE>,
+    num_guesses: usize,
+}
+
+impl<const WORD_SIZE: usize, const ALPHABET_SIZE: u8>
+    JointSearchableWords<WORD_SIZE, ALPHABET_SIZE>
+{
+    /// Build a joint search table over the guess list and the answer list.
+    pub fn build(
+        guesses: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+        answers: Vec<Word<WORD_SIZE, ALPHABET_SIZE>>,
+    ) -> Self {
+        let num_guesses = guesses.len();
+        let mut words = guesses;
+        words.extend(answers);
+        Self {
+            table: SearchableWords::build(words),
+            num_guesses,
+        }
+    }
+
+    /// Evaluate the query against both lists, producing a (guess mask, answer mask) pair.
+    pub fn eval_query(&self, query: Query) -> (Column, Column) {
+        self.table.eval_query(query).split_at(self.num_guesses)
+    }
+
+    /// Evaluate many independent queries in parallel, producing one pair of masks per query.
+    pub fn eval_queries(&self, queries: Vec<Query>) -> Vec<(Column, Column)> {
+        self.table
+            .eval_queries(queries)
+            .into_iter()
+            .map(|mask| mask.split_at(self.num_guesses))
+            .collect()
+    }
+
+    /// Get the guess words, in table order.
+    pub fn guesses(&self) -> &[Word<WORD_SIZE, ALPHABET_SIZE>] {
+        &self.table.words()[..self.num_guesses]
+    }
+
+    /// Get the answer words, in table order.
+    pub fn answers(&self) -> &[Word<WORD_SIZE, ALPHABET_SIZE>] {
+        &self.table.words()[self.num_guesses..]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{hint::WordHint, hint_matrix::compute_hints};
 
     fn set_subtract(a: &[&'static str], b: &[&'static str]) -> Vec<&'static str> {
         a.iter().copied().filter(|item| !b.contains(item)).collect()
     }
 
     fn words_from_strs<const WORD_SIZE: usize>(words: &[&str]) -> Vec<Word<WORD_SIZE, 26>> {
-        words.iter().map(|word| Word::from_str(word)).collect()
+        words
+            .iter()
+            .map(|word| Word::try_from(*word).unwrap())
+            .collect()
     }
 
     fn assert_query_result<const WORD_SIZE: usize>(
@@ -164,7 +682,10 @@ mod tests {
     ) {
         let words: SearchableWords<WORD_SIZE, 26> = SearchableWords::build(words_from_strs(words));
         // println!("{:#?}", words.columns.iter().map(|col| col.to_bools()).collect::<Vec<Vec<bool>>>());
-        let mask = words.eval_query(query);
+        let mask = words.eval_query(query.clone());
+        for (ind, word) in words.words().iter().enumerate() {
+            assert_eq!(query.matches(word), mask.get(ind), "{} {:?}", word, query);
+        }
         let result = words.filter_words(&mask);
         assert_eq!(
             result,
@@ -251,6 +772,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_query_count_at_most() {
+        assert_query_result_and_inverse::<3>(
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+            Query::CountAtMost { count: 0, chr: 0 },
+            &["bbc", "cbc"],
+        );
+        assert_query_result_and_inverse::<3>(
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+            Query::CountAtMost { count: 2, chr: 0 },
+            &["bbc", "cbc", "abc", "bca", "baa", "aac"],
+        );
+        assert_query_result_and_inverse::<3>(
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+            Query::CountAtMost { count: 3, chr: 0 },
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+        );
+    }
+
+    #[test]
+    fn test_query_not_at_positions() {
+        assert_query_result_and_inverse::<3>(
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+            Query::NotAtPositions {
+                inds: vec![0, 2],
+                chr: 0,
+            },
+            &["bbc", "cbc"],
+        );
+        assert_query_result_and_inverse::<3>(
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+            Query::NotAtPositions {
+                inds: vec![],
+                chr: 0,
+            },
+            &["bbc", "cbc", "abc", "bca", "baa", "aac", "aaa"],
+        );
+    }
+
     #[test]
     fn test_query_and_group() {
         assert_query_result_and_inverse::<3>(
@@ -306,4 +866,234 @@ mod tests {
             &["bread"],
         );
     }
+
+    #[test]
+    fn test_joint_matches_separate_tables() {
+        let guesses: Vec<Word<3, 26>> = words_from_strs(&["foo", "bar", "baz", "biz"]);
+        let answers: Vec<Word<3, 26>> = words_from_strs(&["buz", "bar", "zap"]);
+        let joint = JointSearchableWords::build(guesses.clone(), answers.clone());
+        let guess_table = SearchableWords::build(guesses.clone());
+        let answer_table = SearchableWords::build(answers.clone());
+        let query = Query::Or(vec![
+            Query::Match { ind: 1, chr: 0 },
+            Query::CountAtLeast { count: 1, chr: 25 },
+        ]);
+
+        let (guess_mask, answer_mask) = joint.eval_query(query.clone());
+        assert_eq!(guess_mask, guess_table.eval_query(query.clone()));
+        assert_eq!(answer_mask, answer_table.eval_query(query));
+        assert_eq!(joint.guesses(), guesses);
+        assert_eq!(joint.answers(), answers);
+    }
+
+    #[test]
+    fn test_restricted_alphabets_match_full_table() {
+        // Only vowels in the middle, and never a Z at the end
+        let alphabets = PositionAlphabets::<3, 26>::unrestricted()
+            .restrict(1, &[0, 4, 8, 14, 20])
+            .restrict(2, &(0..25).collect::<Vec<u8>>());
+        let words = words_from_strs::<3>(&["cat", "bee", "zoo", "zaa", "aaa", "sun"]);
+        let restricted = SearchableWords::build_with_alphabets(words.clone(), &alphabets);
+        let full = SearchableWords::build(words);
+        assert!(restricted.columns.iter().any(Option::is_none));
+
+        for chr in [0, 1, 4, 14, 19, 25] {
+            for ind in 0..3 {
+                let query = Query::Match { ind, chr };
+                assert_eq!(restricted.eval_query(query.clone()), full.eval_query(query));
+            }
+            for count in 0..=3 {
+                for query in [
+                    Query::CountExact { count, chr },
+                    Query::CountAtLeast { count, chr },
+                ] {
+                    assert_eq!(restricted.eval_query(query.clone()), full.eval_query(query));
+                }
+            }
+        }
+
+        let mask = restricted.eval_query(Query::CountAtLeast { count: 1, chr: 0 });
+        let filtered = restricted.filter(&mask);
+        assert_eq!(
+            filtered.eval_query(Query::Match { ind: 2, chr: 25 }),
+            Column::from_false(filtered.len())
+        );
+    }
+
+    #[test]
+    fn test_missing_families_match_full_table() {
+        let words = words_from_strs::<3>(&["cat", "bee", "zoo", "zaa", "aaa", "sun", "ebb"]);
+        let full = SearchableWords::build(words.clone());
+        for families in [
+            ColumnFamilies {
+                matches: false,
+                ..ColumnFamilies::ALL
+            },
+            ColumnFamilies {
+                count_exact: false,
+                count_at_least: false,
+                ..ColumnFamilies::ALL
+            },
+            ColumnFamilies {
+                matches: false,
+                count_exact: false,
+                count_at_least: false,
+            },
+        ] {
+            let partial = SearchableWords::build_with_families(
+                words.clone(),
+                &PositionAlphabets::unrestricted(),
+                families,
+            );
+            assert!(
+                partial.columns.iter().flatten().count() < full.columns.iter().flatten().count()
+            );
+            let filtered_mask = partial.eval_query(Query::CountAtLeast { count: 1, chr: 0 });
+            let filtered = partial.filter(&filtered_mask);
+            let full_filtered = full.filter(&filtered_mask);
+            for chr in [0, 1, 4, 14, 25] {
+                let mut queries: Vec<Query> = (0..3).map(|ind| Query::Match { ind, chr }).collect();
+                for count in 0..=3 {
+                    queries.push(Query::CountExact { count, chr });
+                    queries.push(Query::CountAtLeast { count, chr });
+                    queries.push(Query::CountAtMost { count, chr });
+                }
+                for query in queries {
+                    // Scanned columns may differ from stored ones past the last word
+                    assert_eq!(
+                        partial.eval_query(query.clone()).true_inds(),
+                        full.eval_query(query.clone()).true_inds()
+                    );
+                    assert_eq!(partial.leaf_count(&query), full.leaf_count(&query));
+                    assert_eq!(
+                        filtered.eval_query(query.clone()).true_inds(),
+                        full_filtered.eval_query(query).true_inds()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_leaf_counts() {
+        let table: SearchableWords<5, 26> = SearchableWords::build(words_from_strs(&[
+            "bread", "board", "abbey", "eerie", "geese",
+        ]));
+        let filtered = table.filter(&Column::from_bools(&[true, false, true, false, true]));
+        for words in [&table, &filtered] {
+            for chr in 0..26 {
+                let mut queries: Vec<Query> = (0..5).map(|ind| Query::Match { ind, chr }).collect();
+                for count in 0..=5 {
+                    queries.push(Query::CountExact { count, chr });
+                    queries.push(Query::CountAtLeast { count, chr });
+                }
+                for query in queries {
+                    assert_eq!(
+                        words.leaf_count(&query),
+                        Some(words.eval_query(query).count_true())
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            filtered.leaf_count(&Query::CountExact { count: 3, chr: 4 }),
+            Some(1)
+        );
+        assert_eq!(table.leaf_count(&Query::And(vec![])), None);
+    }
+
+    #[test]
+    fn test_match_counts() {
+        let words: Vec<Word<5, 26>> =
+            words_from_strs(&["fight", "light", "board", "bread", "lemon"]);
+        let mask = Column::from_bools(&[true, true, false, true, true]);
+        let expected = |words: &[Word<5, 26>]| {
+            let mut counts = vec![vec![0; 26]; 5];
+            for word in words {
+                for (ind, chr) in word.0.iter().enumerate() {
+                    counts[ind][*chr as usize] += 1;
+                }
+            }
+            counts
+        };
+        let masked: Vec<Word<5, 26>> = words
+            .iter()
+            .zip(mask.to_bools())
+            .filter(|(_, keep)| *keep)
+            .map(|(word, _)| *word)
+            .collect();
+        let full = SearchableWords::build(words.clone());
+        let counts = full.match_counts(&mask);
+        assert_eq!(counts, expected(&masked));
+        // L leads both light and lemon
+        assert_eq!(counts[0][(b'l' - b'a') as usize], 2);
+
+        let no_matches = SearchableWords::build_with_families(
+            words,
+            &PositionAlphabets::unrestricted(),
+            ColumnFamilies {
+                matches: false,
+                ..ColumnFamilies::ALL
+            },
+        );
+        assert_eq!(no_matches.match_counts(&mask), counts);
+    }
+
+    #[test]
+    fn test_shares_any_letter() {
+        let table: SearchableWords<5, 26> =
+            SearchableWords::build(words_from_strs(&["bread", "board", "abbey"]));
+        let filtered = table.filter(&Column::from_bools(&[false, true, false]));
+        let [pilot, upset, quick]: [Word<5, 26>; 3] = words_from_strs(&["pilot", "upset", "quick"])
+            .try_into()
+            .unwrap();
+        assert!(table.shares_any_letter(&pilot));
+        assert!(table.shares_any_letter(&upset));
+        assert!(!table.shares_any_letter(&quick));
+        assert!(filtered.shares_any_letter(&pilot));
+        assert!(!filtered.shares_any_letter(&upset));
+    }
+
+    #[test]
+    fn test_letter_screen() {
+        let answers: SearchableWords<5, 26> =
+            SearchableWords::build(words_from_strs(&["bread", "board", "abbey"]));
+        let screen = LetterScreen::new(&words_from_strs(&["pilot", "upset", "quick"]), &answers);
+        let all = screen.letters_within(&Column::from_true(3));
+        assert!(screen.shares_any_letter(0, &all));
+        assert!(screen.shares_any_letter(1, &all));
+        assert!(!screen.shares_any_letter(2, &all));
+        let board = screen.letters_within(&Column::from_bools(&[false, true, false]));
+        assert!(screen.shares_any_letter(0, &board));
+        assert!(!screen.shares_any_letter(1, &board));
+        assert!(screen.shares_any_letter(3, &board));
+    }
+
+    #[test]
+    fn test_index_mapping_through_filters() {
+        let words = words_from_strs(&["bread", "board", "abbey", "eerie", "geese", "fight"]);
+        let all_hints = compute_hints(&words, &words);
+        let table: SearchableWords<5, 26> = SearchableWords::build(words.clone());
+        assert_eq!(table.index_mapping(), &IndexMapping::identity(words.len()));
+
+        let once = table.filter(&Column::from_bools(&[false, true, true, false, true, true]));
+        let twice = once.filter(&Column::from_bools(&[true, false, true, true]));
+        assert_eq!(twice.index_mapping().original_inds(), &[1, 4, 5]);
+        assert_eq!(twice.index_mapping().to_filtered(4), Some(1));
+        assert_eq!(twice.index_mapping().to_filtered(2), None);
+
+        // The original hint matrix still describes the filtered rows
+        let guess = &words[0];
+        for (filtered_ind, answer) in twice.words().iter().enumerate() {
+            let original_ind = twice.index_mapping().to_original(filtered_ind);
+            assert_eq!(
+                original_ind,
+                words.iter().position(|w| w == answer).unwrap()
+            );
+            assert_eq!(
+                all_hints.hint_id(0, original_ind),
+                WordHint::from_guess_and_answer(guess, answer).hint_id()
+            );
+        }
+    }
 }
@@ -0,0 +1,300 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+use rayon::{ThreadPool, ThreadPoolBuilder, prelude::*};
+
+use crate::error::WordGameError;
+
+/// Thread count set by `PoolConfig::set_threads`, or 0 if unset
+static GLOBAL_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Pools already built, by thread count, so repeated parallel calls don't respawn threads
+static POOLS: OnceLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = OnceLock::new();
+
+/// How parallel work is spread across threads, shared by every parallel feature of the crate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Threads to run on, or `None` for the count given to `set_threads`, or rayon's global
+    /// pool if that was never set
+    pub num_threads: Option<usize>,
+}
+
+impl PoolConfig {
+    /// Set the thread count for every config that doesn't give its own, including the ones
+    /// used internally by calls that take no config, like hint matrix construction. `None`
+    /// goes back to rayon's global pool.
+    pub fn set_threads(num_threads: Option<usize>) {
+        GLOBAL_THREADS.store(num_threads.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Get the thread count this config runs on, or `None` for rayon's global pool.
+    pub fn threads(&self) -> Option<usize> {
+        self.num_threads.or_else(|| {
+            let num_threads = GLOBAL_THREADS.load(Ordering::Relaxed);
+            (num_threads > 0).then_some(num_threads)
+        })
+    }
+
+    /// Run `op` on a pool with the configured number of threads.
+    pub fn install<T: Send>(&self, op: impl FnOnce() -> T + Send) -> Result<T, WordGameError> {
+        match self.threads() {
+            Some(num_threads) => Ok(pool_with_threads(num_threads)?.install(op)),
+            None => Ok(op()),
+        }
+    }
+}
+
+/// Get the pool with this many threads, building it the first time. Every parallel feature of
+/// the crate gets its pool from here, through `PoolConfig::install`.
+fn pool_with_threads(num_threads: usize) -> Result<Arc<ThreadPool>, WordGameError> {
+    let mut pools = POOLS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(pool) = pools.get(&num_threads) {
+        return Ok(pool.clone());
+    }
+    let pool = Arc::new(
+        ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|err| WordGameError::ThreadPool(err.to_string()))?,
+    );
+    pools.insert(num_threads, pool.clone());
+    Ok(pool)
+}
+
+/// A flag for stopping parallel work early, shared by cloning.
+#[derive(Debug, Default, Clone)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop any work watching this token from starting more items.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Runs one operation over many items in parallel, with the same thread pool configuration,
+/// panic handling, progress reporting and cancellation wherever it's used.
+pub struct WorkerPool<'a> {
+    config: PoolConfig,
+    progress: Option<&'a (dyn Fn(usize, usize) + Sync)>,
+    cancel: Vec<CancelToken>,
+}
+
+impl<'a> WorkerPool<'a> {
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            config,
+            progress: None,
+            cancel: Vec::new(),
+        }
+    }
+
+    /// Call `progress` with the number of items finished and the total after each item. It's
+    /// called from the worker threads, so calls may arrive out of order.
+    pub fn with_progress(mut self, progress: &'a (dyn Fn(usize, usize) + Sync)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Stop starting new items once the token is cancelled. With more than one token, stop
+    /// once any of them is.
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel.push(cancel);
+        self
+    }
+
+    /// Apply `op` to every item with its index, returning the results in item order.
+    ///
+    /// If any call panics, no further items are started and the panic comes back as an error,
+    /// as does cancellation. Items already underway run to completion either way.
+    pub fn map<I: Sync, T: Send>(
+        &self,
+        items: &[I],
+        op: impl Fn(usize, &I) -> T + Sync,
+    ) -> Result<Vec<T>, WordGameError> {
+        self.run(items, op).map_err(|stopped| match stopped {
+            Stopped::Panicked(payload) => {
+                WordGameError::WorkerPanicked(panic_message(payload.as_ref()))
+            }
+            Stopped::Failed(err) => err,
+        })
+    }
+
+    /// Apply `op` to every item like [`map`](Self::map), for callers whose work can't fail
+    /// other than by panicking. A panic is resumed on the calling thread with its original
+    /// payload, just as if `op` had been called there, and a pool that can't be built or a
+    /// cancelled token panics with the error.
+    pub fn map_or_unwind<I: Sync, T: Send>(
+        &self,
+        items: &[I],
+        op: impl Fn(usize, &I) -> T + Sync,
+    ) -> Vec<T> {
+        match self.run(items, op) {
+            Ok(results) => results,
+            Err(Stopped::Panicked(payload)) => panic::resume_unwind(payload),
+            Err(Stopped::Failed(err)) => panic!("{err}"),
+        }
+    }
+
+    fn run<I: Sync, T: Send>(
+        &self,
+        items: &[I],
+        op: impl Fn(usize, &I) -> T + Sync,
+    ) -> Result<Vec<T>, Stopped> {
+        let num_done = AtomicUsize::new(0);
+        let panicked: Mutex<Option<Box<dyn Any + Send>>> = Mutex::new(None);
+        let stop = AtomicBool::new(false);
+        let results: Vec<Option<T>> = self
+            .config
+            .install(|| {
+                items
+                    .par_iter()
+                    .enumerate()
+                    .map(|(ind, item)| {
+                        if stop.load(Ordering::Relaxed)
+                            || self.cancel.iter().any(CancelToken::is_cancelled)
+                        {
+                            return None;
+                        }
+                        match panic::catch_unwind(AssertUnwindSafe(|| op(ind, item))) {
+                            Ok(result) => {
+                                let done = num_done.fetch_add(1, Ordering::Relaxed) + 1;
+                                if let Some(progress) = self.progress {
+                                    progress(done, items.len());
+                                }
+                                Some(result)
+                            }
+                            Err(payload) => {
+                                stop.store(true, Ordering::Relaxed);
+                                panicked
+                                    .lock()
+                                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                    .get_or_insert(payload);
+                                None
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .map_err(Stopped::Failed)?;
+        if let Some(payload) = panicked.into_inner().unwrap_or_else(|err| err.into_inner()) {
+            return Err(Stopped::Panicked(payload));
+        }
+        results
+            .into_iter()
+            .collect::<Option<Vec<T>>>()
+            .ok_or(Stopped::Failed(WordGameError::Cancelled))
+    }
+}
+
+/// Why a run over the items stopped short.
+enum Stopped {
+    /// The payload of the first call to panic
+    Panicked(Box<dyn Any + Send>),
+    Failed(WordGameError),
+}
+
+/// Get the message a panic was raised with, if it was a string.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_pool_map() {
+        let items: Vec<u64> = (0..100).collect();
+        let pool = WorkerPool::new(PoolConfig {
+            num_threads: Some(2),
+        });
+        assert_eq!(
+            pool.map(&items, |ind, item| ind as u64 + item).unwrap(),
+            (0..100).map(|item| 2 * item).collect::<Vec<u64>>()
+        );
+
+        let num_reports = AtomicUsize::new(0);
+        let report = |_done: usize, total: usize| {
+            assert_eq!(total, 100);
+            num_reports.fetch_add(1, Ordering::Relaxed);
+        };
+        WorkerPool::new(PoolConfig::default())
+            .with_progress(&report)
+            .map(&items, |_, item| *item)
+            .unwrap();
+        assert_eq!(num_reports.load(Ordering::Relaxed), 100);
+
+        let err = pool
+            .map(&items, |_, item| {
+                if *item == 50 {
+                    panic!("bad item");
+                }
+                *item
+            })
+            .unwrap_err();
+        assert!(matches!(err, WordGameError::WorkerPanicked(message) if message == "bad item"));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert!(matches!(
+            pool.with_cancel(cancel).map(&items, |_, item| *item),
+            Err(WordGameError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_map_or_unwind() {
+        let items: Vec<u64> = (0..100).collect();
+        let pool = WorkerPool::new(PoolConfig {
+            num_threads: Some(2),
+        });
+        assert_eq!(pool.map_or_unwind(&items, |_, item| *item), items);
+
+        // The panic comes back with its own payload, not just its message
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.map_or_unwind(&items, |_, item| {
+                if *item == 50 {
+                    panic::panic_any(*item);
+                }
+                *item
+            })
+        }))
+        .unwrap_err();
+        assert_eq!(payload.downcast_ref::<u64>(), Some(&50));
+    }
+
+    #[test]
+    fn test_pools_shared_by_thread_count() {
+        assert!(Arc::ptr_eq(
+            &pool_with_threads(3).unwrap(),
+            &pool_with_threads(3).unwrap()
+        ));
+        let config = PoolConfig {
+            num_threads: Some(3),
+        };
+        assert_eq!(config.install(rayon::current_num_threads).unwrap(), 3);
+    }
+}